@@ -285,8 +285,12 @@ export interface VibeteaEvent<T extends EventType = EventType> {
   readonly timestamp: string;
   /** Event type discriminator */
   readonly type: T;
+  /** Wire format version, absent on events from older servers */
+  readonly schemaVersion?: number;
   /** Event payload, typed based on the event type */
   readonly payload: EventPayloadMap[T];
+  /** IDs of the hubs that relayed the event, oldest first; absent if not relayed */
+  readonly hops?: readonly string[];
 }
 
 // -----------------------------------------------------------------------------
@@ -14,6 +14,8 @@
 //! | `VIBETEA_BUFFER_SIZE` | No | 1000 | Event buffer capacity |
 //! | `VIBETEA_BASENAME_ALLOWLIST` | No | (all) | Comma-separated extensions to allow |
 //! | `VIBETEA_MAX_SESSIONS` | No | 1000 | Maximum tracked sessions (LRU eviction) |
+//! | `VIBETEA_TLS_CLIENT_CERT` | No | - | PEM client certificate, for servers that require one |
+//! | `VIBETEA_TLS_CLIENT_KEY` | No | - | PKCS#8 PEM private key for `VIBETEA_TLS_CLIENT_CERT` |
 //!
 //! # Example
 //!
@@ -89,6 +91,12 @@ pub struct Config {
     /// Maximum number of sessions to track simultaneously.
     /// When this limit is reached, the least recently used session is evicted.
     pub max_sessions: usize,
+
+    /// PEM client certificate presented to servers that require one.
+    pub tls_client_cert: Option<PathBuf>,
+
+    /// PKCS#8 PEM private key for `tls_client_cert`.
+    pub tls_client_key: Option<PathBuf>,
 }
 
 impl Config {
@@ -178,6 +186,18 @@ impl Config {
             Err(_) => MAX_TRACKED_SESSIONS,
         };
 
+        // Optional: VIBETEA_TLS_CLIENT_CERT and VIBETEA_TLS_CLIENT_KEY (set together)
+        let tls_client_cert = env::var("VIBETEA_TLS_CLIENT_CERT").ok().map(PathBuf::from);
+        let tls_client_key = env::var("VIBETEA_TLS_CLIENT_KEY").ok().map(PathBuf::from);
+        if tls_client_cert.is_some() != tls_client_key.is_some() {
+            let missing = if tls_client_cert.is_some() {
+                "VIBETEA_TLS_CLIENT_KEY"
+            } else {
+                "VIBETEA_TLS_CLIENT_CERT"
+            };
+            return Err(ConfigError::MissingEnvVar(missing.to_string()));
+        }
+
         Ok(Self {
             server_url,
             source_id,
@@ -186,6 +206,8 @@ impl Config {
             buffer_size,
             basename_allowlist,
             max_sessions,
+            tls_client_cert,
+            tls_client_key,
         })
     }
 }
@@ -888,4 +910,38 @@ mod tests {
             ));
         });
     }
+
+    #[test]
+    #[serial]
+    fn test_tls_client_certificate() {
+        with_clean_env(|| {
+            env::set_var("VIBETEA_SERVER_URL", "https://test.example.com");
+            env::set_var("VIBETEA_TLS_CLIENT_CERT", "/etc/vibetea/client.pem");
+            env::set_var("VIBETEA_TLS_CLIENT_KEY", "/etc/vibetea/client.key");
+
+            let config = Config::from_env().expect("should parse config with client certificate");
+            assert_eq!(
+                config.tls_client_cert,
+                Some(PathBuf::from("/etc/vibetea/client.pem"))
+            );
+            assert_eq!(
+                config.tls_client_key,
+                Some(PathBuf::from("/etc/vibetea/client.key"))
+            );
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_tls_client_key_required_with_cert() {
+        with_clean_env(|| {
+            env::set_var("VIBETEA_SERVER_URL", "https://test.example.com");
+            env::set_var("VIBETEA_TLS_CLIENT_CERT", "/etc/vibetea/client.pem");
+
+            let err = Config::from_env().unwrap_err();
+            assert!(
+                matches!(err, ConfigError::MissingEnvVar(ref s) if s == "VIBETEA_TLS_CLIENT_KEY")
+            );
+        });
+    }
 }
@@ -80,6 +80,8 @@ ENVIRONMENT VARIABLES:
     VIBETEA_CLAUDE_DIR         Claude directory (default: ~/.claude)
     VIBETEA_BUFFER_SIZE        Event buffer size (default: 1000)
     VIBETEA_BASENAME_ALLOWLIST Comma-separated file extensions to include
+    VIBETEA_TLS_CLIENT_CERT    PEM client certificate, for servers that require one
+    VIBETEA_TLS_CLIENT_KEY     PKCS#8 PEM private key for the client certificate
 
 EXAMPLES:
     # Launch interactive TUI (default)
@@ -427,6 +429,21 @@ async fn run_monitor() -> Result<()> {
         config.source_id.clone(),
         config.buffer_size,
     );
+    let sender_config = match (&config.tls_client_cert, &config.tls_client_key) {
+        (Some(cert_path), Some(key_path)) => {
+            let cert = std::fs::read(cert_path).context(format!(
+                "Failed to read client certificate {}",
+                cert_path.display()
+            ))?;
+            let key = std::fs::read(key_path)
+                .context(format!("Failed to read client key {}", key_path.display()))?;
+            let identity = reqwest::Identity::from_pkcs8_pem(&cert, &key)
+                .context("Invalid client certificate or key")?;
+            info!(path = %cert_path.display(), "Client certificate loaded");
+            sender_config.with_client_identity(identity)
+        }
+        _ => sender_config,
+    };
     let mut sender = Sender::new(sender_config, crypto);
 
     // Create privacy pipeline
@@ -1044,18 +1061,10 @@ fn convert_to_event(
     // Apply privacy filtering
     let sanitized_payload = privacy_pipeline.process(payload);
 
-    Some(Event {
-        id: vibetea_monitor::types::Event::new(
-            source_id.to_string(),
-            event_type,
-            sanitized_payload.clone(),
-        )
-        .id,
-        source: source_id.to_string(),
-        timestamp: parsed.timestamp,
-        event_type,
-        payload: sanitized_payload,
-    })
+    Some(
+        Event::new(source_id.to_string(), event_type, sanitized_payload)
+            .with_timestamp(parsed.timestamp),
+    )
 }
 
 /// Initializes the logging subsystem.
@@ -0,0 +1,9 @@
+//! Prints the JSON Schema of the event wire format.
+//!
+//! ```sh
+//! cargo run -p vibetea-protocol --example event_schema > protocol/schema/event.schema.json
+//! ```
+
+fn main() {
+    print!("{}", vibetea_protocol::schema::event_schema_json());
+}
@@ -0,0 +1,602 @@
+//! Subscriber filters and the multi-value filter sets they are built from.
+//!
+//! A [`FilterSet`] holds the values a subscriber wants to include and exclude
+//! for one event field. [`SubscriberFilter`] keeps one set per field (source,
+//! type, project and tool name), and the server ANDs them together.
+//!
+//! # Syntax
+//!
+//! In query strings a set is written as comma-separated values. A value
+//! prefixed with `!` is excluded. Source, project and tool values may contain
+//! `*`, which matches any run of characters:
+//!
+//! ```text
+//! /ws?type=tool,agent_spawn&project=vibetea,website&source=!ci-*
+//! ```
+//!
+//! A value matches a set if it matches at least one included value (or none
+//! are given) and no excluded value. In JSON filter objects each field is
+//! either the same comma-separated string or an array of values:
+//!
+//! ```json
+//! {"type": ["tool", "session"], "project": ["vibetea", "website"], "tool": "!Read"}
+//! ```
+//!
+//! # Example
+//!
+//! ```rust
+//! use vibetea_protocol::filter::{EventTypeSet, PatternSet};
+//! use vibetea_protocol::types::EventType;
+//!
+//! let sources = PatternSet::parse("ci-*,!ci-flaky").unwrap();
+//! assert!(sources.matches(Some("ci-linux")));
+//! assert!(!sources.matches(Some("ci-flaky")));
+//! assert!(!sources.matches(Some("alice-laptop")));
+//!
+//! let types = EventTypeSet::parse("!activity").unwrap();
+//! assert!(types.matches(EventType::Tool));
+//! assert!(!types.matches(EventType::Activity));
+//! ```
+
+use serde::de::{self, IntoDeserializer};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::types::{Event, EventPayload, EventType};
+
+/// Prefix marking a value as excluded.
+const EXCLUDE_PREFIX: char = '!';
+
+/// Separator between values in the textual syntax.
+const SEPARATOR: char = ',';
+
+/// Errors that can occur when parsing a filter.
+#[derive(Debug, Error)]
+pub enum FilterError {
+    /// A value names an event type that does not exist.
+    #[error("unknown event type: {0}")]
+    UnknownEventType(String),
+
+    /// A value is empty, e.g. a lone `!`.
+    #[error("empty filter value")]
+    EmptyValue,
+
+    /// A JSON filter object is malformed.
+    #[error("invalid filter object: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    /// A JSON filter object was combined with individual filter parameters.
+    #[error("filter cannot be combined with source, type, project or tool parameters")]
+    Conflict,
+}
+
+/// A value that can appear in a [`FilterSet`].
+pub trait FilterValue: Sized {
+    /// Parses a single value (without the `!` prefix).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`FilterError`] if the value is not valid for this field.
+    fn parse_value(value: &str) -> Result<Self, FilterError>;
+
+    /// Returns the textual form of the value.
+    fn as_filter_str(&self) -> &str;
+}
+
+impl FilterValue for String {
+    fn parse_value(value: &str) -> Result<Self, FilterError> {
+        Ok(value.to_string())
+    }
+
+    fn as_filter_str(&self) -> &str {
+        self
+    }
+}
+
+impl FilterValue for EventType {
+    fn parse_value(value: &str) -> Result<Self, FilterError> {
+        let deserializer: de::value::StrDeserializer<'_, de::value::Error> =
+            value.into_deserializer();
+        Self::deserialize(deserializer).map_err(|_| FilterError::UnknownEventType(value.into()))
+    }
+
+    fn as_filter_str(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Included and excluded values for one event field.
+///
+/// An empty set matches everything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterSet<T> {
+    /// Values to include. Empty means every value is included.
+    pub include: Vec<T>,
+
+    /// Values to exclude, applied after `include`.
+    pub exclude: Vec<T>,
+}
+
+/// Glob patterns for source IDs, project names and tool names.
+pub type PatternSet = FilterSet<String>;
+
+/// Event types to include or exclude.
+pub type EventTypeSet = FilterSet<EventType>;
+
+impl<T> Default for FilterSet<T> {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl<T: FilterValue> FilterSet<T> {
+    /// Creates an empty set that matches everything.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses comma-separated values, where a `!` prefix excludes a value.
+    ///
+    /// Blank entries are ignored, so an empty string yields an empty set.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`FilterError`] if a value is empty after its `!` prefix or
+    /// is not valid for this field.
+    pub fn parse(text: &str) -> Result<Self, FilterError> {
+        let mut set = Self::new();
+        set.extend_from_str(text)?;
+        Ok(set)
+    }
+
+    /// Adds a value to include (builder pattern).
+    #[must_use]
+    pub fn with_include(mut self, value: impl Into<T>) -> Self {
+        self.include.push(value.into());
+        self
+    }
+
+    /// Adds a value to exclude (builder pattern).
+    #[must_use]
+    pub fn with_exclude(mut self, value: impl Into<T>) -> Self {
+        self.exclude.push(value.into());
+        self
+    }
+
+    /// Returns `true` if the set places no restriction.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Returns `true` if `matches_value` holds for at least one included
+    /// value (or none are given) and for no excluded value.
+    fn matches_by(&self, matches_value: impl Fn(&T) -> bool) -> bool {
+        (self.include.is_empty() || self.include.iter().any(&matches_value))
+            && !self.exclude.iter().any(&matches_value)
+    }
+
+    fn extend_from_str(&mut self, text: &str) -> Result<(), FilterError> {
+        for entry in text.split(SEPARATOR).map(str::trim) {
+            if entry.is_empty() {
+                continue;
+            }
+            let (negated, value) = match entry.strip_prefix(EXCLUDE_PREFIX) {
+                Some(value) => (true, value.trim()),
+                None => (false, entry),
+            };
+            if value.is_empty() {
+                return Err(FilterError::EmptyValue);
+            }
+            let value = T::parse_value(value)?;
+            if negated {
+                self.exclude.push(value);
+            } else {
+                self.include.push(value);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PatternSet {
+    /// Returns `true` if `value` is allowed by the set.
+    ///
+    /// A missing value is never included by a pattern, so it only matches
+    /// when there are no included patterns.
+    #[must_use]
+    pub fn matches(&self, value: Option<&str>) -> bool {
+        match value {
+            Some(value) => self.matches_by(|pattern| glob_match(pattern, value)),
+            None => self.include.is_empty(),
+        }
+    }
+}
+
+impl EventTypeSet {
+    /// Returns `true` if `event_type` is allowed by the set.
+    #[must_use]
+    pub fn matches(&self, event_type: EventType) -> bool {
+        self.matches_by(|value| *value == event_type)
+    }
+}
+
+impl<T: FilterValue> Serialize for FilterSet<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.include.len() + self.exclude.len()))?;
+        for value in &self.include {
+            seq.serialize_element(value.as_filter_str())?;
+        }
+        for value in &self.exclude {
+            seq.serialize_element(&format!("{EXCLUDE_PREFIX}{}", value.as_filter_str()))?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T: FilterValue> Deserialize<'de> for FilterSet<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// Accepts either a comma-separated string or an array of values.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        let entries = match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(text) => vec![text],
+            OneOrMany::Many(values) => values,
+        };
+
+        let mut set = Self::new();
+        for entry in &entries {
+            set.extend_from_str(entry).map_err(de::Error::custom)?;
+        }
+        Ok(set)
+    }
+}
+
+/// Matches `value` against a pattern in which `*` matches any run of
+/// characters.
+///
+/// # Example
+///
+/// ```
+/// use vibetea_protocol::filter::glob_match;
+///
+/// assert!(glob_match("ci-*", "ci-linux"));
+/// assert!(!glob_match("ci-*", "alice-laptop"));
+/// ```
+#[must_use]
+pub fn glob_match(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // `split` always yields at least one part
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No `*` in the pattern
+        return rest.is_empty();
+    };
+
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Events a subscriber asks to receive, as sent to `/ws`, `/stream` and
+/// `GET /events`.
+///
+/// Each field is a [`FilterSet`]; an event must match every non-empty field.
+/// The tool field only narrows tool events. The server may narrow the filter
+/// further to the subscriber's token scope.
+///
+/// The filter serializes to the JSON filter object accepted in the `filter`
+/// query parameter.
+///
+/// # Example
+///
+/// ```
+/// use vibetea_protocol::filter::SubscriberFilter;
+/// use vibetea_protocol::types::EventType;
+///
+/// // Tool and session events from two projects, skipping CI machines
+/// let filter = SubscriberFilter::new()
+///     .with_event_type(EventType::Tool)
+///     .with_event_type(EventType::Session)
+///     .with_project("web")
+///     .with_project("api")
+///     .without_source("ci-*");
+///
+/// assert_eq!(
+///     serde_json::to_string(&filter).unwrap(),
+///     r#"{"source":["!ci-*"],"type":["tool","session"],"project":["web","api"]}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SubscriberFilter {
+    /// Filter by source ID (monitor identifier).
+    #[serde(skip_serializing_if = "FilterSet::is_empty")]
+    pub source: PatternSet,
+
+    /// Filter by event type.
+    #[serde(rename = "type", skip_serializing_if = "FilterSet::is_empty")]
+    pub event_type: EventTypeSet,
+
+    /// Filter by project name.
+    #[serde(skip_serializing_if = "FilterSet::is_empty")]
+    pub project: PatternSet,
+
+    /// Filter tool events by tool name. Other events are unaffected.
+    #[serde(skip_serializing_if = "FilterSet::is_empty")]
+    pub tool: PatternSet,
+}
+
+impl SubscriberFilter {
+    /// Creates a new empty filter that matches all events.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a source pattern to include (builder pattern).
+    #[must_use]
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = self.source.with_include(source.into());
+        self
+    }
+
+    /// Adds a source pattern to exclude (builder pattern).
+    #[must_use]
+    pub fn without_source(mut self, source: impl Into<String>) -> Self {
+        self.source = self.source.with_exclude(source.into());
+        self
+    }
+
+    /// Adds an event type to include (builder pattern).
+    #[must_use]
+    pub fn with_event_type(mut self, event_type: EventType) -> Self {
+        self.event_type = self.event_type.with_include(event_type);
+        self
+    }
+
+    /// Adds an event type to exclude (builder pattern).
+    #[must_use]
+    pub fn without_event_type(mut self, event_type: EventType) -> Self {
+        self.event_type = self.event_type.with_exclude(event_type);
+        self
+    }
+
+    /// Adds a project pattern to include (builder pattern).
+    #[must_use]
+    pub fn with_project(mut self, project: impl Into<String>) -> Self {
+        self.project = self.project.with_include(project.into());
+        self
+    }
+
+    /// Adds a project pattern to exclude (builder pattern).
+    #[must_use]
+    pub fn without_project(mut self, project: impl Into<String>) -> Self {
+        self.project = self.project.with_exclude(project.into());
+        self
+    }
+
+    /// Adds a tool name pattern to include (builder pattern).
+    #[must_use]
+    pub fn with_tool(mut self, tool: impl Into<String>) -> Self {
+        self.tool = self.tool.with_include(tool.into());
+        self
+    }
+
+    /// Adds a tool name pattern to exclude (builder pattern).
+    #[must_use]
+    pub fn without_tool(mut self, tool: impl Into<String>) -> Self {
+        self.tool = self.tool.with_exclude(tool.into());
+        self
+    }
+
+    /// Returns `true` if the filter places no restriction.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty()
+            && self.event_type.is_empty()
+            && self.project.is_empty()
+            && self.tool.is_empty()
+    }
+
+    /// Checks if an event matches every field of the filter.
+    ///
+    /// The project is read from the event's payload (see
+    /// [`EventPayload::project`]); events without one never match an included
+    /// project. The tool field only applies to tool events.
+    #[must_use]
+    pub fn matches(&self, event: &Event) -> bool {
+        self.matches_source_and_project(&event.source, event.payload.project())
+            && self.event_type.matches(event.event_type)
+            && match &event.payload {
+                EventPayload::Tool { tool, .. } => self.tool.matches(Some(tool)),
+                _ => true,
+            }
+    }
+
+    /// Checks only the source and project fields, for things that are not
+    /// events such as sessions.
+    #[must_use]
+    pub fn matches_source_and_project(&self, source: &str, project: Option<&str>) -> bool {
+        self.source.matches(Some(source)) && self.project.matches(project)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(glob_match("ci-*", "ci-linux"));
+        assert!(glob_match("ci-*", "ci-"));
+        assert!(!glob_match("ci-*", "alice-ci-linux"));
+        assert!(glob_match("*-laptop", "alice-laptop"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxcyyb"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+        assert!(!glob_match("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn parses_includes_and_excludes() {
+        let set = PatternSet::parse(" a , !b,, c* ").unwrap();
+        assert_eq!(set.include, vec!["a".to_string(), "c*".to_string()]);
+        assert_eq!(set.exclude, vec!["b".to_string()]);
+
+        assert!(PatternSet::parse("").unwrap().is_empty());
+        assert!(matches!(
+            PatternSet::parse("a,!"),
+            Err(FilterError::EmptyValue)
+        ));
+    }
+
+    #[test]
+    fn parses_event_types() {
+        let set = EventTypeSet::parse("tool,agent_spawn,!session").unwrap();
+        assert_eq!(set.include, vec![EventType::Tool, EventType::AgentSpawn]);
+        assert_eq!(set.exclude, vec![EventType::Session]);
+
+        assert!(matches!(
+            EventTypeSet::parse("tool,bogus"),
+            Err(FilterError::UnknownEventType(ref t)) if t == "bogus"
+        ));
+    }
+
+    #[test]
+    fn pattern_set_matching() {
+        let set = PatternSet::new()
+            .with_include("ci-*")
+            .with_include("alice-laptop")
+            .with_exclude("ci-flaky");
+
+        assert!(set.matches(Some("ci-linux")));
+        assert!(set.matches(Some("alice-laptop")));
+        assert!(!set.matches(Some("ci-flaky")));
+        assert!(!set.matches(Some("bob-laptop")));
+        assert!(!set.matches(None));
+
+        let exclude_only = PatternSet::new().with_exclude("legacy");
+        assert!(exclude_only.matches(Some("vibetea")));
+        assert!(!exclude_only.matches(Some("legacy")));
+        assert!(exclude_only.matches(None));
+
+        assert!(PatternSet::new().matches(None));
+    }
+
+    #[test]
+    fn event_type_set_matching() {
+        let set = EventTypeSet::parse("!activity").unwrap();
+        assert!(set.matches(EventType::Tool));
+        assert!(!set.matches(EventType::Activity));
+
+        let set = EventTypeSet::parse("tool,session").unwrap();
+        assert!(set.matches(EventType::Session));
+        assert!(!set.matches(EventType::Agent));
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let set: EventTypeSet = serde_json::from_str(r#"["tool", "!session"]"#).unwrap();
+        assert_eq!(set, EventTypeSet::parse("tool,!session").unwrap());
+        assert_eq!(
+            serde_json::to_string(&set).unwrap(),
+            r#"["tool","!session"]"#
+        );
+
+        let set: PatternSet = serde_json::from_str(r#""a*,!b""#).unwrap();
+        assert_eq!(set, PatternSet::parse("a*,!b").unwrap());
+
+        assert!(serde_json::from_str::<EventTypeSet>(r#"["bogus"]"#).is_err());
+        assert!(serde_json::from_str::<PatternSet>("42").is_err());
+    }
+
+    #[test]
+    fn subscriber_filter_round_trip() {
+        let filter = SubscriberFilter::new()
+            .with_event_type(EventType::Tool)
+            .without_tool("Read")
+            .with_source("ci-*");
+
+        let json = serde_json::to_string(&filter).unwrap();
+        assert_eq!(
+            json,
+            r#"{"source":["ci-*"],"type":["tool"],"tool":["!Read"]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<SubscriberFilter>(&json).unwrap(),
+            filter
+        );
+
+        assert!(SubscriberFilter::new().is_empty());
+        assert_eq!(
+            serde_json::to_string(&SubscriberFilter::new()).unwrap(),
+            "{}"
+        );
+        assert!(serde_json::from_str::<SubscriberFilter>(r#"{"bogus": "x"}"#).is_err());
+    }
+
+    #[test]
+    fn subscriber_filter_matches_events() {
+        use crate::types::ToolStatus;
+
+        let tool = |name: &str, project: Option<&str>| {
+            Event::new(
+                "ci-linux".to_string(),
+                EventType::Tool,
+                EventPayload::Tool {
+                    session_id: uuid::Uuid::new_v4(),
+                    tool: name.to_string(),
+                    status: ToolStatus::Completed,
+                    context: None,
+                    project: project.map(str::to_string),
+                },
+            )
+        };
+        let summary = Event::new(
+            "ci-linux".to_string(),
+            EventType::Summary,
+            EventPayload::Summary {
+                session_id: uuid::Uuid::new_v4(),
+                summary: "done".to_string(),
+            },
+        );
+
+        assert!(SubscriberFilter::new().matches(&summary));
+
+        let filter = SubscriberFilter::new()
+            .with_source("ci-*")
+            .with_project("web")
+            .without_tool("Read");
+        assert!(filter.matches(&tool("Bash", Some("web"))));
+        assert!(!filter.matches(&tool("Read", Some("web"))));
+        assert!(!filter.matches(&tool("Bash", Some("api"))));
+        assert!(!filter.matches(&tool("Bash", None)));
+        assert!(!filter.matches(&summary));
+
+        let filter = SubscriberFilter::new().with_tool("Bash");
+        assert!(filter.matches(&summary));
+        assert!(!filter.with_event_type(EventType::Tool).matches(&summary));
+    }
+}
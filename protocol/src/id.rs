@@ -0,0 +1,74 @@
+//! Event ID generation and checking.
+//!
+//! Event IDs are `evt_` followed by 20 alphanumeric characters, for example
+//! `evt_k7m2n9p4q1r6s3t8u5v0`. Monitors generate them with
+//! [`generate_event_id`]; the server rejects events whose ID does not pass
+//! [`is_valid_event_id`].
+
+use rand::Rng;
+
+/// Prefix of every event ID.
+pub const EVENT_ID_PREFIX: &str = "evt_";
+
+/// Number of alphanumeric characters after [`EVENT_ID_PREFIX`].
+pub const EVENT_ID_SUFFIX_LEN: usize = 20;
+
+/// Generates a random event ID.
+///
+/// # Example
+///
+/// ```
+/// use vibetea_protocol::id::{generate_event_id, is_valid_event_id};
+///
+/// let id = generate_event_id();
+/// assert!(id.starts_with("evt_"));
+/// assert!(is_valid_event_id(&id));
+/// ```
+#[must_use]
+pub fn generate_event_id() -> String {
+    const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+    let mut rng = rand::rng();
+    let suffix: String = (0..EVENT_ID_SUFFIX_LEN)
+        .map(|_| {
+            let idx = rng.random_range(0..CHARSET.len());
+            CHARSET[idx] as char
+        })
+        .collect();
+
+    format!("{EVENT_ID_PREFIX}{suffix}")
+}
+
+/// Returns `true` if `id` is [`EVENT_ID_PREFIX`] followed by
+/// [`EVENT_ID_SUFFIX_LEN`] ASCII alphanumeric characters.
+#[must_use]
+pub fn is_valid_event_id(id: &str) -> bool {
+    id.strip_prefix(EVENT_ID_PREFIX).is_some_and(|suffix| {
+        suffix.len() == EVENT_ID_SUFFIX_LEN && suffix.bytes().all(|b| b.is_ascii_alphanumeric())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_ids_have_correct_format() {
+        let id = generate_event_id();
+        assert!(id.starts_with("evt_"));
+        assert_eq!(id.len(), 24); // "evt_" (4) + 20 alphanumeric
+        assert!(id[4..].chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(id, generate_event_id());
+    }
+
+    #[test]
+    fn checks_id_format() {
+        assert!(is_valid_event_id("evt_k7m2n9p4q1r6s3t8u5v0"));
+        assert!(is_valid_event_id("evt_K7M2N9P4Q1R6S3T8U5V0"));
+        assert!(!is_valid_event_id("evt_short"));
+        assert!(!is_valid_event_id("evt_k7m2n9p4q1r6s3t8u5v0x"));
+        assert!(!is_valid_event_id("evt_k7m2n9p4q1r6s3t8u5v-"));
+        assert!(!is_valid_event_id("abc_k7m2n9p4q1r6s3t8u5v0"));
+        assert!(!is_valid_event_id(""));
+    }
+}
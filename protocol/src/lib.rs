@@ -0,0 +1,45 @@
+//! VibeTea Protocol - Wire types shared by monitors, the server and clients.
+//!
+//! This crate owns everything that must agree on both ends of a VibeTea
+//! connection, so the monitor, the server and third-party Rust clients
+//! cannot drift apart:
+//!
+//! - [`types`]: Events, their payloads and the versioned wire format
+//! - [`id`]: Event ID generation and checking
+//! - [`signing`]: The message monitors sign for `POST /events`
+//! - [`filter`]: Filters subscribers use to select events
+//! - [`schema`]: A JSON Schema of the wire format, generated from [`types`]
+//!
+//! # Example
+//!
+//! ```
+//! use uuid::Uuid;
+//! use vibetea_protocol::{Event, EventPayload, EventType, ToolStatus};
+//!
+//! let event = Event::new(
+//!     "macbook-pro".to_string(),
+//!     EventType::Tool,
+//!     EventPayload::Tool {
+//!         session_id: Uuid::new_v4(),
+//!         tool: "Read".to_string(),
+//!         status: ToolStatus::Completed,
+//!         context: None,
+//!         project: Some("vibetea".to_string()),
+//!     },
+//! );
+//!
+//! let json = serde_json::to_string(&event).unwrap();
+//! let parsed: Event = serde_json::from_str(&json).unwrap();
+//! assert_eq!(parsed, event);
+//! ```
+
+pub mod filter;
+pub mod id;
+pub mod schema;
+pub mod signing;
+pub mod types;
+
+pub use types::{
+    Event, EventPayload, EventType, SessionAction, ToolStatus, LEGACY_SCHEMA_VERSION,
+    SCHEMA_VERSION, SUPPORTED_SCHEMA_VERSIONS,
+};
@@ -0,0 +1,37 @@
+//! JSON Schema for the event wire format.
+//!
+//! The schema is generated from the types in [`crate::types`] and describes
+//! events as they are written: [`SCHEMA_VERSION`](crate::types::SCHEMA_VERSION)
+//! envelopes with `type`-tagged payloads. A copy is checked in at
+//! `protocol/schema/event.schema.json` for clients in other languages;
+//! regenerate it with:
+//!
+//! ```sh
+//! cargo run -p vibetea-protocol --example event_schema > protocol/schema/event.schema.json
+//! ```
+
+use schemars::Schema;
+
+use crate::types::Event;
+
+/// Returns the JSON Schema of a single [`Event`].
+///
+/// # Example
+///
+/// ```
+/// let schema = vibetea_protocol::schema::event_schema();
+/// assert_eq!(schema.get("title").unwrap(), "Event");
+/// ```
+#[must_use]
+pub fn event_schema() -> Schema {
+    schemars::schema_for!(Event)
+}
+
+/// Returns [`event_schema`] as pretty-printed JSON with a trailing newline.
+#[must_use]
+pub fn event_schema_json() -> String {
+    let mut json =
+        serde_json::to_string_pretty(&event_schema()).expect("JSON Schema is always serializable");
+    json.push('\n');
+    json
+}
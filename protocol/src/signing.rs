@@ -0,0 +1,48 @@
+//! Request signing for `POST /events`.
+//!
+//! Monitors sign each request with their Ed25519 key. The signature covers
+//! the bytes returned by [`signed_message`], and is sent base64-encoded with
+//! the inputs in these headers:
+//!
+//! - [`HEADER_SOURCE_ID`]: the monitor's source identifier
+//! - [`HEADER_TIMESTAMP`]: Unix time in seconds when the request was signed
+//! - [`HEADER_NONCE`]: a random value unique to the request
+//! - [`HEADER_SIGNATURE`]: the signature
+//!
+//! The server rebuilds the message from the headers and the raw body, so
+//! both sides must produce exactly the same bytes.
+
+/// Header carrying the monitor's source identifier.
+pub const HEADER_SOURCE_ID: &str = "X-Source-ID";
+
+/// Header carrying the base64-encoded Ed25519 signature.
+pub const HEADER_SIGNATURE: &str = "X-Signature";
+
+/// Header carrying the Unix time in seconds at which a request was signed.
+pub const HEADER_TIMESTAMP: &str = "X-Timestamp";
+
+/// Header carrying a random value unique to each request.
+pub const HEADER_NONCE: &str = "X-Nonce";
+
+/// Builds the message signed for a `POST /events` request.
+///
+/// The message is the timestamp and nonce, each followed by a newline, then
+/// the raw request body.
+///
+/// # Example
+///
+/// ```
+/// use vibetea_protocol::signing::signed_message;
+///
+/// assert_eq!(signed_message("1767225600", "3f2a9c", b"[]"), b"1767225600\n3f2a9c\n[]");
+/// ```
+#[must_use]
+pub fn signed_message(timestamp: &str, nonce: &str, body: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(timestamp.len() + nonce.len() + body.len() + 2);
+    message.extend_from_slice(timestamp.as_bytes());
+    message.push(b'\n');
+    message.extend_from_slice(nonce.as_bytes());
+    message.push(b'\n');
+    message.extend_from_slice(body);
+    message
+}
@@ -0,0 +1,1324 @@
+//! Event types for the VibeTea wire format.
+//!
+//! This module defines the events monitors send to the server and the server
+//! broadcasts to clients. All field names serialize to camelCase JSON.
+//!
+//! # Wire Format
+//!
+//! Events are versioned by a `schemaVersion` field on the event envelope:
+//!
+//! - Version 2 ([`SCHEMA_VERSION`]) payloads carry their own `type` tag, so
+//!   each payload is decoded as exactly the variant it names.
+//! - Version 1 ([`LEGACY_SCHEMA_VERSION`]) events have no `schemaVersion`
+//!   and may have untagged payloads. They are read in compatibility mode,
+//!   using the event's `type` as the payload tag.
+//!
+//! Events are always written as version 2.
+//!
+//! # Relayed Events
+//!
+//! When a hub forwards events to another hub (see the server's relay mode),
+//! it appends its own ID to the event's `hops`, leaving the original `source`
+//! untouched. `hops` lists the hubs an event passed through, oldest first, so
+//! a hub can recognise its own events coming back. It is omitted for events
+//! that have not been relayed, and older readers ignore it.
+//!
+//! ```json
+//! {
+//!   "schemaVersion": 2,
+//!   "id": "evt_k7m2n9p4q1r6s3t8u5v0",
+//!   "source": "macbook-pro",
+//!   "timestamp": "2026-02-02T14:30:00Z",
+//!   "type": "tool",
+//!   "payload": {"type": "tool", "sessionId": "...", "tool": "Read", "status": "completed"}
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::id::generate_event_id;
+
+/// Current event wire format version, written on every event.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Version assumed for events without a `schemaVersion` field.
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// Wire format versions this crate can read.
+pub const SUPPORTED_SCHEMA_VERSIONS: [u32; 2] = [LEGACY_SCHEMA_VERSION, SCHEMA_VERSION];
+
+/// The type of event being transmitted.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    Session,
+    Activity,
+    Tool,
+    Agent,
+    Summary,
+    Error,
+    AgentSpawn,
+    SkillInvocation,
+    TokenUsage,
+    SessionMetrics,
+    ActivityPattern,
+    ModelDistribution,
+    TodoProgress,
+    FileChange,
+    ProjectActivity,
+}
+
+impl EventType {
+    /// Every event type, in declaration order.
+    pub const ALL: [Self; 15] = [
+        Self::Session,
+        Self::Activity,
+        Self::Tool,
+        Self::Agent,
+        Self::Summary,
+        Self::Error,
+        Self::AgentSpawn,
+        Self::SkillInvocation,
+        Self::TokenUsage,
+        Self::SessionMetrics,
+        Self::ActivityPattern,
+        Self::ModelDistribution,
+        Self::TodoProgress,
+        Self::FileChange,
+        Self::ProjectActivity,
+    ];
+
+    /// Returns the wire name of the event type (as used in the `type` field).
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Session => "session",
+            Self::Activity => "activity",
+            Self::Tool => "tool",
+            Self::Agent => "agent",
+            Self::Summary => "summary",
+            Self::Error => "error",
+            Self::AgentSpawn => "agent_spawn",
+            Self::SkillInvocation => "skill_invocation",
+            Self::TokenUsage => "token_usage",
+            Self::SessionMetrics => "session_metrics",
+            Self::ActivityPattern => "activity_pattern",
+            Self::ModelDistribution => "model_distribution",
+            Self::TodoProgress => "todo_progress",
+            Self::FileChange => "file_change",
+            Self::ProjectActivity => "project_activity",
+        }
+    }
+}
+
+/// Action performed on a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionAction {
+    Started,
+    Ended,
+}
+
+/// Status of a tool invocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolStatus {
+    Started,
+    Completed,
+}
+
+/// Event tracking Task tool agent spawns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentSpawnEvent {
+    /// The session in which the agent was spawned.
+    pub session_id: String,
+    /// Type of agent (e.g., "task", "background").
+    pub agent_type: String,
+    /// Description of the agent's task.
+    pub description: String,
+    /// When the agent was spawned.
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Event tracking skill/slash command invocations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillInvocationEvent {
+    /// The session in which the skill was invoked.
+    pub session_id: String,
+    /// Name of the skill (e.g., "commit", "review-pr").
+    pub skill_name: String,
+    /// Project context for the skill invocation.
+    pub project: String,
+    /// When the skill was invoked.
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Event tracking per-model token consumption.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsageEvent {
+    /// The model that consumed the tokens.
+    pub model: String,
+    /// Number of input tokens consumed.
+    pub input_tokens: u64,
+    /// Number of output tokens generated.
+    pub output_tokens: u64,
+    /// Number of tokens read from cache.
+    pub cache_read_tokens: u64,
+    /// Number of tokens written to cache.
+    pub cache_creation_tokens: u64,
+}
+
+/// Event tracking global session metrics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMetricsEvent {
+    /// Total number of sessions tracked.
+    pub total_sessions: u64,
+    /// Total number of messages across all sessions.
+    pub total_messages: u64,
+    /// Total number of tool invocations.
+    pub total_tool_usage: u64,
+    /// Identifier of the longest session.
+    pub longest_session: String,
+}
+
+/// Event tracking hourly activity distribution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityPatternEvent {
+    /// Map of hour (0-23 as string) to activity count.
+    /// Hours are stored as strings for JSON compatibility.
+    pub hour_counts: HashMap<String, u64>,
+}
+
+/// Summary of token usage for a specific model.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsageSummary {
+    /// Number of input tokens consumed.
+    pub input_tokens: u64,
+    /// Number of output tokens generated.
+    pub output_tokens: u64,
+    /// Number of tokens read from cache.
+    pub cache_read_tokens: u64,
+    /// Number of tokens written to cache.
+    pub cache_creation_tokens: u64,
+}
+
+/// Event tracking usage distribution across models.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelDistributionEvent {
+    /// Map of model name to token usage summary.
+    pub model_usage: HashMap<String, TokenUsageSummary>,
+}
+
+/// Event tracking todo list progress per session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoProgressEvent {
+    /// The session being tracked.
+    pub session_id: String,
+    /// Number of completed todo items.
+    pub completed: u32,
+    /// Number of in-progress todo items.
+    pub in_progress: u32,
+    /// Number of pending todo items.
+    pub pending: u32,
+    /// Whether the todo list was abandoned.
+    pub abandoned: bool,
+}
+
+/// Event tracking file edit history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChangeEvent {
+    /// The session in which the file was changed.
+    pub session_id: String,
+    /// Hash identifying the file (for privacy).
+    pub file_hash: String,
+    /// Version number of this change.
+    pub version: u32,
+    /// Number of lines added.
+    pub lines_added: u32,
+    /// Number of lines removed.
+    pub lines_removed: u32,
+    /// Number of lines modified.
+    pub lines_modified: u32,
+    /// When the file was changed.
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Event tracking project activity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectActivityEvent {
+    /// Path to the project.
+    pub project_path: String,
+    /// The active session in this project.
+    pub session_id: String,
+    /// Whether the project is currently active.
+    pub is_active: bool,
+}
+
+/// Type-specific payload for events.
+///
+/// Each variant corresponds to an [`EventType`] and contains the relevant data
+/// for that event type. The payload is tagged with a `type` field naming its
+/// variant, so variants may be declared in any order. Field names use
+/// `camelCase` to match the JSON API contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventPayload {
+    /// Tool invocation events.
+    ///
+    /// Requires `session_id`, `tool`, and `status`.
+    #[serde(rename_all = "camelCase")]
+    Tool {
+        session_id: Uuid,
+        tool: String,
+        status: ToolStatus,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        context: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        project: Option<String>,
+    },
+
+    /// Session lifecycle events (start/end).
+    ///
+    /// Requires `session_id`, `action`, and `project`.
+    #[serde(rename_all = "camelCase")]
+    Session {
+        session_id: Uuid,
+        action: SessionAction,
+        project: String,
+    },
+
+    /// Session summary events (marks session end).
+    ///
+    /// Requires `session_id` and `summary`.
+    #[serde(rename_all = "camelCase")]
+    Summary { session_id: Uuid, summary: String },
+
+    /// Agent state change events.
+    ///
+    /// Requires `session_id` and `state`.
+    #[serde(rename_all = "camelCase")]
+    Agent { session_id: Uuid, state: String },
+
+    /// Error events for monitoring purposes.
+    ///
+    /// Requires `session_id` and `category`.
+    #[serde(rename_all = "camelCase")]
+    Error { session_id: Uuid, category: String },
+
+    /// File change tracking events.
+    ///
+    /// Very specific: has `version`, `lines_added`, `lines_removed`, `lines_modified`, `file_hash`.
+    FileChange(FileChangeEvent),
+
+    /// Agent spawn tracking events.
+    ///
+    /// Has `agent_type` and `description` fields.
+    AgentSpawn(AgentSpawnEvent),
+
+    /// Skill invocation tracking events.
+    ///
+    /// Has `skill_name` field.
+    SkillInvocation(SkillInvocationEvent),
+
+    /// Token usage tracking events.
+    ///
+    /// Has `model` and various token count fields.
+    TokenUsage(TokenUsageEvent),
+
+    /// Session metrics tracking events.
+    ///
+    /// Has `total_*` fields and `longest_session`.
+    SessionMetrics(SessionMetricsEvent),
+
+    /// Model distribution tracking events.
+    ///
+    /// Has `model_usage` HashMap.
+    ModelDistribution(ModelDistributionEvent),
+
+    /// Todo progress tracking events.
+    ///
+    /// Has `completed`, `pending`, `in_progress`, `abandoned` fields.
+    TodoProgress(TodoProgressEvent),
+
+    /// Activity pattern tracking events.
+    ///
+    /// Has `hour_counts` HashMap.
+    ActivityPattern(ActivityPatternEvent),
+
+    /// Project activity tracking events.
+    ///
+    /// Has `project_path` and `is_active` fields.
+    ProjectActivity(ProjectActivityEvent),
+
+    /// Activity heartbeat events.
+    ///
+    /// Requires only `session_id`.
+    #[serde(rename_all = "camelCase")]
+    Activity {
+        session_id: Uuid,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        project: Option<String>,
+    },
+}
+
+impl EventPayload {
+    /// Returns the event type this payload variant belongs to.
+    #[must_use]
+    pub const fn event_type(&self) -> EventType {
+        match self {
+            Self::Tool { .. } => EventType::Tool,
+            Self::Session { .. } => EventType::Session,
+            Self::Summary { .. } => EventType::Summary,
+            Self::Agent { .. } => EventType::Agent,
+            Self::Error { .. } => EventType::Error,
+            Self::FileChange(_) => EventType::FileChange,
+            Self::AgentSpawn(_) => EventType::AgentSpawn,
+            Self::SkillInvocation(_) => EventType::SkillInvocation,
+            Self::TokenUsage(_) => EventType::TokenUsage,
+            Self::SessionMetrics(_) => EventType::SessionMetrics,
+            Self::ModelDistribution(_) => EventType::ModelDistribution,
+            Self::TodoProgress(_) => EventType::TodoProgress,
+            Self::ActivityPattern(_) => EventType::ActivityPattern,
+            Self::ProjectActivity(_) => EventType::ProjectActivity,
+            Self::Activity { .. } => EventType::Activity,
+        }
+    }
+
+    /// Returns the project this payload belongs to, if it names one.
+    ///
+    /// Session and skill invocation payloads always name a project; tool and
+    /// activity payloads may. Other payloads have no project.
+    #[must_use]
+    pub fn project(&self) -> Option<&str> {
+        match self {
+            Self::Session { project, .. } => Some(project.as_str()),
+            Self::Tool { project, .. } | Self::Activity { project, .. } => project.as_deref(),
+            Self::SkillInvocation(event) => Some(event.project.as_str()),
+            Self::Agent { .. }
+            | Self::Summary { .. }
+            | Self::Error { .. }
+            | Self::FileChange(_)
+            | Self::AgentSpawn(_)
+            | Self::TokenUsage(_)
+            | Self::SessionMetrics(_)
+            | Self::ModelDistribution(_)
+            | Self::TodoProgress(_)
+            | Self::ActivityPattern(_)
+            | Self::ProjectActivity(_) => None,
+        }
+    }
+}
+
+/// An event flowing through the VibeTea system.
+///
+/// Events are the core data unit and are immutable once created. Each event
+/// has a unique ID, originates from a specific source (monitor), and contains
+/// a type-specific payload.
+///
+/// # Event ID Format
+///
+/// Event IDs follow the format: `evt_` + 20 alphanumeric characters.
+/// Example: `evt_a1b2c3d4e5f6g7h8i9j0`
+///
+/// # Serialization
+///
+/// Events are serialized in the version 2 wire format and deserialized from
+/// either version (see the [module documentation](self)).
+///
+/// # Example
+///
+/// ```
+/// use vibetea_protocol::types::{Event, EventType, EventPayload, ToolStatus};
+/// use chrono::Utc;
+/// use uuid::Uuid;
+///
+/// let event = Event::new(
+///     "macbook-pro".to_string(),
+///     EventType::Tool,
+///     EventPayload::Tool {
+///         session_id: Uuid::new_v4(),
+///         tool: "Read".to_string(),
+///         status: ToolStatus::Completed,
+///         context: Some("main.rs".to_string()),
+///         project: Some("vibetea".to_string()),
+///     },
+/// )
+/// .with_id("evt_k7m2n9p4q1r6s3t8u5v0")
+/// .with_timestamp(Utc::now());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// Unique event identifier (evt_ + 20 alphanumeric chars).
+    pub id: String,
+
+    /// Monitor identifier (hostname or custom ID).
+    pub source: String,
+
+    /// RFC 3339 UTC timestamp.
+    pub timestamp: DateTime<Utc>,
+
+    /// The type of event.
+    pub event_type: EventType,
+
+    /// Type-specific event payload.
+    pub payload: EventPayload,
+
+    /// IDs of the hubs that relayed the event, oldest first. Empty for
+    /// events received directly from their monitor.
+    pub hops: Vec<String>,
+}
+
+impl Event {
+    /// Creates a new event with a randomly generated ID, timestamped now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use uuid::Uuid;
+    /// use vibetea_protocol::types::{Event, EventType, EventPayload, SessionAction};
+    ///
+    /// let event = Event::new(
+    ///     "monitor-1".to_string(),
+    ///     EventType::Session,
+    ///     EventPayload::Session {
+    ///         session_id: Uuid::new_v4(),
+    ///         action: SessionAction::Started,
+    ///         project: "my-project".to_string(),
+    ///     },
+    /// );
+    ///
+    /// assert!(event.id.starts_with("evt_"));
+    /// assert_eq!(event.id.len(), 24); // "evt_" + 20 chars
+    /// ```
+    #[must_use]
+    pub fn new(source: String, event_type: EventType, payload: EventPayload) -> Self {
+        Self {
+            id: generate_event_id(),
+            source,
+            timestamp: Utc::now(),
+            event_type,
+            payload,
+            hops: Vec::new(),
+        }
+    }
+
+    /// Replaces the generated event ID.
+    #[must_use]
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Replaces the event's timestamp.
+    #[must_use]
+    pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Sets the hubs the event passed through, oldest first.
+    #[must_use]
+    pub fn with_hops(mut self, hops: Vec<String>) -> Self {
+        self.hops = hops;
+        self
+    }
+
+    /// Returns `true` if the event passed through the hub `hub_id`.
+    #[must_use]
+    pub fn relayed_by(&self, hub_id: &str) -> bool {
+        self.hops.iter().any(|hop| hop == hub_id)
+    }
+}
+
+// Borrowed view of an `Event` in the version 2 wire format. It also
+// describes `Event` in the generated JSON Schema, so the field docs below
+// are part of the published schema.
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[schemars(
+    rename = "Event",
+    description = "An event flowing through the VibeTea system."
+)]
+struct WireEventRef<'a> {
+    /// Wire format version of the event (2).
+    schema_version: u32,
+    /// Unique event identifier (`evt_` + 20 alphanumeric chars).
+    id: &'a str,
+    /// Monitor identifier (hostname or custom ID).
+    source: &'a str,
+    /// RFC 3339 UTC timestamp.
+    timestamp: &'a DateTime<Utc>,
+    /// The type of event, matching the payload's `type`.
+    #[serde(rename = "type")]
+    event_type: EventType,
+    /// Type-specific event payload.
+    payload: &'a EventPayload,
+    /// IDs of the hubs that relayed the event, oldest first.
+    #[serde(default, skip_serializing_if = "<[String]>::is_empty")]
+    hops: &'a [String],
+}
+
+/// An [`Event`] as received, before its payload is decoded.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireEvent {
+    #[serde(default)]
+    schema_version: Option<u32>,
+    id: String,
+    source: String,
+    timestamp: DateTime<Utc>,
+    #[serde(rename = "type")]
+    event_type: EventType,
+    payload: serde_json::Value,
+    #[serde(default)]
+    hops: Vec<String>,
+}
+
+impl Serialize for Event {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        WireEventRef {
+            schema_version: SCHEMA_VERSION,
+            id: &self.id,
+            source: &self.source,
+            timestamp: &self.timestamp,
+            event_type: self.event_type,
+            payload: &self.payload,
+            hops: &self.hops,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl JsonSchema for Event {
+    fn schema_name() -> Cow<'static, str> {
+        "Event".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        WireEventRef::json_schema(generator)
+    }
+}
+
+impl<'de> Deserialize<'de> for Event {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = WireEvent::deserialize(deserializer)?;
+        let mut payload = wire.payload;
+
+        match wire.schema_version.unwrap_or(LEGACY_SCHEMA_VERSION) {
+            LEGACY_SCHEMA_VERSION => {
+                // Version 1 payloads may be untagged; the event type says
+                // which variant they are
+                if let Some(fields) = payload.as_object_mut() {
+                    fields
+                        .entry("type")
+                        .or_insert_with(|| wire.event_type.as_str().into());
+                }
+            }
+            SCHEMA_VERSION => {
+                if payload.get("type").is_none() {
+                    return Err(de::Error::custom(format_args!(
+                        "payload must have a `type` field in schemaVersion {SCHEMA_VERSION}"
+                    )));
+                }
+            }
+            other => {
+                return Err(de::Error::custom(format_args!(
+                    "unsupported schemaVersion {other}"
+                )));
+            }
+        }
+
+        let payload = EventPayload::deserialize(payload).map_err(de::Error::custom)?;
+        Ok(Self {
+            id: wire.id,
+            source: wire.source,
+            timestamp: wire.timestamp,
+            event_type: wire.event_type,
+            payload,
+            hops: wire.hops,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_type_serialization() {
+        assert_eq!(
+            serde_json::to_string(&EventType::Session).unwrap(),
+            r#""session""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::Activity).unwrap(),
+            r#""activity""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::Tool).unwrap(),
+            r#""tool""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::Agent).unwrap(),
+            r#""agent""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::Summary).unwrap(),
+            r#""summary""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::Error).unwrap(),
+            r#""error""#
+        );
+        // New event types
+        assert_eq!(
+            serde_json::to_string(&EventType::AgentSpawn).unwrap(),
+            r#""agent_spawn""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::SkillInvocation).unwrap(),
+            r#""skill_invocation""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::TokenUsage).unwrap(),
+            r#""token_usage""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::SessionMetrics).unwrap(),
+            r#""session_metrics""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::ActivityPattern).unwrap(),
+            r#""activity_pattern""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::ModelDistribution).unwrap(),
+            r#""model_distribution""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::TodoProgress).unwrap(),
+            r#""todo_progress""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::FileChange).unwrap(),
+            r#""file_change""#
+        );
+        assert_eq!(
+            serde_json::to_string(&EventType::ProjectActivity).unwrap(),
+            r#""project_activity""#
+        );
+    }
+
+    #[test]
+    fn test_event_type_deserialization() {
+        assert_eq!(
+            serde_json::from_str::<EventType>(r#""session""#).unwrap(),
+            EventType::Session
+        );
+        assert_eq!(
+            serde_json::from_str::<EventType>(r#""tool""#).unwrap(),
+            EventType::Tool
+        );
+    }
+
+    #[test]
+    fn test_event_type_as_str_matches_serde() {
+        for event_type in EventType::ALL {
+            assert_eq!(
+                serde_json::to_string(&event_type).unwrap(),
+                format!("\"{}\"", event_type.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_session_action_serialization() {
+        assert_eq!(
+            serde_json::to_string(&SessionAction::Started).unwrap(),
+            r#""started""#
+        );
+        assert_eq!(
+            serde_json::to_string(&SessionAction::Ended).unwrap(),
+            r#""ended""#
+        );
+    }
+
+    #[test]
+    fn test_tool_status_serialization() {
+        assert_eq!(
+            serde_json::to_string(&ToolStatus::Started).unwrap(),
+            r#""started""#
+        );
+        assert_eq!(
+            serde_json::to_string(&ToolStatus::Completed).unwrap(),
+            r#""completed""#
+        );
+    }
+
+    #[test]
+    fn test_event_serialization_tool() {
+        let session_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
+        let timestamp = DateTime::parse_from_rfc3339("2026-02-02T14:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let event = Event {
+            id: "evt_k7m2n9p4q1r6s3t8u5v0".to_string(),
+            source: "macbook-pro".to_string(),
+            timestamp,
+            event_type: EventType::Tool,
+            payload: EventPayload::Tool {
+                session_id,
+                tool: "Read".to_string(),
+                status: ToolStatus::Completed,
+                context: Some("main.rs".to_string()),
+                project: Some("vibetea".to_string()),
+            },
+            hops: Vec::new(),
+        };
+
+        let json = serde_json::to_string_pretty(&event).unwrap();
+
+        // Verify the JSON structure matches the expected format
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["id"], "evt_k7m2n9p4q1r6s3t8u5v0");
+        assert_eq!(parsed["source"], "macbook-pro");
+        assert_eq!(parsed["type"], "tool");
+        assert_eq!(
+            parsed["payload"]["sessionId"],
+            "550e8400-e29b-41d4-a716-446655440000"
+        );
+        assert_eq!(parsed["payload"]["tool"], "Read");
+        assert_eq!(parsed["payload"]["status"], "completed");
+        assert_eq!(parsed["payload"]["context"], "main.rs");
+        assert_eq!(parsed["payload"]["project"], "vibetea");
+    }
+
+    #[test]
+    fn test_event_serialization_session() {
+        let session_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
+        let timestamp = DateTime::parse_from_rfc3339("2026-02-02T14:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let event = Event {
+            id: "evt_a1b2c3d4e5f6g7h8i9j0".to_string(),
+            source: "macbook-pro".to_string(),
+            timestamp,
+            event_type: EventType::Session,
+            payload: EventPayload::Session {
+                session_id,
+                action: SessionAction::Started,
+                project: "vibetea".to_string(),
+            },
+            hops: Vec::new(),
+        };
+
+        let json = serde_json::to_string_pretty(&event).unwrap();
+
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["type"], "session");
+        assert_eq!(parsed["payload"]["action"], "started");
+        assert_eq!(parsed["payload"]["project"], "vibetea");
+    }
+
+    #[test]
+    fn test_event_deserialization_from_json() {
+        let json = r#"{
+            "id": "evt_k7m2n9p4q1r6s3t8u5v0",
+            "source": "macbook-pro",
+            "timestamp": "2026-02-02T14:30:00Z",
+            "type": "tool",
+            "payload": {
+                "sessionId": "550e8400-e29b-41d4-a716-446655440000",
+                "tool": "Read",
+                "status": "completed",
+                "context": "main.rs",
+                "project": "vibetea"
+            }
+        }"#;
+
+        let event: Event = serde_json::from_str(json).unwrap();
+        assert_eq!(event.id, "evt_k7m2n9p4q1r6s3t8u5v0");
+        assert_eq!(event.source, "macbook-pro");
+        assert_eq!(event.event_type, EventType::Tool);
+
+        if let EventPayload::Tool {
+            session_id,
+            tool,
+            status,
+            context,
+            project,
+        } = event.payload
+        {
+            assert_eq!(
+                session_id,
+                Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap()
+            );
+            assert_eq!(tool, "Read");
+            assert_eq!(status, ToolStatus::Completed);
+            assert_eq!(context, Some("main.rs".to_string()));
+            assert_eq!(project, Some("vibetea".to_string()));
+        } else {
+            panic!("Expected Tool payload");
+        }
+    }
+
+    #[test]
+    fn test_event_serializes_schema_version_and_tagged_payload() {
+        let event = Event {
+            id: "evt_k7m2n9p4q1r6s3t8u5v0".to_string(),
+            source: "macbook-pro".to_string(),
+            timestamp: Utc::now(),
+            event_type: EventType::Agent,
+            payload: EventPayload::Agent {
+                session_id: Uuid::new_v4(),
+                state: "thinking".to_string(),
+            },
+            hops: Vec::new(),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["schemaVersion"], SCHEMA_VERSION);
+        assert_eq!(json["type"], "agent");
+        assert_eq!(json["payload"]["type"], "agent");
+        assert_eq!(json["payload"]["state"], "thinking");
+    }
+
+    #[test]
+    fn test_legacy_payload_is_read_by_event_type() {
+        // A version 1 summary payload. Without the event type, its fields
+        // would also satisfy the activity variant.
+        let json = r#"{
+            "id": "evt_k7m2n9p4q1r6s3t8u5v0",
+            "source": "macbook-pro",
+            "timestamp": "2026-02-02T14:30:00Z",
+            "type": "summary",
+            "payload": {
+                "sessionId": "550e8400-e29b-41d4-a716-446655440000",
+                "summary": "Refactored the parser"
+            }
+        }"#;
+
+        let event: Event = serde_json::from_str(json).unwrap();
+        assert!(matches!(event.payload, EventPayload::Summary { .. }));
+
+        // A version 1 event whose payload does not fit its type is rejected
+        // rather than read as another variant
+        let json = json.replace(r#""type": "summary""#, r#""type": "tool""#);
+        assert!(serde_json::from_str::<Event>(&json).is_err());
+    }
+
+    #[test]
+    fn test_schema_version_2_requires_tagged_payload() {
+        let event = |payload: &str| {
+            format!(
+                r#"{{
+                    "schemaVersion": 2,
+                    "id": "evt_k7m2n9p4q1r6s3t8u5v0",
+                    "source": "macbook-pro",
+                    "timestamp": "2026-02-02T14:30:00Z",
+                    "type": "activity",
+                    "payload": {payload}
+                }}"#
+            )
+        };
+
+        let tagged =
+            event(r#"{"type": "activity", "sessionId": "550e8400-e29b-41d4-a716-446655440000"}"#);
+        let parsed: Event = serde_json::from_str(&tagged).unwrap();
+        assert!(matches!(parsed.payload, EventPayload::Activity { .. }));
+
+        let untagged = event(r#"{"sessionId": "550e8400-e29b-41d4-a716-446655440000"}"#);
+        let err = serde_json::from_str::<Event>(&untagged).unwrap_err();
+        assert!(err.to_string().contains("`type`"));
+
+        // Unknown payload types are not swallowed by the activity variant
+        let unknown = event(
+            r#"{"type": "new_tracker", "sessionId": "550e8400-e29b-41d4-a716-446655440000"}"#,
+        );
+        assert!(serde_json::from_str::<Event>(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_schema_version_is_rejected() {
+        let json = r#"{
+            "schemaVersion": 3,
+            "id": "evt_k7m2n9p4q1r6s3t8u5v0",
+            "source": "macbook-pro",
+            "timestamp": "2026-02-02T14:30:00Z",
+            "type": "activity",
+            "payload": {"type": "activity", "sessionId": "550e8400-e29b-41d4-a716-446655440000"}
+        }"#;
+
+        let err = serde_json::from_str::<Event>(json).unwrap_err();
+        assert!(err.to_string().contains("unsupported schemaVersion 3"));
+    }
+
+    #[test]
+    fn test_optional_fields_omitted_when_none() {
+        let session_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
+
+        let payload = EventPayload::Activity {
+            session_id,
+            project: None,
+        };
+
+        let json = serde_json::to_string(&payload).unwrap();
+        assert!(!json.contains("project"));
+    }
+
+    #[test]
+    fn test_hops_omitted_until_relayed() {
+        let mut event = Event::new(
+            "macbook-pro".to_string(),
+            EventType::Agent,
+            EventPayload::Agent {
+                session_id: Uuid::new_v4(),
+                state: "idle".to_string(),
+            },
+        );
+        let json = serde_json::to_value(&event).unwrap();
+        assert!(json.get("hops").is_none());
+        assert!(!event.relayed_by("office-berlin"));
+
+        event.hops = vec!["office-berlin".to_string(), "eu".to_string()];
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["hops"], serde_json::json!(["office-berlin", "eu"]));
+        assert_eq!(json["source"], "macbook-pro");
+
+        let roundtrip: Event = serde_json::from_value(json).unwrap();
+        assert_eq!(roundtrip, event);
+        assert!(roundtrip.relayed_by("office-berlin"));
+        assert!(!roundtrip.relayed_by("us"));
+    }
+
+    #[test]
+    fn test_roundtrip_all_event_types() {
+        let session_id = Uuid::new_v4();
+        let timestamp = Utc::now();
+
+        let payloads = vec![
+            EventPayload::Session {
+                session_id,
+                action: SessionAction::Started,
+                project: "test".to_string(),
+            },
+            EventPayload::Activity {
+                session_id,
+                project: Some("test".to_string()),
+            },
+            EventPayload::Tool {
+                session_id,
+                tool: "Write".to_string(),
+                status: ToolStatus::Started,
+                context: None,
+                project: None,
+            },
+            EventPayload::Agent {
+                session_id,
+                state: "thinking".to_string(),
+            },
+            EventPayload::Summary {
+                session_id,
+                summary: "Completed refactoring".to_string(),
+            },
+            EventPayload::Error {
+                session_id,
+                category: "network".to_string(),
+            },
+            // New event types
+            EventPayload::FileChange(FileChangeEvent {
+                session_id: session_id.to_string(),
+                file_hash: "abc123".to_string(),
+                version: 1,
+                lines_added: 10,
+                lines_removed: 5,
+                lines_modified: 3,
+                timestamp,
+            }),
+            EventPayload::AgentSpawn(AgentSpawnEvent {
+                session_id: session_id.to_string(),
+                agent_type: "task".to_string(),
+                description: "Running tests".to_string(),
+                timestamp,
+            }),
+            EventPayload::SkillInvocation(SkillInvocationEvent {
+                session_id: session_id.to_string(),
+                skill_name: "commit".to_string(),
+                project: "vibetea".to_string(),
+                timestamp,
+            }),
+            EventPayload::TokenUsage(TokenUsageEvent {
+                model: "claude-3".to_string(),
+                input_tokens: 1000,
+                output_tokens: 500,
+                cache_read_tokens: 200,
+                cache_creation_tokens: 100,
+            }),
+            EventPayload::SessionMetrics(SessionMetricsEvent {
+                total_sessions: 10,
+                total_messages: 100,
+                total_tool_usage: 50,
+                longest_session: "2h 30m".to_string(),
+            }),
+            EventPayload::ModelDistribution(ModelDistributionEvent {
+                model_usage: {
+                    let mut map = HashMap::new();
+                    map.insert(
+                        "claude-3".to_string(),
+                        TokenUsageSummary {
+                            input_tokens: 5000,
+                            output_tokens: 2500,
+                            cache_read_tokens: 1000,
+                            cache_creation_tokens: 500,
+                        },
+                    );
+                    map
+                },
+            }),
+            EventPayload::TodoProgress(TodoProgressEvent {
+                session_id: session_id.to_string(),
+                completed: 5,
+                in_progress: 2,
+                pending: 3,
+                abandoned: false,
+            }),
+            EventPayload::ActivityPattern(ActivityPatternEvent {
+                hour_counts: {
+                    let mut map = HashMap::new();
+                    map.insert("9".to_string(), 10);
+                    map.insert("14".to_string(), 25);
+                    map
+                },
+            }),
+            EventPayload::ProjectActivity(ProjectActivityEvent {
+                project_path: "/home/user/project".to_string(),
+                session_id: session_id.to_string(),
+                is_active: true,
+            }),
+        ];
+
+        for (i, payload) in payloads.into_iter().enumerate() {
+            let event = Event {
+                id: format!("evt_test{:0>19}", i),
+                source: "test".to_string(),
+                timestamp,
+                event_type: payload.event_type(),
+                payload,
+                hops: Vec::new(),
+            };
+
+            let json = serde_json::to_string(&event).unwrap();
+            let roundtrip: Event = serde_json::from_str(&json).unwrap();
+            assert_eq!(event, roundtrip);
+        }
+    }
+
+    #[test]
+    fn event_new_generates_valid_id() {
+        let event = Event::new(
+            "test".to_string(),
+            EventType::Activity,
+            EventPayload::Activity {
+                session_id: Uuid::new_v4(),
+                project: None,
+            },
+        );
+
+        assert!(event.id.starts_with("evt_"));
+        assert_eq!(event.id.len(), 24);
+    }
+
+    #[test]
+    fn agent_spawn_event_serializes_with_camel_case() {
+        let event = AgentSpawnEvent {
+            session_id: "sess_123".to_string(),
+            agent_type: "task".to_string(),
+            description: "Run unit tests".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["sessionId"], "sess_123");
+        assert_eq!(json["agentType"], "task");
+        assert_eq!(json["description"], "Run unit tests");
+        assert!(json.get("timestamp").is_some());
+    }
+
+    #[test]
+    fn skill_invocation_event_serializes_with_camel_case() {
+        let event = SkillInvocationEvent {
+            session_id: "sess_456".to_string(),
+            skill_name: "commit".to_string(),
+            project: "my-project".to_string(),
+            timestamp: DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["sessionId"], "sess_456");
+        assert_eq!(json["skillName"], "commit");
+        assert_eq!(json["project"], "my-project");
+    }
+
+    #[test]
+    fn token_usage_event_serializes_with_camel_case() {
+        let event = TokenUsageEvent {
+            model: "claude-3-opus".to_string(),
+            input_tokens: 1000,
+            output_tokens: 500,
+            cache_read_tokens: 200,
+            cache_creation_tokens: 100,
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["model"], "claude-3-opus");
+        assert_eq!(json["inputTokens"], 1000);
+        assert_eq!(json["outputTokens"], 500);
+        assert_eq!(json["cacheReadTokens"], 200);
+        assert_eq!(json["cacheCreationTokens"], 100);
+    }
+
+    #[test]
+    fn session_metrics_event_serializes_with_camel_case() {
+        let event = SessionMetricsEvent {
+            total_sessions: 42,
+            total_messages: 1234,
+            total_tool_usage: 567,
+            longest_session: "sess_longest".to_string(),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["totalSessions"], 42);
+        assert_eq!(json["totalMessages"], 1234);
+        assert_eq!(json["totalToolUsage"], 567);
+        assert_eq!(json["longestSession"], "sess_longest");
+    }
+
+    #[test]
+    fn activity_pattern_event_serializes_with_camel_case() {
+        let mut hour_counts = HashMap::new();
+        hour_counts.insert("9".to_string(), 15);
+        hour_counts.insert("14".to_string(), 25);
+        hour_counts.insert("17".to_string(), 10);
+
+        let event = ActivityPatternEvent { hour_counts };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert!(json.get("hourCounts").is_some());
+        let counts = &json["hourCounts"];
+        assert_eq!(counts["9"], 15);
+        assert_eq!(counts["14"], 25);
+        assert_eq!(counts["17"], 10);
+    }
+
+    #[test]
+    fn model_distribution_event_serializes_with_camel_case() {
+        let mut model_usage = HashMap::new();
+        model_usage.insert(
+            "claude-3-opus".to_string(),
+            TokenUsageSummary {
+                input_tokens: 5000,
+                output_tokens: 2500,
+                cache_read_tokens: 1000,
+                cache_creation_tokens: 500,
+            },
+        );
+        model_usage.insert(
+            "claude-3-sonnet".to_string(),
+            TokenUsageSummary {
+                input_tokens: 3000,
+                output_tokens: 1500,
+                cache_read_tokens: 600,
+                cache_creation_tokens: 300,
+            },
+        );
+
+        let event = ModelDistributionEvent { model_usage };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert!(json.get("modelUsage").is_some());
+        let opus = &json["modelUsage"]["claude-3-opus"];
+        assert_eq!(opus["inputTokens"], 5000);
+        assert_eq!(opus["outputTokens"], 2500);
+    }
+
+    #[test]
+    fn todo_progress_event_serializes_with_camel_case() {
+        let event = TodoProgressEvent {
+            session_id: "sess_789".to_string(),
+            completed: 5,
+            in_progress: 2,
+            pending: 3,
+            abandoned: false,
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["sessionId"], "sess_789");
+        assert_eq!(json["completed"], 5);
+        assert_eq!(json["inProgress"], 2);
+        assert_eq!(json["pending"], 3);
+        assert_eq!(json["abandoned"], false);
+    }
+
+    #[test]
+    fn file_change_event_serializes_with_camel_case() {
+        let event = FileChangeEvent {
+            session_id: "sess_abc".to_string(),
+            file_hash: "sha256_abc123".to_string(),
+            version: 3,
+            lines_added: 50,
+            lines_removed: 20,
+            lines_modified: 15,
+            timestamp: DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["sessionId"], "sess_abc");
+        assert_eq!(json["fileHash"], "sha256_abc123");
+        assert_eq!(json["version"], 3);
+        assert_eq!(json["linesAdded"], 50);
+        assert_eq!(json["linesRemoved"], 20);
+        assert_eq!(json["linesModified"], 15);
+    }
+
+    #[test]
+    fn project_activity_event_serializes_with_camel_case() {
+        let event = ProjectActivityEvent {
+            project_path: "/home/user/projects/vibetea".to_string(),
+            session_id: "sess_xyz".to_string(),
+            is_active: true,
+        };
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["projectPath"], "/home/user/projects/vibetea");
+        assert_eq!(json["sessionId"], "sess_xyz");
+        assert_eq!(json["isActive"], true);
+    }
+}
@@ -0,0 +1,108 @@
+//! Round-trip tests for the event wire format across versions.
+//!
+//! `fixtures/v1_events.json` and `fixtures/v2_events.json` hold the same
+//! events, one per event type, as monitors wrote them before and after
+//! payloads were tagged. Both must decode to the same events, and events
+//! must always be written back in the current format.
+
+use std::collections::BTreeSet;
+
+use vibetea_protocol::id::is_valid_event_id;
+use vibetea_protocol::schema::event_schema_json;
+use vibetea_protocol::{Event, EventType, SCHEMA_VERSION};
+
+// =============================================================================
+// Test Helpers
+// =============================================================================
+
+const V1_EVENTS: &str = include_str!("fixtures/v1_events.json");
+const V2_EVENTS: &str = include_str!("fixtures/v2_events.json");
+const EVENT_SCHEMA: &str = include_str!("../schema/event.schema.json");
+
+fn fixture_values(json: &str) -> Vec<serde_json::Value> {
+    serde_json::from_str(json).unwrap()
+}
+
+fn decode(values: &[serde_json::Value]) -> Vec<Event> {
+    values
+        .iter()
+        .map(|value| {
+            serde_json::from_value(value.clone())
+                .unwrap_or_else(|e| panic!("failed to decode {value}: {e}"))
+        })
+        .collect()
+}
+
+// =============================================================================
+// Round-trip Tests
+// =============================================================================
+
+#[test]
+fn fixtures_cover_every_event_type() {
+    let events = decode(&fixture_values(V2_EVENTS));
+
+    let types: BTreeSet<EventType> = events.iter().map(|event| event.event_type).collect();
+    assert_eq!(types, BTreeSet::from(EventType::ALL));
+
+    for event in &events {
+        assert!(
+            is_valid_event_id(&event.id),
+            "invalid fixture ID {}",
+            event.id
+        );
+        assert_eq!(event.payload.event_type(), event.event_type);
+    }
+}
+
+#[test]
+fn v1_and_v2_decode_to_the_same_events() {
+    let v1 = decode(&fixture_values(V1_EVENTS));
+    let v2 = decode(&fixture_values(V2_EVENTS));
+
+    assert_eq!(v1, v2);
+}
+
+#[test]
+fn events_are_written_in_the_current_format() {
+    let v2 = fixture_values(V2_EVENTS);
+
+    for (event, expected) in decode(&fixture_values(V1_EVENTS)).iter().zip(&v2) {
+        let written = serde_json::to_value(event).unwrap();
+        assert_eq!(&written, expected);
+        assert_eq!(written["schemaVersion"], SCHEMA_VERSION);
+    }
+}
+
+#[test]
+fn written_events_decode_unchanged() {
+    for event in decode(&fixture_values(V1_EVENTS)) {
+        let json = serde_json::to_string(&event).unwrap();
+        let roundtrip: Event = serde_json::from_str(&json).unwrap();
+        assert_eq!(roundtrip, event);
+    }
+}
+
+// =============================================================================
+// Schema Tests
+// =============================================================================
+
+#[test]
+fn checked_in_schema_is_up_to_date() {
+    assert!(
+        EVENT_SCHEMA == event_schema_json(),
+        "protocol/schema/event.schema.json is stale; regenerate it with \
+         `cargo run -p vibetea-protocol --example event_schema > protocol/schema/event.schema.json`"
+    );
+}
+
+#[test]
+fn schema_requires_tagged_payloads() {
+    let schema: serde_json::Value = serde_json::from_str(EVENT_SCHEMA).unwrap();
+
+    let variants = schema["$defs"]["EventPayload"]["oneOf"].as_array().unwrap();
+    assert_eq!(variants.len(), EventType::ALL.len());
+    for variant in variants {
+        let required = variant["required"].as_array().unwrap();
+        assert!(required.contains(&"type".into()), "{variant}");
+    }
+}
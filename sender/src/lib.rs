@@ -0,0 +1,17 @@
+//! VibeTea Sender - Signed event delivery.
+//!
+//! This crate holds the pieces needed to submit events to a VibeTea server's
+//! `POST /events` endpoint. It is shared by the monitor, which reports its
+//! own sessions, and by the server in relay mode, which forwards accepted
+//! events to an upstream hub.
+//!
+//! # Modules
+//!
+//! - [`crypto`]: Ed25519 keypair generation, storage, and event signing
+//! - [`sender`]: HTTP client with retry, buffering, and rate limiting
+
+pub mod crypto;
+pub mod sender;
+
+pub use crypto::{Crypto, CryptoError, KeySource};
+pub use sender::{RetryPolicy, Sender, SenderConfig, SenderError, SenderMetrics};
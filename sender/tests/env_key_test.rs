@@ -15,7 +15,7 @@ use ed25519_dalek::Verifier;
 use serial_test::serial;
 use std::env;
 use tempfile::TempDir;
-use vibetea_monitor::crypto::{Crypto, KeySource};
+use vibetea_sender::crypto::{Crypto, KeySource};
 
 // =============================================================================
 // Test Helpers
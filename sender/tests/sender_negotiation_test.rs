@@ -0,0 +1,157 @@
+//! Integration tests for event format negotiation.
+//!
+//! These tests verify that the sender reads the server's capabilities from
+//! `GET /health` and sends events in a format the server accepts.
+
+use uuid::Uuid;
+use vibetea_protocol::types::{Event, EventPayload, EventType, ToolStatus};
+use vibetea_sender::crypto::Crypto;
+use vibetea_sender::sender::{RetryPolicy, Sender, SenderConfig, ServerCapabilities};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// =============================================================================
+// Test Helpers
+// =============================================================================
+
+fn tool_event() -> Event {
+    Event::new(
+        "test-monitor".to_string(),
+        EventType::Tool,
+        EventPayload::Tool {
+            session_id: Uuid::new_v4(),
+            tool: "Read".to_string(),
+            status: ToolStatus::Completed,
+            context: None,
+            project: None,
+        },
+    )
+}
+
+fn activity_event() -> Event {
+    Event::new(
+        "test-monitor".to_string(),
+        EventType::Activity,
+        EventPayload::Activity {
+            session_id: Uuid::new_v4(),
+            project: None,
+        },
+    )
+}
+
+fn create_test_sender(server_url: &str) -> Sender {
+    let config = SenderConfig::new(server_url.to_string(), "test-monitor".to_string(), 100)
+        .with_retry_policy(RetryPolicy::fast_for_tests());
+    Sender::new(config, Crypto::generate())
+}
+
+async fn mount_events_ok(mock_server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(mock_server)
+        .await;
+}
+
+/// Returns the events in the single `POST /events` request received.
+async fn posted_events(mock_server: &MockServer) -> Vec<serde_json::Value> {
+    let requests: Vec<_> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.url.path() == "/events")
+        .collect();
+    assert_eq!(requests.len(), 1);
+    serde_json::from_slice(&requests[0].body).unwrap()
+}
+
+// =============================================================================
+// Negotiation Tests
+// =============================================================================
+
+/// Verifies that the newest common version is used and unsupported event
+/// types are dropped.
+#[tokio::test]
+async fn test_sends_negotiated_schema_version() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "status": "ok",
+            "schema_versions": [1, 2, 99],
+            "event_types": ["tool"]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    mount_events_ok(&mock_server).await;
+
+    let mut sender = create_test_sender(&mock_server.uri());
+    sender.queue(tool_event());
+    sender.queue(activity_event());
+    sender.flush().await.unwrap();
+
+    let events = posted_events(&mock_server).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["schemaVersion"], 2);
+    assert_eq!(events[0]["type"], "tool");
+    assert_eq!(events[0]["payload"]["type"], "tool");
+
+    let metrics = sender.metrics();
+    assert_eq!((metrics.sent, metrics.failed), (1, 1));
+
+    // Capabilities are cached after the first request
+    sender.queue(tool_event());
+    sender.flush().await.unwrap();
+}
+
+/// Verifies that servers without capabilities get the legacy format.
+#[tokio::test]
+async fn test_legacy_server_gets_unversioned_events() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "status": "ok",
+            "connections": 0
+        })))
+        .mount(&mock_server)
+        .await;
+    mount_events_ok(&mock_server).await;
+
+    let mut sender = create_test_sender(&mock_server.uri());
+    assert_eq!(
+        sender.capabilities().await.unwrap(),
+        ServerCapabilities::legacy()
+    );
+
+    sender.queue(tool_event());
+    sender.queue(activity_event());
+    sender.flush().await.unwrap();
+
+    let events = posted_events(&mock_server).await;
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .all(|event| event.get("schemaVersion").is_none()));
+}
+
+/// Verifies that nothing is sent while the server cannot be reached, so the
+/// events stay buffered until the format can be negotiated.
+#[tokio::test]
+async fn test_unreachable_server_keeps_events_buffered() {
+    // Nothing listens on a port freed by dropping its listener
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let uri = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let mut sender = create_test_sender(&uri);
+    assert!(sender.capabilities().await.is_err());
+
+    sender.queue(tool_event());
+    assert!(sender.flush().await.is_err());
+    assert_eq!(sender.buffer_len(), 1);
+}
@@ -4,9 +4,9 @@
 //! and recovers gracefully, particularly around oversized events.
 
 use uuid::Uuid;
-use vibetea_monitor::crypto::Crypto;
-use vibetea_monitor::sender::{RetryPolicy, Sender, SenderConfig};
-use vibetea_monitor::types::{Event, EventPayload, EventType, ToolStatus};
+use vibetea_protocol::types::{Event, EventPayload, EventType, ToolStatus};
+use vibetea_sender::crypto::Crypto;
+use vibetea_sender::sender::{RetryPolicy, Sender, SenderConfig};
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
@@ -167,3 +167,32 @@ async fn test_server_error_still_fails_flush() {
     let result = sender.flush().await;
     assert!(result.is_err(), "Flush should fail on 500 error");
 }
+
+/// Verifies that a partially rejected batch (207) is not retried.
+#[tokio::test]
+async fn test_partially_rejected_batch_is_not_retried() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(207).set_body_json(serde_json::json!({
+            "accepted": 1,
+            "duplicates": 0,
+            "rejected": 1,
+            "errors": [{"index": 1, "error": "invalid", "code": "type_mismatch"}]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut sender = create_test_sender(&mock_server.uri());
+    sender.queue(create_small_event());
+    sender.queue(create_small_event());
+
+    let result = sender.flush().await;
+    assert!(result.is_ok(), "Flush should succeed: {:?}", result);
+    assert!(sender.is_empty(), "Buffer should be empty after flush");
+
+    let metrics = sender.metrics();
+    assert_eq!((metrics.sent, metrics.failed), (1, 1));
+}
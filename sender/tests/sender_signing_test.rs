@@ -0,0 +1,186 @@
+//! Integration tests for request signing.
+//!
+//! These tests verify that the sender signs each request over a timestamp,
+//! nonce and body, and re-signs retries so the server's replay protection
+//! does not reject them. Servers that do not advertise replay protection
+//! verify the body alone, so they get the body signed alone.
+
+use base64::prelude::*;
+use chrono::Utc;
+use ed25519_dalek::{Signature, VerifyingKey};
+use uuid::Uuid;
+use vibetea_protocol::types::{Event, EventPayload, EventType, SessionAction};
+use vibetea_sender::crypto::Crypto;
+use vibetea_sender::sender::{
+    signed_message, RetryPolicy, Sender, SenderConfig, HEADER_NONCE, HEADER_TIMESTAMP,
+};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+// =============================================================================
+// Test Helpers
+// =============================================================================
+
+fn create_event() -> Event {
+    Event::new(
+        "test-monitor".to_string(),
+        EventType::Session,
+        EventPayload::Session {
+            session_id: Uuid::new_v4(),
+            action: SessionAction::Started,
+            project: "test-project".to_string(),
+        },
+    )
+}
+
+fn header<'a>(request: &'a Request, name: &str) -> &'a str {
+    request
+        .headers
+        .get(name)
+        .unwrap_or_else(|| panic!("missing {name} header"))
+        .to_str()
+        .unwrap()
+}
+
+/// Answers `GET /health` with the given replay protection capability.
+async fn mount_health(mock_server: &MockServer, replay_protection: bool) {
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "status": "ok",
+            "schema_versions": [1, 2],
+            "replay_protection": replay_protection
+        })))
+        .mount(mock_server)
+        .await;
+}
+
+fn signature(request: &Request) -> Signature {
+    let bytes: [u8; 64] = BASE64_STANDARD
+        .decode(header(request, "X-Signature"))
+        .unwrap()
+        .try_into()
+        .unwrap();
+    Signature::from_bytes(&bytes)
+}
+
+/// Returns the `POST /events` requests the server received, skipping
+/// capability negotiation.
+async fn event_requests(mock_server: &MockServer) -> Vec<Request> {
+    mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.url.path() == "/events")
+        .collect()
+}
+
+/// Verifies the request's signature over its timestamp, nonce and body.
+fn verify(request: &Request, key: &VerifyingKey) {
+    let timestamp = header(request, HEADER_TIMESTAMP);
+    let nonce = header(request, HEADER_NONCE);
+
+    key.verify_strict(
+        &signed_message(timestamp, nonce, &request.body),
+        &signature(request),
+    )
+    .expect("signature should cover timestamp, nonce and body");
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_request_is_signed_with_timestamp_and_nonce() {
+    let mock_server = MockServer::start().await;
+    mount_health(&mock_server, true).await;
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&mock_server)
+        .await;
+
+    let crypto = Crypto::generate();
+    let key = crypto.verifying_key();
+    let config = SenderConfig::new(mock_server.uri(), "test-monitor".to_string(), 100);
+    let mut sender = Sender::new(config, crypto);
+
+    sender.send(create_event()).await.unwrap();
+
+    let requests = event_requests(&mock_server).await;
+    assert_eq!(requests.len(), 1);
+    verify(&requests[0], &key);
+
+    let timestamp: i64 = header(&requests[0], HEADER_TIMESTAMP).parse().unwrap();
+    assert!((Utc::now().timestamp() - timestamp).abs() <= 5);
+}
+
+#[tokio::test]
+async fn test_retries_use_a_fresh_nonce() {
+    let mock_server = MockServer::start().await;
+    mount_health(&mock_server, true).await;
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&mock_server)
+        .await;
+
+    let crypto = Crypto::generate();
+    let key = crypto.verifying_key();
+    let config = SenderConfig::new(mock_server.uri(), "test-monitor".to_string(), 100)
+        .with_retry_policy(RetryPolicy::fast_for_tests());
+    let mut sender = Sender::new(config, crypto);
+
+    sender.send(create_event()).await.unwrap();
+
+    let requests = event_requests(&mock_server).await;
+    assert_eq!(requests.len(), 2);
+    for request in &requests {
+        verify(request, &key);
+    }
+    assert_ne!(
+        header(&requests[0], HEADER_NONCE),
+        header(&requests[1], HEADER_NONCE)
+    );
+}
+
+#[tokio::test]
+async fn test_legacy_server_gets_body_signature() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "status": "ok",
+            "connections": 0
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&mock_server)
+        .await;
+
+    let crypto = Crypto::generate();
+    let key = crypto.verifying_key();
+    let config = SenderConfig::new(mock_server.uri(), "test-monitor".to_string(), 100);
+    let mut sender = Sender::new(config, crypto);
+
+    sender.send(create_event()).await.unwrap();
+
+    // Verify as servers without replay protection do, over the body alone
+    let requests = event_requests(&mock_server).await;
+    assert_eq!(requests.len(), 1);
+    key.verify_strict(&requests[0].body, &signature(&requests[0]))
+        .expect("signature should cover the body alone");
+    assert!(requests[0].headers.get(HEADER_NONCE).is_none());
+    assert!(requests[0].headers.get(HEADER_TIMESTAMP).is_none());
+}
@@ -11,10 +11,20 @@
 //! The authentication flow works as follows:
 //! 1. Each Monitor is assigned a unique `source_id` and generates an Ed25519 key pair
 //! 2. The public key is registered with the server via `VIBETEA_PUBLIC_KEYS` config
-//! 3. When submitting events, Monitors sign the message body and include:
+//! 3. When submitting events, Monitors sign the [`signed_message`] built from
+//!    a timestamp, a random nonce and the body, and include:
 //!    - `X-Source-ID` header: The monitor's unique identifier
+//!    - `X-Timestamp` header: Unix time in seconds when the request was signed
+//!    - `X-Nonce` header: A random value unique to the request
 //!    - `X-Signature` header: Base64-encoded Ed25519 signature
-//! 4. The server verifies the signature against the registered public key
+//! 4. The server rejects timestamps outside the allowed clock skew (see
+//!    [`check_timestamp`]), verifies the signature against the registered
+//!    public key, and rejects nonces it has already seen (see
+//!    [`crate::nonce::NonceCache`])
+//!
+//! Covering the timestamp and nonce with the signature means a captured
+//! request cannot be replayed once it falls outside the skew window, nor
+//! within it once the nonce has been recorded.
 //!
 //! # Example
 //!
@@ -40,12 +50,23 @@
 //! ```
 
 use std::collections::HashMap;
+use std::time::Duration;
 
 use base64::prelude::*;
+use chrono::{DateTime, Utc};
 use ed25519_dalek::{Signature, VerifyingKey, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
 use subtle::ConstantTimeEq;
 use thiserror::Error;
 
+pub use vibetea_protocol::signing::signed_message;
+
+/// Default maximum difference between a request's `X-Timestamp` and the
+/// server clock.
+pub const DEFAULT_MAX_CLOCK_SKEW: Duration = Duration::from_secs(300);
+
+/// Maximum length of an `X-Nonce` header value.
+pub const MAX_NONCE_LENGTH: usize = 128;
+
 /// Errors that can occur during signature verification.
 ///
 /// These errors provide detailed information about why authentication failed,
@@ -89,6 +110,33 @@ pub enum AuthError {
     /// due to an incorrect or missing bearer token.
     #[error("invalid token")]
     InvalidToken,
+
+    /// The request timestamp is not a Unix time in seconds.
+    #[error("invalid timestamp")]
+    InvalidTimestamp,
+
+    /// The request timestamp is outside the allowed clock skew.
+    ///
+    /// Either the request was captured and replayed later, or the Monitor's
+    /// clock has drifted.
+    #[error("timestamp outside allowed clock skew")]
+    StaleTimestamp,
+
+    /// The request nonce is empty, too long, or contains invalid characters.
+    #[error("invalid nonce")]
+    InvalidNonce,
+
+    /// The request nonce has already been used by this source.
+    #[error("nonce already used")]
+    ReplayedNonce,
+
+    /// The signature matches a key that has been revoked in the key registry.
+    #[error("key revoked")]
+    RevokedKey,
+
+    /// The signature matches a key whose expiry time has passed.
+    #[error("key expired")]
+    ExpiredKey,
 }
 
 impl AuthError {
@@ -143,6 +191,79 @@ impl AuthError {
     pub fn is_token_error(&self) -> bool {
         matches!(self, Self::InvalidToken)
     }
+
+    /// Returns `true` if the signature matched a key that is no longer active.
+    pub fn is_inactive_key_error(&self) -> bool {
+        matches!(self, Self::RevokedKey | Self::ExpiredKey)
+    }
+
+    /// Returns `true` if this error indicates a stale or replayed request.
+    pub fn is_replay_error(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidTimestamp
+                | Self::StaleTimestamp
+                | Self::InvalidNonce
+                | Self::ReplayedNonce
+        )
+    }
+}
+
+/// Parses an `X-Timestamp` header and checks it is within `max_skew` of `now`.
+///
+/// # Errors
+///
+/// Returns [`AuthError::InvalidTimestamp`] if the value is not a Unix time in
+/// seconds, or [`AuthError::StaleTimestamp`] if it is too far from `now` in
+/// either direction.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use chrono::{TimeZone, Utc};
+/// use vibetea_server::auth::{check_timestamp, AuthError};
+///
+/// let now = Utc.timestamp_opt(1_767_225_600, 0).unwrap();
+/// let skew = Duration::from_secs(300);
+///
+/// assert!(check_timestamp("1767225500", now, skew).is_ok());
+/// assert_eq!(check_timestamp("1767225000", now, skew), Err(AuthError::StaleTimestamp));
+/// assert_eq!(check_timestamp("yesterday", now, skew), Err(AuthError::InvalidTimestamp));
+/// ```
+pub fn check_timestamp(
+    timestamp: &str,
+    now: DateTime<Utc>,
+    max_skew: Duration,
+) -> Result<DateTime<Utc>, AuthError> {
+    let signed_at = timestamp
+        .parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .ok_or(AuthError::InvalidTimestamp)?;
+
+    let skew = (now - signed_at).abs().to_std().unwrap_or(Duration::MAX);
+    if skew > max_skew {
+        return Err(AuthError::StaleTimestamp);
+    }
+
+    Ok(signed_at)
+}
+
+/// Checks that an `X-Nonce` header is non-empty, at most
+/// [`MAX_NONCE_LENGTH`] bytes, and printable ASCII without spaces.
+///
+/// # Errors
+///
+/// Returns [`AuthError::InvalidNonce`] if any of these checks fail.
+pub fn check_nonce(nonce: &str) -> Result<(), AuthError> {
+    if nonce.is_empty()
+        || nonce.len() > MAX_NONCE_LENGTH
+        || !nonce.bytes().all(|b| b.is_ascii_graphic())
+    {
+        return Err(AuthError::InvalidNonce);
+    }
+    Ok(())
 }
 
 /// Verifies an Ed25519 signature for a given message.
@@ -200,6 +321,21 @@ pub fn verify_signature(
         .get(source_id)
         .ok_or_else(|| AuthError::unknown_source(source_id))?;
 
+    let verifying_key = parse_public_key(public_key_base64)?;
+    let signature = decode_signature(signature_base64)?;
+
+    verifying_key
+        .verify_strict(message, &signature)
+        .map_err(|_| AuthError::InvalidSignature)
+}
+
+/// Decodes a base64-encoded Ed25519 public key.
+///
+/// # Errors
+///
+/// Returns [`AuthError::InvalidBase64`] if the value is not valid base64, or
+/// [`AuthError::InvalidPublicKey`] if it is not a valid Ed25519 public key.
+pub fn parse_public_key(public_key_base64: &str) -> Result<VerifyingKey, AuthError> {
     // Decode the base64-encoded public key
     let public_key_bytes = BASE64_STANDARD
         .decode(public_key_base64)
@@ -211,9 +347,16 @@ pub fn verify_signature(
         .map_err(|_| AuthError::InvalidPublicKey)?;
 
     // Parse the public key
-    let verifying_key =
-        VerifyingKey::from_bytes(&public_key_array).map_err(|_| AuthError::InvalidPublicKey)?;
+    VerifyingKey::from_bytes(&public_key_array).map_err(|_| AuthError::InvalidPublicKey)
+}
 
+/// Decodes a base64-encoded Ed25519 signature.
+///
+/// # Errors
+///
+/// Returns [`AuthError::InvalidBase64`] if the value is not valid base64, or
+/// [`AuthError::InvalidSignature`] if it has the wrong length.
+pub fn decode_signature(signature_base64: &str) -> Result<Signature, AuthError> {
     // Decode the base64-encoded signature
     let signature_bytes = BASE64_STANDARD
         .decode(signature_base64)
@@ -224,12 +367,7 @@ pub fn verify_signature(
         .try_into()
         .map_err(|_| AuthError::InvalidSignature)?;
 
-    // Parse and verify the signature
-    let signature = Signature::from_bytes(&signature_array);
-
-    verifying_key
-        .verify_strict(message, &signature)
-        .map_err(|_| AuthError::InvalidSignature)
+    Ok(Signature::from_bytes(&signature_array))
 }
 
 /// Validates a token for WebSocket client authentication.
@@ -762,4 +900,90 @@ mod tests {
         let err2 = err1.clone();
         assert_eq!(err1, err2);
     }
+
+    // ========================================================================
+    // Replay protection
+    // ========================================================================
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn signed_message_covers_timestamp_nonce_and_body() {
+        let (signing_key, public_key_base64) = generate_test_keypair();
+        let public_keys = create_keys_map("monitor-1", &public_key_base64);
+
+        let message = signed_message("1767225600", "abc", b"[]");
+        let signature_base64 = BASE64_STANDARD.encode(signing_key.sign(&message).to_bytes());
+
+        assert!(verify_signature("monitor-1", &signature_base64, &message, &public_keys).is_ok());
+
+        // Changing any part of the request invalidates the signature
+        for tampered in [
+            signed_message("1767225601", "abc", b"[]"),
+            signed_message("1767225600", "abd", b"[]"),
+            signed_message("1767225600", "abc", b"[ ]"),
+        ] {
+            assert_eq!(
+                verify_signature("monitor-1", &signature_base64, &tampered, &public_keys),
+                Err(AuthError::InvalidSignature)
+            );
+        }
+    }
+
+    #[test]
+    fn check_timestamp_accepts_within_skew_in_both_directions() {
+        let now = at(1_000_000);
+        let skew = Duration::from_secs(60);
+
+        assert_eq!(check_timestamp("1000000", now, skew), Ok(now));
+        assert!(check_timestamp("999940", now, skew).is_ok());
+        assert!(check_timestamp("1000060", now, skew).is_ok());
+    }
+
+    #[test]
+    fn check_timestamp_rejects_outside_skew() {
+        let now = at(1_000_000);
+        let skew = Duration::from_secs(60);
+
+        let err = check_timestamp("999939", now, skew).unwrap_err();
+        assert_eq!(err, AuthError::StaleTimestamp);
+        assert!(err.is_replay_error());
+        assert_eq!(
+            check_timestamp("1000061", now, skew),
+            Err(AuthError::StaleTimestamp)
+        );
+    }
+
+    #[test]
+    fn check_timestamp_rejects_malformed_values() {
+        let now = at(1_000_000);
+        let skew = Duration::from_secs(60);
+
+        for value in [
+            "",
+            "abc",
+            "1000000.5",
+            "2026-01-01T00:00:00Z",
+            "99999999999999999999",
+        ] {
+            assert_eq!(
+                check_timestamp(value, now, skew),
+                Err(AuthError::InvalidTimestamp),
+                "{value:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn check_nonce_validates_format() {
+        assert!(check_nonce("9b2f0c1e-6a4d-4f3e-8d2b-1c0a9e8f7d6c").is_ok());
+        assert_eq!(check_nonce(""), Err(AuthError::InvalidNonce));
+        assert_eq!(check_nonce("has space"), Err(AuthError::InvalidNonce));
+        assert_eq!(
+            check_nonce(&"a".repeat(MAX_NONCE_LENGTH + 1)),
+            Err(AuthError::InvalidNonce)
+        );
+    }
 }
@@ -6,9 +6,13 @@
 //!
 //! # Architecture
 //!
-//! The broadcast system consists of two main components:
+//! The broadcast system consists of four main components:
 //!
 //! - [`EventBroadcaster`] - The central hub that distributes events to all subscribers
+//! - [`ClientConnections`] - A count of the WebSocket and SSE clients
+//!   currently connected
+//! - [`EventHistory`] - A bounded window of recently broadcast events, used to
+//!   replay missed events to reconnecting subscribers
 //! - [`SubscriberFilter`] - Optional filtering criteria for subscribers to receive
 //!   only events they care about
 //!
@@ -27,17 +31,15 @@
 //! let mut rx = broadcaster.subscribe();
 //!
 //! // Create and broadcast an event
-//! let event = Event {
-//!     id: "evt_k7m2n9p4q1r6s3t8u5v0".to_string(),
-//!     source: "monitor-1".to_string(),
-//!     timestamp: Utc::now(),
-//!     event_type: EventType::Session,
-//!     payload: EventPayload::Session {
+//! let event = Event::new(
+//!     "monitor-1".to_string(),
+//!     EventType::Session,
+//!     EventPayload::Session {
 //!         session_id: Uuid::new_v4(),
 //!         action: SessionAction::Started,
 //!         project: "my-project".to_string(),
 //!     },
-//! };
+//! );
 //!
 //! broadcaster.broadcast(event.clone());
 //!
@@ -49,10 +51,18 @@
 //! assert!(filter.matches(&event));
 //! ```
 
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast::{self, Receiver, Sender};
 use tracing::{debug, trace, warn};
 
-use crate::types::{Event, EventPayload, EventType};
+use crate::sessions::Session;
+use crate::tokens::TokenScope;
+use crate::types::{Event, EventType};
 
 /// Default channel capacity for high-throughput event distribution.
 ///
@@ -61,6 +71,81 @@ use crate::types::{Event, EventPayload, EventType};
 /// start receiving `RecvError::Lagged` errors indicating missed events.
 pub const DEFAULT_CHANNEL_CAPACITY: usize = 1000;
 
+/// Default number of recent events retained for replay.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 10_000;
+
+/// Bounded window of the most recently broadcast events.
+///
+/// The history is updated under the same lock that is held while sending to
+/// the broadcast channel, so a subscriber created through
+/// [`EventBroadcaster::subscribe_with_history`] sees every event exactly once:
+/// either in the history snapshot or on its receiver.
+#[derive(Debug)]
+pub struct EventHistory {
+    events: VecDeque<Event>,
+    capacity: usize,
+    truncated: bool,
+}
+
+impl EventHistory {
+    fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity.min(DEFAULT_CHANNEL_CAPACITY)),
+            capacity,
+            truncated: false,
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        if self.capacity == 0 {
+            self.truncated = true;
+            return;
+        }
+        while self.events.len() >= self.capacity {
+            self.events.pop_front();
+            self.truncated = true;
+        }
+        self.events.push_back(event);
+    }
+
+    /// Returns an iterator over retained events, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Event> {
+        self.events.iter()
+    }
+
+    /// Returns the number of retained events.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no events are retained.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the position of the event with the given ID, if retained.
+    #[must_use]
+    pub fn position(&self, event_id: &str) -> Option<usize> {
+        self.events.iter().rposition(|event| event.id == event_id)
+    }
+
+    /// Returns the events after `index`, oldest first.
+    pub fn after(&self, index: usize) -> impl Iterator<Item = &Event> {
+        self.events.iter().skip(index + 1)
+    }
+
+    /// Returns `true` if older events have been evicted from the window.
+    ///
+    /// When `false`, the history contains every event broadcast since the
+    /// broadcaster was created.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
 /// Central event distribution hub for broadcasting events to multiple subscribers.
 ///
 /// `EventBroadcaster` wraps a tokio broadcast channel and provides a simple
@@ -96,6 +181,7 @@ pub const DEFAULT_CHANNEL_CAPACITY: usize = 1000;
 #[derive(Debug, Clone)]
 pub struct EventBroadcaster {
     sender: Sender<Event>,
+    history: Arc<Mutex<EventHistory>>,
 }
 
 impl EventBroadcaster {
@@ -138,7 +224,31 @@ impl EventBroadcaster {
     pub fn with_capacity(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
         debug!(capacity, "Created event broadcaster");
-        Self { sender }
+        Self {
+            sender,
+            history: Arc::new(Mutex::new(EventHistory::new(DEFAULT_HISTORY_CAPACITY))),
+        }
+    }
+
+    /// Sets how many recent events are retained for replay (builder pattern).
+    ///
+    /// A capacity of 0 disables the history. Defaults to
+    /// [`DEFAULT_HISTORY_CAPACITY`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use vibetea_server::broadcast::EventBroadcaster;
+    ///
+    /// let broadcaster = EventBroadcaster::new().with_history_capacity(500);
+    /// assert_eq!(broadcaster.history_len(), 0);
+    /// ```
+    #[must_use]
+    pub fn with_history_capacity(self, capacity: usize) -> Self {
+        Self {
+            sender: self.sender,
+            history: Arc::new(Mutex::new(EventHistory::new(capacity))),
+        }
     }
 
     /// Subscribes to receive broadcast events.
@@ -170,6 +280,44 @@ impl EventBroadcaster {
         rx
     }
 
+    /// Subscribes while inspecting the retained history atomically.
+    ///
+    /// The closure runs with the history locked and the subscription is
+    /// created before the lock is released, so no event can be broadcast in
+    /// between. Every event is therefore visible either to `inspect` or on the
+    /// returned receiver, never both and never neither.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use vibetea_server::broadcast::EventBroadcaster;
+    ///
+    /// let broadcaster = EventBroadcaster::new();
+    /// let (missed, _rx) = broadcaster.subscribe_with_history(|history| history.len());
+    /// assert_eq!(missed, 0);
+    /// ```
+    pub fn subscribe_with_history<T>(
+        &self,
+        inspect: impl FnOnce(&EventHistory) -> T,
+    ) -> (T, Receiver<Event>) {
+        let history = self.lock_history();
+        let result = inspect(&history);
+        let rx = self.subscribe();
+        drop(history);
+        (result, rx)
+    }
+
+    /// Inspects the retained history without subscribing.
+    pub fn read_history<T>(&self, inspect: impl FnOnce(&EventHistory) -> T) -> T {
+        inspect(&self.lock_history())
+    }
+
+    /// Returns the number of events currently retained for replay.
+    #[must_use]
+    pub fn history_len(&self) -> usize {
+        self.read_history(EventHistory::len)
+    }
+
     /// Broadcasts an event to all current subscribers.
     ///
     /// Returns the number of subscribers that received the event, or 0 if
@@ -191,16 +339,14 @@ impl EventBroadcaster {
     /// let broadcaster = EventBroadcaster::new();
     /// let _rx = broadcaster.subscribe();
     ///
-    /// let event = Event {
-    ///     id: "evt_k7m2n9p4q1r6s3t8u5v0".to_string(),
-    ///     source: "monitor-1".to_string(),
-    ///     timestamp: Utc::now(),
-    ///     event_type: EventType::Activity,
-    ///     payload: EventPayload::Activity {
+    /// let event = Event::new(
+    ///     "monitor-1".to_string(),
+    ///     EventType::Activity,
+    ///     EventPayload::Activity {
     ///         session_id: Uuid::new_v4(),
     ///         project: None,
     ///     },
-    /// };
+    /// );
     ///
     /// let receivers = broadcaster.broadcast(event);
     /// assert_eq!(receivers, 1);
@@ -213,6 +359,10 @@ impl EventBroadcaster {
             "Broadcasting event"
         );
 
+        // Hold the history lock while sending so history and channel order agree
+        let mut history = self.lock_history();
+        history.push(event.clone());
+
         match self.sender.send(event) {
             Ok(receivers) => {
                 trace!(receivers, "Event broadcast successful");
@@ -249,6 +399,12 @@ impl EventBroadcaster {
     pub fn subscriber_count(&self) -> usize {
         self.sender.receiver_count()
     }
+
+    fn lock_history(&self) -> MutexGuard<'_, EventHistory> {
+        self.history
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
 }
 
 impl Default for EventBroadcaster {
@@ -257,13 +413,78 @@ impl Default for EventBroadcaster {
     }
 }
 
+/// Counts the WebSocket and SSE clients currently connected.
+///
+/// Each connection holds the [`ClientConnection`] returned by
+/// [`connect`](Self::connect) for its lifetime. Unlike
+/// [`EventBroadcaster::subscriber_count`], the count does not include the
+/// receivers held by the server itself, such as the relay and webhook
+/// dispatchers.
+///
+/// # Example
+///
+/// ```rust
+/// use vibetea_server::broadcast::ClientConnections;
+///
+/// let clients = ClientConnections::new();
+/// let connection = clients.connect();
+/// assert_eq!(clients.count(), 1);
+///
+/// drop(connection);
+/// assert_eq!(clients.count(), 0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClientConnections {
+    count: Arc<AtomicUsize>,
+}
+
+impl ClientConnections {
+    /// Creates a counter with no connections.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a new client connection, which is counted until the returned
+    /// guard is dropped.
+    #[must_use]
+    pub fn connect(&self) -> ClientConnection {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        ClientConnection {
+            count: Arc::clone(&self.count),
+        }
+    }
+
+    /// Returns the number of connected clients.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+/// A connected client, counted by [`ClientConnections`] until dropped.
+#[derive(Debug)]
+pub struct ClientConnection {
+    count: Arc<AtomicUsize>,
+}
+
+impl Drop for ClientConnection {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 /// Filter criteria for selecting which events a subscriber receives.
 ///
-/// `SubscriberFilter` allows clients to specify optional filtering criteria
-/// so they only receive events they're interested in. All specified filters
-/// use AND logic - an event must match ALL specified criteria to pass.
+/// Wraps the [`SubscriberFilter`](vibetea_protocol::filter::SubscriberFilter)
+/// a client asks for, which holds a [`FilterSet`](crate::filter::FilterSet) of
+/// included and excluded values per field, and ANDs it with the subscriber's
+/// [`TokenScope`], so a scoped token never sees events outside its scope
+/// whatever it asks for. The requested fields are reachable through `Deref`.
 ///
-/// Unset filters (None) always match, allowing for flexible partial filtering.
+/// Filters can also be given as JSON objects, with the same field names as
+/// the query string (see [`crate::filter`] for the syntax). The scope is never
+/// read from or written to JSON.
 ///
 /// # Example
 ///
@@ -275,211 +496,166 @@ impl Default for EventBroadcaster {
 /// let filter = SubscriberFilter::new()
 ///     .with_source("monitor-1")
 ///     .with_event_type(EventType::Tool);
+/// assert_eq!(filter.source.include, vec!["monitor-1".to_string()]);
 ///
-/// // Filter for all events from a specific project
+/// // Filter for tool and session events from two projects
 /// let filter = SubscriberFilter::new()
-///     .with_project("my-app");
+///     .with_event_type(EventType::Tool)
+///     .with_event_type(EventType::Session)
+///     .with_project("my-app")
+///     .with_project("my-api");
+///
+/// // Filter for everything except activity events from CI machines
+/// let filter = SubscriberFilter::new()
+///     .without_event_type(EventType::Activity)
+///     .without_source("ci-*");
+///
+/// // The same filter as a JSON object
+/// let parsed: SubscriberFilter =
+///     serde_json::from_str(r#"{"type": "!activity", "source": ["!ci-*"]}"#).unwrap();
+/// assert_eq!(parsed, filter);
 ///
 /// // Empty filter matches all events
 /// let filter = SubscriberFilter::new();
 /// ```
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct SubscriberFilter {
-    /// Filter by source ID (monitor identifier).
-    pub source: Option<String>,
-
-    /// Filter by event type.
-    pub event_type: Option<EventType>,
+    /// The criteria the subscriber asked for.
+    pub filter: vibetea_protocol::filter::SubscriberFilter,
 
-    /// Filter by project name.
-    pub project: Option<String>,
+    /// Limits imposed by the subscriber's token.
+    #[serde(skip)]
+    pub scope: TokenScope,
 }
 
 impl SubscriberFilter {
     /// Creates a new empty filter that matches all events.
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use vibetea_server::broadcast::SubscriberFilter;
-    ///
-    /// let filter = SubscriberFilter::new();
-    /// assert!(filter.source.is_none());
-    /// assert!(filter.event_type.is_none());
-    /// assert!(filter.project.is_none());
-    /// ```
     #[must_use]
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Sets the source filter (builder pattern).
-    ///
-    /// # Arguments
-    ///
-    /// * `source` - The source ID to filter by.
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use vibetea_server::broadcast::SubscriberFilter;
-    ///
-    /// let filter = SubscriberFilter::new().with_source("monitor-1");
-    /// assert_eq!(filter.source, Some("monitor-1".to_string()));
-    /// ```
+    /// Adds a source pattern to include (builder pattern).
     #[must_use]
-    pub fn with_source(mut self, source: impl Into<String>) -> Self {
-        self.source = Some(source.into());
-        self
+    pub fn with_source(self, source: impl Into<String>) -> Self {
+        self.map(|filter| filter.with_source(source))
     }
 
-    /// Sets the event type filter (builder pattern).
-    ///
-    /// # Arguments
-    ///
-    /// * `event_type` - The event type to filter by.
-    ///
-    /// # Example
-    ///
-    /// ```rust
-    /// use vibetea_server::broadcast::SubscriberFilter;
-    /// use vibetea_server::types::EventType;
-    ///
-    /// let filter = SubscriberFilter::new().with_event_type(EventType::Session);
-    /// assert_eq!(filter.event_type, Some(EventType::Session));
-    /// ```
+    /// Adds a source pattern to exclude (builder pattern).
     #[must_use]
-    pub fn with_event_type(mut self, event_type: EventType) -> Self {
-        self.event_type = Some(event_type);
-        self
+    pub fn without_source(self, source: impl Into<String>) -> Self {
+        self.map(|filter| filter.without_source(source))
     }
 
-    /// Sets the project filter (builder pattern).
-    ///
-    /// # Arguments
+    /// Adds an event type to include (builder pattern).
+    #[must_use]
+    pub fn with_event_type(self, event_type: EventType) -> Self {
+        self.map(|filter| filter.with_event_type(event_type))
+    }
+
+    /// Adds an event type to exclude (builder pattern).
+    #[must_use]
+    pub fn without_event_type(self, event_type: EventType) -> Self {
+        self.map(|filter| filter.without_event_type(event_type))
+    }
+
+    /// Adds a project pattern to include (builder pattern).
+    #[must_use]
+    pub fn with_project(self, project: impl Into<String>) -> Self {
+        self.map(|filter| filter.with_project(project))
+    }
+
+    /// Adds a project pattern to exclude (builder pattern).
+    #[must_use]
+    pub fn without_project(self, project: impl Into<String>) -> Self {
+        self.map(|filter| filter.without_project(project))
+    }
+
+    /// Adds a tool name pattern to include (builder pattern).
     ///
-    /// * `project` - The project name to filter by.
+    /// Only tool events are filtered by tool name.
+    #[must_use]
+    pub fn with_tool(self, tool: impl Into<String>) -> Self {
+        self.map(|filter| filter.with_tool(tool))
+    }
+
+    /// Adds a tool name pattern to exclude (builder pattern).
+    #[must_use]
+    pub fn without_tool(self, tool: impl Into<String>) -> Self {
+        self.map(|filter| filter.without_tool(tool))
+    }
+
+    /// Restricts the filter to the given token scope (builder pattern).
     ///
     /// # Example
     ///
     /// ```rust
     /// use vibetea_server::broadcast::SubscriberFilter;
+    /// use vibetea_server::tokens::TokenScope;
     ///
-    /// let filter = SubscriberFilter::new().with_project("vibetea");
-    /// assert_eq!(filter.project, Some("vibetea".to_string()));
+    /// let scope = TokenScope::new().with_sources(["ci-*"]);
+    /// let filter = SubscriberFilter::new().with_scope(scope.clone());
+    /// assert_eq!(filter.scope, scope);
     /// ```
     #[must_use]
-    pub fn with_project(mut self, project: impl Into<String>) -> Self {
-        self.project = Some(project.into());
+    pub fn with_scope(mut self, scope: TokenScope) -> Self {
+        self.scope = scope;
         self
     }
 
-    /// Checks if an event matches this filter's criteria.
-    ///
-    /// Returns `true` if the event matches ALL specified filter criteria.
-    /// Unset filters (None) always match. This implements AND logic across
-    /// all filter fields.
-    ///
-    /// # Filter Matching Rules
-    ///
-    /// - **source**: Matches if filter source is None OR equals event.source
-    /// - **event_type**: Matches if filter event_type is None OR equals event.event_type
-    /// - **project**: Matches if filter project is None OR the event's payload
-    ///   contains a matching project field
-    ///
-    /// # Project Field Extraction
+    /// Checks if an event matches both the requested criteria and the scope.
     ///
-    /// The project field is extracted from the event payload based on the
-    /// payload variant:
-    ///
-    /// - `Session`: Always has a project field
-    /// - `Tool`: Optional project field
-    /// - `Activity`: Optional project field
-    /// - `Agent`, `Summary`, `Error`: No project field (will only match if
-    ///   filter.project is None)
+    /// See [`vibetea_protocol::filter::SubscriberFilter::matches`] for how
+    /// the requested criteria are applied. The event's source, type and
+    /// project must also all be allowed by the token scope.
     ///
     /// # Example
     ///
     /// ```rust
     /// use vibetea_server::broadcast::SubscriberFilter;
+    /// use vibetea_server::tokens::TokenScope;
     /// use vibetea_server::types::{Event, EventType, EventPayload, SessionAction};
-    /// use chrono::Utc;
     /// use uuid::Uuid;
     ///
-    /// let event = Event {
-    ///     id: "evt_test1234567890abcdef".to_string(),
-    ///     source: "monitor-1".to_string(),
-    ///     timestamp: Utc::now(),
-    ///     event_type: EventType::Session,
-    ///     payload: EventPayload::Session {
+    /// let event = Event::new(
+    ///     "monitor-1".to_string(),
+    ///     EventType::Session,
+    ///     EventPayload::Session {
     ///         session_id: Uuid::new_v4(),
     ///         action: SessionAction::Started,
     ///         project: "my-project".to_string(),
     ///     },
-    /// };
+    /// );
     ///
     /// // Empty filter matches all events
     /// assert!(SubscriberFilter::new().matches(&event));
     ///
-    /// // Source filter
-    /// assert!(SubscriberFilter::new().with_source("monitor-1").matches(&event));
-    /// assert!(!SubscriberFilter::new().with_source("other").matches(&event));
-    ///
-    /// // Event type filter
-    /// assert!(SubscriberFilter::new().with_event_type(EventType::Session).matches(&event));
+    /// // Requested criteria
+    /// assert!(SubscriberFilter::new().with_source("monitor-*").matches(&event));
     /// assert!(!SubscriberFilter::new().with_event_type(EventType::Tool).matches(&event));
-    ///
-    /// // Project filter
-    /// assert!(SubscriberFilter::new().with_project("my-project").matches(&event));
     /// assert!(!SubscriberFilter::new().with_project("other").matches(&event));
+    ///
+    /// // Token scope
+    /// let scope = TokenScope::new().with_sources(["ci-*"]);
+    /// assert!(!SubscriberFilter::new().with_scope(scope).matches(&event));
     /// ```
     #[must_use]
     pub fn matches(&self, event: &Event) -> bool {
-        // Check source filter
-        if let Some(ref filter_source) = self.source {
-            if &event.source != filter_source {
-                return false;
-            }
-        }
-
-        // Check event type filter
-        if let Some(filter_event_type) = self.event_type {
-            if event.event_type != filter_event_type {
-                return false;
-            }
-        }
-
-        // Check project filter
-        if let Some(ref filter_project) = self.project {
-            let event_project = Self::extract_project(&event.payload);
-            match event_project {
-                Some(project) if project == filter_project => {}
-                _ => return false,
-            }
-        }
+        self.filter.matches(event)
+            && self.scope.allows_source(&event.source)
+            && self.scope.allows_event_type(event.event_type)
+            && self.scope.allows_project(event.payload.project())
+    }
 
-        true
-    }
-
-    /// Extracts the project field from an event payload, if present.
-    fn extract_project(payload: &EventPayload) -> Option<&str> {
-        match payload {
-            EventPayload::Session { project, .. } => Some(project.as_str()),
-            EventPayload::Tool { project, .. } => project.as_deref(),
-            EventPayload::Activity { project, .. } => project.as_deref(),
-            EventPayload::SkillInvocation(event) => Some(event.project.as_str()),
-            EventPayload::Agent { .. }
-            | EventPayload::Summary { .. }
-            | EventPayload::Error { .. }
-            | EventPayload::FileChange(_)
-            | EventPayload::AgentSpawn(_)
-            | EventPayload::TokenUsage(_)
-            | EventPayload::SessionMetrics(_)
-            | EventPayload::ModelDistribution(_)
-            | EventPayload::TodoProgress(_)
-            | EventPayload::ActivityPattern(_)
-            | EventPayload::ProjectActivity(_) => None,
-        }
+    /// Checks if a session matches this filter's source, project and scope.
+    ///
+    /// The event type and tool criteria do not apply to sessions.
+    #[must_use]
+    pub fn matches_session(&self, session: &Session) -> bool {
+        self.filter
+            .matches_source_and_project(&session.source, session.project.as_deref())
+            && self.scope.allows_session(session)
     }
 
     /// Returns `true` if this filter has no criteria set (matches all events).
@@ -488,33 +664,60 @@ impl SubscriberFilter {
     ///
     /// ```rust
     /// use vibetea_server::broadcast::SubscriberFilter;
-    /// use vibetea_server::types::EventType;
     ///
     /// assert!(SubscriberFilter::new().is_empty());
     /// assert!(!SubscriberFilter::new().with_source("test").is_empty());
     /// ```
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.source.is_none() && self.event_type.is_none() && self.project.is_none()
+        self.filter.is_empty() && self.scope.is_unrestricted()
+    }
+
+    /// Applies a builder of the requested criteria.
+    fn map(
+        mut self,
+        build: impl FnOnce(
+            vibetea_protocol::filter::SubscriberFilter,
+        ) -> vibetea_protocol::filter::SubscriberFilter,
+    ) -> Self {
+        self.filter = build(self.filter);
+        self
+    }
+}
+
+impl From<vibetea_protocol::filter::SubscriberFilter> for SubscriberFilter {
+    fn from(filter: vibetea_protocol::filter::SubscriberFilter) -> Self {
+        Self {
+            filter,
+            scope: TokenScope::default(),
+        }
+    }
+}
+
+impl Deref for SubscriberFilter {
+    type Target = vibetea_protocol::filter::SubscriberFilter;
+
+    fn deref(&self) -> &Self::Target {
+        &self.filter
+    }
+}
+
+impl DerefMut for SubscriberFilter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.filter
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{SessionAction, ToolStatus};
-    use chrono::Utc;
+    use crate::types::{EventPayload, SessionAction, ToolStatus};
     use uuid::Uuid;
 
     /// Helper to create a test event with customizable fields.
     fn make_event(source: &str, event_type: EventType, payload: EventPayload) -> Event {
-        Event {
-            id: format!("evt_test{:0>16}", rand_id()),
-            source: source.to_string(),
-            timestamp: Utc::now(),
-            event_type,
-            payload,
-        }
+        Event::new(source.to_string(), event_type, payload)
+            .with_id(format!("evt_test{:0>16}", rand_id()))
     }
 
     /// Generate a simple pseudo-random ID for test events.
@@ -645,6 +848,22 @@ mod tests {
         assert_eq!(broadcaster.subscriber_count(), 0);
     }
 
+    #[test]
+    fn client_connections_ignore_broadcaster_receivers() {
+        let broadcaster = EventBroadcaster::new();
+        let clients = ClientConnections::new();
+
+        let _relay = broadcaster.subscribe();
+        let first = clients.connect();
+        let second = clients.clone().connect();
+        assert_eq!(clients.count(), 2);
+
+        drop(first);
+        drop(second);
+        assert_eq!(clients.count(), 0);
+        assert_eq!(broadcaster.subscriber_count(), 1);
+    }
+
     #[test]
     fn broadcaster_clone_shares_channel() {
         let broadcaster1 = EventBroadcaster::new();
@@ -749,6 +968,74 @@ mod tests {
         assert_eq!(received2.id, id2);
     }
 
+    // ========================================================================
+    // EventHistory tests
+    // ========================================================================
+
+    #[test]
+    fn broadcast_records_history_without_subscribers() {
+        let broadcaster = EventBroadcaster::new();
+        broadcaster.broadcast(make_session_event("monitor-1", "project"));
+        assert_eq!(broadcaster.history_len(), 1);
+    }
+
+    #[test]
+    fn history_evicts_oldest_beyond_capacity() {
+        let broadcaster = EventBroadcaster::new().with_history_capacity(2);
+        let events: Vec<Event> = (0..3)
+            .map(|_| make_session_event("monitor-1", "project"))
+            .collect();
+        for event in &events {
+            broadcaster.broadcast(event.clone());
+        }
+
+        let (ids, _rx) = broadcaster.subscribe_with_history(|history| {
+            assert!(history.is_truncated());
+            history.iter().map(|e| e.id.clone()).collect::<Vec<_>>()
+        });
+        assert_eq!(ids, vec![events[1].id.clone(), events[2].id.clone()]);
+    }
+
+    #[test]
+    fn history_disabled_with_zero_capacity() {
+        let broadcaster = EventBroadcaster::new().with_history_capacity(0);
+        broadcaster.broadcast(make_session_event("monitor-1", "project"));
+        assert_eq!(broadcaster.history_len(), 0);
+    }
+
+    #[test]
+    fn history_position_and_after() {
+        let broadcaster = EventBroadcaster::new();
+        let events: Vec<Event> = (0..3).map(|_| make_tool_event("monitor-1", None)).collect();
+        for event in &events {
+            broadcaster.broadcast(event.clone());
+        }
+
+        let (after, _rx) = broadcaster.subscribe_with_history(|history| {
+            assert!(!history.is_truncated());
+            let index = history.position(&events[0].id).unwrap();
+            history
+                .after(index)
+                .map(|e| e.id.clone())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(after, vec![events[1].id.clone(), events[2].id.clone()]);
+    }
+
+    #[tokio::test]
+    async fn subscribe_with_history_receives_later_events() {
+        let broadcaster = EventBroadcaster::new();
+        broadcaster.broadcast(make_session_event("monitor-1", "before"));
+
+        let (seen, mut rx) = broadcaster.subscribe_with_history(|history| history.len());
+        let later = make_session_event("monitor-1", "after");
+        broadcaster.broadcast(later.clone());
+
+        assert_eq!(seen, 1);
+        assert_eq!(rx.recv().await.unwrap().id, later.id);
+        assert!(rx.try_recv().is_err());
+    }
+
     // ========================================================================
     // SubscriberFilter tests
     // ========================================================================
@@ -756,9 +1043,10 @@ mod tests {
     #[test]
     fn filter_new_creates_empty_filter() {
         let filter = SubscriberFilter::new();
-        assert!(filter.source.is_none());
-        assert!(filter.event_type.is_none());
-        assert!(filter.project.is_none());
+        assert!(filter.source.is_empty());
+        assert!(filter.event_type.is_empty());
+        assert!(filter.project.is_empty());
+        assert!(filter.tool.is_empty());
         assert!(filter.is_empty());
     }
 
@@ -771,21 +1059,21 @@ mod tests {
     #[test]
     fn filter_with_source_sets_source() {
         let filter = SubscriberFilter::new().with_source("monitor-1");
-        assert_eq!(filter.source, Some("monitor-1".to_string()));
+        assert_eq!(filter.source.include, vec!["monitor-1".to_string()]);
         assert!(!filter.is_empty());
     }
 
     #[test]
     fn filter_with_event_type_sets_event_type() {
         let filter = SubscriberFilter::new().with_event_type(EventType::Tool);
-        assert_eq!(filter.event_type, Some(EventType::Tool));
+        assert_eq!(filter.event_type.include, vec![EventType::Tool]);
         assert!(!filter.is_empty());
     }
 
     #[test]
     fn filter_with_project_sets_project() {
         let filter = SubscriberFilter::new().with_project("my-project");
-        assert_eq!(filter.project, Some("my-project".to_string()));
+        assert_eq!(filter.project.include, vec!["my-project".to_string()]);
         assert!(!filter.is_empty());
     }
 
@@ -796,9 +1084,9 @@ mod tests {
             .with_event_type(EventType::Session)
             .with_project("vibetea");
 
-        assert_eq!(filter.source, Some("monitor-1".to_string()));
-        assert_eq!(filter.event_type, Some(EventType::Session));
-        assert_eq!(filter.project, Some("vibetea".to_string()));
+        assert_eq!(filter.source.include, vec!["monitor-1".to_string()]);
+        assert_eq!(filter.event_type.include, vec![EventType::Session]);
+        assert_eq!(filter.project.include, vec!["vibetea".to_string()]);
     }
 
     #[test]
@@ -1035,6 +1323,124 @@ mod tests {
             .is_empty());
     }
 
+    #[test]
+    fn filter_matches_any_included_value() {
+        let filter = SubscriberFilter::new()
+            .with_event_type(EventType::Tool)
+            .with_event_type(EventType::Session)
+            .with_project("web")
+            .with_project("api");
+
+        assert!(filter.matches(&make_session_event("any", "web")));
+        assert!(filter.matches(&make_tool_event("any", Some("api"))));
+        assert!(!filter.matches(&make_tool_event("any", Some("docs"))));
+        assert!(!filter.matches(&make_activity_event("any", Some("web"))));
+    }
+
+    #[test]
+    fn filter_excludes_negated_values() {
+        let filter = SubscriberFilter::new()
+            .without_event_type(EventType::Activity)
+            .without_project("legacy");
+
+        assert!(filter.matches(&make_session_event("any", "web")));
+        assert!(filter.matches(&make_agent_event("any")));
+        assert!(!filter.matches(&make_activity_event("any", Some("web"))));
+        assert!(!filter.matches(&make_session_event("any", "legacy")));
+    }
+
+    #[test]
+    fn filter_matches_source_and_project_globs() {
+        let filter = SubscriberFilter::new()
+            .with_source("ci-*")
+            .without_source("ci-flaky")
+            .with_project("vibe*");
+
+        assert!(filter.matches(&make_session_event("ci-linux", "vibetea")));
+        assert!(!filter.matches(&make_session_event("ci-flaky", "vibetea")));
+        assert!(!filter.matches(&make_session_event("dev", "vibetea")));
+        assert!(!filter.matches(&make_session_event("ci-linux", "other")));
+    }
+
+    #[test]
+    fn filter_tool_names_only_apply_to_tool_events() {
+        let filter = SubscriberFilter::new().with_tool("Bash");
+
+        assert!(!filter.matches(&make_tool_event("any", None)));
+        assert!(filter.matches(&make_session_event("any", "web")));
+
+        let filter = SubscriberFilter::new().with_tool("Re*");
+        assert!(filter.matches(&make_tool_event("any", None)));
+
+        let filter = SubscriberFilter::new().without_tool("Read");
+        assert!(!filter.matches(&make_tool_event("any", None)));
+    }
+
+    #[test]
+    fn filter_deserializes_from_json_without_scope() {
+        let filter: SubscriberFilter = serde_json::from_str(
+            r#"{"source": "ci-*", "type": ["tool", "!session"], "project": [], "tool": "Bash"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            filter,
+            SubscriberFilter::new()
+                .with_source("ci-*")
+                .with_event_type(EventType::Tool)
+                .without_event_type(EventType::Session)
+                .with_tool("Bash")
+        );
+
+        let json = serde_json::to_string(&filter).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SubscriberFilter>(&json).unwrap(),
+            filter
+        );
+
+        // The scope can only come from the token
+        assert!(serde_json::from_str::<SubscriberFilter>(r#"{"scope": {}}"#).is_err());
+        let scoped = filter.with_scope(TokenScope::new().with_sources(["ci-*"]));
+        assert!(!serde_json::to_string(&scoped).unwrap().contains("scope"));
+    }
+
+    #[test]
+    fn filter_scope_is_anded_with_criteria() {
+        let scope = TokenScope::new()
+            .with_sources(["ci-*"])
+            .with_projects(["vibetea"]);
+        let filter = SubscriberFilter::new().with_scope(scope.clone());
+
+        assert!(!filter.is_empty());
+        assert!(filter.matches(&make_session_event("ci-linux", "vibetea")));
+        assert!(!filter.matches(&make_session_event("dev-laptop", "vibetea")));
+        assert!(!filter.matches(&make_session_event("ci-linux", "other")));
+
+        // Asking for an out-of-scope source still yields nothing
+        let filter = SubscriberFilter::new()
+            .with_source("dev-laptop")
+            .with_scope(scope.clone());
+        assert!(!filter.matches(&make_session_event("dev-laptop", "vibetea")));
+
+        // Narrowing within the scope works as usual
+        let filter = SubscriberFilter::new()
+            .with_source("ci-linux")
+            .with_scope(scope);
+        assert!(filter.matches(&make_session_event("ci-linux", "vibetea")));
+        assert!(!filter.matches(&make_session_event("ci-mac", "vibetea")));
+    }
+
+    #[test]
+    fn filter_scope_restricts_event_types() {
+        let filter = SubscriberFilter::new()
+            .with_scope(TokenScope::new().with_event_types([EventType::Tool]));
+
+        assert!(!filter.matches(&make_session_event("any", "p")));
+        assert!(!SubscriberFilter::new()
+            .with_event_type(EventType::Session)
+            .with_scope(TokenScope::new().with_event_types([EventType::Tool]))
+            .matches(&make_session_event("any", "p")));
+    }
+
     #[test]
     fn filter_debug_format() {
         let filter = SubscriberFilter::new()
@@ -1,27 +1,102 @@
 //! Server configuration module.
 //!
-//! Parses configuration from environment variables for the VibeTea server.
+//! Parses configuration for the VibeTea server from command-line flags,
+//! environment variables and an optional TOML config file, in that order of
+//! precedence (see [`Config::load`] and [`Settings`]).
 //!
 //! # Environment Variables
 //!
+//! Each variable can also be set in the config file, using its name without
+//! the `VIBETEA_` prefix in lowercase (for example `retention_hours = 48`).
+//! `VIBETEA_PUBLIC_KEYS` becomes a `[public_keys]` table.
+//!
 //! | Variable | Required | Default | Description |
 //! |----------|----------|---------|-------------|
-//! | `VIBETEA_PUBLIC_KEYS` | Yes* | - | Format: `source1:pubkey1,source2:pubkey2` |
-//! | `VIBETEA_SUBSCRIBER_TOKEN` | Yes* | - | Auth token for Clients |
-//! | `PORT` | No | 8080 | HTTP server port |
+//! | `VIBETEA_PUBLIC_KEYS` | Yes† | - | Format: `source1:pubkey1,source2:pubkey2` |
+//! | `VIBETEA_SUBSCRIBER_TOKEN` | Yes‡ | - | Auth token for Clients |
+//! | `VIBETEA_HOST` | No | 0.0.0.0 | Address to bind to |
+//! | `VIBETEA_PORT` | No | 8080 | HTTP server port (`PORT` is also accepted) |
 //! | `VIBETEA_UNSAFE_NO_AUTH` | No | false | Disable all authentication (dev only) |
+//! | `VIBETEA_TLS_CERT` | No | - | PEM certificate chain; enables HTTPS, reloaded on change (see [`crate::tls`]) |
+//! | `VIBETEA_TLS_KEY` | No | - | PEM private key for `VIBETEA_TLS_CERT` |
+//! | `VIBETEA_ALLOWED_ORIGINS` | No | - | Comma-separated browser origins allowed for CORS and `/ws` (`*` for any, see [`crate::cors`]) |
+//! | `VIBETEA_CORS_ALLOW_CREDENTIALS` | No | false | Allow credentialed cross-origin requests |
+//! | `VIBETEA_TLS_CLIENT_CA` | No | - | PEM CA certificates for monitor client certificates; required on `POST /events` when set |
+//! | `VIBETEA_KEY_FILE` | No | - | JSON key registry, reloaded on change (see [`crate::keys`]) |
+//! | `VIBETEA_SUBSCRIBER_TOKENS_FILE` | No | - | JSON file of named, scoped subscriber tokens, reloaded on change (see [`crate::tokens`]) |
+//! | `VIBETEA_WEBHOOKS_FILE` | No | - | JSON file of outbound webhooks (see [`crate::webhooks`]) |
+//! | `VIBETEA_RELAY_URL` | No | - | Upstream hub to forward accepted events to (see [`crate::relay`]) |
+//! | `VIBETEA_RELAY_ID` | No¶ | - | This hub's source ID upstream, also recorded in relayed events' `hops` |
+//! | `VIBETEA_RELAY_KEY_PATH` | No¶ | - | Directory containing this hub's `key.priv`, as written by `vibetea-monitor init` |
+//! | `VIBETEA_RELAY_BUFFER_SIZE` | No | 10000 | Events buffered for the upstream hub while it is unreachable |
+//! | `VIBETEA_RELAY_SOURCES` | No | - | Comma-separated downstream hub IDs allowed to submit events relayed from other sources |
+//! | `VIBETEA_DATA_DIR` | No | - | Directory for the persistent event log (disabled if unset) |
+//! | `VIBETEA_RETENTION_HOURS` | No | 24 | Hours of events kept in the event log |
+//! | `VIBETEA_RETENTION_MAX_MB` | No | 1024 | Maximum size of the event log in MiB |
+//! | `VIBETEA_HISTORY_CAPACITY` | No | 10000 | Recent events kept in memory for resuming subscribers |
+//! | `VIBETEA_CHANNEL_CAPACITY` | No | 1000 | Live events buffered for subscribers before they lag |
+//! | `VIBETEA_RATE_LIMIT` | No | 100 | Requests per second allowed per source |
+//! | `VIBETEA_RATE_BURST` | No | 100 | Burst capacity per source |
+//! | `VIBETEA_GLOBAL_RATE_LIMIT` | No | 1000 | Events per second across all sources (0 disables) |
+//! | `VIBETEA_GLOBAL_RATE_BURST` | No | 1000 | Burst capacity across all sources, in events |
+//! | `VIBETEA_MAX_WS_PER_IP` | No | 32 | Concurrent WebSocket and SSE connections per client IP (0 disables) |
+//! | `VIBETEA_DEDUP_WINDOW_SECS` | No | 600 | How long accepted event IDs are remembered to drop retried duplicates (0 disables, see [`crate::dedup`]) |
+//! | `VIBETEA_MAX_CLOCK_SKEW_SECS` | No | 300 | Allowed difference between a signed request's timestamp and the server clock, also the furthest an event timestamp may be in the future |
+//! | `VIBETEA_ACCEPT_LEGACY_EVENTS` | No | true | Accept events without `schemaVersion` (see [`crate::types`]) |
+//! | `VIBETEA_MAX_EVENT_AGE_SECS` | No | 604800 | Oldest event timestamp accepted at ingest (0 disables, see [`crate::validation`]) |
+//! | `VIBETEA_STATS_INTERVAL_SECS` | No | 60 | Interval between rollups pushed to subscribers (0 disables, see [`crate::stats`]) |
+//! | `VIBETEA_WS_QUEUE_CAPACITY` | No | 1024 | Messages queued per WebSocket client before the slow-consumer policy applies |
+//! | `VIBETEA_SLOW_CONSUMER_POLICY` | No | drop_oldest | `drop_oldest`, `coalesce` or `disconnect` (see [`crate::outbox`]) |
+//! | `VIBETEA_WS_WRITE_TIMEOUT_SECS` | No | 10 | Seconds a single WebSocket write may take before the client is disconnected |
+//! | `VIBETEA_WS_PING_INTERVAL_SECS` | No | 30 | Interval between server-initiated WebSocket pings (0 disables, see [`crate::ws`]) |
+//! | `VIBETEA_WS_MAX_MISSED_PONGS` | No | 2 | Consecutive unanswered pings before a WebSocket client is dropped |
+//! | `VIBETEA_WS_MAX_LIFETIME_SECS` | No | 0 | Maximum WebSocket connection lifetime (0 disables) |
+//!
+//! †Not required if `VIBETEA_UNSAFE_NO_AUTH=true` or `VIBETEA_KEY_FILE` is set
 //!
-//! *Not required if `VIBETEA_UNSAFE_NO_AUTH=true`
+//! ‡Not required if `VIBETEA_UNSAFE_NO_AUTH=true` or `VIBETEA_SUBSCRIBER_TOKENS_FILE` is set
+//!
+//! ¶Required if `VIBETEA_RELAY_URL` is set
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::warn;
 
+use crate::auth::DEFAULT_MAX_CLOCK_SKEW;
+use crate::broadcast::{DEFAULT_CHANNEL_CAPACITY, DEFAULT_HISTORY_CAPACITY};
+use crate::cors::{validate_origin, OriginPolicy, ANY_ORIGIN};
+use crate::dedup::{DedupCache, DEFAULT_WINDOW as DEFAULT_DEDUP_WINDOW};
+use crate::outbox::{SlowConsumerPolicy, DEFAULT_QUEUE_CAPACITY, DEFAULT_WRITE_TIMEOUT};
+use crate::rate_limit::{
+    ConnectionLimiter, RateLimiter, DEFAULT_CAPACITY, DEFAULT_GLOBAL_CAPACITY, DEFAULT_GLOBAL_RATE,
+    DEFAULT_MAX_CONNECTIONS_PER_IP, DEFAULT_RATE,
+};
+use crate::relay::{RelayConfig, DEFAULT_BUFFER_SIZE as DEFAULT_RELAY_BUFFER_SIZE};
+use crate::stats::DEFAULT_PUSH_INTERVAL;
+use crate::store::{StoreConfig, DEFAULT_MAX_AGE, DEFAULT_MAX_BYTES};
+use crate::tls::TlsConfig;
+use crate::validation::{Validator, DEFAULT_MAX_EVENT_AGE};
+use crate::ws::{DEFAULT_MAX_MISSED_PONGS, DEFAULT_PING_INTERVAL};
+
+/// Default address the HTTP server binds to (all interfaces).
+const DEFAULT_HOST: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+
 /// Default HTTP server port.
 const DEFAULT_PORT: u16 = 8080;
 
+/// Placeholder shown in place of secrets by [`Settings::redact_secrets`].
+pub const REDACTED: &str = "<redacted>";
+
+/// Bytes per MiB, used to convert `VIBETEA_RETENTION_MAX_MB`.
+const BYTES_PER_MIB: u64 = 1024 * 1024;
+
 /// Errors that can occur when parsing configuration.
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -40,22 +115,434 @@ pub enum ConfigError {
     /// Configuration validation failed.
     #[error("configuration validation failed: {0}")]
     ValidationError(String),
+
+    /// The config file could not be read or parsed.
+    #[error("invalid config file {}: {message}", path.display())]
+    File { path: PathBuf, message: String },
 }
 
-/// Server configuration parsed from environment variables.
+/// Effective server configuration.
 #[derive(Debug, Clone)]
 pub struct Config {
     /// Map of source_id to base64-encoded Ed25519 public key.
     pub public_keys: HashMap<String, String>,
 
+    /// Path to a key registry file, reloaded whenever it changes.
+    pub key_file: Option<PathBuf>,
+
     /// Authentication token for subscriber clients.
     pub subscriber_token: Option<String>,
 
+    /// Path to a file of named, scoped subscriber tokens, reloaded whenever
+    /// it changes.
+    pub subscriber_tokens_file: Option<PathBuf>,
+
+    /// Path to a file of outbound webhooks. `None` disables webhooks.
+    pub webhooks_file: Option<PathBuf>,
+
+    /// Upstream hub that accepted events are forwarded to. `None` disables
+    /// relaying.
+    pub relay_url: Option<String>,
+
+    /// This hub's source ID at the upstream hub, also appended to the hops
+    /// of every event it relays.
+    pub relay_id: Option<String>,
+
+    /// Directory containing the private key the relay signs with.
+    pub relay_key_path: Option<PathBuf>,
+
+    /// Events buffered for the upstream hub while it is unreachable.
+    pub relay_buffer_size: usize,
+
+    /// Downstream hubs allowed to submit events relayed from other sources.
+    pub relay_sources: Vec<String>,
+
+    /// Address the HTTP server binds to.
+    pub host: IpAddr,
+
     /// HTTP server port.
     pub port: u16,
 
     /// When true, disables all authentication (development only).
     pub unsafe_no_auth: bool,
+
+    /// Browser origins allowed to call the REST routes and open WebSocket
+    /// and SSE subscriptions. Empty leaves browsers unrestricted.
+    pub allowed_origins: Vec<String>,
+
+    /// When true, browsers may send credentials with cross-origin requests.
+    pub cors_allow_credentials: bool,
+
+    /// PEM certificate chain served over TLS. `None` serves plain HTTP.
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM private key for `tls_cert`.
+    pub tls_key: Option<PathBuf>,
+
+    /// PEM CA certificates that sign monitor client certificates. When set,
+    /// `POST /events` requires a client certificate.
+    pub tls_client_ca: Option<PathBuf>,
+
+    /// Directory for the persistent event log. `None` disables persistence.
+    pub data_dir: Option<PathBuf>,
+
+    /// Maximum age of events kept in the event log.
+    pub retention_max_age: Duration,
+
+    /// Maximum combined size of the event log in bytes.
+    pub retention_max_bytes: u64,
+
+    /// Number of recent events kept in memory for resuming subscribers.
+    pub history_capacity: usize,
+
+    /// Capacity of the live broadcast channel shared by all subscribers.
+    pub channel_capacity: usize,
+
+    /// Requests per second allowed per source.
+    pub source_rate_limit: u32,
+
+    /// Burst capacity per source.
+    pub source_rate_burst: u32,
+
+    /// Events per second allowed across all sources. 0 disables the limit.
+    pub global_rate_limit: u32,
+
+    /// Burst capacity across all sources, in events.
+    pub global_rate_burst: u32,
+
+    /// Concurrent WebSocket and SSE connections allowed per client IP. 0 disables the cap.
+    pub max_ws_connections_per_ip: usize,
+
+    /// Allowed difference between a signed request's `X-Timestamp` and the
+    /// server clock. Nonces are remembered for the same window.
+    pub max_clock_skew: Duration,
+
+    /// How long accepted event IDs are remembered so retried duplicates can
+    /// be dropped. Zero disables deduplication.
+    pub dedup_window: Duration,
+
+    /// Oldest event timestamp accepted at ingest, relative to the server
+    /// clock. Zero accepts events of any age.
+    pub max_event_age: Duration,
+
+    /// Whether events in the version 1 wire format, without
+    /// `schemaVersion`, are accepted at ingest.
+    pub accept_legacy_events: bool,
+
+    /// Interval between rollups pushed to subscribers. Zero disables pushing;
+    /// `GET /stats` is always available.
+    pub stats_push_interval: Duration,
+
+    /// Messages queued per WebSocket client before the slow-consumer policy
+    /// applies.
+    pub ws_queue_capacity: usize,
+
+    /// What to do when a WebSocket client's queue is full.
+    pub slow_consumer_policy: SlowConsumerPolicy,
+
+    /// Maximum time a single WebSocket write may take before the client is
+    /// disconnected.
+    pub ws_write_timeout: Duration,
+
+    /// Interval between server-initiated WebSocket pings. Zero disables
+    /// pings.
+    pub ws_ping_interval: Duration,
+
+    /// Consecutive unanswered pings before a WebSocket client is dropped.
+    pub ws_max_missed_pongs: u32,
+
+    /// Maximum lifetime of a WebSocket connection. Zero means no limit.
+    pub ws_max_lifetime: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            public_keys: HashMap::new(),
+            key_file: None,
+            subscriber_token: None,
+            subscriber_tokens_file: None,
+            webhooks_file: None,
+            relay_url: None,
+            relay_id: None,
+            relay_key_path: None,
+            relay_buffer_size: DEFAULT_RELAY_BUFFER_SIZE,
+            relay_sources: Vec::new(),
+            host: DEFAULT_HOST,
+            port: DEFAULT_PORT,
+            unsafe_no_auth: false,
+            allowed_origins: Vec::new(),
+            cors_allow_credentials: false,
+            tls_cert: None,
+            tls_key: None,
+            tls_client_ca: None,
+            data_dir: None,
+            retention_max_age: DEFAULT_MAX_AGE,
+            retention_max_bytes: DEFAULT_MAX_BYTES,
+            history_capacity: DEFAULT_HISTORY_CAPACITY,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            source_rate_limit: DEFAULT_RATE as u32,
+            source_rate_burst: DEFAULT_CAPACITY,
+            global_rate_limit: DEFAULT_GLOBAL_RATE as u32,
+            global_rate_burst: DEFAULT_GLOBAL_CAPACITY,
+            max_ws_connections_per_ip: DEFAULT_MAX_CONNECTIONS_PER_IP,
+            max_clock_skew: DEFAULT_MAX_CLOCK_SKEW,
+            dedup_window: DEFAULT_DEDUP_WINDOW,
+            max_event_age: DEFAULT_MAX_EVENT_AGE,
+            accept_legacy_events: true,
+            stats_push_interval: DEFAULT_PUSH_INTERVAL,
+            ws_queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            slow_consumer_policy: SlowConsumerPolicy::default(),
+            ws_write_timeout: DEFAULT_WRITE_TIMEOUT,
+            ws_ping_interval: DEFAULT_PING_INTERVAL,
+            ws_max_missed_pongs: DEFAULT_MAX_MISSED_PONGS,
+            ws_max_lifetime: Duration::ZERO,
+        }
+    }
+}
+
+/// A partial configuration from one source: a config file, the environment
+/// or command-line flags.
+///
+/// Every field is optional, and unset fields fall back to the next source
+/// (see [`Config::load`]). Field names are the environment variable names
+/// without the `VIBETEA_` prefix, in lowercase, so a config file reads like
+/// the environment:
+///
+/// ```toml
+/// host = "127.0.0.1"
+/// port = 8080
+/// subscriber_token = "secret-token"
+/// retention_hours = 48
+///
+/// [public_keys]
+/// laptop = "base64-encoded-public-key"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    pub host: Option<IpAddr>,
+    pub port: Option<u16>,
+    pub unsafe_no_auth: Option<bool>,
+    pub public_keys: Option<BTreeMap<String, String>>,
+    pub key_file: Option<PathBuf>,
+    pub subscriber_token: Option<String>,
+    pub subscriber_tokens_file: Option<PathBuf>,
+    pub webhooks_file: Option<PathBuf>,
+    pub relay_url: Option<String>,
+    pub relay_id: Option<String>,
+    pub relay_key_path: Option<PathBuf>,
+    pub relay_buffer_size: Option<usize>,
+    pub relay_sources: Option<Vec<String>>,
+    pub allowed_origins: Option<Vec<String>>,
+    pub cors_allow_credentials: Option<bool>,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+    pub tls_client_ca: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub retention_hours: Option<u64>,
+    pub retention_max_mb: Option<u64>,
+    pub history_capacity: Option<usize>,
+    pub channel_capacity: Option<usize>,
+    pub rate_limit: Option<u32>,
+    pub rate_burst: Option<u32>,
+    pub global_rate_limit: Option<u32>,
+    pub global_rate_burst: Option<u32>,
+    pub max_ws_per_ip: Option<usize>,
+    pub max_clock_skew_secs: Option<u64>,
+    pub dedup_window_secs: Option<u64>,
+    pub max_event_age_secs: Option<u64>,
+    pub accept_legacy_events: Option<bool>,
+    pub stats_interval_secs: Option<u64>,
+    pub ws_queue_capacity: Option<usize>,
+    pub slow_consumer_policy: Option<SlowConsumerPolicy>,
+    pub ws_write_timeout_secs: Option<u64>,
+    pub ws_ping_interval_secs: Option<u64>,
+    pub ws_max_missed_pongs: Option<u32>,
+    pub ws_max_lifetime_secs: Option<u64>,
+}
+
+impl Settings {
+    /// Reads settings from a TOML config file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::File`] if the file cannot be read or is not
+    /// valid TOML with known keys.
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let file_error = |message: String| ConfigError::File {
+            path: path.to_path_buf(),
+            message,
+        };
+        let contents = fs::read_to_string(path).map_err(|err| file_error(err.to_string()))?;
+        toml::from_str(&contents).map_err(|err| file_error(err.message().to_string()))
+    }
+
+    /// Reads settings from `VIBETEA_*` environment variables (and `PORT`).
+    ///
+    /// Unset and empty variables are left unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError` if a variable is set to an invalid value.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Ok(Self {
+            host: parse_host()?,
+            port: parse_port()?,
+            unsafe_no_auth: env::var_os("VIBETEA_UNSAFE_NO_AUTH")
+                .map(|_| parse_bool_env("VIBETEA_UNSAFE_NO_AUTH")),
+            public_keys: Some(parse_public_keys()?)
+                .filter(|keys| !keys.is_empty())
+                .map(|keys| keys.into_iter().collect()),
+            key_file: parse_path_env("VIBETEA_KEY_FILE"),
+            subscriber_token: env::var("VIBETEA_SUBSCRIBER_TOKEN").ok(),
+            subscriber_tokens_file: parse_path_env("VIBETEA_SUBSCRIBER_TOKENS_FILE"),
+            webhooks_file: parse_path_env("VIBETEA_WEBHOOKS_FILE"),
+            relay_url: env::var("VIBETEA_RELAY_URL").ok(),
+            relay_id: env::var("VIBETEA_RELAY_ID").ok(),
+            relay_key_path: parse_path_env("VIBETEA_RELAY_KEY_PATH"),
+            relay_buffer_size: parse_usize_env("VIBETEA_RELAY_BUFFER_SIZE")?,
+            relay_sources: parse_list_env("VIBETEA_RELAY_SOURCES"),
+            allowed_origins: parse_list_env("VIBETEA_ALLOWED_ORIGINS"),
+            cors_allow_credentials: env::var_os("VIBETEA_CORS_ALLOW_CREDENTIALS")
+                .map(|_| parse_bool_env("VIBETEA_CORS_ALLOW_CREDENTIALS")),
+            tls_cert: parse_path_env("VIBETEA_TLS_CERT"),
+            tls_key: parse_path_env("VIBETEA_TLS_KEY"),
+            tls_client_ca: parse_path_env("VIBETEA_TLS_CLIENT_CA"),
+            data_dir: parse_path_env("VIBETEA_DATA_DIR"),
+            retention_hours: parse_u64_env("VIBETEA_RETENTION_HOURS")?,
+            retention_max_mb: parse_u64_env("VIBETEA_RETENTION_MAX_MB")?,
+            history_capacity: parse_usize_env("VIBETEA_HISTORY_CAPACITY")?,
+            channel_capacity: parse_usize_env("VIBETEA_CHANNEL_CAPACITY")?,
+            rate_limit: parse_u32_env("VIBETEA_RATE_LIMIT")?,
+            rate_burst: parse_u32_env("VIBETEA_RATE_BURST")?,
+            global_rate_limit: parse_u32_env("VIBETEA_GLOBAL_RATE_LIMIT")?,
+            global_rate_burst: parse_u32_env("VIBETEA_GLOBAL_RATE_BURST")?,
+            max_ws_per_ip: parse_usize_env("VIBETEA_MAX_WS_PER_IP")?,
+            max_clock_skew_secs: parse_u64_env("VIBETEA_MAX_CLOCK_SKEW_SECS")?,
+            dedup_window_secs: parse_u64_env("VIBETEA_DEDUP_WINDOW_SECS")?,
+            max_event_age_secs: parse_u64_env("VIBETEA_MAX_EVENT_AGE_SECS")?,
+            accept_legacy_events: env::var_os("VIBETEA_ACCEPT_LEGACY_EVENTS")
+                .map(|_| parse_bool_env("VIBETEA_ACCEPT_LEGACY_EVENTS")),
+            stats_interval_secs: parse_u64_env("VIBETEA_STATS_INTERVAL_SECS")?,
+            ws_queue_capacity: parse_usize_env("VIBETEA_WS_QUEUE_CAPACITY")?,
+            slow_consumer_policy: parse_slow_consumer_policy()?,
+            ws_write_timeout_secs: parse_u64_env("VIBETEA_WS_WRITE_TIMEOUT_SECS")?,
+            ws_ping_interval_secs: parse_u64_env("VIBETEA_WS_PING_INTERVAL_SECS")?,
+            ws_max_missed_pongs: parse_u32_env("VIBETEA_WS_MAX_MISSED_PONGS")?,
+            ws_max_lifetime_secs: parse_u64_env("VIBETEA_WS_MAX_LIFETIME_SECS")?,
+        })
+    }
+
+    /// Returns these settings, with unset fields taken from `fallback`.
+    #[must_use]
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            host: self.host.or(fallback.host),
+            port: self.port.or(fallback.port),
+            unsafe_no_auth: self.unsafe_no_auth.or(fallback.unsafe_no_auth),
+            public_keys: self.public_keys.or(fallback.public_keys),
+            key_file: self.key_file.or(fallback.key_file),
+            subscriber_token: self.subscriber_token.or(fallback.subscriber_token),
+            subscriber_tokens_file: self
+                .subscriber_tokens_file
+                .or(fallback.subscriber_tokens_file),
+            webhooks_file: self.webhooks_file.or(fallback.webhooks_file),
+            relay_url: self.relay_url.or(fallback.relay_url),
+            relay_id: self.relay_id.or(fallback.relay_id),
+            relay_key_path: self.relay_key_path.or(fallback.relay_key_path),
+            relay_buffer_size: self.relay_buffer_size.or(fallback.relay_buffer_size),
+            relay_sources: self.relay_sources.or(fallback.relay_sources),
+            allowed_origins: self.allowed_origins.or(fallback.allowed_origins),
+            cors_allow_credentials: self
+                .cors_allow_credentials
+                .or(fallback.cors_allow_credentials),
+            tls_cert: self.tls_cert.or(fallback.tls_cert),
+            tls_key: self.tls_key.or(fallback.tls_key),
+            tls_client_ca: self.tls_client_ca.or(fallback.tls_client_ca),
+            data_dir: self.data_dir.or(fallback.data_dir),
+            retention_hours: self.retention_hours.or(fallback.retention_hours),
+            retention_max_mb: self.retention_max_mb.or(fallback.retention_max_mb),
+            history_capacity: self.history_capacity.or(fallback.history_capacity),
+            channel_capacity: self.channel_capacity.or(fallback.channel_capacity),
+            rate_limit: self.rate_limit.or(fallback.rate_limit),
+            rate_burst: self.rate_burst.or(fallback.rate_burst),
+            global_rate_limit: self.global_rate_limit.or(fallback.global_rate_limit),
+            global_rate_burst: self.global_rate_burst.or(fallback.global_rate_burst),
+            max_ws_per_ip: self.max_ws_per_ip.or(fallback.max_ws_per_ip),
+            max_clock_skew_secs: self.max_clock_skew_secs.or(fallback.max_clock_skew_secs),
+            dedup_window_secs: self.dedup_window_secs.or(fallback.dedup_window_secs),
+            max_event_age_secs: self.max_event_age_secs.or(fallback.max_event_age_secs),
+            accept_legacy_events: self.accept_legacy_events.or(fallback.accept_legacy_events),
+            stats_interval_secs: self.stats_interval_secs.or(fallback.stats_interval_secs),
+            ws_queue_capacity: self.ws_queue_capacity.or(fallback.ws_queue_capacity),
+            slow_consumer_policy: self.slow_consumer_policy.or(fallback.slow_consumer_policy),
+            ws_write_timeout_secs: self
+                .ws_write_timeout_secs
+                .or(fallback.ws_write_timeout_secs),
+            ws_ping_interval_secs: self
+                .ws_ping_interval_secs
+                .or(fallback.ws_ping_interval_secs),
+            ws_max_missed_pongs: self.ws_max_missed_pongs.or(fallback.ws_max_missed_pongs),
+            ws_max_lifetime_secs: self.ws_max_lifetime_secs.or(fallback.ws_max_lifetime_secs),
+        }
+    }
+
+    /// Replaces secrets with [`REDACTED`], for display.
+    #[must_use]
+    pub fn redact_secrets(mut self) -> Self {
+        if self.subscriber_token.is_some() {
+            self.subscriber_token = Some(REDACTED.to_string());
+        }
+        self
+    }
+}
+
+impl From<&Config> for Settings {
+    /// Returns every setting of an effective configuration.
+    fn from(config: &Config) -> Self {
+        Self {
+            host: Some(config.host),
+            port: Some(config.port),
+            unsafe_no_auth: Some(config.unsafe_no_auth),
+            public_keys: Some(config.public_keys.clone().into_iter().collect()),
+            key_file: config.key_file.clone(),
+            subscriber_token: config.subscriber_token.clone(),
+            subscriber_tokens_file: config.subscriber_tokens_file.clone(),
+            webhooks_file: config.webhooks_file.clone(),
+            relay_url: config.relay_url.clone(),
+            relay_id: config.relay_id.clone(),
+            relay_key_path: config.relay_key_path.clone(),
+            relay_buffer_size: Some(config.relay_buffer_size),
+            relay_sources: Some(config.relay_sources.clone()),
+            allowed_origins: Some(config.allowed_origins.clone()),
+            cors_allow_credentials: Some(config.cors_allow_credentials),
+            tls_cert: config.tls_cert.clone(),
+            tls_key: config.tls_key.clone(),
+            tls_client_ca: config.tls_client_ca.clone(),
+            data_dir: config.data_dir.clone(),
+            retention_hours: Some(config.retention_max_age.as_secs() / 3600),
+            retention_max_mb: Some(config.retention_max_bytes / BYTES_PER_MIB),
+            history_capacity: Some(config.history_capacity),
+            channel_capacity: Some(config.channel_capacity),
+            rate_limit: Some(config.source_rate_limit),
+            rate_burst: Some(config.source_rate_burst),
+            global_rate_limit: Some(config.global_rate_limit),
+            global_rate_burst: Some(config.global_rate_burst),
+            max_ws_per_ip: Some(config.max_ws_connections_per_ip),
+            max_clock_skew_secs: Some(config.max_clock_skew.as_secs()),
+            dedup_window_secs: Some(config.dedup_window.as_secs()),
+            max_event_age_secs: Some(config.max_event_age.as_secs()),
+            accept_legacy_events: Some(config.accept_legacy_events),
+            stats_interval_secs: Some(config.stats_push_interval.as_secs()),
+            ws_queue_capacity: Some(config.ws_queue_capacity),
+            slow_consumer_policy: Some(config.slow_consumer_policy),
+            ws_write_timeout_secs: Some(config.ws_write_timeout.as_secs()),
+            ws_ping_interval_secs: Some(config.ws_ping_interval.as_secs()),
+            ws_max_missed_pongs: Some(config.ws_max_missed_pongs),
+            ws_max_lifetime_secs: Some(config.ws_max_lifetime.as_secs()),
+        }
+    }
 }
 
 impl Config {
@@ -77,16 +564,120 @@ impl Config {
     /// println!("Server will listen on port {}", config.port);
     /// ```
     pub fn from_env() -> Result<Self, ConfigError> {
-        let unsafe_no_auth = parse_bool_env("VIBETEA_UNSAFE_NO_AUTH");
-        let port = parse_port()?;
-        let public_keys = parse_public_keys()?;
-        let subscriber_token = env::var("VIBETEA_SUBSCRIBER_TOKEN").ok();
+        Self::from_settings(Settings::from_env()?)
+    }
 
+    /// Loads configuration from `overrides` (usually command-line flags),
+    /// the environment and an optional config file, in that order of
+    /// precedence.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError` if the config file cannot be read, a setting is
+    /// invalid, or the combined configuration fails validation.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    /// use vibetea_server::config::{Config, Settings};
+    ///
+    /// let overrides = Settings { port: Some(9000), ..Settings::default() };
+    /// let config = Config::load(Some(Path::new("server.toml")), overrides)
+    ///     .expect("Failed to load config");
+    /// assert_eq!(config.port, 9000);
+    /// ```
+    pub fn load(file: Option<&Path>, overrides: Settings) -> Result<Self, ConfigError> {
+        let file = file
+            .map(Settings::from_file)
+            .transpose()?
+            .unwrap_or_default();
+        Self::from_settings(overrides.or(Settings::from_env()?).or(file))
+    }
+
+    /// Builds a validated configuration from `settings`, using defaults for
+    /// unset fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError` if the configuration fails validation.
+    pub fn from_settings(settings: Settings) -> Result<Self, ConfigError> {
         let config = Self {
-            public_keys,
-            subscriber_token,
-            port,
-            unsafe_no_auth,
+            public_keys: settings
+                .public_keys
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            key_file: settings.key_file,
+            subscriber_token: settings.subscriber_token,
+            subscriber_tokens_file: settings.subscriber_tokens_file,
+            webhooks_file: settings.webhooks_file,
+            relay_url: settings.relay_url,
+            relay_id: settings.relay_id,
+            relay_key_path: settings.relay_key_path,
+            relay_buffer_size: settings
+                .relay_buffer_size
+                .unwrap_or(DEFAULT_RELAY_BUFFER_SIZE),
+            relay_sources: settings.relay_sources.unwrap_or_default(),
+            host: settings.host.unwrap_or(DEFAULT_HOST),
+            port: settings.port.unwrap_or(DEFAULT_PORT),
+            unsafe_no_auth: settings.unsafe_no_auth.unwrap_or(false),
+            allowed_origins: settings.allowed_origins.unwrap_or_default(),
+            cors_allow_credentials: settings.cors_allow_credentials.unwrap_or(false),
+            tls_cert: settings.tls_cert,
+            tls_key: settings.tls_key,
+            tls_client_ca: settings.tls_client_ca,
+            data_dir: settings.data_dir,
+            retention_max_age: settings
+                .retention_hours
+                .map_or(DEFAULT_MAX_AGE, |hours| Duration::from_secs(hours * 3600)),
+            retention_max_bytes: settings
+                .retention_max_mb
+                .map_or(DEFAULT_MAX_BYTES, |mb| mb.saturating_mul(BYTES_PER_MIB)),
+            history_capacity: settings
+                .history_capacity
+                .unwrap_or(DEFAULT_HISTORY_CAPACITY),
+            channel_capacity: settings
+                .channel_capacity
+                .unwrap_or(DEFAULT_CHANNEL_CAPACITY),
+            source_rate_limit: settings.rate_limit.unwrap_or(DEFAULT_RATE as u32),
+            source_rate_burst: settings.rate_burst.unwrap_or(DEFAULT_CAPACITY),
+            global_rate_limit: settings
+                .global_rate_limit
+                .unwrap_or(DEFAULT_GLOBAL_RATE as u32),
+            global_rate_burst: settings
+                .global_rate_burst
+                .unwrap_or(DEFAULT_GLOBAL_CAPACITY),
+            max_ws_connections_per_ip: settings
+                .max_ws_per_ip
+                .unwrap_or(DEFAULT_MAX_CONNECTIONS_PER_IP),
+            max_clock_skew: settings
+                .max_clock_skew_secs
+                .map_or(DEFAULT_MAX_CLOCK_SKEW, Duration::from_secs),
+            dedup_window: settings
+                .dedup_window_secs
+                .map_or(DEFAULT_DEDUP_WINDOW, Duration::from_secs),
+            max_event_age: settings
+                .max_event_age_secs
+                .map_or(DEFAULT_MAX_EVENT_AGE, Duration::from_secs),
+            accept_legacy_events: settings.accept_legacy_events.unwrap_or(true),
+            stats_push_interval: settings
+                .stats_interval_secs
+                .map_or(DEFAULT_PUSH_INTERVAL, Duration::from_secs),
+            ws_queue_capacity: settings.ws_queue_capacity.unwrap_or(DEFAULT_QUEUE_CAPACITY),
+            slow_consumer_policy: settings.slow_consumer_policy.unwrap_or_default(),
+            ws_write_timeout: settings
+                .ws_write_timeout_secs
+                .map_or(DEFAULT_WRITE_TIMEOUT, Duration::from_secs),
+            ws_ping_interval: settings
+                .ws_ping_interval_secs
+                .map_or(DEFAULT_PING_INTERVAL, Duration::from_secs),
+            ws_max_missed_pongs: settings
+                .ws_max_missed_pongs
+                .unwrap_or(DEFAULT_MAX_MISSED_PONGS),
+            ws_max_lifetime: settings
+                .ws_max_lifetime_secs
+                .map_or(Duration::ZERO, Duration::from_secs),
         };
 
         config.validate()?;
@@ -101,22 +692,196 @@ impl Config {
         Ok(config)
     }
 
+    /// Returns the address the server listens on.
+    #[must_use]
+    pub fn bind_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.host, self.port)
+    }
+
+    /// Returns the event store configuration, or `None` if persistence is disabled.
+    #[must_use]
+    pub fn store_config(&self) -> Option<StoreConfig> {
+        self.data_dir.as_ref().map(|dir| {
+            StoreConfig::new(dir)
+                .with_max_age(self.retention_max_age)
+                .with_max_bytes(self.retention_max_bytes)
+        })
+    }
+
+    /// Builds the browser origin policy from the configured origins.
+    #[must_use]
+    pub fn origin_policy(&self) -> OriginPolicy {
+        OriginPolicy::new(&self.allowed_origins, self.cors_allow_credentials)
+    }
+
+    /// Returns the TLS configuration, or `None` if the server uses plain HTTP.
+    #[must_use]
+    pub fn tls_config(&self) -> Option<TlsConfig> {
+        match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => Some(TlsConfig {
+                cert: cert.clone(),
+                key: key.clone(),
+                client_ca: self.tls_client_ca.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the relay configuration, or `None` if events are not
+    /// forwarded upstream.
+    #[must_use]
+    pub fn relay_config(&self) -> Option<RelayConfig> {
+        match (&self.relay_url, &self.relay_id, &self.relay_key_path) {
+            (Some(url), Some(hub_id), Some(key_path)) => Some(
+                RelayConfig::new(url.clone(), hub_id.clone(), key_path.clone())
+                    .with_buffer_size(self.relay_buffer_size),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Builds the ingest rate limiter from the configured limits.
+    #[must_use]
+    pub fn rate_limiter(&self) -> RateLimiter {
+        let limiter = RateLimiter::new(f64::from(self.source_rate_limit), self.source_rate_burst);
+        if self.global_rate_limit > 0 {
+            limiter.with_global_limit(f64::from(self.global_rate_limit), self.global_rate_burst)
+        } else {
+            limiter
+        }
+    }
+
+    /// Builds the event ID cache used to drop retried duplicates.
+    #[must_use]
+    pub fn dedup_cache(&self) -> DedupCache {
+        DedupCache::new(self.dedup_window)
+    }
+
+    /// Builds the validator applied to ingested events. Timestamps may run
+    /// ahead of the server clock by the same skew allowed for signatures.
+    #[must_use]
+    pub fn validator(&self) -> Validator {
+        Validator::new(self.max_clock_skew, self.max_event_age)
+            .with_legacy_events(self.accept_legacy_events)
+    }
+
+    /// Builds the WebSocket connection limiter from the configured cap.
+    #[must_use]
+    pub fn connection_limiter(&self) -> ConnectionLimiter {
+        ConnectionLimiter::new(self.max_ws_connections_per_ip)
+    }
+
     /// Validate the configuration.
     ///
-    /// Ensures that either `unsafe_no_auth` is true, or both `public_keys` and
-    /// `subscriber_token` are properly configured.
+    /// Ensures that either `unsafe_no_auth` is true, or both a key source
+    /// (`public_keys` or `key_file`) and a subscriber credential
+    /// (`subscriber_token` or `subscriber_tokens_file`) are configured.
     fn validate(&self) -> Result<(), ConfigError> {
+        if self.source_rate_limit == 0 || self.source_rate_burst == 0 {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_RATE_LIMIT and VIBETEA_RATE_BURST must be greater than 0".to_string(),
+            ));
+        }
+
+        for origin in &self.allowed_origins {
+            validate_origin(origin).map_err(|message| ConfigError::InvalidFormat {
+                var: "VIBETEA_ALLOWED_ORIGINS".to_string(),
+                message,
+            })?;
+        }
+
+        if self.cors_allow_credentials
+            && self
+                .allowed_origins
+                .iter()
+                .any(|origin| origin == ANY_ORIGIN)
+        {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_CORS_ALLOW_CREDENTIALS cannot be combined with VIBETEA_ALLOWED_ORIGINS=*"
+                    .to_string(),
+            ));
+        }
+
+        if self.tls_cert.is_some() != self.tls_key.is_some() {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_TLS_CERT and VIBETEA_TLS_KEY must be set together".to_string(),
+            ));
+        }
+
+        if self.tls_client_ca.is_some() && self.tls_cert.is_none() {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_TLS_CLIENT_CA requires VIBETEA_TLS_CERT and VIBETEA_TLS_KEY".to_string(),
+            ));
+        }
+
+        if self.channel_capacity == 0 {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_CHANNEL_CAPACITY must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.ws_queue_capacity == 0 {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_WS_QUEUE_CAPACITY must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.ws_write_timeout.is_zero() {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_WS_WRITE_TIMEOUT_SECS must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.ws_max_missed_pongs == 0 {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_WS_MAX_MISSED_PONGS must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.max_clock_skew.is_zero() {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_MAX_CLOCK_SKEW_SECS must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.global_rate_limit > 0 && self.global_rate_burst == 0 {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_GLOBAL_RATE_BURST must be greater than 0".to_string(),
+            ));
+        }
+
+        if let Some(url) = &self.relay_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(ConfigError::InvalidFormat {
+                    var: "VIBETEA_RELAY_URL".to_string(),
+                    message: format!("expected an http:// or https:// URL, got {url:?}"),
+                });
+            }
+            if self.relay_id.is_none() || self.relay_key_path.is_none() {
+                return Err(ConfigError::ValidationError(
+                    "VIBETEA_RELAY_URL requires VIBETEA_RELAY_ID and VIBETEA_RELAY_KEY_PATH"
+                        .to_string(),
+                ));
+            }
+        }
+
+        if self.relay_buffer_size == 0 {
+            return Err(ConfigError::ValidationError(
+                "VIBETEA_RELAY_BUFFER_SIZE must be greater than 0".to_string(),
+            ));
+        }
+
         if self.unsafe_no_auth {
             return Ok(());
         }
 
-        if self.public_keys.is_empty() {
+        if self.public_keys.is_empty() && self.key_file.is_none() {
             return Err(ConfigError::MissingEnvVar(
                 "VIBETEA_PUBLIC_KEYS".to_string(),
             ));
         }
 
-        if self.subscriber_token.is_none() {
+        if self.subscriber_token.is_none() && self.subscriber_tokens_file.is_none() {
             return Err(ConfigError::MissingEnvVar(
                 "VIBETEA_SUBSCRIBER_TOKEN".to_string(),
             ));
@@ -136,15 +901,128 @@ fn parse_bool_env(name: &str) -> bool {
         .unwrap_or(false)
 }
 
-/// Parse the PORT environment variable.
+/// Parse an optional unsigned integer environment variable.
+///
+/// Returns `None` if the variable is not set or empty.
+fn parse_u64_env(name: &str) -> Result<Option<u64>, ConfigError> {
+    match env::var(name) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError::InvalidFormat {
+                var: name.to_string(),
+                message: format!("expected a non-negative integer, got '{value}'"),
+            }),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(ConfigError::InvalidFormat {
+            var: name.to_string(),
+            message: "contains invalid unicode".to_string(),
+        }),
+    }
+}
+
+/// Parse an optional `usize` environment variable.
+fn parse_usize_env(name: &str) -> Result<Option<usize>, ConfigError> {
+    Ok(parse_u64_env(name)?.map(|value| usize::try_from(value).unwrap_or(usize::MAX)))
+}
+
+/// Parse an optional path environment variable.
+///
+/// Returns `None` if the variable is not set or empty.
+fn parse_path_env(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Parse an optional comma-separated list environment variable.
+///
+/// Returns `None` if the variable is not set or has no entries.
+fn parse_list_env(name: &str) -> Option<Vec<String>> {
+    let value = env::var(name).ok()?;
+    let items: Vec<String> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(ToString::to_string)
+        .collect();
+    (!items.is_empty()).then_some(items)
+}
+
+/// Parse an optional `u32` environment variable.
+fn parse_u32_env(name: &str) -> Result<Option<u32>, ConfigError> {
+    parse_u64_env(name)?
+        .map(|value| {
+            u32::try_from(value).map_err(|_| ConfigError::InvalidFormat {
+                var: name.to_string(),
+                message: format!("must be at most {}", u32::MAX),
+            })
+        })
+        .transpose()
+}
+
+/// Parse the VIBETEA_SLOW_CONSUMER_POLICY environment variable.
 ///
-/// Returns the default port if not set.
-fn parse_port() -> Result<u16, ConfigError> {
-    match env::var("PORT") {
-        Ok(port_str) => Ok(port_str.parse()?),
-        Err(env::VarError::NotPresent) => Ok(DEFAULT_PORT),
+/// Returns `None` if not set or empty.
+fn parse_slow_consumer_policy() -> Result<Option<SlowConsumerPolicy>, ConfigError> {
+    const VAR: &str = "VIBETEA_SLOW_CONSUMER_POLICY";
+    match env::var(VAR) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|message| ConfigError::InvalidFormat {
+                var: VAR.to_string(),
+                message,
+            }),
+        Err(env::VarError::NotPresent) => Ok(None),
         Err(env::VarError::NotUnicode(_)) => Err(ConfigError::InvalidFormat {
-            var: "PORT".to_string(),
+            var: VAR.to_string(),
+            message: "contains invalid unicode".to_string(),
+        }),
+    }
+}
+
+/// Parse the VIBETEA_PORT environment variable, falling back to PORT as set
+/// by most hosting platforms.
+///
+/// Returns `None` if neither is set.
+fn parse_port() -> Result<Option<u16>, ConfigError> {
+    for var in ["VIBETEA_PORT", "PORT"] {
+        match env::var(var) {
+            Ok(port_str) => return Ok(Some(port_str.parse()?)),
+            Err(env::VarError::NotPresent) => {}
+            Err(env::VarError::NotUnicode(_)) => {
+                return Err(ConfigError::InvalidFormat {
+                    var: var.to_string(),
+                    message: "contains invalid unicode".to_string(),
+                })
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Parse the VIBETEA_HOST environment variable.
+///
+/// Returns `None` if not set or empty.
+fn parse_host() -> Result<Option<IpAddr>, ConfigError> {
+    match env::var("VIBETEA_HOST") {
+        Ok(host) if host.trim().is_empty() => Ok(None),
+        Ok(host) => host
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError::InvalidFormat {
+                var: "VIBETEA_HOST".to_string(),
+                message: format!("expected an IP address, got '{host}'"),
+            }),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(ConfigError::InvalidFormat {
+            var: "VIBETEA_HOST".to_string(),
             message: "contains invalid unicode".to_string(),
         }),
     }
@@ -230,7 +1108,8 @@ mod tests {
 
     impl Drop for EnvGuard {
         fn drop(&mut self) {
-            for (key, value) in &self.vars {
+            // Restore in reverse so a variable set twice gets its original value
+            for (key, value) in self.vars.iter().rev() {
                 match value {
                     Some(v) => env::set_var(key, v),
                     None => env::remove_var(key),
@@ -288,6 +1167,7 @@ mod tests {
         let mut guard = EnvGuard::new();
         guard.remove("VIBETEA_UNSAFE_NO_AUTH");
         guard.remove("VIBETEA_PUBLIC_KEYS");
+        guard.remove("VIBETEA_KEY_FILE");
         guard.set("VIBETEA_SUBSCRIBER_TOKEN", "secret-token");
 
         let result = Config::from_env();
@@ -296,6 +1176,23 @@ mod tests {
         assert!(matches!(err, ConfigError::MissingEnvVar(ref v) if v == "VIBETEA_PUBLIC_KEYS"));
     }
 
+    #[test]
+    #[serial]
+    fn test_config_key_file_replaces_public_keys_requirement() {
+        let mut guard = EnvGuard::new();
+        guard.remove("VIBETEA_UNSAFE_NO_AUTH");
+        guard.remove("VIBETEA_PUBLIC_KEYS");
+        guard.set("VIBETEA_KEY_FILE", "/etc/vibetea/keys.json");
+        guard.set("VIBETEA_SUBSCRIBER_TOKEN", "secret-token");
+
+        let config = Config::from_env().expect("should parse config");
+        assert!(config.public_keys.is_empty());
+        assert_eq!(
+            config.key_file,
+            Some(PathBuf::from("/etc/vibetea/keys.json"))
+        );
+    }
+
     #[test]
     #[serial]
     fn test_config_missing_subscriber_token_without_unsafe_no_auth() {
@@ -303,6 +1200,7 @@ mod tests {
         guard.remove("VIBETEA_UNSAFE_NO_AUTH");
         guard.set("VIBETEA_PUBLIC_KEYS", "source1:pubkey1");
         guard.remove("VIBETEA_SUBSCRIBER_TOKEN");
+        guard.remove("VIBETEA_SUBSCRIBER_TOKENS_FILE");
 
         let result = Config::from_env();
         assert!(result.is_err());
@@ -312,6 +1210,23 @@ mod tests {
         );
     }
 
+    #[test]
+    #[serial]
+    fn test_config_tokens_file_replaces_subscriber_token_requirement() {
+        let mut guard = EnvGuard::new();
+        guard.remove("VIBETEA_UNSAFE_NO_AUTH");
+        guard.set("VIBETEA_PUBLIC_KEYS", "source1:pubkey1");
+        guard.remove("VIBETEA_SUBSCRIBER_TOKEN");
+        guard.set("VIBETEA_SUBSCRIBER_TOKENS_FILE", "/etc/vibetea/tokens.json");
+
+        let config = Config::from_env().expect("should parse config");
+        assert!(config.subscriber_token.is_none());
+        assert_eq!(
+            config.subscriber_tokens_file,
+            Some(PathBuf::from("/etc/vibetea/tokens.json"))
+        );
+    }
+
     #[test]
     #[serial]
     fn test_parse_public_keys_valid() {
@@ -398,30 +1313,571 @@ mod tests {
         assert!(!parse_bool_env("TEST_BOOL"));
     }
 
+    #[test]
+    #[serial]
+    fn test_config_store_disabled_by_default() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_DATA_DIR");
+        guard.remove("VIBETEA_RETENTION_HOURS");
+        guard.remove("VIBETEA_RETENTION_MAX_MB");
+        guard.remove("VIBETEA_HISTORY_CAPACITY");
+
+        let config = Config::from_env().expect("should parse config");
+        assert!(config.data_dir.is_none());
+        assert!(config.store_config().is_none());
+        assert_eq!(config.retention_max_age, DEFAULT_MAX_AGE);
+        assert_eq!(config.retention_max_bytes, DEFAULT_MAX_BYTES);
+        assert_eq!(config.history_capacity, DEFAULT_HISTORY_CAPACITY);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_stats_interval() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_STATS_INTERVAL_SECS");
+
+        let config = Config::from_env().expect("should parse config");
+        assert_eq!(config.stats_push_interval, DEFAULT_PUSH_INTERVAL);
+
+        guard.set("VIBETEA_STATS_INTERVAL_SECS", "0");
+        let config = Config::from_env().expect("should parse config");
+        assert!(config.stats_push_interval.is_zero());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_invalid_stats_interval() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_STATS_INTERVAL_SECS", "soon");
+
+        assert!(Config::from_env().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_slow_consumer_settings() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_WS_QUEUE_CAPACITY");
+        guard.remove("VIBETEA_WS_WRITE_TIMEOUT_SECS");
+        guard.set("VIBETEA_SLOW_CONSUMER_POLICY", "coalesce");
+
+        let config = Config::from_env().expect("should parse config");
+        assert_eq!(config.ws_queue_capacity, DEFAULT_QUEUE_CAPACITY);
+        assert_eq!(config.ws_write_timeout, DEFAULT_WRITE_TIMEOUT);
+        assert_eq!(config.slow_consumer_policy, SlowConsumerPolicy::Coalesce);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_invalid_slow_consumer_settings() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_SLOW_CONSUMER_POLICY", "block");
+        assert!(Config::from_env().is_err());
+        drop(guard);
+
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_WS_QUEUE_CAPACITY", "0");
+        assert!(Config::from_env().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_heartbeat_settings() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_WS_PING_INTERVAL_SECS", "0");
+        guard.remove("VIBETEA_WS_MAX_MISSED_PONGS");
+        guard.set("VIBETEA_WS_MAX_LIFETIME_SECS", "3600");
+
+        let config = Config::from_env().expect("should parse config");
+        assert!(config.ws_ping_interval.is_zero());
+        assert_eq!(config.ws_max_missed_pongs, DEFAULT_MAX_MISSED_PONGS);
+        assert_eq!(config.ws_max_lifetime, Duration::from_secs(3600));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_invalid_max_missed_pongs() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_WS_MAX_MISSED_PONGS", "0");
+
+        assert!(Config::from_env().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_tls_settings() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_TLS_CERT", "/etc/vibetea/cert.pem");
+        guard.set("VIBETEA_TLS_KEY", "/etc/vibetea/key.pem");
+        guard.remove("VIBETEA_TLS_CLIENT_CA");
+
+        let tls = Config::from_env()
+            .expect("should parse config")
+            .tls_config()
+            .expect("TLS should be enabled");
+        assert_eq!(tls.cert, PathBuf::from("/etc/vibetea/cert.pem"));
+        assert_eq!(tls.key, PathBuf::from("/etc/vibetea/key.pem"));
+        assert!(tls.client_ca.is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_tls_disabled_by_default() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_TLS_CERT");
+        guard.remove("VIBETEA_TLS_KEY");
+        guard.remove("VIBETEA_TLS_CLIENT_CA");
+
+        assert!(Config::from_env().unwrap().tls_config().is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_invalid_tls_settings() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_TLS_CERT", "/etc/vibetea/cert.pem");
+        guard.remove("VIBETEA_TLS_KEY");
+        guard.remove("VIBETEA_TLS_CLIENT_CA");
+        assert!(Config::from_env().is_err());
+        drop(guard);
+
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_TLS_CERT");
+        guard.remove("VIBETEA_TLS_KEY");
+        guard.set("VIBETEA_TLS_CLIENT_CA", "/etc/vibetea/ca.pem");
+        assert!(Config::from_env().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_allowed_origins() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set(
+            "VIBETEA_ALLOWED_ORIGINS",
+            "https://dashboard.example.com, http://localhost:5173",
+        );
+        guard.set("VIBETEA_CORS_ALLOW_CREDENTIALS", "true");
+
+        let config = Config::from_env().expect("should parse config");
+        assert_eq!(
+            config.allowed_origins,
+            vec!["https://dashboard.example.com", "http://localhost:5173"]
+        );
+        assert!(config.cors_allow_credentials);
+        assert!(config.origin_policy().is_configured());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_invalid_allowed_origins() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_ALLOWED_ORIGINS", "dashboard.example.com");
+        guard.remove("VIBETEA_CORS_ALLOW_CREDENTIALS");
+        assert!(matches!(
+            Config::from_env(),
+            Err(ConfigError::InvalidFormat { ref var, .. }) if var == "VIBETEA_ALLOWED_ORIGINS"
+        ));
+        drop(guard);
+
+        // Browsers refuse credentials with a wildcard origin
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_ALLOWED_ORIGINS", "*");
+        guard.set("VIBETEA_CORS_ALLOW_CREDENTIALS", "true");
+        assert!(Config::from_env().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_dedup_window() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_DEDUP_WINDOW_SECS");
+        assert_eq!(
+            Config::from_env().unwrap().dedup_window,
+            DEFAULT_DEDUP_WINDOW
+        );
+        drop(guard);
+
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_DEDUP_WINDOW_SECS", "0");
+        let config = Config::from_env().unwrap();
+        assert!(config.dedup_window.is_zero());
+        assert!(!config.dedup_cache().is_enabled());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_max_event_age() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_MAX_EVENT_AGE_SECS");
+        assert_eq!(
+            Config::from_env().unwrap().max_event_age,
+            DEFAULT_MAX_EVENT_AGE
+        );
+        drop(guard);
+
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_MAX_EVENT_AGE_SECS", "3600");
+        assert_eq!(
+            Config::from_env().unwrap().max_event_age,
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_accept_legacy_events() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_ACCEPT_LEGACY_EVENTS");
+        let config = Config::from_env().unwrap();
+        assert!(config.accept_legacy_events);
+        assert_eq!(config.validator().schema_versions(), [1, 2]);
+        drop(guard);
+
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_ACCEPT_LEGACY_EVENTS", "false");
+        let config = Config::from_env().unwrap();
+        assert!(!config.accept_legacy_events);
+        assert_eq!(config.validator().schema_versions(), [2]);
+    }
+
+    fn write_config_file(contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, contents.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_load_precedence() {
+        let file = write_config_file(
+            r#"
+            unsafe_no_auth = true
+            host = "127.0.0.1"
+            port = 7000
+            retention_hours = 48
+            history_capacity = 50
+            "#,
+        );
+        let mut guard = EnvGuard::new();
+        guard.remove("VIBETEA_UNSAFE_NO_AUTH");
+        guard.remove("VIBETEA_HOST");
+        guard.remove("VIBETEA_PORT");
+        guard.remove("PORT");
+        guard.remove("VIBETEA_HISTORY_CAPACITY");
+        guard.set("VIBETEA_RETENTION_HOURS", "12");
+        guard.set("VIBETEA_CHANNEL_CAPACITY", "64");
+
+        let overrides = Settings {
+            port: Some(9000),
+            channel_capacity: Some(128),
+            ..Settings::default()
+        };
+        let config = Config::load(Some(file.path()), overrides).expect("should load config");
+
+        // File only
+        assert!(config.unsafe_no_auth);
+        assert_eq!(config.history_capacity, 50);
+        assert_eq!(
+            config.bind_addr(),
+            "127.0.0.1:9000".parse::<SocketAddr>().unwrap()
+        );
+        // Environment over file
+        assert_eq!(config.retention_max_age, Duration::from_secs(12 * 3600));
+        // Overrides over environment
+        assert_eq!(config.channel_capacity, 128);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_file_public_keys() {
+        let file = write_config_file(
+            r#"
+            subscriber_token = "secret-token"
+
+            [public_keys]
+            source1 = "cHVia2V5MQ=="
+            "#,
+        );
+        let mut guard = EnvGuard::new();
+        guard.remove("VIBETEA_UNSAFE_NO_AUTH");
+        guard.remove("VIBETEA_PUBLIC_KEYS");
+        guard.remove("VIBETEA_SUBSCRIBER_TOKEN");
+
+        let config = Config::load(Some(file.path()), Settings::default()).expect("should load");
+        assert_eq!(
+            config.public_keys.get("source1"),
+            Some(&"cHVia2V5MQ==".to_string())
+        );
+        assert_eq!(config.subscriber_token.as_deref(), Some("secret-token"));
+    }
+
+    #[test]
+    fn test_config_file_rejects_unknown_keys() {
+        let file = write_config_file("retention_days = 2\n");
+        let result = Settings::from_file(file.path());
+        assert!(matches!(result, Err(ConfigError::File { .. })));
+    }
+
+    #[test]
+    fn test_config_file_missing() {
+        let result = Settings::from_file(Path::new("/nonexistent/server.toml"));
+        assert!(matches!(result, Err(ConfigError::File { .. })));
+    }
+
+    #[test]
+    fn test_effective_settings_redact_secrets_and_round_trip() {
+        let config = Config {
+            subscriber_token: Some("secret-token".to_string()),
+            retention_max_age: Duration::from_secs(48 * 3600),
+            ..Config::default()
+        };
+
+        let settings = Settings::from(&config).redact_secrets();
+        assert_eq!(settings.subscriber_token.as_deref(), Some(REDACTED));
+
+        let text = toml::to_string(&settings).unwrap();
+        assert!(!text.contains("secret-token"));
+        let parsed: Settings = toml::from_str(&text).unwrap();
+        assert_eq!(parsed, settings);
+        assert_eq!(parsed.retention_hours, Some(48));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_host_and_vibetea_port() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_HOST", "::1");
+        guard.set("VIBETEA_PORT", "4000");
+        guard.set("PORT", "5000");
+
+        let config = Config::from_env().expect("should parse config");
+        assert_eq!(
+            config.bind_addr(),
+            "[::1]:4000".parse::<SocketAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_invalid_host() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_HOST", "localhost:80");
+
+        assert!(Config::from_env().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_webhooks_file() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_WEBHOOKS_FILE");
+
+        let config = Config::from_env().expect("should parse config");
+        assert!(config.webhooks_file.is_none());
+
+        guard.set("VIBETEA_WEBHOOKS_FILE", "/etc/vibetea/webhooks.json");
+        let config = Config::from_env().expect("should parse config");
+        assert_eq!(
+            config.webhooks_file,
+            Some(PathBuf::from("/etc/vibetea/webhooks.json"))
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_relay() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_RELAY_URL");
+        guard.remove("VIBETEA_RELAY_ID");
+        guard.remove("VIBETEA_RELAY_KEY_PATH");
+        guard.remove("VIBETEA_RELAY_BUFFER_SIZE");
+        guard.remove("VIBETEA_RELAY_SOURCES");
+
+        let config = Config::from_env().expect("should parse config");
+        assert!(config.relay_config().is_none());
+        assert!(config.relay_sources.is_empty());
+
+        guard.set("VIBETEA_RELAY_URL", "https://global.example.com");
+        guard.set("VIBETEA_RELAY_ID", "hub-eu");
+        guard.set("VIBETEA_RELAY_KEY_PATH", "/etc/vibetea/relay");
+        guard.set("VIBETEA_RELAY_BUFFER_SIZE", "500");
+        guard.set("VIBETEA_RELAY_SOURCES", "hub-berlin, hub-paris");
+        let config = Config::from_env().expect("should parse config");
+        let relay = config.relay_config().expect("relay should be enabled");
+        assert_eq!(relay.url, "https://global.example.com");
+        assert_eq!(relay.hub_id, "hub-eu");
+        assert_eq!(relay.key_path, PathBuf::from("/etc/vibetea/relay"));
+        assert_eq!(relay.buffer_size, 500);
+        assert_eq!(config.relay_sources, vec!["hub-berlin", "hub-paris"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_relay_requires_identity() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_RELAY_URL", "https://global.example.com");
+        guard.remove("VIBETEA_RELAY_ID");
+        guard.set("VIBETEA_RELAY_KEY_PATH", "/etc/vibetea/relay");
+
+        let result = Config::from_env();
+        assert!(matches!(result, Err(ConfigError::ValidationError(_))));
+
+        guard.set("VIBETEA_RELAY_ID", "hub-eu");
+        guard.set("VIBETEA_RELAY_URL", "global.example.com");
+        let result = Config::from_env();
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidFormat { var, .. }) if var == "VIBETEA_RELAY_URL"
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_rate_limit_settings() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_RATE_LIMIT", "10");
+        guard.set("VIBETEA_RATE_BURST", "20");
+        guard.set("VIBETEA_GLOBAL_RATE_LIMIT", "0");
+        guard.set("VIBETEA_MAX_WS_PER_IP", "4");
+
+        let config = Config::from_env().expect("should parse config");
+        assert_eq!(config.source_rate_limit, 10);
+        assert_eq!(config.source_rate_burst, 20);
+        assert_eq!(config.global_rate_limit, 0);
+        assert_eq!(config.global_rate_burst, DEFAULT_GLOBAL_CAPACITY);
+        assert_eq!(config.max_ws_connections_per_ip, 4);
+        assert_eq!(config.connection_limiter().max_per_ip(), 4);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_max_clock_skew() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.remove("VIBETEA_MAX_CLOCK_SKEW_SECS");
+        assert_eq!(
+            Config::from_env().unwrap().max_clock_skew,
+            DEFAULT_MAX_CLOCK_SKEW
+        );
+
+        guard.set("VIBETEA_MAX_CLOCK_SKEW_SECS", "30");
+        assert_eq!(
+            Config::from_env().unwrap().max_clock_skew,
+            Duration::from_secs(30)
+        );
+
+        guard.set("VIBETEA_MAX_CLOCK_SKEW_SECS", "0");
+        assert!(matches!(
+            Config::from_env(),
+            Err(ConfigError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_rejects_zero_source_rate() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_RATE_LIMIT", "0");
+
+        let result = Config::from_env();
+        assert!(matches!(result, Err(ConfigError::ValidationError(_))));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_history_capacity() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_HISTORY_CAPACITY", "500");
+
+        let config = Config::from_env().expect("should parse config");
+        assert_eq!(config.history_capacity, 500);
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_store_settings() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_UNSAFE_NO_AUTH", "true");
+        guard.set("VIBETEA_DATA_DIR", "/var/lib/vibetea");
+        guard.set("VIBETEA_RETENTION_HOURS", "6");
+        guard.set("VIBETEA_RETENTION_MAX_MB", "64");
+
+        let config = Config::from_env().expect("should parse config");
+        let store = config.store_config().expect("store should be enabled");
+        assert_eq!(store.dir, PathBuf::from("/var/lib/vibetea"));
+        assert_eq!(store.max_age, Duration::from_secs(6 * 3600));
+        assert_eq!(store.max_bytes, 64 * BYTES_PER_MIB);
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_u64_env_invalid() {
+        let mut guard = EnvGuard::new();
+        guard.set("VIBETEA_RETENTION_HOURS", "forever");
+
+        let result = parse_u64_env("VIBETEA_RETENTION_HOURS");
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidFormat { var, .. }) if var == "VIBETEA_RETENTION_HOURS"
+        ));
+    }
+
     #[test]
     #[serial]
     fn test_parse_port_default() {
         let mut guard = EnvGuard::new();
+        guard.remove("VIBETEA_PORT");
         guard.remove("PORT");
 
         let port = parse_port().expect("should parse port");
-        assert_eq!(port, DEFAULT_PORT);
+        assert_eq!(port, None);
     }
 
     #[test]
     #[serial]
     fn test_parse_port_custom() {
         let mut guard = EnvGuard::new();
+        guard.remove("VIBETEA_PORT");
         guard.set("PORT", "3000");
 
         let port = parse_port().expect("should parse port");
-        assert_eq!(port, 3000);
+        assert_eq!(port, Some(3000));
     }
 
     #[test]
     #[serial]
     fn test_parse_port_invalid() {
         let mut guard = EnvGuard::new();
+        guard.remove("VIBETEA_PORT");
         guard.set("PORT", "not-a-number");
 
         let result = parse_port();
@@ -433,6 +1889,7 @@ mod tests {
     #[serial]
     fn test_parse_port_out_of_range() {
         let mut guard = EnvGuard::new();
+        guard.remove("VIBETEA_PORT");
         guard.set("PORT", "99999");
 
         let result = parse_port();
@@ -0,0 +1,232 @@
+//! Browser origin policy.
+//!
+//! Browsers attach an `Origin` header to cross-origin requests and WebSocket
+//! upgrades. [`OriginPolicy`] decides which origins may use the server:
+//!
+//! - REST routes get CORS headers for allowed origins (see
+//!   [`OriginPolicy::cors_layer`]), so a dashboard hosted on another origin
+//!   can call them without a proxy.
+//! - `/ws` upgrades from other origins are rejected with `403`, because
+//!   browsers do not apply CORS to WebSockets and any page that learned a
+//!   token could otherwise open a socket.
+//!
+//! Requests without an `Origin` header, such as monitors, `curl` or native
+//! clients, are not affected.
+//!
+//! # Configuration
+//!
+//! `VIBETEA_ALLOWED_ORIGINS` is a comma-separated list of origins such as
+//! `https://dashboard.example.com`. `*` allows every origin. When unset, no
+//! CORS headers are sent and WebSocket upgrades are accepted from any origin,
+//! as before the policy existed.
+//!
+//! `VIBETEA_CORS_ALLOW_CREDENTIALS=true` lets browsers send credentials
+//! (cookies and `Authorization` headers managed by the browser) with
+//! cross-origin requests. It cannot be combined with `*`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use axum::http::HeaderValue;
+//! use vibetea_server::cors::OriginPolicy;
+//!
+//! let policy = OriginPolicy::new(&["https://dashboard.example.com".to_string()], false);
+//! assert!(policy.allows(Some(&HeaderValue::from_static("https://dashboard.example.com"))));
+//! assert!(!policy.allows(Some(&HeaderValue::from_static("https://evil.example"))));
+//! assert!(policy.allows(None));
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// Matches any origin in `VIBETEA_ALLOWED_ORIGINS`.
+pub const ANY_ORIGIN: &str = "*";
+
+/// How long browsers may cache a preflight response.
+const PREFLIGHT_MAX_AGE: Duration = Duration::from_secs(600);
+
+/// Request headers browsers may send cross-origin.
+const ALLOWED_HEADERS: [HeaderName; 8] = [
+    ACCEPT,
+    AUTHORIZATION,
+    CONTENT_TYPE,
+    HeaderName::from_static("last-event-id"),
+    HeaderName::from_static("x-source-id"),
+    HeaderName::from_static("x-timestamp"),
+    HeaderName::from_static("x-nonce"),
+    HeaderName::from_static("x-signature"),
+];
+
+/// Response headers exposed to cross-origin scripts.
+const EXPOSED_HEADERS: [HeaderName; 2] = [RETRY_AFTER, HeaderName::from_static("x-next-cursor")];
+
+/// Which browser origins may use the server.
+///
+/// Cloning is cheap and shares the origin list.
+#[derive(Debug, Clone, Default)]
+pub struct OriginPolicy {
+    allowed: Arc<[String]>,
+    any: bool,
+    allow_credentials: bool,
+}
+
+impl OriginPolicy {
+    /// Creates a policy from configured origins. An empty list leaves
+    /// browsers unrestricted.
+    #[must_use]
+    pub fn new(origins: &[String], allow_credentials: bool) -> Self {
+        Self {
+            allowed: origins
+                .iter()
+                .filter(|origin| *origin != ANY_ORIGIN)
+                .map(|origin| normalize(origin))
+                .collect(),
+            any: origins.iter().any(|origin| origin == ANY_ORIGIN),
+            allow_credentials,
+        }
+    }
+
+    /// Returns `true` if origins are configured.
+    #[must_use]
+    pub fn is_configured(&self) -> bool {
+        self.any || !self.allowed.is_empty()
+    }
+
+    /// Returns `true` if a request with this `Origin` header may proceed.
+    ///
+    /// Requests without an `Origin` header do not come from a browser page
+    /// and are always allowed.
+    #[must_use]
+    pub fn allows(&self, origin: Option<&HeaderValue>) -> bool {
+        let Some(origin) = origin else {
+            return true;
+        };
+        if self.any || self.allowed.is_empty() {
+            return true;
+        }
+        origin
+            .to_str()
+            .is_ok_and(|origin| self.allowed.contains(&normalize(origin)))
+    }
+
+    /// Builds the CORS layer for the REST routes, or `None` if no origins
+    /// are configured.
+    #[must_use]
+    pub fn cors_layer(&self) -> Option<CorsLayer> {
+        if !self.is_configured() {
+            return None;
+        }
+
+        let allow_origin = if self.any {
+            AllowOrigin::any()
+        } else {
+            let policy = self.clone();
+            AllowOrigin::predicate(move |origin, _| policy.allows(Some(origin)))
+        };
+
+        Some(
+            CorsLayer::new()
+                .allow_origin(allow_origin)
+                .allow_methods([Method::GET, Method::POST])
+                .allow_headers(ALLOWED_HEADERS)
+                .expose_headers(EXPOSED_HEADERS)
+                .allow_credentials(self.allow_credentials)
+                .max_age(PREFLIGHT_MAX_AGE),
+        )
+    }
+}
+
+/// Validates an entry of `VIBETEA_ALLOWED_ORIGINS`.
+///
+/// An origin is a scheme, host and optional port, such as
+/// `https://dashboard.example.com:8443`, without a path.
+///
+/// # Errors
+///
+/// Returns a description of the problem if `origin` is not `*` or a valid
+/// `http` or `https` origin.
+pub fn validate_origin(origin: &str) -> Result<(), String> {
+    if origin == ANY_ORIGIN {
+        return Ok(());
+    }
+    let host = origin
+        .strip_prefix("https://")
+        .or_else(|| origin.strip_prefix("http://"))
+        .ok_or_else(|| format!("'{origin}' must start with http:// or https://"))?;
+    let host = host.strip_suffix('/').unwrap_or(host);
+    if host.is_empty() || host.contains(['/', '?', '#', '*']) || host.contains(char::is_whitespace)
+    {
+        return Err(format!(
+            "'{origin}' must be a scheme and host, such as https://dashboard.example.com"
+        ));
+    }
+    Ok(())
+}
+
+/// Normalizes an origin for comparison. Scheme and host are
+/// case-insensitive, and a trailing slash is tolerated in configuration.
+fn normalize(origin: &str) -> String {
+    origin.trim().trim_end_matches('/').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(origins: &[&str]) -> OriginPolicy {
+        let origins: Vec<String> = origins.iter().map(ToString::to_string).collect();
+        OriginPolicy::new(&origins, false)
+    }
+
+    fn allows(policy: &OriginPolicy, origin: &'static str) -> bool {
+        policy.allows(Some(&HeaderValue::from_static(origin)))
+    }
+
+    #[test]
+    fn unconfigured_policy_allows_everything() {
+        let policy = policy(&[]);
+        assert!(!policy.is_configured());
+        assert!(allows(&policy, "https://anywhere.example"));
+        assert!(policy.cors_layer().is_none());
+    }
+
+    #[test]
+    fn matches_configured_origins() {
+        let policy = policy(&["https://Dashboard.example.com/", "http://localhost:5173"]);
+        assert!(allows(&policy, "https://dashboard.example.com"));
+        assert!(allows(&policy, "http://localhost:5173"));
+        assert!(!allows(&policy, "http://localhost:3000"));
+        assert!(!allows(
+            &policy,
+            "https://dashboard.example.com.evil.example"
+        ));
+        assert!(!allows(&policy, "null"));
+    }
+
+    #[test]
+    fn requests_without_origin_are_allowed() {
+        assert!(policy(&["https://dashboard.example.com"]).allows(None));
+    }
+
+    #[test]
+    fn wildcard_allows_any_origin() {
+        let policy = policy(&["*"]);
+        assert!(policy.is_configured());
+        assert!(allows(&policy, "https://anywhere.example"));
+    }
+
+    #[test]
+    fn validates_origins() {
+        assert!(validate_origin("*").is_ok());
+        assert!(validate_origin("https://dashboard.example.com").is_ok());
+        assert!(validate_origin("http://localhost:5173/").is_ok());
+        assert!(validate_origin("dashboard.example.com").is_err());
+        assert!(validate_origin("https://dashboard.example.com/app").is_err());
+        assert!(validate_origin("https://*.example.com").is_err());
+        assert!(validate_origin("https://").is_err());
+    }
+}
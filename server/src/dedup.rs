@@ -0,0 +1,227 @@
+//! Deduplication of ingested events by ID.
+//!
+//! Monitors retry a whole batch when `POST /events` fails. If the server
+//! accepted the batch but the response was lost, the retry carries events
+//! that were already broadcast. [`DedupCache`] remembers the IDs of events
+//! accepted from each source for a configurable window
+//! (`VIBETEA_DEDUP_WINDOW_SECS`), and `POST /events` drops events whose ID
+//! it has already seen before they are persisted or broadcast.
+//!
+//! IDs are scoped per source, and expire based on when the server received
+//! them rather than the event timestamp, so monitor clock drift does not
+//! matter. Expired entries are pruned as new IDs are recorded.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use chrono::Utc;
+//! use vibetea_server::dedup::DedupCache;
+//!
+//! let cache = DedupCache::new(Duration::from_secs(600));
+//! let now = Utc::now();
+//!
+//! assert!(cache.insert("monitor-1", "evt_1", now));
+//! assert!(!cache.insert("monitor-1", "evt_1", now)); // duplicate
+//! assert!(cache.insert("monitor-2", "evt_1", now)); // other source
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::types::Event;
+
+/// Default time an event ID is remembered (10 minutes).
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(600);
+
+/// Thread-safe record of event IDs accepted within the window.
+///
+/// Cloning is cheap and shares the underlying state.
+#[derive(Debug, Clone)]
+pub struct DedupCache {
+    inner: Arc<Mutex<Inner>>,
+    window: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// When each `(source, event ID)` pair was first accepted.
+    seen: HashMap<(String, String), DateTime<Utc>>,
+
+    /// When expired entries were last removed.
+    last_pruned: Option<DateTime<Utc>>,
+}
+
+impl DedupCache {
+    /// Creates an empty cache that remembers IDs for `window`. A zero window
+    /// disables deduplication.
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner::default())),
+            window,
+        }
+    }
+
+    /// Returns `true` if deduplication is enabled.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        !self.window.is_zero()
+    }
+
+    /// Records event `id` from `source`, received at `now`.
+    ///
+    /// Returns `false` if the ID was already recorded for this source within
+    /// the window, meaning the event is a duplicate.
+    pub fn insert(&self, source: &str, id: &str, now: DateTime<Utc>) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+
+        let mut inner = self.lock();
+        let window = chrono::Duration::from_std(self.window).unwrap_or(chrono::Duration::MAX);
+
+        // Prune at most once per window so the cost stays amortised
+        if inner.last_pruned.is_none_or(|last| now - last >= window) {
+            let cutoff = now - window;
+            inner.seen.retain(|_, seen_at| *seen_at >= cutoff);
+            inner.last_pruned = Some(now);
+        }
+
+        let key = (source.to_string(), id.to_string());
+        match inner.seen.get(&key) {
+            Some(seen_at) if now - *seen_at < window => false,
+            _ => {
+                inner.seen.insert(key, now);
+                true
+            }
+        }
+    }
+
+    /// Splits `events` into those not seen before, in order, and the number
+    /// of duplicates dropped. Repeats within `events` count as duplicates.
+    pub fn retain_new(&self, events: Vec<Event>, now: DateTime<Utc>) -> (Vec<Event>, usize) {
+        let total = events.len();
+        let fresh: Vec<Event> = events
+            .into_iter()
+            .filter(|event| self.insert(&event.source, &event.id, now))
+            .collect();
+        let duplicates = total - fresh.len();
+        (fresh, duplicates)
+    }
+
+    /// Returns the window IDs are remembered for.
+    #[must_use]
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Returns the number of IDs currently remembered.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().seen.len()
+    }
+
+    /// Returns `true` if no IDs are remembered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{EventPayload, EventType};
+    use uuid::Uuid;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    fn event(source: &str, id: &str) -> Event {
+        Event::new(
+            source.to_string(),
+            EventType::Activity,
+            EventPayload::Activity {
+                session_id: Uuid::new_v4(),
+                project: None,
+            },
+        )
+        .with_id(id.to_string())
+    }
+
+    #[test]
+    fn rejects_repeated_id_for_same_source() {
+        let cache = DedupCache::new(WINDOW);
+        let now = Utc::now();
+
+        assert!(cache.insert("a", "evt_1", now));
+        assert!(!cache.insert("a", "evt_1", now));
+        assert!(cache.insert("a", "evt_2", now));
+        assert!(cache.insert("b", "evt_1", now));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn forgets_ids_after_the_window() {
+        let cache = DedupCache::new(WINDOW);
+        let start = Utc::now();
+
+        assert!(cache.insert("a", "evt_1", start));
+
+        let later = start + chrono::Duration::seconds(61);
+        assert!(cache.insert("a", "evt_1", later));
+        assert!(cache.insert("a", "evt_2", later));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn retain_new_drops_duplicates_within_and_across_batches() {
+        let cache = DedupCache::new(WINDOW);
+        let now = Utc::now();
+
+        let (fresh, duplicates) = cache.retain_new(
+            vec![
+                event("a", "evt_1"),
+                event("a", "evt_2"),
+                event("a", "evt_1"),
+            ],
+            now,
+        );
+        assert_eq!(duplicates, 1);
+        let ids: Vec<&str> = fresh.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["evt_1", "evt_2"]);
+
+        // A retried batch with one new event
+        let (fresh, duplicates) = cache.retain_new(
+            vec![
+                event("a", "evt_1"),
+                event("a", "evt_2"),
+                event("a", "evt_3"),
+            ],
+            now,
+        );
+        assert_eq!(duplicates, 2);
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].id, "evt_3");
+    }
+
+    #[test]
+    fn zero_window_disables_deduplication() {
+        let cache = DedupCache::new(Duration::ZERO);
+        let now = Utc::now();
+
+        assert!(!cache.is_enabled());
+        assert!(cache.insert("a", "evt_1", now));
+        assert!(cache.insert("a", "evt_1", now));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,161 @@
+//! Reloading configuration files when they change on disk.
+//!
+//! [`watch_file`] runs a callback whenever a file is created, modified or
+//! removed. It backs hot reload of the key registry ([`crate::keys`]),
+//! subscriber tokens ([`crate::tokens`]) and TLS certificates
+//! ([`crate::tls`]).
+//!
+//! The parent directory is watched rather than the file itself, so that
+//! editors and tools that replace the file by renaming are picked up. Bursts
+//! of changes are coalesced into a single callback.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use vibetea_server::file_watch::watch_file;
+//!
+//! # async fn example() -> Result<(), notify::Error> {
+//! let _watcher = watch_file("/etc/vibetea/keys.json", || {
+//!     println!("keys.json changed");
+//! })?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// How long to wait after a change before running the callback, so that
+/// editors which write a file in several steps only trigger it once.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Handle to a running file watcher.
+///
+/// Dropping the handle stops watching.
+#[derive(Debug)]
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Calls `on_change` whenever `path` changes, until the returned
+/// [`FileWatcher`] is dropped.
+///
+/// Must be called from within a Tokio runtime.
+///
+/// # Errors
+///
+/// Returns an error if the watcher cannot be started, for example because
+/// the parent directory does not exist.
+pub fn watch_file<F>(path: impl AsRef<Path>, on_change: F) -> Result<FileWatcher, notify::Error>
+where
+    F: Fn() + Send + 'static,
+{
+    let path = path.as_ref();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_name = path.file_name().map(ToOwned::to_owned);
+
+    let (tx, mut rx) = mpsc::channel::<()>(1);
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else { return };
+        // Ignore access events, which reading the file would otherwise trigger
+        let changed = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        );
+        if changed
+            && event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == file_name.as_deref())
+        {
+            let _ = tx.try_send(());
+        }
+    })?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+    let task = tokio::spawn(async move {
+        while rx.recv().await.is_some() {
+            tokio::time::sleep(DEBOUNCE).await;
+            // Coalesce changes that arrived while waiting
+            while rx.try_recv().is_ok() {}
+            on_change();
+        }
+    });
+
+    Ok(FileWatcher {
+        _watcher: watcher,
+        task,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    async fn wait_for(count: &AtomicUsize, at_least: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while count.load(Ordering::SeqCst) < at_least {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("callback should run");
+    }
+
+    #[tokio::test]
+    async fn calls_back_on_change_to_watched_file_only() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("watched.json");
+        std::fs::write(&path, "1").unwrap();
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        let _watcher = watch_file(&path, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        std::fs::write(dir.path().join("other.json"), "x").unwrap();
+        tokio::time::sleep(DEBOUNCE * 2).await;
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        std::fs::write(&path, "2").unwrap();
+        wait_for(&count, 1).await;
+    }
+
+    #[tokio::test]
+    async fn picks_up_replacement_by_rename() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("watched.json");
+        std::fs::write(&path, "1").unwrap();
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        let _watcher = watch_file(&path, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        let staging = dir.path().join("watched.json.tmp");
+        std::fs::write(&staging, "2").unwrap();
+        std::fs::rename(&staging, &path).unwrap();
+        wait_for(&count, 1).await;
+    }
+}
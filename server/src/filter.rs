@@ -0,0 +1,11 @@
+//! Multi-value filter sets for subscriber filters.
+//!
+//! The filter syntax is part of the wire protocol and is defined in
+//! [`vibetea_protocol::filter`], re-exported here.
+//! [`SubscriberFilter`](crate::broadcast::SubscriberFilter) keeps one
+//! [`FilterSet`] per field (source, type, project and tool name) and ANDs them
+//! together with the subscriber's token scope.
+
+pub use vibetea_protocol::filter::{
+    glob_match, EventTypeSet, FilterError, FilterSet, FilterValue, PatternSet,
+};
@@ -0,0 +1,597 @@
+//! Registry of Monitor public keys with hot reload and revocation.
+//!
+//! Keys come from two places: the `VIBETEA_PUBLIC_KEYS` environment variable,
+//! which is fixed for the life of the process, and an optional JSON key file
+//! (`VIBETEA_KEY_FILE`) that can be edited while the server is running.
+//!
+//! The key file lists any number of keys per source, so a Monitor can be
+//! rotated onto a new key before the old one is revoked:
+//!
+//! ```json
+//! {
+//!   "keys": [
+//!     {
+//!       "source": "alice-laptop",
+//!       "publicKey": "MCowBQYDK2VwAyEA...",
+//!       "owner": "alice@example.com",
+//!       "created": "2026-01-10T09:00:00Z",
+//!       "expires": "2027-01-10T09:00:00Z"
+//!     },
+//!     {
+//!       "source": "alice-laptop",
+//!       "publicKey": "MCowBQYDK2VwAyEB...",
+//!       "owner": "alice@example.com",
+//!       "revoked": true
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! A request is accepted if its signature verifies against any active key for
+//! its source. Keys that are revoked or past their `expires` time are kept so
+//! that requests signed with them can be reported as such.
+//!
+//! [`KeyRegistry::watch`] reloads the file whenever it changes. A file that
+//! fails to parse, or contains an invalid key, is rejected as a whole and the
+//! previous keys stay in effect; a successful reload replaces the whole set at
+//! once, so a request never sees a mix of old and new keys.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//! use vibetea_server::auth::AuthError;
+//! use vibetea_server::keys::KeyRegistry;
+//!
+//! let mut env_keys = HashMap::new();
+//! env_keys.insert("monitor-1".to_string(), "MCowBQYDK2VwAyEA".to_string());
+//!
+//! let registry = KeyRegistry::new(&env_keys);
+//! assert_eq!(registry.len(), 1);
+//!
+//! let result = registry.verify("unknown", "c2ln", b"message");
+//! assert!(matches!(result, Err(AuthError::UnknownSource(_))));
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::auth::{decode_signature, parse_public_key, AuthError};
+use crate::file_watch::{watch_file, FileWatcher};
+
+/// Errors that can occur when loading or watching a key file.
+#[derive(Debug, Error)]
+pub enum KeyError {
+    /// The key file could not be read.
+    #[error("failed to read key file {path}: {source}")]
+    Io {
+        /// Path to the key file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// The key file is not valid JSON in the expected format.
+    #[error("failed to parse key file {path}: {source}")]
+    Parse {
+        /// Path to the key file.
+        path: PathBuf,
+        /// Underlying parse error.
+        source: serde_json::Error,
+    },
+
+    /// A key entry is invalid.
+    #[error("invalid key for source {source_id}: {reason}")]
+    InvalidKey {
+        /// Source the key belongs to.
+        source_id: String,
+        /// Why the key was rejected.
+        reason: String,
+    },
+
+    /// The file watcher could not be started.
+    #[error("failed to watch key file: {0}")]
+    Watch(#[from] notify::Error),
+}
+
+/// Whether a key may currently be used to sign requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStatus {
+    /// The key is accepted.
+    Active,
+
+    /// The key's `expires` time has passed.
+    Expired,
+
+    /// The key has been revoked.
+    Revoked,
+}
+
+/// A public key and its metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyRecord {
+    /// Source the key authenticates.
+    pub source: String,
+
+    /// Base64-encoded Ed25519 public key.
+    pub public_key: String,
+
+    /// Person or team responsible for the key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+
+    /// When the key was issued.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
+
+    /// When the key stops being accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<DateTime<Utc>>,
+
+    /// Whether the key has been revoked.
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+impl KeyRecord {
+    /// Creates an active key with no metadata.
+    #[must_use]
+    pub fn new(source: impl Into<String>, public_key: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            public_key: public_key.into(),
+            owner: None,
+            created: None,
+            expires: None,
+            revoked: false,
+        }
+    }
+
+    /// Returns whether the key may be used at `now`.
+    #[must_use]
+    pub fn status(&self, now: DateTime<Utc>) -> KeyStatus {
+        if self.revoked {
+            KeyStatus::Revoked
+        } else if self.expires.is_some_and(|expires| expires <= now) {
+            KeyStatus::Expired
+        } else {
+            KeyStatus::Active
+        }
+    }
+}
+
+/// Keys grouped by source.
+type KeysBySource = HashMap<String, Vec<KeyRecord>>;
+
+/// On-disk format of the key file.
+#[derive(Debug, Deserialize)]
+struct KeyFile {
+    keys: Vec<KeyRecord>,
+}
+
+/// Loads and validates the keys in a key file.
+///
+/// # Errors
+///
+/// Returns a [`KeyError`] if the file cannot be read or parsed, or if any
+/// entry has an empty source or a malformed public key.
+pub fn load_key_file(path: &Path) -> Result<Vec<KeyRecord>, KeyError> {
+    let contents = std::fs::read(path).map_err(|source| KeyError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let file: KeyFile = serde_json::from_slice(&contents).map_err(|source| KeyError::Parse {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    for key in &file.keys {
+        if key.source.trim().is_empty() {
+            return Err(KeyError::InvalidKey {
+                source_id: key.source.clone(),
+                reason: "source must not be empty".to_string(),
+            });
+        }
+        parse_public_key(&key.public_key).map_err(|err| KeyError::InvalidKey {
+            source_id: key.source.clone(),
+            reason: err.to_string(),
+        })?;
+    }
+
+    Ok(file.keys)
+}
+
+/// Thread-safe set of public keys, grouped by source.
+///
+/// Cloning is cheap and shares the underlying state.
+#[derive(Debug, Clone)]
+pub struct KeyRegistry {
+    /// Keys from the environment, present in every reload.
+    env_keys: Arc<Vec<KeyRecord>>,
+
+    /// Current keys by source. Replaced wholesale on reload.
+    keys: Arc<RwLock<Arc<KeysBySource>>>,
+}
+
+impl KeyRegistry {
+    /// Creates a registry from `source_id -> public_key` pairs, as parsed
+    /// from `VIBETEA_PUBLIC_KEYS`.
+    #[must_use]
+    pub fn new(env_keys: &HashMap<String, String>) -> Self {
+        let env_keys: Vec<KeyRecord> = env_keys
+            .iter()
+            .map(|(source, key)| KeyRecord::new(source, key))
+            .collect();
+        let keys = group_by_source(env_keys.iter().cloned());
+
+        Self {
+            env_keys: Arc::new(env_keys),
+            keys: Arc::new(RwLock::new(Arc::new(keys))),
+        }
+    }
+
+    /// Replaces the file-provided keys with `records`.
+    ///
+    /// Environment keys are always kept.
+    pub fn replace(&self, records: Vec<KeyRecord>) {
+        let keys = group_by_source(self.env_keys.iter().cloned().chain(records));
+        *self
+            .keys
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(keys);
+    }
+
+    /// Loads `path` and replaces the file-provided keys with its contents.
+    ///
+    /// Returns the number of keys loaded from the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`KeyError`] if the file is invalid, in which case the
+    /// current keys are left unchanged.
+    pub fn load_file(&self, path: &Path) -> Result<usize, KeyError> {
+        let records = load_key_file(path)?;
+        let count = records.len();
+        self.replace(records);
+        Ok(count)
+    }
+
+    /// Returns all keys registered for `source`.
+    #[must_use]
+    pub fn keys_for(&self, source: &str) -> Vec<KeyRecord> {
+        self.snapshot().get(source).cloned().unwrap_or_default()
+    }
+
+    /// Returns the total number of keys, including inactive ones.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.snapshot().values().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if no keys are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Verifies `signature_base64` over `message` against the keys for
+    /// `source_id`.
+    ///
+    /// # Errors
+    ///
+    /// - [`AuthError::UnknownSource`] if the source has no keys
+    /// - [`AuthError::RevokedKey`] or [`AuthError::ExpiredKey`] if the
+    ///   signature only matches an inactive key
+    /// - [`AuthError::InvalidSignature`] if it matches no key
+    /// - [`AuthError::InvalidBase64`] if the signature is not valid base64
+    /// - [`AuthError::InvalidPublicKey`] if none of the source's keys parse
+    pub fn verify(
+        &self,
+        source_id: &str,
+        signature_base64: &str,
+        message: &[u8],
+    ) -> Result<(), AuthError> {
+        self.verify_at(source_id, signature_base64, message, Utc::now())
+    }
+
+    /// Like [`verify`](Self::verify), evaluating key expiry at `now`.
+    ///
+    /// # Errors
+    ///
+    /// See [`verify`](Self::verify).
+    pub fn verify_at(
+        &self,
+        source_id: &str,
+        signature_base64: &str,
+        message: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<(), AuthError> {
+        let snapshot = self.snapshot();
+        let keys = snapshot
+            .get(source_id)
+            .ok_or_else(|| AuthError::unknown_source(source_id))?;
+
+        let mut parse_error = None;
+        let mut inactive_match = None;
+        let mut signature = None;
+
+        for key in keys {
+            let verifying_key = match parse_public_key(&key.public_key) {
+                Ok(verifying_key) => verifying_key,
+                Err(err) => {
+                    parse_error = Some(err);
+                    continue;
+                }
+            };
+            let signature = match &signature {
+                Some(signature) => signature,
+                None => signature.insert(decode_signature(signature_base64)?),
+            };
+            if verifying_key.verify_strict(message, signature).is_err() {
+                continue;
+            }
+
+            match key.status(now) {
+                KeyStatus::Active => return Ok(()),
+                KeyStatus::Revoked => inactive_match = Some(AuthError::RevokedKey),
+                KeyStatus::Expired => {
+                    inactive_match.get_or_insert(AuthError::ExpiredKey);
+                }
+            }
+        }
+
+        Err(match (inactive_match, signature) {
+            (Some(err), _) => err,
+            (None, None) => parse_error.unwrap_or(AuthError::InvalidSignature),
+            (None, Some(_)) => AuthError::InvalidSignature,
+        })
+    }
+
+    /// Watches `path` and reloads the registry whenever it changes.
+    ///
+    /// Reload failures are logged and the previous keys are kept. Watching
+    /// stops when the returned [`FileWatcher`] is dropped.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeyError::Watch`] if the watcher cannot be started.
+    pub fn watch(&self, path: impl Into<PathBuf>) -> Result<FileWatcher, KeyError> {
+        let path = path.into();
+        let registry = self.clone();
+        let watcher = watch_file(path.clone(), move || match registry.load_file(&path) {
+            Ok(count) => info!(path = %path.display(), keys = count, "Reloaded key file"),
+            Err(err) => warn!(error = %err, "Key file reload failed, keeping previous keys"),
+        })?;
+        Ok(watcher)
+    }
+
+    fn snapshot(&self) -> Arc<KeysBySource> {
+        Arc::clone(
+            &self
+                .keys
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+}
+
+fn group_by_source(records: impl Iterator<Item = KeyRecord>) -> KeysBySource {
+    let mut keys = KeysBySource::new();
+    for record in records {
+        keys.entry(record.source.clone()).or_default().push(record);
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::prelude::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn keypair(seed: u8) -> (SigningKey, String) {
+        let signing_key = SigningKey::from_bytes(&[seed; 32]);
+        let public_key = BASE64_STANDARD.encode(signing_key.verifying_key().to_bytes());
+        (signing_key, public_key)
+    }
+
+    fn sign(key: &SigningKey, message: &[u8]) -> String {
+        BASE64_STANDARD.encode(key.sign(message).to_bytes())
+    }
+
+    fn write_keys(path: &Path, keys: &[KeyRecord]) {
+        let json = serde_json::json!({ "keys": keys });
+        std::fs::write(path, serde_json::to_vec_pretty(&json).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn env_keys_verify() {
+        let (signing_key, public_key) = keypair(1);
+        let registry = KeyRegistry::new(&HashMap::from([("a".to_string(), public_key)]));
+
+        assert!(registry
+            .verify("a", &sign(&signing_key, b"m"), b"m")
+            .is_ok());
+        assert_eq!(
+            registry.verify("b", &sign(&signing_key, b"m"), b"m"),
+            Err(AuthError::unknown_source("b"))
+        );
+    }
+
+    #[test]
+    fn any_active_key_for_the_source_is_accepted() {
+        let (old_key, old_public) = keypair(1);
+        let (new_key, new_public) = keypair(2);
+        let registry = KeyRegistry::new(&HashMap::new());
+        registry.replace(vec![
+            KeyRecord::new("a", old_public),
+            KeyRecord::new("a", new_public),
+        ]);
+
+        assert!(registry.verify("a", &sign(&old_key, b"m"), b"m").is_ok());
+        assert!(registry.verify("a", &sign(&new_key, b"m"), b"m").is_ok());
+        assert_eq!(registry.keys_for("a").len(), 2);
+    }
+
+    #[test]
+    fn revoked_and_expired_keys_are_rejected() {
+        let (revoked_key, revoked_public) = keypair(1);
+        let (expired_key, expired_public) = keypair(2);
+        let (_, other_public) = keypair(3);
+        let now = Utc::now();
+
+        let registry = KeyRegistry::new(&HashMap::new());
+        registry.replace(vec![
+            KeyRecord {
+                revoked: true,
+                ..KeyRecord::new("a", revoked_public)
+            },
+            KeyRecord {
+                expires: Some(now - chrono::Duration::hours(1)),
+                ..KeyRecord::new("a", expired_public)
+            },
+            KeyRecord::new("a", other_public),
+        ]);
+
+        assert_eq!(
+            registry.verify_at("a", &sign(&revoked_key, b"m"), b"m", now),
+            Err(AuthError::RevokedKey)
+        );
+        assert_eq!(
+            registry.verify_at("a", &sign(&expired_key, b"m"), b"m", now),
+            Err(AuthError::ExpiredKey)
+        );
+        assert_eq!(
+            registry.verify_at("a", &sign(&keypair(4).0, b"m"), b"m", now),
+            Err(AuthError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn key_is_active_until_it_expires() {
+        let now = Utc::now();
+        let key = KeyRecord {
+            expires: Some(now + chrono::Duration::minutes(1)),
+            ..KeyRecord::new("a", "k")
+        };
+
+        assert_eq!(key.status(now), KeyStatus::Active);
+        assert_eq!(
+            key.status(now + chrono::Duration::minutes(1)),
+            KeyStatus::Expired
+        );
+    }
+
+    #[test]
+    fn load_file_keeps_env_keys_and_replaces_file_keys() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("keys.json");
+        let (env_key, env_public) = keypair(1);
+        let (file_key, file_public) = keypair(2);
+        let registry = KeyRegistry::new(&HashMap::from([("env".to_string(), env_public)]));
+
+        write_keys(&path, &[KeyRecord::new("file", file_public)]);
+        assert_eq!(registry.load_file(&path).unwrap(), 1);
+        assert!(registry
+            .verify("file", &sign(&file_key, b"m"), b"m")
+            .is_ok());
+        assert!(registry.verify("env", &sign(&env_key, b"m"), b"m").is_ok());
+
+        write_keys(&path, &[]);
+        registry.load_file(&path).unwrap();
+        assert!(registry.keys_for("file").is_empty());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn invalid_file_leaves_keys_unchanged() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("keys.json");
+        let (_, public_key) = keypair(1);
+        let registry = KeyRegistry::new(&HashMap::new());
+
+        write_keys(&path, &[KeyRecord::new("a", public_key.clone())]);
+        registry.load_file(&path).unwrap();
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(matches!(
+            registry.load_file(&path),
+            Err(KeyError::Parse { .. })
+        ));
+
+        write_keys(
+            &path,
+            &[
+                KeyRecord::new("a", public_key),
+                KeyRecord::new("b", "not-a-key"),
+            ],
+        );
+        assert!(matches!(
+            registry.load_file(&path),
+            Err(KeyError::InvalidKey { source_id, .. }) if source_id == "b"
+        ));
+
+        assert_eq!(registry.keys_for("a").len(), 1);
+        assert!(registry.keys_for("b").is_empty());
+    }
+
+    #[test]
+    fn parses_key_metadata() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("keys.json");
+        let (_, public_key) = keypair(1);
+        std::fs::write(
+            &path,
+            format!(
+                r#"{{"keys": [{{"source": "a", "publicKey": "{public_key}", "owner": "alice",
+                   "created": "2026-01-01T00:00:00Z", "expires": "2027-01-01T00:00:00Z"}}]}}"#
+            ),
+        )
+        .unwrap();
+
+        let keys = load_key_file(&path).unwrap();
+        assert_eq!(keys[0].owner.as_deref(), Some("alice"));
+        assert_eq!(
+            keys[0].expires.unwrap().to_rfc3339(),
+            "2027-01-01T00:00:00+00:00"
+        );
+        assert!(!keys[0].revoked);
+    }
+
+    #[tokio::test]
+    async fn watch_reloads_on_change() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("keys.json");
+        let (signing_key, public_key) = keypair(1);
+        write_keys(&path, &[]);
+
+        let registry = KeyRegistry::new(&HashMap::new());
+        registry.load_file(&path).unwrap();
+        let _watcher = registry.watch(&path).unwrap();
+
+        write_keys(&path, &[KeyRecord::new("a", public_key)]);
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while registry.keys_for("a").is_empty() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("key file should be reloaded");
+
+        assert!(registry
+            .verify("a", &sign(&signing_key, b"m"), b"m")
+            .is_ok());
+    }
+}
@@ -4,20 +4,41 @@
 //! - Receiving events from monitors
 //! - Authenticating and validating events
 //! - Broadcasting events to subscribed clients
+//! - Persisting recent events to an optional on-disk event log
 //!
 //! # Architecture
 //!
 //! The server acts as a hub between monitors (event producers) and clients
-//! (event consumers). Events are validated and broadcast in real-time without
-//! persistent storage.
+//! (event consumers). Events are validated and broadcast in real-time. When a
+//! data directory is configured, accepted events are also appended to a
+//! file-backed log (see [`store`]) with age- and size-based retention.
+//!
+//! Recently broadcast events are also kept in memory so that subscribers can
+//! resume after a disconnect without missing events (see [`replay`]).
+//!
+//! The server also tracks session state from the event stream (see
+//! [`sessions`]), so clients do not have to derive it themselves, and keeps
+//! rolling per-minute and per-hour aggregates across all sources (see
+//! [`stats`]).
+//!
+//! The server can terminate TLS itself, optionally requiring client
+//! certificates from monitors (see [`tls`]).
+//!
+//! Hubs can be chained: a hub in relay mode forwards the events it accepts
+//! to an upstream hub, keeping their original source (see [`relay`]).
 //!
 //! # HTTP API
 //!
 //! The server exposes the following endpoints:
 //!
 //! - `POST /events` - Ingest events from monitors (requires authentication)
+//! - `GET /events` - Query retained events (requires token)
+//! - `GET /sessions` - Snapshot of known sessions (requires token)
+//! - `GET /stats` - Rolling aggregates over the last hour, day or week (requires token)
 //! - `GET /ws` - WebSocket subscription for clients (requires token)
+//! - `GET /stream` - Server-Sent Events subscription for clients (requires token)
 //! - `GET /health` - Health check endpoint (no authentication)
+//! - `GET /metrics` - Prometheus metrics (requires token)
 //!
 //! # Example
 //!
@@ -40,7 +61,27 @@
 pub mod auth;
 pub mod broadcast;
 pub mod config;
+pub mod cors;
+pub mod dedup;
 pub mod error;
+pub mod file_watch;
+pub mod filter;
+pub mod keys;
+pub mod metrics;
+pub mod nonce;
+pub mod outbox;
+pub mod query;
 pub mod rate_limit;
+pub mod relay;
+pub mod replay;
 pub mod routes;
+pub mod sessions;
+pub mod sse;
+pub mod stats;
+pub mod store;
+pub mod tls;
+pub mod tokens;
 pub mod types;
+pub mod validation;
+pub mod webhooks;
+pub mod ws;
@@ -3,68 +3,399 @@
 //! This binary starts the VibeTea event hub server with:
 //! - Structured JSON logging for production
 //! - Graceful shutdown handling (SIGTERM/SIGINT)
-//! - Background rate limiter cleanup
+//! - Background rate limiter and session registry cleanup
+//! - Periodic stats rollups pushed to subscribers
+//! - Optional persistent event log with background retention
+//! - Deduplication of retried events, restored from the event log on startup
+//! - Optional key registry file, reloaded when it changes
+//! - Optional TLS termination, with certificates reloaded when they change
 //!
 //! # Configuration
 //!
-//! See [`vibetea_server::config`] for environment variable configuration.
+//! Settings come from command-line flags, `VIBETEA_*` environment variables
+//! and an optional TOML file given with `--config`, in that order of
+//! precedence. See [`vibetea_server::config`] for the full list.
 //!
 //! # Example
 //!
 //! ```bash
 //! # Development mode (no auth)
-//! VIBETEA_UNSAFE_NO_AUTH=true cargo run --bin vibetea-server
+//! cargo run --bin vibetea-server -- --unsafe-no-auth
 //!
 //! # Production mode
 //! VIBETEA_PUBLIC_KEYS="monitor1:base64pubkey" \
 //! VIBETEA_SUBSCRIBER_TOKEN="secret-token" \
-//! PORT=8080 \
-//! cargo run --release --bin vibetea-server
+//! cargo run --release --bin vibetea-server -- --port 8080
+//!
+//! # Validate a config file and print the effective configuration
+//! cargo run --bin vibetea-server -- --config server.toml check-config
 //! ```
 
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::process::ExitCode;
 use std::time::Duration;
 
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
+
 use tokio::net::TcpListener;
 use tokio::signal;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{fmt, EnvFilter};
 
-use vibetea_server::config::Config;
+use vibetea_server::config::{Config, Settings};
+use vibetea_server::keys::KeyRegistry;
+use vibetea_server::relay::Relay;
 use vibetea_server::routes::{create_router, AppState};
+use vibetea_server::sessions::REMOVAL_THRESHOLD;
+use vibetea_server::stats;
+use vibetea_server::store::{EventStore, StoreError};
+use vibetea_server::tls::{ServerTls, TlsConnectInfo, TlsListener};
+use vibetea_server::tokens::TokenRegistry;
+use vibetea_server::webhooks::{load_webhooks_file, WebhookDispatcher};
 
 /// Cleanup interval for stale rate limiter entries (30 seconds).
 const RATE_LIMITER_CLEANUP_INTERVAL: Duration = Duration::from_secs(30);
 
+/// Interval between event store retention checks (60 seconds).
+const STORE_RETENTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Interval for pruning expired sessions from the session registry.
+const SESSION_CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Graceful shutdown timeout for in-flight requests (30 seconds).
 const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
 
-#[tokio::main]
-async fn main() -> ExitCode {
-    // Initialize structured logging
-    init_logging();
+/// VibeTea Server - Real-time event hub.
+///
+/// Receives signed events from monitors and streams them to dashboards.
+/// Settings come from command-line flags, then `VIBETEA_*` environment
+/// variables, then the config file.
+#[derive(Parser, Debug)]
+#[command(name = "vibetea-server")]
+#[command(author, version, about, long_about = None)]
+#[command(after_help = "\
+ENVIRONMENT VARIABLES:
+    VIBETEA_PUBLIC_KEYS            Monitor public keys: source1:pubkey1,source2:pubkey2
+    VIBETEA_SUBSCRIBER_TOKEN       Auth token for WebSocket and SSE clients
+    VIBETEA_HOST                   Address to bind to (default: 0.0.0.0)
+    VIBETEA_PORT                   HTTP server port, PORT is also accepted (default: 8080)
+    VIBETEA_UNSAFE_NO_AUTH         Disable auth (dev only, set to 'true')
+    VIBETEA_ALLOWED_ORIGINS        Comma-separated browser origins for CORS and /ws, * for any
+    VIBETEA_CORS_ALLOW_CREDENTIALS Allow credentialed cross-origin requests (set to 'true')
+    VIBETEA_TLS_CERT               PEM certificate chain, enables HTTPS (reloaded on change)
+    VIBETEA_TLS_KEY                PEM private key for VIBETEA_TLS_CERT
+    VIBETEA_TLS_CLIENT_CA          PEM CA for monitor client certificates (required on POST /events)
+    VIBETEA_KEY_FILE               JSON key registry, reloaded on change
+    VIBETEA_SUBSCRIBER_TOKENS_FILE JSON file of named, scoped subscriber tokens
+    VIBETEA_WEBHOOKS_FILE          JSON file of outbound webhooks
+    VIBETEA_RELAY_URL              Upstream hub to forward accepted events to
+    VIBETEA_RELAY_ID               This hub's source ID upstream (required with VIBETEA_RELAY_URL)
+    VIBETEA_RELAY_KEY_PATH         Directory containing this hub's key.priv (required with VIBETEA_RELAY_URL)
+    VIBETEA_RELAY_BUFFER_SIZE      Events buffered while the upstream hub is down (default: 10000)
+    VIBETEA_RELAY_SOURCES          Comma-separated downstream hubs allowed to relay events
+    VIBETEA_DATA_DIR               Directory for the persistent event log
+    VIBETEA_RETENTION_HOURS        Hours of events to keep (default: 24)
+    VIBETEA_RETENTION_MAX_MB       Maximum event log size in MiB (default: 1024)
+    VIBETEA_HISTORY_CAPACITY       Recent events kept in memory for resume (default: 10000)
+    VIBETEA_CHANNEL_CAPACITY       Live events buffered for subscribers (default: 1000)
+    VIBETEA_RATE_LIMIT             Requests per second per source (default: 100)
+    VIBETEA_RATE_BURST             Burst capacity per source (default: 100)
+    VIBETEA_GLOBAL_RATE_LIMIT      Events per second across sources, 0 disables (default: 1000)
+    VIBETEA_GLOBAL_RATE_BURST      Event burst capacity across sources (default: 1000)
+    VIBETEA_MAX_WS_PER_IP          WebSocket and SSE connections per client IP, 0 disables (default: 32)
+    VIBETEA_MAX_CLOCK_SKEW_SECS    Allowed signed request clock skew (default: 300)
+    VIBETEA_DEDUP_WINDOW_SECS      How long event IDs are remembered to drop duplicates, 0 disables (default: 600)
+    VIBETEA_MAX_EVENT_AGE_SECS     Oldest event timestamp accepted at ingest, 0 disables (default: 604800)
+    VIBETEA_ACCEPT_LEGACY_EVENTS   Accept events without a schemaVersion (default: true)
+    VIBETEA_STATS_INTERVAL_SECS    Interval between pushed rollups, 0 disables (default: 60)
+    VIBETEA_WS_QUEUE_CAPACITY      Messages queued per WebSocket client (default: 1024)
+    VIBETEA_SLOW_CONSUMER_POLICY   drop_oldest, coalesce or disconnect (default: drop_oldest)
+    VIBETEA_WS_WRITE_TIMEOUT_SECS  Timeout for a single WebSocket write (default: 10)
+    VIBETEA_WS_PING_INTERVAL_SECS  Interval between WebSocket pings, 0 disables (default: 30)
+    VIBETEA_WS_MAX_MISSED_PONGS    Unanswered pings before dropping a client (default: 2)
+    VIBETEA_WS_MAX_LIFETIME_SECS   Maximum WebSocket connection lifetime, 0 disables (default: 0)
+    RUST_LOG                       Log level filter (default: info)
+
+CONFIG FILE:
+    Every variable can also be set in the TOML file given with --config,
+    using its name without the VIBETEA_ prefix in lowercase, for example
+    `retention_hours = 48`. Public keys go in a [public_keys] table.
+
+EXAMPLES:
+    # Development mode (no auth)
+    vibetea-server --unsafe-no-auth
+
+    # Run with a config file, overriding the port
+    vibetea-server --config server.toml --port 9000
+
+    # Validate a config file and print the effective configuration
+    vibetea-server --config server.toml check-config
+")]
+struct Cli {
+    /// Path to a TOML config file.
+    #[arg(short, long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    #[command(flatten)]
+    flags: Flags,
+
+    /// Subcommand to run. If omitted, starts the server.
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Settings that can be overridden on the command line.
+///
+/// Secrets such as the subscriber token have no flag, since command lines
+/// are visible to other users of the machine.
+#[derive(Args, Debug)]
+struct Flags {
+    /// Address to bind to.
+    #[arg(long, global = true, value_name = "ADDR")]
+    host: Option<IpAddr>,
+
+    /// HTTP server port.
+    #[arg(short, long, global = true)]
+    port: Option<u16>,
+
+    /// Disable all authentication (development only).
+    #[arg(long, global = true)]
+    unsafe_no_auth: bool,
+
+    /// Browser origins allowed for CORS and WebSocket upgrades (comma-separated, * for any).
+    #[arg(long, global = true, value_name = "ORIGINS", value_delimiter = ',')]
+    allowed_origins: Vec<String>,
+
+    /// Allow credentialed cross-origin requests.
+    #[arg(long, global = true)]
+    cors_allow_credentials: bool,
+
+    /// PEM certificate chain; enables HTTPS.
+    #[arg(long, global = true, value_name = "PATH")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key for --tls-cert.
+    #[arg(long, global = true, value_name = "PATH")]
+    tls_key: Option<PathBuf>,
+
+    /// PEM CA certificates that sign monitor client certificates.
+    #[arg(long, global = true, value_name = "PATH")]
+    tls_client_ca: Option<PathBuf>,
+
+    /// JSON key registry, reloaded on change.
+    #[arg(long, global = true, value_name = "PATH")]
+    key_file: Option<PathBuf>,
+
+    /// JSON file of named, scoped subscriber tokens, reloaded on change.
+    #[arg(long, global = true, value_name = "PATH")]
+    subscriber_tokens_file: Option<PathBuf>,
+
+    /// JSON file of outbound webhooks.
+    #[arg(long, global = true, value_name = "PATH")]
+    webhooks_file: Option<PathBuf>,
+
+    /// Upstream hub to forward accepted events to.
+    #[arg(long, global = true, value_name = "URL")]
+    relay_url: Option<String>,
+
+    /// This hub's source ID at the upstream hub.
+    #[arg(long, global = true, value_name = "ID")]
+    relay_id: Option<String>,
+
+    /// Directory containing this hub's key.priv, for signing relayed events.
+    #[arg(long, global = true, value_name = "PATH")]
+    relay_key_path: Option<PathBuf>,
+
+    /// Downstream hubs allowed to relay events from other sources (comma-separated).
+    #[arg(long, global = true, value_name = "IDS", value_delimiter = ',')]
+    relay_sources: Vec<String>,
+
+    /// Directory for the persistent event log.
+    #[arg(long, global = true, value_name = "PATH")]
+    data_dir: Option<PathBuf>,
+
+    /// Hours of events kept in the event log.
+    #[arg(long, global = true, value_name = "HOURS")]
+    retention_hours: Option<u64>,
+
+    /// Maximum size of the event log in MiB.
+    #[arg(long, global = true, value_name = "MIB")]
+    retention_max_mb: Option<u64>,
+
+    /// Recent events kept in memory for resuming subscribers.
+    #[arg(long, global = true, value_name = "EVENTS")]
+    history_capacity: Option<usize>,
+
+    /// Live events buffered for subscribers before they lag.
+    #[arg(long, global = true, value_name = "EVENTS")]
+    channel_capacity: Option<usize>,
+
+    /// Requests per second allowed per source.
+    #[arg(long, global = true, value_name = "RPS")]
+    rate_limit: Option<u32>,
+
+    /// Burst capacity per source.
+    #[arg(long, global = true, value_name = "REQUESTS")]
+    rate_burst: Option<u32>,
+
+    /// Events per second across all sources (0 disables).
+    #[arg(long, global = true, value_name = "RPS")]
+    global_rate_limit: Option<u32>,
+
+    /// Burst capacity across all sources, in events.
+    #[arg(long, global = true, value_name = "EVENTS")]
+    global_rate_burst: Option<u32>,
+
+    /// Concurrent WebSocket and SSE connections per client IP (0 disables).
+    #[arg(long, global = true, value_name = "CONNECTIONS")]
+    max_ws_per_ip: Option<usize>,
+}
+
+impl Flags {
+    /// Converts the flags into settings that override every other source.
+    fn into_settings(self) -> Settings {
+        Settings {
+            host: self.host,
+            port: self.port,
+            unsafe_no_auth: self.unsafe_no_auth.then_some(true),
+            allowed_origins: (!self.allowed_origins.is_empty()).then_some(self.allowed_origins),
+            cors_allow_credentials: self.cors_allow_credentials.then_some(true),
+            tls_cert: self.tls_cert,
+            tls_key: self.tls_key,
+            tls_client_ca: self.tls_client_ca,
+            key_file: self.key_file,
+            subscriber_tokens_file: self.subscriber_tokens_file,
+            webhooks_file: self.webhooks_file,
+            relay_url: self.relay_url,
+            relay_id: self.relay_id,
+            relay_key_path: self.relay_key_path,
+            relay_sources: (!self.relay_sources.is_empty()).then_some(self.relay_sources),
+            data_dir: self.data_dir,
+            retention_hours: self.retention_hours,
+            retention_max_mb: self.retention_max_mb,
+            history_capacity: self.history_capacity,
+            channel_capacity: self.channel_capacity,
+            rate_limit: self.rate_limit,
+            rate_burst: self.rate_burst,
+            global_rate_limit: self.global_rate_limit,
+            global_rate_burst: self.global_rate_burst,
+            max_ws_per_ip: self.max_ws_per_ip,
+            ..Settings::default()
+        }
+    }
+}
+
+/// CLI subcommands.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Start the server. This is the default when no subcommand is given.
+    Serve,
+
+    /// Validate the configuration and print it with secrets redacted.
+    ///
+    /// Also checks that the TLS certificates, key registry, subscriber tokens
+    /// and webhooks files load. Exits with a non-zero status if anything is invalid.
+    CheckConfig,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Serve);
+
+    // check-config writes the configuration to stdout, so keep logs out of it
+    if matches!(command, Command::Serve) {
+        init_logging();
+    }
 
     // Load configuration
-    let config = match Config::from_env() {
+    let config = match Config::load(cli.config.as_deref(), cli.flags.into_settings()) {
         Ok(config) => config,
         Err(err) => {
             error!(error = %err, "Failed to load configuration");
             eprintln!("Error: {err}");
             eprintln!();
-            eprintln!("Required environment variables (when auth enabled):");
-            eprintln!("  VIBETEA_PUBLIC_KEYS      - Format: source1:pubkey1,source2:pubkey2");
-            eprintln!("  VIBETEA_SUBSCRIBER_TOKEN - Auth token for WebSocket clients");
-            eprintln!();
-            eprintln!("Optional environment variables:");
-            eprintln!("  PORT                     - HTTP server port (default: 8080)");
-            eprintln!("  RUST_LOG                 - Log level filter (default: info)");
-            eprintln!("  VIBETEA_UNSAFE_NO_AUTH   - Disable auth (dev only, set to 'true')");
+            eprintln!("Run 'vibetea-server --help' for configuration options.");
             return ExitCode::from(1);
         }
     };
 
+    match command {
+        Command::CheckConfig => check_config(&config),
+        Command::Serve => {
+            let runtime = match tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(err) => {
+                    eprintln!("Error: failed to start async runtime: {err}");
+                    return ExitCode::from(1);
+                }
+            };
+            runtime.block_on(serve(config))
+        }
+    }
+}
+
+/// Validates the files the configuration refers to and prints the effective
+/// configuration as TOML, with secrets redacted.
+fn check_config(config: &Config) -> ExitCode {
+    let mut valid = true;
+
+    if let Some(tls_config) = config.tls_config() {
+        if let Err(err) = ServerTls::load(tls_config) {
+            eprintln!("Error: {err}");
+            valid = false;
+        }
+    }
+    if let Some(key_file) = &config.key_file {
+        if let Err(err) = KeyRegistry::new(&config.public_keys).load_file(key_file) {
+            eprintln!("Error: {err}");
+            valid = false;
+        }
+    }
+    if let Some(tokens_file) = &config.subscriber_tokens_file {
+        let tokens = TokenRegistry::new(config.subscriber_token.as_deref());
+        if let Err(err) = tokens.load_file(tokens_file) {
+            eprintln!("Error: {err}");
+            valid = false;
+        }
+    }
+    if let Some(webhooks_file) = &config.webhooks_file {
+        if let Err(err) = load_webhooks_file(webhooks_file) {
+            eprintln!("Error: {err}");
+            valid = false;
+        }
+    }
+    if let Some(relay_config) = config.relay_config() {
+        if let Err(err) = relay_config.load_key() {
+            eprintln!("Error: failed to load relay key: {err}");
+            valid = false;
+        }
+    }
+
+    match toml::to_string(&Settings::from(config).redact_secrets()) {
+        Ok(effective) => print!("{effective}"),
+        Err(err) => {
+            eprintln!("Error: failed to format configuration: {err}");
+            valid = false;
+        }
+    }
+
+    if valid {
+        eprintln!("Configuration is valid");
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+/// Runs the server until a shutdown signal is received.
+async fn serve(config: Config) -> ExitCode {
     // Log startup information
     let auth_mode = if config.unsafe_no_auth {
         "disabled (UNSAFE)"
@@ -75,11 +406,181 @@ async fn main() -> ExitCode {
         port = config.port,
         auth_mode = auth_mode,
         public_key_count = config.public_keys.len(),
+        key_file = ?config.key_file,
+        subscriber_tokens_file = ?config.subscriber_tokens_file,
+        webhooks_file = ?config.webhooks_file,
+        relay_url = ?config.relay_url,
+        tls = config.tls_cert.is_some(),
+        allowed_origins = ?config.allowed_origins,
         "VibeTea server starting"
     );
 
+    // Load the TLS certificate and reload it on change, if configured
+    let mut tls = None;
+    let mut tls_watchers = Vec::new();
+    if let Some(tls_config) = config.tls_config() {
+        let server_tls = match ServerTls::load(tls_config) {
+            Ok(server_tls) => server_tls,
+            Err(err) => {
+                error!(error = %err, "Failed to load TLS certificate");
+                return ExitCode::from(1);
+            }
+        };
+        info!(
+            cert = %server_tls.config().cert.display(),
+            client_auth = server_tls.client_auth(),
+            "TLS enabled"
+        );
+        match server_tls.watch() {
+            Ok(watchers) => tls_watchers = watchers,
+            Err(err) => {
+                warn!(error = %err, "Failed to watch TLS certificate, renewals require a restart");
+            }
+        }
+        tls = Some(server_tls);
+    }
+
     // Create application state
-    let state = AppState::new(config.clone());
+    let mut state = AppState::new(config.clone());
+
+    // Load the key registry file and reload it on change, if configured
+    let mut key_watcher = None;
+    if let Some(key_file) = &config.key_file {
+        match state.keys.load_file(key_file) {
+            Ok(count) => info!(path = %key_file.display(), keys = count, "Loaded key file"),
+            Err(err) => {
+                error!(error = %err, "Failed to load key file");
+                return ExitCode::from(1);
+            }
+        }
+        match state.keys.watch(key_file) {
+            Ok(watcher) => key_watcher = Some(watcher),
+            Err(err) => warn!(error = %err, "Failed to watch key file, changes require a restart"),
+        }
+    }
+
+    // Load the subscriber tokens file and reload it on change, if configured
+    let mut tokens_watcher = None;
+    if let Some(tokens_file) = &config.subscriber_tokens_file {
+        match state.tokens.load_file(tokens_file) {
+            Ok(count) => {
+                info!(path = %tokens_file.display(), tokens = count, "Loaded subscriber tokens file");
+            }
+            Err(err) => {
+                error!(error = %err, "Failed to load subscriber tokens file");
+                return ExitCode::from(1);
+            }
+        }
+        match state.tokens.watch(tokens_file) {
+            Ok(watcher) => tokens_watcher = Some(watcher),
+            Err(err) => {
+                warn!(error = %err, "Failed to watch subscriber tokens file, changes require a restart");
+            }
+        }
+    }
+
+    // Open the persistent event store, if configured
+    let mut retention_handle = None;
+    if let Some(store_config) = config.store_config() {
+        let store = match EventStore::open(store_config) {
+            Ok(store) => store,
+            Err(err) => {
+                error!(error = %err, "Failed to open event store");
+                return ExitCode::from(1);
+            }
+        };
+        retention_handle = Some(store.spawn_retention_task(STORE_RETENTION_INTERVAL));
+        info!(
+            dir = %store.config().dir.display(),
+            max_age_secs = store.config().max_age.as_secs(),
+            max_bytes = store.config().max_bytes,
+            "Event store enabled"
+        );
+
+        // Rebuild the session registry and stats from recent history
+        let now = Utc::now();
+        let sessions_since =
+            now - chrono::Duration::from_std(REMOVAL_THRESHOLD).unwrap_or_default();
+        let stats_since = now - chrono::Duration::from_std(stats::RETENTION).unwrap_or_default();
+        let dedup_since = now - chrono::Duration::from_std(config.dedup_window).unwrap_or_default();
+        let mut restored = 0;
+        let restore = store.snapshot().events().try_for_each(|event| {
+            let event = event?;
+            if event.timestamp < stats_since {
+                return Ok(());
+            }
+            restored += 1;
+            if event.timestamp >= sessions_since {
+                state.sessions.record(&event);
+            }
+            state.stats.record(&event);
+            // Retries that straddle a restart are still recognised. IDs
+            // expire by receive time, which the store does not keep, so
+            // restored IDs count as received now
+            if event.timestamp >= dedup_since {
+                state.dedup.insert(&event.source, &event.id, now);
+            }
+            Ok::<_, StoreError>(())
+        });
+        match restore {
+            Ok(()) => info!(
+                sessions = state.sessions.len(),
+                events = restored,
+                "Restored sessions, stats and recent event IDs from event store"
+            ),
+            Err(err) => {
+                error!(error = %err, "Failed to restore sessions and stats from event store")
+            }
+        }
+
+        state = state.with_store(store);
+    }
+
+    // Start delivering outbound webhooks, if configured
+    let mut webhooks = None;
+    if let Some(webhooks_file) = &config.webhooks_file {
+        let webhook_config = match load_webhooks_file(webhooks_file) {
+            Ok(webhook_config) => webhook_config,
+            Err(err) => {
+                error!(error = %err, "Failed to load webhooks file");
+                return ExitCode::from(1);
+            }
+        };
+        let dispatcher = WebhookDispatcher::start(webhook_config, &state);
+        info!(path = %webhooks_file.display(), webhooks = dispatcher.len(), "Webhooks enabled");
+        webhooks = Some(dispatcher);
+    }
+
+    // Start forwarding accepted events upstream, if configured
+    let mut relay = None;
+    if let Some(relay_config) = config.relay_config() {
+        let crypto = match relay_config.load_key() {
+            Ok(crypto) => crypto,
+            Err(err) => {
+                error!(error = %err, path = %relay_config.key_path.display(), "Failed to load relay key");
+                return ExitCode::from(1);
+            }
+        };
+        info!(
+            url = %relay_config.url,
+            hub_id = %relay_config.hub_id,
+            public_key = %crypto.public_key_base64(),
+            "Relay enabled"
+        );
+        relay = Some(Relay::start(relay_config, crypto, &state));
+    }
+
+    // Spawn session registry cleanup task
+    let session_cleanup_handle = state.sessions.spawn_cleanup_task(SESSION_CLEANUP_INTERVAL);
+
+    // Spawn the stats rollup push task, if enabled
+    let stats_push_handle = (!config.stats_push_interval.is_zero()).then(|| {
+        info!(
+            interval_secs = config.stats_push_interval.as_secs(),
+            "Stats rollup push task started"
+        );
+        state.stats.spawn_push_task(config.stats_push_interval)
+    });
 
     // Spawn rate limiter cleanup task
     let cleanup_handle = state
@@ -94,8 +595,8 @@ async fn main() -> ExitCode {
     let app = create_router(state);
 
     // Bind to address
-    let bind_addr = format!("0.0.0.0:{}", config.port);
-    let listener = match TcpListener::bind(&bind_addr).await {
+    let bind_addr = config.bind_addr();
+    let listener = match TcpListener::bind(bind_addr).await {
         Ok(listener) => {
             info!(
                 port = config.port,
@@ -114,13 +615,28 @@ async fn main() -> ExitCode {
         }
     };
 
-    // Start server with graceful shutdown
-    let server = axum::serve(listener, app).with_graceful_shutdown(shutdown_signal());
-
     info!("Server ready to accept connections");
 
-    // Run the server
-    if let Err(err) = server.await {
+    // Run the server with graceful shutdown
+    let result = match tls {
+        Some(tls) => {
+            axum::serve(
+                TlsListener::new(listener, tls),
+                app.into_make_service_with_connect_info::<TlsConnectInfo>(),
+            )
+            .with_graceful_shutdown(shutdown_signal())
+            .await
+        }
+        None => {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(shutdown_signal())
+            .await
+        }
+    };
+    if let Err(err) = result {
         error!(error = %err, "Server error");
         return ExitCode::from(1);
     }
@@ -132,6 +648,34 @@ async fn main() -> ExitCode {
     cleanup_handle.abort();
     info!("Rate limiter cleanup task stopped");
 
+    session_cleanup_handle.abort();
+    info!("Session cleanup task stopped");
+
+    if let Some(handle) = stats_push_handle {
+        handle.abort();
+        info!("Stats rollup push task stopped");
+    }
+
+    drop(key_watcher);
+    drop(tokens_watcher);
+    drop(tls_watchers);
+
+    if let Some(dispatcher) = webhooks {
+        drop(dispatcher);
+        info!("Webhook delivery stopped");
+    }
+
+    if let Some(relay) = relay {
+        let buffered = relay.buffered();
+        drop(relay);
+        info!(buffered = buffered, "Relay stopped");
+    }
+
+    if let Some(handle) = retention_handle {
+        handle.abort();
+        info!("Event store retention task stopped");
+    }
+
     // Note: axum's graceful shutdown already waits for in-flight requests
     // The GRACEFUL_SHUTDOWN_TIMEOUT is enforced by the shutdown_signal implementation
     // which gives connections time to complete before forcing shutdown
@@ -0,0 +1,594 @@
+//! Prometheus metrics for the VibeTea server.
+//!
+//! [`Metrics`] collects counters and histograms as requests are handled and
+//! renders them in the Prometheus text exposition format (version 0.0.4),
+//! which is also accepted by OpenMetrics scrapers.
+//!
+//! # Exported Metrics
+//!
+//! | Name | Type | Labels | Description |
+//! |------|------|--------|-------------|
+//! | `vibetea_events_accepted_total` | counter | `source`, `type` | Events accepted by `POST /events` |
+//! | `vibetea_auth_failures_total` | counter | `reason` | Rejected `POST /events` authentication |
+//! | `vibetea_rate_limited_total` | counter | `source` | Requests rejected with 429 |
+//! | `vibetea_duplicate_events_total` | counter | `source` | Events dropped because their ID was already accepted |
+//! | `vibetea_invalid_events_total` | counter | `source`, `reason` | Events rejected by validation, labelled with the error code |
+//! | `vibetea_ws_connections_rejected_total` | counter | - | WebSocket connections refused by the per-IP cap |
+//! | `vibetea_subscriber_lag_total` | counter | `subscriber` | Times a subscriber fell behind the broadcast channel |
+//! | `vibetea_subscriber_lagged_events` | histogram | `subscriber` | Events skipped per lag incident |
+//! | `vibetea_webhook_deliveries_total` | counter | `webhook`, `outcome` | Webhook batches `delivered`, `failed` after all retries, or `dropped` from a full delivery queue |
+//! | `vibetea_relay_events_total` | counter | `outcome` | Relayed events `forwarded` upstream, or `dropped` from a full buffer or rejected upstream (see [`crate::relay`]) |
+//! | `vibetea_request_body_bytes` | histogram | - | `POST /events` body sizes |
+//! | `vibetea_ingest_duration_seconds` | histogram | - | `POST /events` handling latency |
+//!
+//! The `subscriber` label is the kind of subscriber that lagged: `websocket`,
+//! `sse`, `relay`, or `webhook:<name>` for a configured webhook. Individual
+//! WebSocket and SSE connections are not labelled, since their number is
+//! unbounded; the lagging connection is identified in the server log.
+//!
+//! Point-in-time gauges such as the connection count are supplied by the
+//! caller when rendering; see [`Metrics::render`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use vibetea_server::metrics::{Gauge, Metrics};
+//! use vibetea_server::types::EventType;
+//!
+//! let metrics = Metrics::new();
+//! metrics.record_accepted("macbook-pro", EventType::Tool);
+//!
+//! let text = metrics.render(&[Gauge::new("vibetea_connections", "Open subscriber connections", 3.0)]);
+//! assert!(text.contains(r#"vibetea_events_accepted_total{source="macbook-pro",type="tool"} 1"#));
+//! assert!(text.contains("vibetea_connections 3"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::types::EventType;
+
+/// Content type for the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Histogram buckets for request body sizes, in bytes (up to the 1 MB limit).
+const BODY_SIZE_BUCKETS: &[f64] = &[
+    256.0,
+    1024.0,
+    4096.0,
+    16384.0,
+    65536.0,
+    262_144.0,
+    1_048_576.0,
+];
+
+/// Histogram buckets for ingest latency, in seconds.
+const LATENCY_BUCKETS: &[f64] = &[0.000_1, 0.000_5, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0];
+
+/// Histogram buckets for events skipped per lag incident.
+const LAG_BUCKETS: &[f64] = &[1.0, 10.0, 100.0, 1000.0, 10_000.0];
+
+/// A point-in-time value rendered alongside the collected metrics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gauge {
+    /// Metric name.
+    pub name: &'static str,
+
+    /// Help text.
+    pub help: &'static str,
+
+    /// Current value.
+    pub value: f64,
+}
+
+impl Gauge {
+    /// Creates a gauge.
+    #[must_use]
+    pub fn new(name: &'static str, help: &'static str, value: f64) -> Self {
+        Self { name, help, value }
+    }
+}
+
+/// Thread-safe metrics registry.
+///
+/// Cloning is cheap and shares the underlying state.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    events_accepted: BTreeMap<(String, &'static str), u64>,
+    auth_failures: BTreeMap<&'static str, u64>,
+    rate_limited: BTreeMap<String, u64>,
+    duplicate_events: BTreeMap<String, u64>,
+    invalid_events: BTreeMap<(String, &'static str), u64>,
+    connections_rejected: u64,
+    subscriber_lag: BTreeMap<String, u64>,
+    lagged_events: BTreeMap<String, Histogram>,
+    webhook_deliveries: BTreeMap<(String, &'static str), u64>,
+    relay_events: BTreeMap<&'static str, u64>,
+    body_bytes: Histogram,
+    ingest_seconds: Histogram,
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Self {
+            events_accepted: BTreeMap::new(),
+            auth_failures: BTreeMap::new(),
+            rate_limited: BTreeMap::new(),
+            duplicate_events: BTreeMap::new(),
+            invalid_events: BTreeMap::new(),
+            connections_rejected: 0,
+            subscriber_lag: BTreeMap::new(),
+            lagged_events: BTreeMap::new(),
+            webhook_deliveries: BTreeMap::new(),
+            relay_events: BTreeMap::new(),
+            body_bytes: Histogram::new(BODY_SIZE_BUCKETS),
+            ingest_seconds: Histogram::new(LATENCY_BUCKETS),
+        }
+    }
+}
+
+impl Metrics {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts an accepted event.
+    pub fn record_accepted(&self, source: &str, event_type: EventType) {
+        *self
+            .lock()
+            .events_accepted
+            .entry((source.to_string(), event_type.as_str()))
+            .or_default() += 1;
+    }
+
+    /// Counts a rejected ingest request, labelled with its error code.
+    pub fn record_auth_failure(&self, reason: &'static str) {
+        *self.lock().auth_failures.entry(reason).or_default() += 1;
+    }
+
+    /// Counts a rate-limited request.
+    pub fn record_rate_limited(&self, source: &str) {
+        *self
+            .lock()
+            .rate_limited
+            .entry(source.to_string())
+            .or_default() += 1;
+    }
+
+    /// Counts events dropped as duplicates of already accepted events.
+    pub fn record_duplicates(&self, source: &str, count: usize) {
+        if count == 0 {
+            return;
+        }
+        *self
+            .lock()
+            .duplicate_events
+            .entry(source.to_string())
+            .or_default() += count as u64;
+    }
+
+    /// Counts an event rejected by validation, labelled with its error code.
+    pub fn record_invalid(&self, source: &str, reason: &'static str) {
+        *self
+            .lock()
+            .invalid_events
+            .entry((source.to_string(), reason))
+            .or_default() += 1;
+    }
+
+    /// Counts a WebSocket connection refused by the per-IP cap.
+    pub fn record_connection_rejected(&self) {
+        self.lock().connections_rejected += 1;
+    }
+
+    /// Records a subscriber falling behind and skipping `skipped` events,
+    /// labelled with the kind of subscriber (see the module docs).
+    pub fn record_lag(&self, subscriber: &str, skipped: u64) {
+        let mut inner = self.lock();
+        *inner
+            .subscriber_lag
+            .entry(subscriber.to_string())
+            .or_default() += 1;
+        inner
+            .lagged_events
+            .entry(subscriber.to_string())
+            .or_insert_with(|| Histogram::new(LAG_BUCKETS))
+            .observe(skipped as f64);
+    }
+
+    /// Counts a webhook batch, labelled with its outcome (`delivered`,
+    /// `failed` or `dropped`).
+    pub fn record_webhook_delivery(&self, webhook: &str, outcome: &'static str) {
+        *self
+            .lock()
+            .webhook_deliveries
+            .entry((webhook.to_string(), outcome))
+            .or_default() += 1;
+    }
+
+    /// Counts `count` relayed events, labelled with their outcome
+    /// (`forwarded` or `dropped`).
+    pub fn record_relay_events(&self, outcome: &'static str, count: usize) {
+        *self.lock().relay_events.entry(outcome).or_default() += count as u64;
+    }
+
+    /// Records the size of an ingest request body.
+    pub fn observe_body_size(&self, bytes: usize) {
+        self.lock().body_bytes.observe(bytes as f64);
+    }
+
+    /// Records how long an ingest request took to handle.
+    pub fn observe_ingest_duration(&self, duration: Duration) {
+        self.lock().ingest_seconds.observe(duration.as_secs_f64());
+    }
+
+    /// Renders all metrics, followed by `gauges`, in the Prometheus text format.
+    #[must_use]
+    pub fn render(&self, gauges: &[Gauge]) -> String {
+        let inner = self.lock();
+        let mut out = String::new();
+
+        write_header(
+            &mut out,
+            "vibetea_events_accepted_total",
+            "Events accepted by POST /events.",
+            "counter",
+        );
+        for ((source, event_type), count) in &inner.events_accepted {
+            let _ = writeln!(
+                out,
+                "vibetea_events_accepted_total{{source=\"{}\",type=\"{}\"}} {count}",
+                escape_label(source),
+                event_type
+            );
+        }
+
+        write_header(
+            &mut out,
+            "vibetea_auth_failures_total",
+            "POST /events requests rejected during authentication.",
+            "counter",
+        );
+        for (reason, count) in &inner.auth_failures {
+            let _ = writeln!(
+                out,
+                "vibetea_auth_failures_total{{reason=\"{reason}\"}} {count}"
+            );
+        }
+
+        write_header(
+            &mut out,
+            "vibetea_rate_limited_total",
+            "Requests rejected by the rate limiter.",
+            "counter",
+        );
+        for (source, count) in &inner.rate_limited {
+            let _ = writeln!(
+                out,
+                "vibetea_rate_limited_total{{source=\"{}\"}} {count}",
+                escape_label(source)
+            );
+        }
+
+        write_header(
+            &mut out,
+            "vibetea_duplicate_events_total",
+            "Events dropped because their ID was already accepted.",
+            "counter",
+        );
+        for (source, count) in &inner.duplicate_events {
+            let _ = writeln!(
+                out,
+                "vibetea_duplicate_events_total{{source=\"{}\"}} {count}",
+                escape_label(source)
+            );
+        }
+
+        write_header(
+            &mut out,
+            "vibetea_invalid_events_total",
+            "Events rejected by validation.",
+            "counter",
+        );
+        for ((source, reason), count) in &inner.invalid_events {
+            let _ = writeln!(
+                out,
+                "vibetea_invalid_events_total{{source=\"{}\",reason=\"{reason}\"}} {count}",
+                escape_label(source)
+            );
+        }
+
+        write_header(
+            &mut out,
+            "vibetea_ws_connections_rejected_total",
+            "WebSocket connections refused by the per-IP cap.",
+            "counter",
+        );
+        let _ = writeln!(
+            out,
+            "vibetea_ws_connections_rejected_total {}",
+            inner.connections_rejected
+        );
+
+        write_header(
+            &mut out,
+            "vibetea_subscriber_lag_total",
+            "Times a subscriber fell behind the broadcast channel.",
+            "counter",
+        );
+        for (subscriber, count) in &inner.subscriber_lag {
+            let _ = writeln!(
+                out,
+                "vibetea_subscriber_lag_total{{subscriber=\"{}\"}} {count}",
+                escape_label(subscriber)
+            );
+        }
+
+        write_header(
+            &mut out,
+            "vibetea_subscriber_lagged_events",
+            "Events skipped per subscriber lag incident.",
+            "histogram",
+        );
+        for (subscriber, histogram) in &inner.lagged_events {
+            let labels = format!("subscriber=\"{}\",", escape_label(subscriber));
+            histogram.render_series(&mut out, "vibetea_subscriber_lagged_events", &labels);
+        }
+
+        write_header(
+            &mut out,
+            "vibetea_webhook_deliveries_total",
+            "Webhook batches delivered, failed after all retries, or dropped from a full queue.",
+            "counter",
+        );
+        for ((webhook, outcome), count) in &inner.webhook_deliveries {
+            let _ = writeln!(
+                out,
+                "vibetea_webhook_deliveries_total{{webhook=\"{}\",outcome=\"{outcome}\"}} {count}",
+                escape_label(webhook)
+            );
+        }
+
+        write_header(
+            &mut out,
+            "vibetea_relay_events_total",
+            "Relayed events forwarded upstream, or dropped from a full buffer or rejected upstream.",
+            "counter",
+        );
+        for (outcome, count) in &inner.relay_events {
+            let _ = writeln!(
+                out,
+                "vibetea_relay_events_total{{outcome=\"{outcome}\"}} {count}"
+            );
+        }
+        inner.body_bytes.render(
+            &mut out,
+            "vibetea_request_body_bytes",
+            "Size of POST /events request bodies.",
+        );
+        inner.ingest_seconds.render(
+            &mut out,
+            "vibetea_ingest_duration_seconds",
+            "Time to authenticate, persist and broadcast POST /events requests.",
+        );
+
+        for gauge in gauges {
+            write_header(&mut out, gauge.name, gauge.help, "gauge");
+            let _ = writeln!(out, "{} {}", gauge.name, gauge.value);
+        }
+
+        out
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A cumulative histogram with fixed bucket bounds.
+#[derive(Debug)]
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        for (bound, count) in self.bounds.iter().zip(&mut self.counts) {
+            if value <= *bound {
+                *count += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        write_header(out, name, help, "histogram");
+        self.render_series(out, name, "");
+    }
+
+    /// Writes the bucket, sum and count samples, with `labels` (each
+    /// followed by a comma) added to every sample.
+    fn render_series(&self, out: &mut String, name: &str, labels: &str) {
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            let _ = writeln!(out, "{name}_bucket{{{labels}le=\"{bound}\"}} {count}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{{labels}le=\"+Inf\"}} {}", self.count);
+        let (sum, count) = (self.sum, self.count);
+        match labels.strip_suffix(',') {
+            Some(labels) => {
+                let _ = writeln!(out, "{name}_sum{{{labels}}} {sum}");
+                let _ = writeln!(out, "{name}_count{{{labels}}} {count}");
+            }
+            None => {
+                let _ = writeln!(out, "{name}_sum {sum}");
+                let _ = writeln!(out, "{name}_count {count}");
+            }
+        }
+    }
+}
+
+fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Escapes a label value per the exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_accepted_events_by_source_and_type() {
+        let metrics = Metrics::new();
+        metrics.record_accepted("a", EventType::Tool);
+        metrics.record_accepted("a", EventType::Tool);
+        metrics.record_accepted("b", EventType::Session);
+
+        let text = metrics.render(&[]);
+        assert!(text.contains(r#"vibetea_events_accepted_total{source="a",type="tool"} 2"#));
+        assert!(text.contains(r#"vibetea_events_accepted_total{source="b",type="session"} 1"#));
+    }
+
+    #[test]
+    fn counts_duplicate_events_by_source() {
+        let metrics = Metrics::new();
+        metrics.record_duplicates("a", 3);
+        metrics.record_duplicates("a", 0);
+        metrics.record_duplicates("b", 0);
+
+        let text = metrics.render(&[]);
+        assert!(text.contains(r#"vibetea_duplicate_events_total{source="a"} 3"#));
+        assert!(!text.contains(r#"vibetea_duplicate_events_total{source="b"}"#));
+    }
+
+    #[test]
+    fn counts_invalid_events_by_source_and_reason() {
+        let metrics = Metrics::new();
+        metrics.record_invalid("a", "invalid_id");
+        metrics.record_invalid("a", "invalid_id");
+        metrics.record_invalid("a", "type_mismatch");
+
+        let text = metrics.render(&[]);
+        assert!(text.contains(r#"vibetea_invalid_events_total{source="a",reason="invalid_id"} 2"#));
+        assert!(
+            text.contains(r#"vibetea_invalid_events_total{source="a",reason="type_mismatch"} 1"#)
+        );
+    }
+
+    #[test]
+    fn counts_failures_and_rate_limits() {
+        let metrics = Metrics::new();
+        metrics.record_auth_failure("invalid_signature");
+        metrics.record_rate_limited("noisy");
+        metrics.record_connection_rejected();
+
+        let text = metrics.render(&[]);
+        assert!(text.contains("vibetea_ws_connections_rejected_total 1"));
+        assert!(text.contains(r#"vibetea_auth_failures_total{reason="invalid_signature"} 1"#));
+        assert!(text.contains(r#"vibetea_rate_limited_total{source="noisy"} 1"#));
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let metrics = Metrics::new();
+        metrics.observe_body_size(100);
+        metrics.observe_body_size(2000);
+
+        let text = metrics.render(&[]);
+        assert!(text.contains(r#"vibetea_request_body_bytes_bucket{le="256"} 1"#));
+        assert!(text.contains(r#"vibetea_request_body_bytes_bucket{le="4096"} 2"#));
+        assert!(text.contains(r#"vibetea_request_body_bytes_bucket{le="+Inf"} 2"#));
+        assert!(text.contains("vibetea_request_body_bytes_sum 2100"));
+        assert!(text.contains("vibetea_request_body_bytes_count 2"));
+    }
+
+    #[test]
+    fn records_subscriber_lag() {
+        let metrics = Metrics::new();
+        metrics.record_lag("sse", 42);
+        metrics.record_lag("webhook:slack", 5);
+        metrics.record_lag("webhook:slack", 500);
+
+        let text = metrics.render(&[]);
+        assert!(text.contains(r#"vibetea_subscriber_lag_total{subscriber="sse"} 1"#));
+        assert!(text.contains(r#"vibetea_subscriber_lag_total{subscriber="webhook:slack"} 2"#));
+        assert!(text
+            .contains(r#"vibetea_subscriber_lagged_events_bucket{subscriber="sse",le="100"} 1"#));
+        assert!(text.contains(
+            r#"vibetea_subscriber_lagged_events_bucket{subscriber="webhook:slack",le="10"} 1"#
+        ));
+        assert!(text
+            .contains(r#"vibetea_subscriber_lagged_events_sum{subscriber="webhook:slack"} 505"#));
+        assert!(text.contains(r#"vibetea_subscriber_lagged_events_count{subscriber="sse"} 1"#));
+    }
+
+    #[test]
+    fn counts_webhook_deliveries_by_outcome() {
+        let metrics = Metrics::new();
+        metrics.record_webhook_delivery("slack", "delivered");
+        metrics.record_webhook_delivery("slack", "delivered");
+        metrics.record_webhook_delivery("slack", "failed");
+
+        let text = metrics.render(&[]);
+        assert!(text.contains(
+            r#"vibetea_webhook_deliveries_total{webhook="slack",outcome="delivered"} 2"#
+        ));
+        assert!(text
+            .contains(r#"vibetea_webhook_deliveries_total{webhook="slack",outcome="failed"} 1"#));
+    }
+
+    #[test]
+    fn counts_relay_events_by_outcome() {
+        let metrics = Metrics::new();
+        metrics.record_relay_events("forwarded", 3);
+        metrics.record_relay_events("forwarded", 2);
+        metrics.record_relay_events("dropped", 1);
+
+        let text = metrics.render(&[]);
+        assert!(text.contains(r#"vibetea_relay_events_total{outcome="forwarded"} 5"#));
+        assert!(text.contains(r#"vibetea_relay_events_total{outcome="dropped"} 1"#));
+    }
+
+    #[test]
+    fn renders_gauges_with_type() {
+        let text = Metrics::new().render(&[Gauge::new("vibetea_connections", "Connections.", 2.0)]);
+        assert!(text.contains("# TYPE vibetea_connections gauge"));
+        assert!(text.contains("vibetea_connections 2\n"));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape_label(r#"a"b\c"#), r#"a\"b\\c"#);
+    }
+}
@@ -0,0 +1,158 @@
+//! Cache of recently used request nonces.
+//!
+//! Signed `POST /events` requests carry an `X-Nonce` header covered by the
+//! signature (see [`crate::auth::signed_message`]). [`NonceCache`] remembers
+//! each nonce per source so that a captured request cannot be replayed while
+//! its timestamp is still inside the allowed clock skew.
+//!
+//! Entries only need to outlive the skew window: once a request's timestamp
+//! is too old, [`crate::auth::check_timestamp`] rejects it before the cache
+//! is consulted. Expired entries are pruned as new nonces are recorded.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use chrono::Utc;
+//! use vibetea_server::nonce::NonceCache;
+//!
+//! let cache = NonceCache::new(Duration::from_secs(300));
+//! let now = Utc::now();
+//!
+//! assert!(cache.insert("monitor-1", "3f2a9c", now, now));
+//! assert!(!cache.insert("monitor-1", "3f2a9c", now, now)); // replay
+//! assert!(cache.insert("monitor-2", "3f2a9c", now, now)); // other source
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// Thread-safe record of nonces seen within the skew window.
+///
+/// Cloning is cheap and shares the underlying state.
+#[derive(Debug, Clone)]
+pub struct NonceCache {
+    inner: Arc<Mutex<Inner>>,
+    max_skew: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Signing time of each `(source, nonce)` pair.
+    seen: HashMap<(String, String), DateTime<Utc>>,
+
+    /// When expired entries were last removed.
+    last_pruned: Option<DateTime<Utc>>,
+}
+
+impl NonceCache {
+    /// Creates an empty cache for requests accepted within `max_skew` of the
+    /// server clock.
+    #[must_use]
+    pub fn new(max_skew: Duration) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner::default())),
+            max_skew,
+        }
+    }
+
+    /// Records `nonce` for `source`, signed at `signed_at`.
+    ///
+    /// Returns `false` if the nonce was already recorded for this source,
+    /// meaning the request is a replay and must be rejected.
+    pub fn insert(
+        &self,
+        source: &str,
+        nonce: &str,
+        signed_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let mut inner = self.lock();
+
+        // Prune at most once per skew window so the cost stays amortised
+        let window = chrono::Duration::from_std(self.max_skew).unwrap_or(chrono::Duration::MAX);
+        if inner.last_pruned.is_none_or(|last| now - last >= window) {
+            let cutoff = now - window;
+            inner.seen.retain(|_, signed_at| *signed_at >= cutoff);
+            inner.last_pruned = Some(now);
+        }
+
+        let key = (source.to_string(), nonce.to_string());
+        if inner.seen.contains_key(&key) {
+            return false;
+        }
+        inner.seen.insert(key, signed_at);
+        true
+    }
+
+    /// Returns the number of nonces currently remembered.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().seen.len()
+    }
+
+    /// Returns `true` if no nonces are remembered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SKEW: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn rejects_repeated_nonce_for_same_source() {
+        let cache = NonceCache::new(SKEW);
+        let now = Utc::now();
+
+        assert!(cache.insert("a", "n1", now, now));
+        assert!(!cache.insert("a", "n1", now, now));
+        assert!(cache.insert("a", "n2", now, now));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn nonces_are_scoped_per_source() {
+        let cache = NonceCache::new(SKEW);
+        let now = Utc::now();
+
+        assert!(cache.insert("a", "n1", now, now));
+        assert!(cache.insert("b", "n1", now, now));
+    }
+
+    #[test]
+    fn prunes_entries_older_than_the_skew_window() {
+        let cache = NonceCache::new(SKEW);
+        let start = Utc::now();
+
+        assert!(cache.insert("a", "old", start, start));
+
+        let later = start + chrono::Duration::seconds(61);
+        assert!(cache.insert("a", "new", later, later));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn keeps_entries_inside_the_skew_window() {
+        let cache = NonceCache::new(SKEW);
+        let start = Utc::now();
+
+        assert!(cache.insert("a", "n1", start, start));
+
+        let later = start + chrono::Duration::seconds(30);
+        assert!(!cache.insert("a", "n1", start, later));
+    }
+}
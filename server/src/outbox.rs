@@ -0,0 +1,530 @@
+//! Bounded per-connection send queues for WebSocket subscribers.
+//!
+//! Each `/ws` connection has an [`Outbox`]. The connection task pushes events
+//! and control messages into it, and a separate writer task sends them to the
+//! client, giving up on any write that takes longer than the configured write
+//! timeout. A client that reads slower than events arrive fills its own queue
+//! instead of stalling the connection task or silently lagging on the shared
+//! broadcast channel.
+//!
+//! # Slow Consumers
+//!
+//! When a live event arrives and the queue already holds its capacity
+//! (`VIBETEA_WS_QUEUE_CAPACITY`), the [`SlowConsumerPolicy`]
+//! (`VIBETEA_SLOW_CONSUMER_POLICY`) decides what happens:
+//!
+//! - `drop_oldest` (default) - the oldest queued event is dropped
+//! - `coalesce` - a queued event that the new one supersedes is dropped, or
+//!   the oldest queued event if there is none (see below)
+//! - `disconnect` - the queue is discarded and the connection is closed with
+//!   [`CLOSE_SLOW_CONSUMER`]
+//!
+//! Dropped events are reported with a `lagged` message, sent ahead of the
+//! next queued message, so clients can tell a gap from a quiet period and
+//! resume from the last event they received:
+//!
+//! ```json
+//! {"type": "lagged", "skipped": 42}
+//! ```
+//!
+//! Control messages are never dropped, and replayed events wait for space
+//! instead of being dropped.
+//!
+//! # Coalescing
+//!
+//! Events that report current state rather than something that happened
+//! supersede older events of the same type from the same source and session
+//! (or model, for `token_usage`, or project, for `project_activity`):
+//! `activity`, `agent`, `token_usage`, `session_metrics`,
+//! `model_distribution`, `activity_pattern`, `todo_progress` and
+//! `project_activity`. Other events are never coalesced.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::types::{Event, EventPayload, EventType};
+use crate::ws::ServerMessage;
+
+/// Default number of messages queued per connection.
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// Default time allowed for a single write to a client.
+pub const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Close code sent when the `disconnect` policy closes a connection
+/// ("Try Again Later").
+pub const CLOSE_SLOW_CONSUMER: u16 = 1013;
+
+/// What to do when a live event arrives for a connection whose queue is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowConsumerPolicy {
+    /// Drop the oldest queued event.
+    #[default]
+    DropOldest,
+
+    /// Drop a queued event superseded by the new one, or else the oldest.
+    Coalesce,
+
+    /// Close the connection with [`CLOSE_SLOW_CONSUMER`].
+    Disconnect,
+}
+
+impl SlowConsumerPolicy {
+    /// Returns the name used in configuration.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::DropOldest => "drop_oldest",
+            Self::Coalesce => "coalesce",
+            Self::Disconnect => "disconnect",
+        }
+    }
+}
+
+impl FromStr for SlowConsumerPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "drop_oldest" => Ok(Self::DropOldest),
+            "coalesce" => Ok(Self::Coalesce),
+            "disconnect" => Ok(Self::Disconnect),
+            other => Err(format!(
+                "unknown policy '{other}', expected drop_oldest, coalesce or disconnect"
+            )),
+        }
+    }
+}
+
+/// A message waiting to be sent to a client.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Outgoing {
+    /// A matching event.
+    Event(Event),
+
+    /// A control message.
+    Control(ServerMessage),
+
+    /// A heartbeat ping frame.
+    Ping,
+
+    /// Close the connection with a close frame.
+    Close {
+        /// WebSocket close code.
+        code: u16,
+
+        /// Human-readable close reason.
+        reason: &'static str,
+    },
+}
+
+/// The outbox has been closed, by either side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Closed;
+
+#[derive(Debug, Default)]
+struct Queue {
+    messages: VecDeque<Outgoing>,
+
+    /// Events dropped since the last `lagged` notice.
+    skipped: u64,
+
+    closed: bool,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+
+    /// Signalled when a message is queued or the outbox is closed.
+    readable: Notify,
+
+    /// Signalled when a message is taken or the outbox is closed.
+    writable: Notify,
+}
+
+/// Bounded queue between a connection task and its writer task.
+///
+/// Cloning is cheap and shares the underlying queue.
+#[derive(Debug, Clone)]
+pub(crate) struct Outbox {
+    shared: Arc<Shared>,
+    capacity: usize,
+    policy: SlowConsumerPolicy,
+}
+
+impl Outbox {
+    /// Creates an empty outbox. A `capacity` of 0 is treated as 1.
+    pub(crate) fn new(capacity: usize, policy: SlowConsumerPolicy) -> Self {
+        Self {
+            shared: Arc::default(),
+            capacity: capacity.max(1),
+            policy,
+        }
+    }
+
+    /// Queues a live event, applying the slow-consumer policy if the queue
+    /// is full.
+    ///
+    /// Returns `Err(Closed)` if the outbox was already closed or the
+    /// `disconnect` policy closed it.
+    pub(crate) fn push_event(&self, event: Event) -> Result<(), Closed> {
+        let mut queue = self.lock();
+        if queue.closed {
+            return Err(Closed);
+        }
+
+        if queue.messages.len() >= self.capacity {
+            let superseded = match self.policy {
+                SlowConsumerPolicy::DropOldest => None,
+                SlowConsumerPolicy::Coalesce => coalesce_key(&event).and_then(|key| {
+                    queue.messages.iter().position(|queued| {
+                        matches!(queued, Outgoing::Event(queued) if coalesce_key(queued).as_ref() == Some(&key))
+                    })
+                }),
+                SlowConsumerPolicy::Disconnect => {
+                    drop(queue);
+                    self.close_with(CLOSE_SLOW_CONSUMER, "slow consumer");
+                    return Err(Closed);
+                }
+            };
+            let dropped = superseded.or_else(|| {
+                queue
+                    .messages
+                    .iter()
+                    .position(|queued| matches!(queued, Outgoing::Event(_)))
+            });
+
+            queue.skipped += 1;
+            match dropped {
+                Some(index) => {
+                    queue.messages.remove(index);
+                }
+                // Only control messages are queued, so drop the new event
+                None => return Ok(()),
+            }
+        }
+
+        queue.messages.push_back(Outgoing::Event(event));
+        drop(queue);
+        self.shared.readable.notify_one();
+        Ok(())
+    }
+
+    /// Queues an event, waiting for space instead of dropping anything.
+    pub(crate) async fn push_event_wait(&self, event: Event) -> Result<(), Closed> {
+        loop {
+            let writable = self.shared.writable.notified();
+            {
+                let mut queue = self.lock();
+                if queue.closed {
+                    return Err(Closed);
+                }
+                if queue.messages.len() < self.capacity {
+                    queue.messages.push_back(Outgoing::Event(event));
+                    drop(queue);
+                    self.shared.readable.notify_one();
+                    return Ok(());
+                }
+            }
+            writable.await;
+        }
+    }
+
+    /// Queues a control message. Control messages are never dropped.
+    pub(crate) fn push_control(&self, message: ServerMessage) -> Result<(), Closed> {
+        let mut queue = self.lock();
+        if queue.closed {
+            return Err(Closed);
+        }
+        queue.messages.push_back(Outgoing::Control(message));
+        drop(queue);
+        self.shared.readable.notify_one();
+        Ok(())
+    }
+
+    /// Queues a heartbeat ping ahead of everything else, so that a client
+    /// working through a backlog still answers it in time.
+    pub(crate) fn push_ping(&self) -> Result<(), Closed> {
+        let mut queue = self.lock();
+        if queue.closed {
+            return Err(Closed);
+        }
+        queue.messages.push_front(Outgoing::Ping);
+        drop(queue);
+        self.shared.readable.notify_one();
+        Ok(())
+    }
+
+    /// Records events the connection missed before they reached the queue,
+    /// so they are included in the next `lagged` notice.
+    pub(crate) fn record_skipped(&self, count: u64) {
+        self.lock().skipped += count;
+        self.shared.readable.notify_one();
+    }
+
+    /// Waits for the next message to send.
+    ///
+    /// A `lagged` notice is returned first if events were dropped. Returns
+    /// `None` once the outbox is closed and empty.
+    pub(crate) async fn pop(&self) -> Option<Outgoing> {
+        loop {
+            let readable = self.shared.readable.notified();
+            {
+                let mut queue = self.lock();
+                if queue.skipped > 0 && !queue.closed {
+                    let skipped = std::mem::take(&mut queue.skipped);
+                    return Some(Outgoing::Control(ServerMessage::Lagged { skipped }));
+                }
+                if let Some(message) = queue.messages.pop_front() {
+                    drop(queue);
+                    self.shared.writable.notify_one();
+                    return Some(message);
+                }
+                if queue.closed {
+                    return None;
+                }
+            }
+            readable.await;
+        }
+    }
+
+    /// Closes the outbox. Queued messages can still be taken, but nothing
+    /// more can be pushed.
+    pub(crate) fn close(&self) {
+        self.lock().closed = true;
+        self.shared.readable.notify_one();
+        self.shared.writable.notify_one();
+    }
+
+    /// Discards queued messages and closes the outbox, leaving only a close
+    /// frame with `code` and `reason` for the writer to send.
+    pub(crate) fn close_with(&self, code: u16, reason: &'static str) {
+        {
+            let mut queue = self.lock();
+            queue.messages.clear();
+            queue.messages.push_back(Outgoing::Close { code, reason });
+            queue.closed = true;
+        }
+        self.shared.readable.notify_one();
+        self.shared.writable.notify_one();
+    }
+
+    /// Returns the number of queued messages.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.lock().messages.len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.shared
+            .queue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Identifies events that supersede each other under the `coalesce` policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CoalesceKey {
+    source: String,
+    event_type: EventType,
+    subject: String,
+}
+
+/// Returns the coalescing key for state-reporting events, or `None` for
+/// events that must not be coalesced.
+fn coalesce_key(event: &Event) -> Option<CoalesceKey> {
+    let subject = match &event.payload {
+        EventPayload::Activity { session_id, .. } | EventPayload::Agent { session_id, .. } => {
+            session_id.to_string()
+        }
+        EventPayload::TokenUsage(usage) => usage.model.clone(),
+        EventPayload::TodoProgress(todos) => todos.session_id.clone(),
+        EventPayload::ProjectActivity(activity) => activity.project_path.clone(),
+        EventPayload::SessionMetrics(_)
+        | EventPayload::ModelDistribution(_)
+        | EventPayload::ActivityPattern(_) => String::new(),
+        _ => return None,
+    };
+    Some(CoalesceKey {
+        source: event.source.clone(),
+        event_type: event.event_type,
+        subject,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ToolStatus;
+    use uuid::Uuid;
+
+    fn event(n: usize, payload: EventPayload) -> Event {
+        let event_type = match &payload {
+            EventPayload::Tool { .. } => EventType::Tool,
+            _ => EventType::Activity,
+        };
+        Event::new("monitor-1".to_string(), event_type, payload).with_id(format!("evt_{n:0>20}"))
+    }
+
+    fn activity(n: usize, session_id: Uuid) -> Event {
+        event(
+            n,
+            EventPayload::Activity {
+                session_id,
+                project: None,
+            },
+        )
+    }
+
+    fn tool(n: usize) -> Event {
+        event(
+            n,
+            EventPayload::Tool {
+                session_id: Uuid::new_v4(),
+                tool: "Read".to_string(),
+                status: ToolStatus::Completed,
+                context: None,
+                project: None,
+            },
+        )
+    }
+
+    async fn event_id(outbox: &Outbox) -> String {
+        match outbox.pop().await {
+            Some(Outgoing::Event(event)) => event.id,
+            other => panic!("expected an event, got {other:?}"),
+        }
+    }
+
+    async fn skipped(outbox: &Outbox) -> u64 {
+        match outbox.pop().await {
+            Some(Outgoing::Control(ServerMessage::Lagged { skipped })) => skipped,
+            other => panic!("expected a lagged notice, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_policies() {
+        for policy in [
+            SlowConsumerPolicy::DropOldest,
+            SlowConsumerPolicy::Coalesce,
+            SlowConsumerPolicy::Disconnect,
+        ] {
+            assert_eq!(policy.as_str().parse::<SlowConsumerPolicy>(), Ok(policy));
+        }
+        assert_eq!(
+            " Coalesce ".parse::<SlowConsumerPolicy>(),
+            Ok(SlowConsumerPolicy::Coalesce)
+        );
+        assert!("drop_newest".parse::<SlowConsumerPolicy>().is_err());
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_reports_skipped_events_first() {
+        let outbox = Outbox::new(2, SlowConsumerPolicy::DropOldest);
+        for n in 0..4 {
+            outbox.push_event(tool(n)).unwrap();
+        }
+        assert_eq!(outbox.len(), 2);
+
+        assert_eq!(skipped(&outbox).await, 2);
+        assert_eq!(event_id(&outbox).await, tool(2).id);
+        assert_eq!(event_id(&outbox).await, tool(3).id);
+    }
+
+    #[tokio::test]
+    async fn control_messages_are_never_dropped() {
+        let outbox = Outbox::new(1, SlowConsumerPolicy::DropOldest);
+        let pong = ServerMessage::Unsubscribed {
+            id: "default".to_string(),
+        };
+        outbox.push_control(pong.clone()).unwrap();
+        outbox.push_event(tool(0)).unwrap();
+
+        assert_eq!(skipped(&outbox).await, 1);
+        assert_eq!(outbox.pop().await, Some(Outgoing::Control(pong)));
+    }
+
+    #[tokio::test]
+    async fn coalesce_replaces_superseded_events() {
+        let outbox = Outbox::new(3, SlowConsumerPolicy::Coalesce);
+        let session = Uuid::new_v4();
+        outbox.push_event(tool(0)).unwrap();
+        outbox.push_event(activity(1, session)).unwrap();
+        outbox.push_event(tool(2)).unwrap();
+
+        // Supersedes the queued activity event for the same session
+        outbox.push_event(activity(3, session)).unwrap();
+        // Nothing to coalesce, so the oldest event goes
+        outbox.push_event(tool(4)).unwrap();
+
+        assert_eq!(skipped(&outbox).await, 2);
+        assert_eq!(event_id(&outbox).await, tool(2).id);
+        assert_eq!(event_id(&outbox).await, activity(3, session).id);
+        assert_eq!(event_id(&outbox).await, tool(4).id);
+    }
+
+    #[tokio::test]
+    async fn disconnect_discards_queue_and_closes() {
+        let outbox = Outbox::new(1, SlowConsumerPolicy::Disconnect);
+        outbox.push_event(tool(0)).unwrap();
+        assert_eq!(outbox.push_event(tool(1)), Err(Closed));
+        assert_eq!(
+            outbox.push_control(ServerMessage::Lagged { skipped: 0 }),
+            Err(Closed)
+        );
+
+        assert_eq!(
+            outbox.pop().await,
+            Some(Outgoing::Close {
+                code: CLOSE_SLOW_CONSUMER,
+                reason: "slow consumer",
+            })
+        );
+        assert_eq!(outbox.pop().await, None);
+    }
+
+    #[tokio::test]
+    async fn push_event_wait_waits_for_space() {
+        let outbox = Outbox::new(1, SlowConsumerPolicy::Disconnect);
+        outbox.push_event(tool(0)).unwrap();
+
+        let writer = outbox.clone();
+        let pushed = tokio::spawn(async move { writer.push_event_wait(tool(1)).await });
+        tokio::task::yield_now().await;
+        assert!(!pushed.is_finished());
+
+        assert_eq!(event_id(&outbox).await, tool(0).id);
+        assert_eq!(pushed.await.unwrap(), Ok(()));
+        assert_eq!(event_id(&outbox).await, tool(1).id);
+    }
+
+    #[tokio::test]
+    async fn close_ends_pop_after_queued_messages() {
+        let outbox = Outbox::new(4, SlowConsumerPolicy::DropOldest);
+        outbox.push_event(tool(0)).unwrap();
+        outbox.close();
+
+        assert_eq!(outbox.push_event(tool(1)), Err(Closed));
+        assert_eq!(event_id(&outbox).await, tool(0).id);
+        assert_eq!(outbox.pop().await, None);
+    }
+
+    #[tokio::test]
+    async fn pings_jump_the_queue() {
+        let outbox = Outbox::new(4, SlowConsumerPolicy::DropOldest);
+        outbox.push_event(tool(0)).unwrap();
+        outbox.push_ping().unwrap();
+
+        assert_eq!(outbox.pop().await, Some(Outgoing::Ping));
+        assert_eq!(event_id(&outbox).await, tool(0).id);
+    }
+}
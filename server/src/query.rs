@@ -0,0 +1,383 @@
+//! Paginated queries over retained events.
+//!
+//! This module backs the `GET /events` endpoint. Queries run against the
+//! persistent [`EventStore`] when one is configured, and otherwise against the
+//! broadcaster's in-memory [`EventHistory`](crate::broadcast::EventHistory).
+//!
+//! Results are returned oldest first. Pages are chained with a cursor, which
+//! is the ID of the last event on the previous page.
+//!
+//! # Example
+//!
+//! ```rust
+//! use vibetea_server::broadcast::{EventBroadcaster, SubscriberFilter};
+//! use vibetea_server::query::EventQuery;
+//!
+//! let broadcaster = EventBroadcaster::new();
+//! let query = EventQuery::new()
+//!     .with_filter(SubscriberFilter::new().with_source("macbook-pro"))
+//!     .with_limit(50);
+//!
+//! let page = query.run(&broadcaster, None).unwrap();
+//! assert!(page.events.is_empty());
+//! assert!(page.next_cursor.is_none());
+//! ```
+
+use std::borrow::Borrow;
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::broadcast::{EventBroadcaster, SubscriberFilter};
+use crate::store::{EventStore, StoreError};
+use crate::types::Event;
+
+/// Default number of events per page.
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+
+/// Maximum number of events per page.
+pub const MAX_PAGE_LIMIT: usize = 1000;
+
+/// Errors that can occur when running a query.
+#[derive(Debug, Error)]
+pub enum QueryError {
+    /// The cursor does not refer to a retained event matching the query.
+    #[error("unknown cursor: {0}")]
+    UnknownCursor(String),
+
+    /// The event store could not be read.
+    #[error("failed to read event store: {0}")]
+    Store(#[from] StoreError),
+}
+
+/// A query over retained events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventQuery {
+    /// Source, type and project filter.
+    pub filter: SubscriberFilter,
+
+    /// Only include events at or after this instant.
+    pub from: Option<DateTime<Utc>>,
+
+    /// Only include events before this instant.
+    pub to: Option<DateTime<Utc>>,
+
+    /// Only include events after the event with this ID.
+    pub cursor: Option<String>,
+
+    /// Maximum number of events to return.
+    pub limit: usize,
+}
+
+/// A page of query results.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Page {
+    /// Matching events, oldest first.
+    pub events: Vec<Event>,
+
+    /// Cursor for the next page, or `None` if this is the last page.
+    pub next_cursor: Option<String>,
+}
+
+impl EventQuery {
+    /// Creates a query matching all retained events, with the default limit.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            filter: SubscriberFilter::new(),
+            from: None,
+            to: None,
+            cursor: None,
+            limit: DEFAULT_PAGE_LIMIT,
+        }
+    }
+
+    /// Sets the source, type and project filter.
+    #[must_use]
+    pub fn with_filter(mut self, filter: SubscriberFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Sets the inclusive lower time bound.
+    #[must_use]
+    pub fn with_from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Sets the exclusive upper time bound.
+    #[must_use]
+    pub fn with_to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// Continues after the event with this ID.
+    #[must_use]
+    pub fn with_cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Sets the page size, clamped to `1..=MAX_PAGE_LIMIT`.
+    #[must_use]
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit.clamp(1, MAX_PAGE_LIMIT);
+        self
+    }
+
+    /// Returns `true` if `event` falls within the query's filter and time range.
+    #[must_use]
+    pub fn matches(&self, event: &Event) -> bool {
+        self.from.is_none_or(|from| event.timestamp >= from)
+            && self.to.is_none_or(|to| event.timestamp < to)
+            && self.filter.matches(event)
+    }
+
+    /// Runs the query against the store if provided, otherwise against the
+    /// broadcaster's in-memory history.
+    ///
+    /// The store is read lazily from a snapshot, and reading stops as soon as
+    /// the page is known to be full.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`QueryError::UnknownCursor`] if the cursor is not a retained
+    /// event matching the query, or [`QueryError::Store`] if the store cannot
+    /// be read.
+    pub fn run(
+        &self,
+        broadcaster: &EventBroadcaster,
+        store: Option<&EventStore>,
+    ) -> Result<Page, QueryError> {
+        match store {
+            Some(store) => self.paginate(store.snapshot().events()),
+            None => broadcaster
+                .read_history(|history| self.paginate(history.iter().map(Ok::<_, StoreError>))),
+        }
+    }
+
+    /// Selects one page from events in arrival order, reading no further
+    /// than the first matching event past the page.
+    fn paginate<E: Borrow<Event>>(
+        &self,
+        events: impl Iterator<Item = Result<E, StoreError>>,
+    ) -> Result<Page, QueryError> {
+        let mut past_cursor = self.cursor.is_none();
+        let mut page: Vec<Event> = Vec::new();
+
+        for event in events {
+            let event = event?;
+            let event = event.borrow();
+            if !self.matches(event) {
+                continue;
+            }
+            if !past_cursor {
+                past_cursor = self.cursor.as_deref() == Some(event.id.as_str());
+                continue;
+            }
+            if page.len() == self.limit {
+                return Ok(Page {
+                    next_cursor: page.last().map(|event| event.id.clone()),
+                    events: page,
+                });
+            }
+            page.push(event.clone());
+        }
+
+        match &self.cursor {
+            Some(cursor) if !past_cursor => Err(QueryError::UnknownCursor(cursor.clone())),
+            _ => Ok(Page {
+                events: page,
+                next_cursor: None,
+            }),
+        }
+    }
+}
+
+impl Default for EventQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::StoreConfig;
+    use crate::types::{EventPayload, EventType};
+    use chrono::Duration;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn make_event(n: usize, source: &str, minutes_ago: i64) -> Event {
+        Event::new(
+            source.to_string(),
+            EventType::Activity,
+            EventPayload::Activity {
+                session_id: Uuid::new_v4(),
+                project: None,
+            },
+        )
+        .with_id(format!("evt_{n:0>20}"))
+        .with_timestamp(Utc::now() - Duration::minutes(minutes_ago))
+    }
+
+    fn ids(page: &Page) -> Vec<String> {
+        ids_of(&page.events)
+    }
+
+    fn ids_of(events: &[Event]) -> Vec<String> {
+        events.iter().map(|e| e.id.clone()).collect()
+    }
+
+    fn broadcaster_with(events: Vec<Event>) -> EventBroadcaster {
+        let broadcaster = EventBroadcaster::new();
+        for event in events {
+            broadcaster.broadcast(event);
+        }
+        broadcaster
+    }
+
+    #[test]
+    fn returns_all_events_oldest_first() {
+        let broadcaster = broadcaster_with((0..3).map(|n| make_event(n, "a", 0)).collect());
+
+        let page = EventQuery::new().run(&broadcaster, None).unwrap();
+
+        assert_eq!(
+            ids(&page),
+            vec![
+                make_event(0, "a", 0).id,
+                make_event(1, "a", 0).id,
+                make_event(2, "a", 0).id
+            ]
+        );
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn paginates_with_cursor() {
+        let broadcaster = broadcaster_with((0..5).map(|n| make_event(n, "a", 0)).collect());
+
+        let first = EventQuery::new()
+            .with_limit(2)
+            .run(&broadcaster, None)
+            .unwrap();
+        assert_eq!(first.events.len(), 2);
+        let cursor = first.next_cursor.clone().expect("more pages");
+        assert_eq!(cursor, make_event(1, "a", 0).id);
+
+        let second = EventQuery::new()
+            .with_limit(2)
+            .with_cursor(cursor)
+            .run(&broadcaster, None)
+            .unwrap();
+        assert_eq!(
+            ids(&second),
+            vec![make_event(2, "a", 0).id, make_event(3, "a", 0).id]
+        );
+
+        let third = EventQuery::new()
+            .with_limit(2)
+            .with_cursor(second.next_cursor.unwrap())
+            .run(&broadcaster, None)
+            .unwrap();
+        assert_eq!(ids(&third), vec![make_event(4, "a", 0).id]);
+        assert!(third.next_cursor.is_none());
+    }
+
+    #[test]
+    fn unknown_cursor_is_an_error() {
+        let broadcaster = broadcaster_with(vec![make_event(0, "a", 0)]);
+
+        let result = EventQuery::new()
+            .with_cursor("evt_missing")
+            .run(&broadcaster, None);
+
+        assert!(matches!(result, Err(QueryError::UnknownCursor(id)) if id == "evt_missing"));
+    }
+
+    #[test]
+    fn applies_filter_and_time_range() {
+        let broadcaster = broadcaster_with(vec![
+            make_event(0, "a", 120),
+            make_event(1, "b", 60),
+            make_event(2, "a", 60),
+            make_event(3, "a", 0),
+        ]);
+
+        let page = EventQuery::new()
+            .with_filter(SubscriberFilter::new().with_source("a"))
+            .with_from(Utc::now() - Duration::minutes(90))
+            .with_to(Utc::now() - Duration::minutes(30))
+            .run(&broadcaster, None)
+            .unwrap();
+
+        assert_eq!(ids(&page), vec![make_event(2, "a", 60).id]);
+    }
+
+    #[test]
+    fn limit_is_clamped() {
+        assert_eq!(EventQuery::new().with_limit(0).limit, 1);
+        assert_eq!(
+            EventQuery::new().with_limit(1_000_000).limit,
+            MAX_PAGE_LIMIT
+        );
+    }
+
+    #[test]
+    fn reads_from_store_when_configured() {
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        let events: Vec<Event> = (0..3).map(|n| make_event(n, "a", 0)).collect();
+        store.append(&events).unwrap();
+
+        // The broadcaster has seen nothing, so results must come from disk
+        let broadcaster = EventBroadcaster::new();
+        let page = EventQuery::new()
+            .with_limit(2)
+            .run(&broadcaster, Some(&store))
+            .unwrap();
+
+        assert_eq!(ids(&page), vec![events[0].id.clone(), events[1].id.clone()]);
+        assert_eq!(page.next_cursor, Some(events[1].id.clone()));
+    }
+
+    #[test]
+    fn store_page_stops_reading_after_the_page() {
+        let dir = TempDir::new().unwrap();
+        let config = StoreConfig::new(dir.path()).with_segment_bytes(1);
+        let store = EventStore::open(config).unwrap();
+        let events: Vec<Event> = (0..10).map(|n| make_event(n, "a", 0)).collect();
+        for event in &events {
+            store.append(std::slice::from_ref(event)).unwrap();
+        }
+
+        // Make the last stored segment unreadable, so any query that reaches
+        // it fails
+        let mut segments: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| std::fs::metadata(path).unwrap().len() > 0)
+            .collect();
+        segments.sort();
+        let last = segments.pop().unwrap();
+        std::fs::remove_file(&last).unwrap();
+        std::fs::create_dir(&last).unwrap();
+
+        let broadcaster = EventBroadcaster::new();
+        let page = EventQuery::new()
+            .with_limit(3)
+            .run(&broadcaster, Some(&store))
+            .unwrap();
+        assert_eq!(ids(&page), ids_of(&events[..3]));
+        assert_eq!(page.next_cursor, Some(events[2].id.clone()));
+
+        let everything = EventQuery::new()
+            .with_limit(100)
+            .run(&broadcaster, Some(&store));
+        assert!(matches!(everything, Err(QueryError::Store(_))));
+    }
+}
@@ -1,15 +1,20 @@
-//! Per-source rate limiting using the token bucket algorithm.
+//! Rate limiting using the token bucket algorithm.
 //!
 //! This module provides rate limiting functionality to protect the VibeTea server
 //! from excessive requests. Each source (identified by the `X-Source-ID` header)
-//! has its own token bucket that replenishes over time.
+//! has its own token bucket that replenishes over time, and an optional global
+//! bucket caps the combined number of events ingested across all sources.
+//!
+//! [`ConnectionLimiter`] separately caps the number of concurrent WebSocket and
+//! SSE connections per client IP address.
 //!
 //! # Algorithm
 //!
 //! The token bucket algorithm works as follows:
 //! - Each source has a bucket that can hold up to `capacity` tokens
 //! - Tokens are added at a rate of `rate` tokens per second
-//! - Each request consumes one token
+//! - Each request consumes one token from its source's bucket, and each event
+//!   in it one token from the global bucket
 //! - If no tokens are available, the request is rejected with a `Retry-After` header
 //!
 //! # Example
@@ -33,7 +38,8 @@
 //! ```
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use tokio::sync::RwLock;
@@ -45,6 +51,15 @@ pub const DEFAULT_RATE: f64 = 100.0;
 /// Default bucket capacity: 100 tokens (allows bursts up to this size).
 pub const DEFAULT_CAPACITY: u32 = 100;
 
+/// Default global rate limit: 1000 events per second across all sources.
+pub const DEFAULT_GLOBAL_RATE: f64 = 1000.0;
+
+/// Default global bucket capacity: 1000 tokens.
+pub const DEFAULT_GLOBAL_CAPACITY: u32 = 1000;
+
+/// Default maximum concurrent WebSocket connections per client IP.
+pub const DEFAULT_MAX_CONNECTIONS_PER_IP: usize = 32;
+
 /// Duration after which inactive entries are cleaned up (60 seconds).
 pub const STALE_ENTRY_TIMEOUT: Duration = Duration::from_secs(60);
 
@@ -149,14 +164,23 @@ impl TokenBucket {
     /// - `RateLimitResult::Allowed` if a token was consumed
     /// - `RateLimitResult::Limited { retry_after_secs }` if rate limited
     pub fn try_consume(&mut self) -> RateLimitResult {
+        self.try_consume_many(1)
+    }
+
+    /// Attempts to consume `count` tokens at once.
+    ///
+    /// A `count` above the capacity is treated as the capacity, so an
+    /// oversized request needs a full bucket rather than never passing.
+    fn try_consume_many(&mut self, count: usize) -> RateLimitResult {
         self.refill();
 
-        if self.tokens >= 1.0 {
-            self.tokens -= 1.0;
+        let count = count.min(self.capacity as usize) as f64;
+        if self.tokens >= count {
+            self.tokens -= count;
             RateLimitResult::Allowed
         } else {
-            // Calculate how long until we have at least one token
-            let tokens_needed = 1.0 - self.tokens;
+            // Calculate how long until we have enough tokens
+            let tokens_needed = count - self.tokens;
             let seconds_until_token = tokens_needed / self.rate;
             // Round up to ensure we have at least one token
             let retry_after_secs = seconds_until_token.ceil() as u64;
@@ -188,6 +212,13 @@ impl TokenBucket {
         self.last_refill.elapsed()
     }
 
+    /// Returns a token consumed by [`try_consume`](Self::try_consume).
+    ///
+    /// Used when a request passes this bucket but is rejected by another.
+    fn refund(&mut self) {
+        self.tokens = (self.tokens + 1.0).min(f64::from(self.capacity));
+    }
+
     /// Returns the current number of tokens (for testing/debugging).
     #[cfg(test)]
     pub fn tokens(&self) -> f64 {
@@ -201,6 +232,13 @@ impl TokenBucket {
 /// has its own [`TokenBucket`]. The rate limiter automatically cleans up
 /// stale entries that have been inactive for longer than [`STALE_ENTRY_TIMEOUT`].
 ///
+/// An optional global bucket, configured with
+/// [`with_global_limit`](Self::with_global_limit), is shared by all sources
+/// and charged one token per event. A request must pass its source bucket
+/// with [`check_rate_limit`](Self::check_rate_limit) and, once its events are
+/// counted, the global bucket with
+/// [`check_global_limit`](Self::check_global_limit).
+///
 /// # Thread Safety
 ///
 /// The `RateLimiter` uses a `RwLock` internally, making it safe to share
@@ -240,6 +278,9 @@ struct RateLimiterInner {
 
     /// Maximum bucket capacity.
     capacity: u32,
+
+    /// Bucket shared by all sources, if a global limit is set.
+    global: Option<TokenBucket>,
 }
 
 impl RateLimiter {
@@ -264,10 +305,35 @@ impl RateLimiter {
                 buckets: HashMap::new(),
                 rate,
                 capacity,
+                global: None,
             })),
         }
     }
 
+    /// Adds a global limit shared by all sources (builder pattern).
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - Number of events allowed per second across all sources
+    /// * `capacity` - Maximum global burst, in events
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use vibetea_server::rate_limit::RateLimiter;
+    ///
+    /// // 100 requests/second per source, 1000 events/second overall
+    /// let limiter = RateLimiter::new(100.0, 100).with_global_limit(1000.0, 1000);
+    /// ```
+    #[must_use]
+    pub fn with_global_limit(self, rate: f64, capacity: u32) -> Self {
+        // Builders run before the limiter is in use, so the lock is free
+        if let Ok(mut inner) = self.inner.try_write() {
+            inner.global = Some(TokenBucket::new(rate, capacity));
+        }
+        self
+    }
+
     /// Creates a new rate limiter with default settings.
     ///
     /// Uses [`DEFAULT_RATE`] (100 tokens/sec) and [`DEFAULT_CAPACITY`] (100 tokens).
@@ -288,7 +354,7 @@ impl RateLimiter {
     /// This method:
     /// 1. Creates a new token bucket for the source if one doesn't exist
     /// 2. Refills tokens based on elapsed time
-    /// 3. Attempts to consume one token
+    /// 3. Attempts to consume one token from the source's bucket
     /// 4. Returns whether the request is allowed or rate limited
     ///
     /// # Arguments
@@ -326,12 +392,52 @@ impl RateLimiter {
         let rate = inner.rate;
         let capacity = inner.capacity;
 
-        let bucket = inner
+        inner
             .buckets
             .entry(source_id.to_string())
-            .or_insert_with(|| TokenBucket::new(rate, capacity));
+            .or_insert_with(|| TokenBucket::new(rate, capacity))
+            .try_consume()
+    }
+
+    /// Charges `events` events from `source_id` against the global limit.
+    ///
+    /// Called once the request body has been parsed, after the request has
+    /// passed [`check_rate_limit`](Self::check_rate_limit). Only sources within
+    /// their own limit draw from the global bucket, so a single flooding
+    /// source cannot drain it on its own. If the global limit rejects the
+    /// request, the token it took from the source's bucket is refunded.
+    ///
+    /// Always allowed when no global limit is configured.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use vibetea_server::rate_limit::RateLimiter;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let limiter = RateLimiter::new(100.0, 100).with_global_limit(1000.0, 1000);
+    ///
+    ///     assert!(limiter.check_rate_limit("source-123").await.is_allowed());
+    ///     assert!(limiter.check_global_limit("source-123", 250).await.is_allowed());
+    /// }
+    /// ```
+    pub async fn check_global_limit(&self, source_id: &str, events: usize) -> RateLimitResult {
+        let mut inner = self.inner.write().await;
+        let RateLimiterInner {
+            buckets, global, ..
+        } = &mut *inner;
 
-        bucket.try_consume()
+        let Some(global) = global else {
+            return RateLimitResult::Allowed;
+        };
+        let result = global.try_consume_many(events);
+        if result.is_limited() {
+            if let Some(bucket) = buckets.get_mut(source_id) {
+                bucket.refund();
+            }
+        }
+        result
     }
 
     /// Removes stale entries that have been inactive for longer than the timeout.
@@ -461,6 +567,111 @@ impl Default for RateLimiter {
     }
 }
 
+/// Caps concurrent connections per client IP address.
+///
+/// [`try_acquire`](Self::try_acquire) returns a [`ConnectionPermit`] that
+/// releases its slot when dropped, so a connection holds its permit for its
+/// whole lifetime.
+///
+/// # Example
+///
+/// ```rust
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use vibetea_server::rate_limit::ConnectionLimiter;
+///
+/// let limiter = ConnectionLimiter::new(1);
+/// let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+///
+/// let permit = limiter.try_acquire(ip).expect("first connection allowed");
+/// assert!(limiter.try_acquire(ip).is_none());
+///
+/// drop(permit);
+/// assert!(limiter.try_acquire(ip).is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectionLimiter {
+    inner: Arc<Mutex<HashMap<IpAddr, usize>>>,
+    max_per_ip: usize,
+}
+
+impl ConnectionLimiter {
+    /// Creates a limiter allowing `max_per_ip` concurrent connections per IP.
+    ///
+    /// A limit of 0 disables the cap.
+    #[must_use]
+    pub fn new(max_per_ip: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(HashMap::new())),
+            max_per_ip,
+        }
+    }
+
+    /// Returns the configured per-IP limit (0 means unlimited).
+    #[must_use]
+    pub fn max_per_ip(&self) -> usize {
+        self.max_per_ip
+    }
+
+    /// Reserves a connection slot for `ip`.
+    ///
+    /// Returns `None` if `ip` already has the maximum number of connections.
+    #[must_use]
+    pub fn try_acquire(&self, ip: IpAddr) -> Option<ConnectionPermit> {
+        let mut counts = self.lock();
+        let count = counts.entry(ip).or_insert(0);
+        if self.max_per_ip > 0 && *count >= self.max_per_ip {
+            return None;
+        }
+        *count += 1;
+
+        Some(ConnectionPermit {
+            limiter: self.clone(),
+            ip,
+        })
+    }
+
+    /// Returns the number of open connections from `ip`.
+    #[must_use]
+    pub fn connection_count(&self, ip: IpAddr) -> usize {
+        self.lock().get(&ip).copied().unwrap_or(0)
+    }
+
+    fn release(&self, ip: IpAddr) {
+        let mut counts = self.lock();
+        if let Some(count) = counts.get_mut(&ip) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                counts.remove(&ip);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<IpAddr, usize>> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for ConnectionLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONNECTIONS_PER_IP)
+    }
+}
+
+/// A reserved connection slot, released when dropped.
+#[derive(Debug)]
+pub struct ConnectionPermit {
+    limiter: ConnectionLimiter,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.limiter.release(self.ip);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -715,4 +926,114 @@ mod tests {
         let result = limiter.check_rate_limit("capped-source").await;
         assert!(result.is_limited());
     }
+
+    // ========================================================================
+    // Global limit tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn global_limit_applies_across_sources() {
+        let limiter = RateLimiter::new(10.0, 10).with_global_limit(1.0, 2);
+
+        assert!(limiter.check_global_limit("source-a", 1).await.is_allowed());
+        assert!(limiter.check_global_limit("source-b", 1).await.is_allowed());
+        assert!(limiter.check_global_limit("source-c", 1).await.is_limited());
+    }
+
+    #[tokio::test]
+    async fn global_limit_counts_events() {
+        let limiter = RateLimiter::new(0.001, 1).with_global_limit(0.001, 10);
+
+        assert!(limiter.check_global_limit("source-a", 8).await.is_allowed());
+        assert!(limiter.check_global_limit("source-b", 3).await.is_limited());
+        assert!(limiter.check_global_limit("source-b", 2).await.is_allowed());
+    }
+
+    #[tokio::test]
+    async fn batch_larger_than_global_burst_needs_full_bucket() {
+        let limiter = RateLimiter::new(0.001, 1).with_global_limit(0.001, 10);
+
+        assert!(limiter
+            .check_global_limit("source-a", 50)
+            .await
+            .is_allowed());
+        assert!(limiter.check_global_limit("source-a", 1).await.is_limited());
+    }
+
+    #[tokio::test]
+    async fn no_global_limit_allows_any_batch() {
+        let limiter = RateLimiter::new(0.001, 1);
+
+        assert!(limiter
+            .check_global_limit("source-a", 10_000)
+            .await
+            .is_allowed());
+    }
+
+    #[tokio::test]
+    async fn global_rejection_refunds_source_token() {
+        let limiter = RateLimiter::new(0.001, 1).with_global_limit(0.001, 1);
+
+        assert!(limiter.check_rate_limit("source-a").await.is_allowed());
+        assert!(limiter.check_global_limit("source-a", 1).await.is_allowed());
+        // Rejected by the global bucket; source-b keeps its only token
+        assert!(limiter.check_rate_limit("source-b").await.is_allowed());
+        assert!(limiter.check_global_limit("source-b", 1).await.is_limited());
+
+        let inner = limiter.inner.read().await;
+        assert!(inner.buckets["source-b"].tokens() >= 1.0);
+    }
+
+    #[tokio::test]
+    async fn limited_source_does_not_drain_global_bucket() {
+        let limiter = RateLimiter::new(0.001, 1).with_global_limit(0.001, 2);
+
+        // As in POST /events, only requests within their source limit are
+        // charged against the global limit
+        for _ in 0..10 {
+            if limiter.check_rate_limit("flooder").await.is_allowed() {
+                assert!(limiter.check_global_limit("flooder", 1).await.is_allowed());
+            }
+        }
+        assert!(limiter.check_rate_limit("polite").await.is_allowed());
+        assert!(limiter.check_global_limit("polite", 1).await.is_allowed());
+    }
+
+    // ========================================================================
+    // ConnectionLimiter tests
+    // ========================================================================
+
+    #[test]
+    fn connection_limiter_caps_per_ip() {
+        let limiter = ConnectionLimiter::new(2);
+        let a: IpAddr = "10.0.0.1".parse().unwrap();
+        let b: IpAddr = "10.0.0.2".parse().unwrap();
+
+        let _p1 = limiter.try_acquire(a).unwrap();
+        let _p2 = limiter.try_acquire(a).unwrap();
+        assert!(limiter.try_acquire(a).is_none());
+        assert!(limiter.try_acquire(b).is_some());
+        assert_eq!(limiter.connection_count(a), 2);
+    }
+
+    #[test]
+    fn connection_permit_releases_on_drop() {
+        let limiter = ConnectionLimiter::new(1);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+
+        let permit = limiter.try_acquire(ip).unwrap();
+        drop(permit);
+
+        assert_eq!(limiter.connection_count(ip), 0);
+        assert!(limiter.try_acquire(ip).is_some());
+    }
+
+    #[test]
+    fn connection_limiter_zero_is_unlimited() {
+        let limiter = ConnectionLimiter::new(0);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+
+        let permits: Vec<_> = (0..100).map(|_| limiter.try_acquire(ip)).collect();
+        assert!(permits.iter().all(Option::is_some));
+    }
 }
@@ -0,0 +1,395 @@
+//! Relay mode: forwarding accepted events to an upstream hub.
+//!
+//! A hub with `VIBETEA_RELAY_URL` set forwards every event it accepts to
+//! another VibeTea server, so per-team or per-region hubs can feed a global
+//! one. The relay submits events to the upstream `POST /events` exactly as a
+//! monitor would, using the same [`Sender`] as the monitor.
+//!
+//! # Authentication
+//!
+//! The relay signs its requests with its own Ed25519 key, read from
+//! `key.priv` in `VIBETEA_RELAY_KEY_PATH` (create one with
+//! `VIBETEA_KEY_PATH=<dir> vibetea-monitor init`), and identifies itself with
+//! `VIBETEA_RELAY_ID` as its source ID. The upstream hub registers the
+//! relay's public key under that ID like any monitor's, and must list the ID
+//! in its `VIBETEA_RELAY_SOURCES` to accept events from other sources.
+//!
+//! # Hops
+//!
+//! Relayed events keep their original `source`. The relay appends its hub ID
+//! to each event's `hops` (see [`crate::types`]), and the upstream hub checks
+//! that the last hop is the hub that signed the request. A hub drops events
+//! that have already passed through it, so a cycle of relays cannot deliver
+//! an event twice, and an event may pass through at most
+//! [`MAX_HOPS`](crate::validation::MAX_HOPS) hubs.
+//!
+//! # Buffering
+//!
+//! Accepted events are queued in memory while the upstream hub is
+//! unreachable, up to `VIBETEA_RELAY_BUFFER_SIZE` (default 10000), after
+//! which the oldest are dropped. Batches are retried with exponential
+//! backoff; a batch that still fails stays queued and is retried after the
+//! maximum backoff delay. Since the upstream hub drops duplicate event IDs, a
+//! batch resent after an ambiguous failure is not broadcast twice. Batches
+//! the upstream hub rejects outright, with a `4xx` status other than `401`
+//! or `429`, are dropped. Buffered events are not persisted across restarts.
+//!
+//! Forwarded and dropped events are counted in `vibetea_relay_events_total`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use vibetea_server::relay::RelayConfig;
+//!
+//! let config = RelayConfig::new("https://global.example.com/", "hub-eu", "/etc/vibetea/relay")
+//!     .with_buffer_size(50_000);
+//! assert_eq!(config.url, "https://global.example.com");
+//! ```
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tracing::{debug, error, warn};
+use vibetea_sender::{Crypto, CryptoError, RetryPolicy, Sender, SenderConfig, SenderError};
+
+use crate::metrics::Metrics;
+use crate::routes::AppState;
+use crate::types::Event;
+
+/// Default number of events buffered while the upstream hub is unreachable.
+pub const DEFAULT_BUFFER_SIZE: usize = 10_000;
+
+/// Maximum number of events forwarded per flush.
+const MAX_BATCH: usize = 500;
+
+/// Configuration for forwarding events to an upstream hub.
+#[derive(Debug, Clone)]
+pub struct RelayConfig {
+    /// Base URL of the upstream hub, without a trailing slash.
+    pub url: String,
+
+    /// This hub's source ID at the upstream hub, appended to the hops of
+    /// every relayed event.
+    pub hub_id: String,
+
+    /// Directory containing the relay's `key.priv`.
+    pub key_path: PathBuf,
+
+    /// Events buffered while the upstream hub is unreachable.
+    pub buffer_size: usize,
+
+    /// Backoff for retrying failed batches.
+    pub retry_policy: RetryPolicy,
+}
+
+impl RelayConfig {
+    /// Creates a relay configuration with the default buffer size and retry
+    /// policy.
+    #[must_use]
+    pub fn new(
+        url: impl Into<String>,
+        hub_id: impl Into<String>,
+        key_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            hub_id: hub_id.into(),
+            key_path: key_path.into(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Sets the number of events buffered while the upstream hub is
+    /// unreachable.
+    #[must_use]
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size.max(1);
+        self
+    }
+
+    /// Sets the backoff for retrying failed batches.
+    #[must_use]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy.validated();
+        self
+    }
+
+    /// Loads the relay's signing key from `key_path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CryptoError` if the key is missing or invalid.
+    pub fn load_key(&self) -> Result<Crypto, CryptoError> {
+        Crypto::load(&self.key_path)
+    }
+}
+
+/// Forwards accepted events to an upstream hub.
+///
+/// Dropping the relay stops forwarding; events still buffered are lost.
+#[derive(Debug)]
+pub struct Relay {
+    queue: Arc<Queue>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for Relay {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl Relay {
+    /// Subscribes to the broadcaster in `state` and starts forwarding
+    /// events to the upstream hub in `config`, signed with `crypto`.
+    ///
+    /// Must be called from within a Tokio runtime.
+    #[must_use]
+    pub fn start(config: RelayConfig, crypto: Crypto, state: &AppState) -> Self {
+        let queue = Arc::new(Queue::new(config.buffer_size));
+        let pause = Duration::from_millis(config.retry_policy.max_delay_ms);
+        let sender = Sender::new(
+            SenderConfig::new(config.url, config.hub_id.clone(), MAX_BATCH)
+                .with_retry_policy(config.retry_policy),
+            crypto,
+        );
+        let forwarder = Forwarder {
+            queue: Arc::clone(&queue),
+            sender,
+            hub_id: config.hub_id,
+            metrics: state.metrics.clone(),
+            pause,
+        };
+
+        let tasks = vec![
+            tokio::spawn(collect(
+                Arc::clone(&queue),
+                state.broadcaster.subscribe(),
+                state.metrics.clone(),
+            )),
+            tokio::spawn(forwarder.run()),
+        ];
+
+        Self { queue, tasks }
+    }
+
+    /// Returns the number of events waiting to be forwarded, not counting
+    /// a batch in flight.
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+/// Events waiting to be forwarded, oldest first.
+#[derive(Debug)]
+struct Queue {
+    events: Mutex<VecDeque<Event>>,
+    capacity: usize,
+    ready: Notify,
+}
+
+impl Queue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            events: Mutex::new(VecDeque::new()),
+            capacity,
+            ready: Notify::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Appends `event`, returning whether the oldest event was dropped to
+    /// make room.
+    fn push(&self, event: Event) -> bool {
+        let dropped = {
+            let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+            let dropped = events.len() >= self.capacity && events.pop_front().is_some();
+            events.push_back(event);
+            dropped
+        };
+        self.ready.notify_one();
+        dropped
+    }
+
+    /// Waits until events are queued and removes up to `max` of them.
+    async fn take(&self, max: usize) -> Vec<Event> {
+        loop {
+            {
+                let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+                if !events.is_empty() {
+                    let count = events.len().min(max);
+                    return events.drain(..count).collect();
+                }
+            }
+            self.ready.notified().await;
+        }
+    }
+}
+
+/// Moves broadcast events into the relay queue until the broadcaster closes.
+async fn collect(queue: Arc<Queue>, mut rx: Receiver<Event>, metrics: Metrics) {
+    let mut overflowing = false;
+    loop {
+        match rx.recv().await {
+            Ok(event) => {
+                let dropped = queue.push(event);
+                if dropped {
+                    metrics.record_relay_events("dropped", 1);
+                    if !overflowing {
+                        warn!("Relay buffer full, dropping oldest events");
+                    }
+                }
+                overflowing = dropped;
+            }
+            Err(RecvError::Lagged(count)) => {
+                warn!(skipped = count, "Relay fell behind, skipped events");
+                metrics.record_lag("relay", count);
+                metrics.record_relay_events("dropped", count as usize);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+    debug!("Event broadcaster closed, relay stopped");
+}
+
+/// Sends queued events to the upstream hub.
+struct Forwarder {
+    queue: Arc<Queue>,
+    sender: Sender,
+    hub_id: String,
+    metrics: Metrics,
+    pause: Duration,
+}
+
+impl Forwarder {
+    async fn run(mut self) {
+        loop {
+            let batch = self.queue.take(MAX_BATCH).await;
+            let count = batch.len();
+            for mut event in batch {
+                event.hops.push(self.hub_id.clone());
+                self.sender.queue(event);
+            }
+
+            loop {
+                match self.sender.flush().await {
+                    Ok(()) => {
+                        self.metrics.record_relay_events("forwarded", count);
+                        break;
+                    }
+                    Err(err) if is_permanent(&err) => {
+                        error!(error = %err, events = count, "Upstream hub rejected relayed events, dropping them");
+                        self.sender.clear();
+                        self.metrics.record_relay_events("dropped", count);
+                        break;
+                    }
+                    Err(err) => {
+                        warn!(
+                            error = %err,
+                            buffered = self.queue.len() + count,
+                            "Upstream hub unavailable, will retry"
+                        );
+                        sleep(self.pause).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns true if resending the same batch would fail the same way.
+fn is_permanent(err: &SenderError) -> bool {
+    match err {
+        SenderError::ServerError { status, .. } => (400..500).contains(status),
+        SenderError::Json(_) | SenderError::InvalidHeader(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    use crate::types::{EventPayload, EventType};
+
+    fn event(n: usize) -> Event {
+        Event::new(
+            "monitor-1".to_string(),
+            EventType::Activity,
+            EventPayload::Activity {
+                session_id: Uuid::new_v4(),
+                project: None,
+            },
+        )
+        .with_id(format!("evt_{n:0>20}"))
+    }
+
+    #[test]
+    fn config_trims_trailing_slash() {
+        let config = RelayConfig::new("http://hub:8080/", "hub-a", "/keys");
+        assert_eq!(config.url, "http://hub:8080");
+        assert_eq!(config.buffer_size, DEFAULT_BUFFER_SIZE);
+    }
+
+    #[tokio::test]
+    async fn queue_drops_oldest_when_full() {
+        let queue = Queue::new(2);
+        assert!(!queue.push(event(1)));
+        assert!(!queue.push(event(2)));
+        assert!(queue.push(event(3)));
+
+        let ids: Vec<String> = queue.take(10).await.into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![event(2).id, event(3).id]);
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn queue_take_waits_for_events() {
+        let queue = Arc::new(Queue::new(10));
+        let taker = tokio::spawn({
+            let queue = Arc::clone(&queue);
+            async move { queue.take(1).await }
+        });
+
+        tokio::task::yield_now().await;
+        queue.push(event(1));
+        queue.push(event(2));
+
+        let batch = taker.await.unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn client_errors_are_permanent() {
+        let rejected = SenderError::ServerError {
+            status: 400,
+            message: String::new(),
+        };
+        let unavailable = SenderError::ServerError {
+            status: 503,
+            message: String::new(),
+        };
+        assert!(is_permanent(&rejected));
+        assert!(!is_permanent(&unavailable));
+        assert!(!is_permanent(&SenderError::AuthFailed));
+        assert!(!is_permanent(&SenderError::MaxRetriesExceeded {
+            attempts: 3
+        }));
+    }
+}
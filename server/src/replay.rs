@@ -0,0 +1,474 @@
+//! Replay of missed events for resuming subscribers.
+//!
+//! A subscriber that reconnects after a network drop can ask to resume from
+//! the last event it saw, either by event ID or by timestamp. This module
+//! resolves such a [`ResumePoint`] against the broadcaster's in-memory
+//! [`EventHistory`](crate::broadcast::EventHistory) and, when the point is
+//! older than that window, the persistent [`EventStore`].
+//!
+//! The returned [`Replay`] and live receiver are produced atomically with
+//! respect to publishing, which appends to the store and broadcasts under
+//! the store's lock (see [`EventStore::append_then`]). A subscriber that
+//! sends the replayed events followed by everything from the receiver
+//! therefore sees no gaps and no duplicates.
+//!
+//! Events older than the in-memory window are read from a snapshot of the
+//! store on the blocking thread pool and streamed to the subscriber through
+//! a bounded channel, so a long replay never sits in memory all at once.
+//!
+//! # Example
+//!
+//! ```rust
+//! use vibetea_server::broadcast::EventBroadcaster;
+//! use vibetea_server::replay::{subscribe_from, ResumePoint};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let broadcaster = EventBroadcaster::new();
+//!     let point = ResumePoint::AfterEvent("evt_k7m2n9p4q1r6s3t8u5v0".to_string());
+//!
+//!     let (mut replay, _rx) = subscribe_from(&broadcaster, None, &point);
+//!     assert!(replay.next().await.is_none());
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::broadcast::{EventBroadcaster, EventHistory};
+use crate::store::{EventStore, Snapshot};
+use crate::types::Event;
+
+/// Number of events read ahead from the store before the subscriber catches
+/// up.
+const STORE_READ_AHEAD: usize = 256;
+
+/// Position in the event stream from which a subscriber wants to resume.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResumePoint {
+    /// Resume with the events broadcast after the event with this ID.
+    AfterEvent(String),
+
+    /// Resume with the events whose timestamp is after this instant.
+    AfterTimestamp(DateTime<Utc>),
+}
+
+/// Events replayed to a resuming subscriber, oldest first.
+///
+/// Call [`next`](Self::next) until it returns `None`, then check
+/// [`gap`](Self::gap).
+#[derive(Debug)]
+pub struct Replay {
+    /// Events being read from the store, ahead of `memory`.
+    store: Option<StoreReplay>,
+
+    /// Events from the in-memory history.
+    memory: std::vec::IntoIter<Event>,
+
+    gap: bool,
+}
+
+/// Events streamed from a store snapshot by a blocking task.
+#[derive(Debug)]
+struct StoreReplay {
+    events: mpsc::Receiver<Event>,
+
+    /// Resolves to `true` if the store contained the resume point and was
+    /// read to the end of the snapshot.
+    reader: JoinHandle<bool>,
+}
+
+impl Replay {
+    /// Returns the next missed event, or `None` once the replay is complete.
+    pub async fn next(&mut self) -> Option<Event> {
+        if let Some(store) = &mut self.store {
+            if let Some(event) = store.events.recv().await {
+                return Some(event);
+            }
+            let covered = (&mut store.reader).await.unwrap_or_else(|err| {
+                warn!(error = %err, "Event store replay task failed");
+                false
+            });
+            if covered {
+                self.gap = false;
+            }
+            self.store = None;
+        }
+        self.memory.next()
+    }
+
+    /// Returns `true` if the resume point fell outside the retained window,
+    /// meaning some events between the resume point and the replay may be
+    /// missing.
+    ///
+    /// Only final once [`next`](Self::next) has returned `None`.
+    #[must_use]
+    pub fn gap(&self) -> bool {
+        self.gap
+    }
+}
+
+/// Subscribes to the broadcaster, replaying events missed since `point`.
+///
+/// The in-memory history is consulted first. If the resume point is older
+/// than that window and a `store` is provided, older events are read from
+/// disk and replayed ahead of the in-memory ones. Reading the store requires
+/// a Tokio runtime.
+#[must_use]
+pub fn subscribe_from(
+    broadcaster: &EventBroadcaster,
+    store: Option<&EventStore>,
+    point: &ResumePoint,
+) -> (Replay, Receiver<Event>) {
+    let store = store.filter(|_| !broadcaster_covers(broadcaster, point));
+
+    // `publish` broadcasts before releasing the store, so subscribing while
+    // the store is locked leaves every event either in the snapshot or on
+    // the receiver, never both. Only the segment list is captured, so
+    // appends are not held up by I/O. The store is locked before the history,
+    // in the same order as `publish`.
+    let subscribe = || broadcaster.subscribe_with_history(|history| after_point(history, point));
+    let (snapshot, ((memory, gap), rx)) = match store {
+        Some(store) => {
+            let (snapshot, subscribed) = store.snapshot_then(subscribe);
+            (Some(snapshot), subscribed)
+        }
+        None => (None, subscribe()),
+    };
+
+    let store = snapshot.map(|snapshot| {
+        let in_memory: HashSet<String> = memory.iter().map(|e| e.id.clone()).collect();
+        let (tx, events) = mpsc::channel(STORE_READ_AHEAD);
+        let point = point.clone();
+        let reader =
+            tokio::task::spawn_blocking(move || read_store(&snapshot, &point, &in_memory, &tx));
+        StoreReplay { events, reader }
+    });
+
+    debug!(
+        from_memory = memory.len(),
+        from_store = store.is_some(),
+        gap,
+        "Resolved subscriber resume point"
+    );
+
+    let replay = Replay {
+        store,
+        memory: memory.into_iter(),
+        gap,
+    };
+    (replay, rx)
+}
+
+/// Returns `true` if the in-memory history alone can satisfy `point`.
+fn broadcaster_covers(broadcaster: &EventBroadcaster, point: &ResumePoint) -> bool {
+    broadcaster.read_history(|history| covers(history, point))
+}
+
+/// Returns `true` if no events after `point` have been evicted from `history`.
+fn covers(history: &EventHistory, point: &ResumePoint) -> bool {
+    match point {
+        ResumePoint::AfterEvent(id) => history.position(id).is_some(),
+        ResumePoint::AfterTimestamp(since) => {
+            !history.is_truncated()
+                || history
+                    .iter()
+                    .next()
+                    .is_some_and(|oldest| oldest.timestamp <= *since)
+        }
+    }
+}
+
+/// Resolves `point` against the in-memory history, returning the events
+/// after it and whether the history has a gap.
+fn after_point(history: &EventHistory, point: &ResumePoint) -> (Vec<Event>, bool) {
+    let events = match point {
+        ResumePoint::AfterEvent(id) => match history.position(id) {
+            Some(index) => history.after(index).cloned().collect(),
+            None => history.iter().cloned().collect(),
+        },
+        ResumePoint::AfterTimestamp(since) => history
+            .iter()
+            .filter(|event| event.timestamp > *since)
+            .cloned()
+            .collect(),
+    };
+
+    (events, !covers(history, point))
+}
+
+/// Sends the events after `point` in `snapshot` to `tx`, skipping those
+/// replayed from memory.
+///
+/// Blocks on file I/O. Returns `true` if the snapshot contained the resume
+/// point and was read to the end.
+fn read_store(
+    snapshot: &Snapshot,
+    point: &ResumePoint,
+    in_memory: &HashSet<String>,
+    tx: &mpsc::Sender<Event>,
+) -> bool {
+    let mut found = matches!(point, ResumePoint::AfterTimestamp(_));
+
+    for event in snapshot.events() {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) => {
+                warn!(error = %err, "Failed to read event store for replay");
+                return false;
+            }
+        };
+
+        let after = match point {
+            ResumePoint::AfterEvent(id) => {
+                // Everything up to and including the resume point is skipped
+                let after = found;
+                found = found || event.id == *id;
+                after
+            }
+            ResumePoint::AfterTimestamp(since) => event.timestamp > *since,
+        };
+        if !after || in_memory.contains(&event.id) {
+            continue;
+        }
+
+        if tx.blocking_send(event).is_err() {
+            // The subscriber went away
+            return false;
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::StoreConfig;
+    use crate::types::{EventPayload, EventType};
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn make_event(n: usize) -> Event {
+        Event::new(
+            "monitor-1".to_string(),
+            EventType::Activity,
+            EventPayload::Activity {
+                session_id: Uuid::new_v4(),
+                project: None,
+            },
+        )
+        .with_id(format!("evt_{n:0>20}"))
+    }
+
+    /// Drains `replay`, returning the replayed event IDs.
+    async fn drain(replay: &mut Replay) -> Vec<String> {
+        let mut ids = Vec::new();
+        while let Some(event) = replay.next().await {
+            ids.push(event.id);
+        }
+        ids
+    }
+
+    /// Appends to the store and broadcasts, mirroring `AppState::publish`.
+    fn ingest(broadcaster: &EventBroadcaster, store: Option<&EventStore>, event: Event) {
+        match store {
+            Some(store) => {
+                let (result, _) = store.append_then(vec![event], |events| {
+                    for event in events {
+                        broadcaster.broadcast(event);
+                    }
+                });
+                result.unwrap();
+            }
+            None => {
+                broadcaster.broadcast(event);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn resumes_after_event_in_memory() {
+        let broadcaster = EventBroadcaster::new();
+        for n in 0..4 {
+            ingest(&broadcaster, None, make_event(n));
+        }
+
+        let point = ResumePoint::AfterEvent(make_event(1).id);
+        let (mut replay, _rx) = subscribe_from(&broadcaster, None, &point);
+
+        assert_eq!(
+            drain(&mut replay).await,
+            vec![make_event(2).id, make_event(3).id]
+        );
+        assert!(!replay.gap());
+    }
+
+    #[tokio::test]
+    async fn resuming_after_latest_event_replays_nothing() {
+        let broadcaster = EventBroadcaster::new();
+        ingest(&broadcaster, None, make_event(0));
+
+        let point = ResumePoint::AfterEvent(make_event(0).id);
+        let (mut replay, _rx) = subscribe_from(&broadcaster, None, &point);
+
+        assert!(drain(&mut replay).await.is_empty());
+        assert!(!replay.gap());
+    }
+
+    #[tokio::test]
+    async fn unknown_event_reports_gap_and_replays_window() {
+        let broadcaster = EventBroadcaster::new();
+        ingest(&broadcaster, None, make_event(0));
+
+        let point = ResumePoint::AfterEvent("evt_unknown".to_string());
+        let (mut replay, _rx) = subscribe_from(&broadcaster, None, &point);
+
+        assert_eq!(drain(&mut replay).await, vec![make_event(0).id]);
+        assert!(replay.gap());
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_store_when_evicted_from_memory() {
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        let broadcaster = EventBroadcaster::new().with_history_capacity(2);
+        for n in 0..5 {
+            ingest(&broadcaster, Some(&store), make_event(n));
+        }
+
+        let point = ResumePoint::AfterEvent(make_event(0).id);
+        let (mut replay, _rx) = subscribe_from(&broadcaster, Some(&store), &point);
+
+        assert_eq!(
+            drain(&mut replay).await,
+            (1..5).map(|n| make_event(n).id).collect::<Vec<_>>()
+        );
+        assert!(!replay.gap());
+    }
+
+    #[tokio::test]
+    async fn replays_only_events_stored_before_subscribing() {
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        let broadcaster = EventBroadcaster::new().with_history_capacity(1);
+        for n in 0..3 {
+            ingest(&broadcaster, Some(&store), make_event(n));
+        }
+
+        let point = ResumePoint::AfterEvent(make_event(0).id);
+        let (mut replay, mut rx) = subscribe_from(&broadcaster, Some(&store), &point);
+        ingest(&broadcaster, Some(&store), make_event(3));
+
+        assert_eq!(
+            drain(&mut replay).await,
+            vec![make_event(1).id, make_event(2).id]
+        );
+        assert!(!replay.gap());
+        assert_eq!(rx.recv().await.unwrap().id, make_event(3).id);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resuming_while_publishing_has_no_duplicates_or_gaps() {
+        const EVENTS: usize = 2000;
+
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        let broadcaster = EventBroadcaster::with_capacity(EVENTS).with_history_capacity(1);
+        ingest(&broadcaster, Some(&store), make_event(0));
+
+        let publisher = std::thread::spawn({
+            let broadcaster = broadcaster.clone();
+            let store = store.clone();
+            move || {
+                for n in 1..EVENTS {
+                    ingest(&broadcaster, Some(&store), make_event(n));
+                }
+            }
+        });
+
+        let point = ResumePoint::AfterEvent(make_event(0).id);
+        let mut resumes = Vec::new();
+        while !publisher.is_finished() && resumes.len() < 50 {
+            resumes.push(subscribe_from(&broadcaster, Some(&store), &point));
+            tokio::time::sleep(std::time::Duration::from_micros(200)).await;
+        }
+        publisher.join().unwrap();
+
+        let expected: Vec<String> = (1..EVENTS).map(|n| make_event(n).id).collect();
+        for (mut replay, mut rx) in resumes {
+            let mut ids = drain(&mut replay).await;
+            while let Ok(event) = rx.try_recv() {
+                ids.push(event.id);
+            }
+            assert_eq!(ids, expected);
+            assert!(!replay.gap());
+        }
+    }
+
+    #[tokio::test]
+    async fn resume_point_missing_from_store_reports_gap() {
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        let broadcaster = EventBroadcaster::new().with_history_capacity(1);
+        for n in 0..3 {
+            ingest(&broadcaster, Some(&store), make_event(n));
+        }
+
+        let point = ResumePoint::AfterEvent("evt_unknown".to_string());
+        let (mut replay, _rx) = subscribe_from(&broadcaster, Some(&store), &point);
+
+        assert_eq!(drain(&mut replay).await, vec![make_event(2).id]);
+        assert!(replay.gap());
+    }
+
+    #[tokio::test]
+    async fn resumes_after_timestamp() {
+        let broadcaster = EventBroadcaster::new();
+        let mut old = make_event(0);
+        old.timestamp = Utc::now() - chrono::Duration::minutes(10);
+        ingest(&broadcaster, None, old);
+        ingest(&broadcaster, None, make_event(1));
+
+        let since = Utc::now() - chrono::Duration::minutes(5);
+        let point = ResumePoint::AfterTimestamp(since);
+        let (mut replay, _rx) = subscribe_from(&broadcaster, None, &point);
+
+        assert_eq!(drain(&mut replay).await, vec![make_event(1).id]);
+        assert!(!replay.gap());
+    }
+
+    #[tokio::test]
+    async fn timestamp_before_truncated_window_reports_gap() {
+        let broadcaster = EventBroadcaster::new().with_history_capacity(1);
+        ingest(&broadcaster, None, make_event(0));
+        ingest(&broadcaster, None, make_event(1));
+
+        let since = Utc::now() - chrono::Duration::hours(1);
+        let (mut replay, _rx) =
+            subscribe_from(&broadcaster, None, &ResumePoint::AfterTimestamp(since));
+
+        assert_eq!(drain(&mut replay).await, vec![make_event(1).id]);
+        assert!(replay.gap());
+    }
+
+    #[tokio::test]
+    async fn live_events_follow_replay_without_duplicates() {
+        let broadcaster = EventBroadcaster::new();
+        ingest(&broadcaster, None, make_event(0));
+        ingest(&broadcaster, None, make_event(1));
+
+        let point = ResumePoint::AfterEvent(make_event(0).id);
+        let (mut replay, mut rx) = subscribe_from(&broadcaster, None, &point);
+        ingest(&broadcaster, None, make_event(2));
+
+        assert_eq!(drain(&mut replay).await, vec![make_event(1).id]);
+        assert_eq!(rx.recv().await.unwrap().id, make_event(2).id);
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -3,15 +3,25 @@
 //! This module provides the HTTP API endpoints:
 //!
 //! - `POST /events` - Ingest events from monitors
+//! - `GET /events` - Query retained events (paginated JSON or NDJSON)
+//! - `GET /sessions` - Snapshot of known sessions
+//! - `GET /stats` - Rolling aggregates over the last hour, day or week
 //! - `GET /ws` - WebSocket subscription endpoint for clients
+//! - `GET /stream` - Server-Sent Events subscription endpoint for clients
 //! - `GET /health` - Health check endpoint
+//! - `GET /metrics` - Prometheus metrics
 //!
 //! # Architecture
 //!
 //! All routes share application state through [`AppState`], which contains:
 //! - Configuration (including auth settings)
 //! - Event broadcaster for distributing events to WebSocket clients
+//! - Subscriber tokens and their scopes
 //! - Rate limiter for protecting against abuse
+//! - Optional persistent event store
+//! - Session registry
+//! - Rolling event aggregates
+//! - Prometheus metrics
 //! - Server start time for uptime reporting
 //!
 //! # Example
@@ -34,40 +44,66 @@
 //! }
 //! ```
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use axum::{
     body::Bytes,
-    extract::{DefaultBodyLimit, Query, State, WebSocketUpgrade},
-    http::{HeaderMap, StatusCode},
+    extract::{ConnectInfo, DefaultBodyLimit, Query, State, WebSocketUpgrade},
+    http::{
+        header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, ORIGIN},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    middleware,
     response::{IntoResponse, Response},
     routing::{get, post},
-    Json, Router,
+    Extension, Json, Router,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use tokio::sync::broadcast::error::RecvError;
 use tokio::time::Instant;
 use tracing::{debug, error, info, trace, warn};
+use vibetea_protocol::signing::{
+    HEADER_NONCE, HEADER_SIGNATURE, HEADER_SOURCE_ID, HEADER_TIMESTAMP,
+};
 
-use crate::auth::{validate_token, verify_signature, AuthError};
-use crate::broadcast::{EventBroadcaster, SubscriberFilter};
+use crate::auth::{check_nonce, check_timestamp, signed_message, AuthError};
+use crate::broadcast::{ClientConnections, EventBroadcaster, SubscriberFilter};
 use crate::config::Config;
-use crate::rate_limit::{RateLimitResult, RateLimiter};
+use crate::cors::OriginPolicy;
+use crate::dedup::DedupCache;
+use crate::filter::{EventTypeSet, FilterError, PatternSet};
+use crate::keys::KeyRegistry;
+use crate::metrics::{self, Gauge, Metrics};
+use crate::nonce::NonceCache;
+use crate::query::{EventQuery, QueryError};
+use crate::rate_limit::{ConnectionLimiter, ConnectionPermit, RateLimitResult, RateLimiter};
+use crate::replay::ResumePoint;
+use crate::sessions::{Session, SessionRegistry};
+use crate::stats::{StatsAggregator, StatsWindow};
+use crate::store::EventStore;
+use crate::tls::{forward_connect_info, TlsConnectInfo};
+use crate::tokens::{Subscriber, TokenRegistry, TokenRejection};
 use crate::types::{Event, EventType};
+use crate::validation::ValidationError;
+use crate::{sse, ws};
 
 // ============================================================================
 // Constants
 // ============================================================================
 
-/// Header name for the source identifier.
-const HEADER_SOURCE_ID: &str = "X-Source-ID";
-
-/// Header name for the Ed25519 signature.
-const HEADER_SIGNATURE: &str = "X-Signature";
-
 /// Header name for rate limit retry delay.
 const HEADER_RETRY_AFTER: &str = "Retry-After";
 
+/// Header name for the next-page cursor on NDJSON query responses.
+const HEADER_NEXT_CURSOR: &str = "X-Next-Cursor";
+
+/// Header name for the SSE resume position sent by reconnecting clients.
+const HEADER_LAST_EVENT_ID: &str = "Last-Event-ID";
+
+/// Content type for newline-delimited JSON responses.
+const CONTENT_TYPE_NDJSON: &str = "application/x-ndjson";
+
 /// Maximum body size for event ingestion (1 MB).
 const MAX_BODY_SIZE: usize = 1024 * 1024;
 
@@ -87,9 +123,42 @@ pub struct AppState {
     /// Event broadcaster for distributing events to WebSocket clients.
     pub broadcaster: EventBroadcaster,
 
+    /// Open WebSocket and SSE client connections.
+    pub clients: ClientConnections,
+
+    /// Monitor public keys, reloadable at runtime.
+    pub keys: KeyRegistry,
+
+    /// Subscriber credentials, reloadable at runtime.
+    pub tokens: TokenRegistry,
+
     /// Rate limiter for protecting against abuse.
     pub rate_limiter: RateLimiter,
 
+    /// Per-IP cap on concurrent WebSocket and SSE connections.
+    pub connection_limiter: ConnectionLimiter,
+
+    /// Browser origins allowed to use the server.
+    pub origins: OriginPolicy,
+
+    /// Nonces of recently accepted signed requests.
+    pub nonces: NonceCache,
+
+    /// IDs of recently accepted events, for dropping retried duplicates.
+    pub dedup: DedupCache,
+
+    /// Persistent event log, if enabled.
+    pub store: Option<EventStore>,
+
+    /// Registry of active sessions, built from ingested events.
+    pub sessions: SessionRegistry,
+
+    /// Rolling aggregates of ingested events.
+    pub stats: StatsAggregator,
+
+    /// Prometheus metrics.
+    pub metrics: Metrics,
+
     /// Server start time for uptime calculation.
     pub start_time: Instant,
 }
@@ -112,10 +181,30 @@ impl AppState {
     /// ```
     #[must_use]
     pub fn new(config: Config) -> Self {
+        let broadcaster = EventBroadcaster::with_capacity(config.channel_capacity)
+            .with_history_capacity(config.history_capacity);
+        let rate_limiter = config.rate_limiter();
+        let connection_limiter = config.connection_limiter();
+        let nonces = NonceCache::new(config.max_clock_skew);
+        let keys = KeyRegistry::new(&config.public_keys);
+        let tokens = TokenRegistry::new(config.subscriber_token.as_deref());
+        let origins = config.origin_policy();
+        let dedup = config.dedup_cache();
         Self {
             config: Arc::new(config),
-            broadcaster: EventBroadcaster::new(),
-            rate_limiter: RateLimiter::default(),
+            broadcaster,
+            clients: ClientConnections::new(),
+            keys,
+            tokens,
+            rate_limiter,
+            connection_limiter,
+            origins,
+            nonces,
+            dedup,
+            store: None,
+            sessions: SessionRegistry::new(),
+            stats: StatsAggregator::new(),
+            metrics: Metrics::new(),
             start_time: Instant::now(),
         }
     }
@@ -129,13 +218,85 @@ impl AppState {
         broadcaster: EventBroadcaster,
         rate_limiter: RateLimiter,
     ) -> Self {
+        let connection_limiter = config.connection_limiter();
+        let nonces = NonceCache::new(config.max_clock_skew);
+        let keys = KeyRegistry::new(&config.public_keys);
+        let tokens = TokenRegistry::new(config.subscriber_token.as_deref());
+        let origins = config.origin_policy();
+        let dedup = config.dedup_cache();
         Self {
             config: Arc::new(config),
             broadcaster,
+            clients: ClientConnections::new(),
+            keys,
+            tokens,
             rate_limiter,
+            connection_limiter,
+            origins,
+            nonces,
+            dedup,
+            store: None,
+            sessions: SessionRegistry::new(),
+            stats: StatsAggregator::new(),
+            metrics: Metrics::new(),
             start_time: Instant::now(),
         }
     }
+
+    /// Attaches a persistent event store (builder pattern).
+    ///
+    /// Every event accepted by `POST /events` is appended to the store
+    /// before it is broadcast.
+    #[must_use]
+    pub fn with_store(mut self, store: EventStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Publishes accepted events to every consumer.
+    ///
+    /// Events are persisted first so history includes everything subscribers
+    /// have seen, then folded into the session registry and aggregates and
+    /// broadcast before the store is unlocked, so a resuming subscriber never
+    /// receives them both from the store and live. Writing to the event log
+    /// is blocking I/O, so it runs on the blocking thread pool. A storage
+    /// failure is logged but does not stop the live feed.
+    pub async fn publish(&self, events: Vec<Event>) {
+        let Some(store) = self.store.clone() else {
+            self.broadcast(events);
+            return;
+        };
+
+        // Broadcast before the store is unlocked, so a resuming subscriber's
+        // store snapshot and live receiver never both see these events
+        let state = self.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            store.append_then(events, |events| state.broadcast(events))
+        })
+        .await;
+        match result {
+            Ok((Ok(()), ())) => {}
+            Ok((Err(err), ())) => error!(error = %err, "Failed to persist events"),
+            Err(err) => error!(error = %err, "Event store task failed, events dropped"),
+        }
+    }
+
+    /// Records and broadcasts accepted events.
+    fn broadcast(&self, events: Vec<Event>) {
+        for event in events {
+            self.metrics
+                .record_accepted(&event.source, event.event_type);
+            trace!(
+                source = %event.source,
+                event_id = %event.id,
+                event_type = ?event.event_type,
+                "Broadcasting event"
+            );
+            self.sessions.record(&event);
+            self.stats.record(&event);
+            self.broadcaster.broadcast(event);
+        }
+    }
 }
 
 impl std::fmt::Debug for AppState {
@@ -143,7 +304,17 @@ impl std::fmt::Debug for AppState {
         f.debug_struct("AppState")
             .field("config", &"<Config>")
             .field("broadcaster", &self.broadcaster)
+            .field("clients", &self.clients.count())
+            .field("keys", &self.keys.len())
+            .field("tokens", &self.tokens.len())
             .field("rate_limiter", &self.rate_limiter)
+            .field("connection_limiter", &self.connection_limiter)
+            .field("nonces", &self.nonces.len())
+            .field("dedup", &self.dedup.len())
+            .field("store", &self.store)
+            .field("sessions", &self.sessions.len())
+            .field("stats", &"<StatsAggregator>")
+            .field("metrics", &"<Metrics>")
             .field("start_time", &self.start_time)
             .finish()
     }
@@ -163,8 +334,12 @@ impl std::fmt::Debug for AppState {
 ///
 /// An axum `Router` with the following routes:
 /// - `POST /events` - Event ingestion endpoint
+/// - `GET /events` - Event query endpoint
+/// - `GET /sessions` - Session registry snapshot
+/// - `GET /stats` - Rolling event aggregates
 /// - `GET /ws` - WebSocket subscription endpoint
 /// - `GET /health` - Health check endpoint
+/// - `GET /metrics` - Prometheus metrics endpoint
 ///
 /// # Example
 ///
@@ -177,18 +352,175 @@ impl std::fmt::Debug for AppState {
 /// let router = create_router(state);
 /// ```
 pub fn create_router(state: AppState) -> Router {
-    Router::new()
-        .route("/events", post(post_events))
+    let cors = state.origins.cors_layer();
+    let router = Router::new()
+        .route("/events", post(post_events).get(get_events))
         .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
+        .route("/sessions", get(get_sessions))
+        .route("/stats", get(get_stats))
         .route("/ws", get(get_ws))
+        .route("/stream", get(get_stream))
         .route("/health", get(get_health))
-        .with_state(state)
+        .route("/metrics", get(get_metrics))
+        .layer(middleware::from_fn(forward_connect_info))
+        .with_state(state);
+
+    match cors {
+        Some(cors) => router.layer(cors),
+        None => router,
+    }
 }
 
 // ============================================================================
 // Error Response Types
 // ============================================================================
 
+/// Reasons a subscriber request is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SubscriberAuthError {
+    /// Auth is enabled but no subscriber token is configured.
+    NotConfigured,
+
+    /// No token was provided.
+    MissingToken,
+
+    /// The provided token does not match.
+    InvalidToken,
+
+    /// The provided token has expired.
+    ExpiredToken,
+
+    /// The token's scope does not cover the requested resource.
+    InsufficientScope,
+}
+
+impl IntoResponse for SubscriberAuthError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            Self::NotConfigured => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ErrorResponse::new("server configuration error"),
+            ),
+            Self::MissingToken => (
+                StatusCode::UNAUTHORIZED,
+                ErrorResponse::new("missing token").with_code("missing_token"),
+            ),
+            Self::InvalidToken => (
+                StatusCode::UNAUTHORIZED,
+                ErrorResponse::new("invalid token").with_code("invalid_token"),
+            ),
+            Self::ExpiredToken => (
+                StatusCode::UNAUTHORIZED,
+                ErrorResponse::new("token expired").with_code("token_expired"),
+            ),
+            Self::InsufficientScope => (
+                StatusCode::FORBIDDEN,
+                ErrorResponse::new("token scope does not allow this request")
+                    .with_code("insufficient_scope"),
+            ),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Checks a subscriber token against the configured credentials and returns
+/// the authenticated subscriber.
+///
+/// Always succeeds with an unrestricted subscriber when `unsafe_no_auth` is
+/// enabled.
+fn authenticate_subscriber(
+    state: &AppState,
+    provided: Option<&str>,
+) -> Result<Subscriber, SubscriberAuthError> {
+    if state.config.unsafe_no_auth {
+        return Ok(Subscriber::unrestricted("anonymous"));
+    }
+
+    if state.tokens.is_empty() {
+        error!("Subscriber token not configured but auth is enabled");
+        return Err(SubscriberAuthError::NotConfigured);
+    }
+
+    let provided_token = match provided {
+        Some(token) if !token.is_empty() => token,
+        _ => {
+            debug!("Missing or empty subscriber token");
+            return Err(SubscriberAuthError::MissingToken);
+        }
+    };
+
+    match state.tokens.authenticate(provided_token, Utc::now()) {
+        Ok(subscriber) => {
+            debug!(subscriber = %subscriber.name, "Subscriber authenticated");
+            Ok(subscriber)
+        }
+        Err(TokenRejection::Invalid) => {
+            debug!("Invalid subscriber token");
+            Err(SubscriberAuthError::InvalidToken)
+        }
+        Err(TokenRejection::Expired) => {
+            debug!("Expired subscriber token");
+            Err(SubscriberAuthError::ExpiredToken)
+        }
+    }
+}
+
+/// Builds a `SubscriberFilter` from optional source, type, project and tool
+/// values, or from a JSON filter object.
+///
+/// Each value uses the syntax described in [`crate::filter`]. The JSON object
+/// cannot be combined with the individual values.
+fn subscriber_filter(
+    source: Option<&str>,
+    event_type: Option<&str>,
+    project: Option<&str>,
+    tool: Option<&str>,
+    filter_json: Option<&str>,
+) -> Result<SubscriberFilter, FilterError> {
+    if let Some(json) = filter_json {
+        if source.is_some() || event_type.is_some() || project.is_some() || tool.is_some() {
+            return Err(FilterError::Conflict);
+        }
+        return Ok(serde_json::from_str(json)?);
+    }
+
+    Ok(vibetea_protocol::filter::SubscriberFilter {
+        source: source
+            .map(PatternSet::parse)
+            .transpose()?
+            .unwrap_or_default(),
+        event_type: event_type
+            .map(EventTypeSet::parse)
+            .transpose()?
+            .unwrap_or_default(),
+        project: project
+            .map(PatternSet::parse)
+            .transpose()?
+            .unwrap_or_default(),
+        tool: tool.map(PatternSet::parse).transpose()?.unwrap_or_default(),
+    }
+    .into())
+}
+
+/// Builds the `400 Bad Request` response for a malformed filter.
+fn invalid_filter(err: &FilterError) -> Response {
+    debug!(error = %err, "Invalid subscriber filter");
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse::new(err.to_string()).with_code("invalid_filter")),
+    )
+        .into_response()
+}
+
+/// Extracts the token from an `Authorization: Bearer <token>` header.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
 /// JSON error response body.
 #[derive(Debug, Serialize)]
 struct ErrorResponse {
@@ -217,53 +549,168 @@ impl ErrorResponse {
 
 /// Request body for event ingestion.
 ///
-/// Accepts either a single event or an array of events.
+/// Accepts either a single event or an array of events. Events are kept as
+/// raw JSON so each one can be deserialized and validated on its own.
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum EventsPayload {
-    Single(Event),
-    Batch(Vec<Event>),
+    Batch(Vec<serde_json::Value>),
+    Single(serde_json::Value),
 }
 
 impl EventsPayload {
-    /// Converts the payload into a vector of events.
-    fn into_events(self) -> Vec<Event> {
+    /// Returns `true` if the body was an array of events.
+    fn is_batch(&self) -> bool {
+        matches!(self, Self::Batch(_))
+    }
+
+    /// Converts the payload into a vector of raw events.
+    fn into_values(self) -> Vec<serde_json::Value> {
         match self {
-            Self::Single(event) => vec![event],
-            Self::Batch(events) => events,
+            Self::Single(value) => vec![value],
+            Self::Batch(values) => values,
         }
     }
 }
 
+/// Returns a header's value if it is present, valid UTF-8 and non-empty.
+fn non_empty_header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+}
+
+/// Builds a 401 response for a rejected ingest request and counts it.
+fn unauthorized(state: &AppState, message: &'static str, code: &'static str) -> Response {
+    state.metrics.record_auth_failure(code);
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ErrorResponse::new(message).with_code(code)),
+    )
+        .into_response()
+}
+
+/// Builds a 429 response for a rate-limited ingest request and counts it.
+fn rate_limited(state: &AppState, source_id: &str, retry_after_secs: u64) -> Response {
+    state.metrics.record_rate_limited(source_id);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(HEADER_RETRY_AFTER, retry_after_secs.to_string())],
+        Json(ErrorResponse::new("rate limit exceeded").with_code("rate_limited")),
+    )
+        .into_response()
+}
+
+/// Verifies a signed ingest request and records its nonce.
+///
+/// The timestamp and nonce are checked before the signature so stale
+/// requests are rejected cheaply; the nonce is only recorded once the
+/// signature proves the request came from `source_id`.
+fn verify_request(
+    state: &AppState,
+    source_id: &str,
+    signature: &str,
+    timestamp: &str,
+    nonce: &str,
+    body: &[u8],
+) -> Result<(), AuthError> {
+    let now = Utc::now();
+    let signed_at = check_timestamp(timestamp, now, state.config.max_clock_skew)?;
+    check_nonce(nonce)?;
+
+    state.keys.verify(
+        source_id,
+        signature,
+        &signed_message(timestamp, nonce, body),
+    )?;
+
+    if !state.nonces.insert(source_id, nonce, signed_at, now) {
+        return Err(AuthError::ReplayedNonce);
+    }
+    Ok(())
+}
+
 /// POST /events - Ingest events from monitors.
 ///
 /// # Authentication
 ///
 /// Unless `unsafe_no_auth` is enabled, requests must include:
 /// - `X-Source-ID` header: Monitor identifier
-/// - `X-Signature` header: Ed25519 signature of the request body
+/// - `X-Timestamp` header: Unix time in seconds when the request was signed
+/// - `X-Nonce` header: Random value unique to the request
+/// - `X-Signature` header: Ed25519 signature of
+///   [`signed_message`](crate::auth::signed_message)
+///
+/// Requests whose timestamp is outside `max_clock_skew`, or whose nonce has
+/// already been used by the same source, are rejected.
+///
+/// When a TLS client CA is configured, the request must also arrive over a
+/// TLS connection on which the monitor presented a client certificate signed
+/// by that CA (see [`crate::tls`]).
 ///
 /// # Rate Limiting
 ///
-/// Requests are rate-limited per source. If the limit is exceeded,
-/// returns 429 with a `Retry-After` header.
+/// Requests are rate-limited per source, and the events they carry are
+/// counted against a global limit shared by all sources. If either limit is
+/// exceeded, returns 429 with a `Retry-After` header.
 ///
 /// # Request Body
 ///
 /// Accepts either a single event or an array of events as JSON.
 ///
+/// # Validation
+///
+/// Each event must use a supported `schemaVersion` (see [`crate::types`]),
+/// deserialize and pass [`crate::validation`]: its payload
+/// must match its `type`, its ID must be `evt_` + 20 alphanumeric
+/// characters, its timestamp must be within `max_clock_skew` of the future
+/// and `max_event_age` of the past, and its strings must not be oversized.
+///
+/// A single invalid event is rejected with `400` and the error code. In a
+/// batch, invalid events, and events the sender may not submit (see below),
+/// are skipped and the rest are accepted; the response is then
+/// `207 Multi-Status` and lists each rejected event in `errors`.
+///
+/// # Deduplication
+///
+/// Events whose ID was already accepted from the same source within
+/// `dedup_window` are dropped, so a retried batch is not broadcast twice
+/// (see [`crate::dedup`]). The response reports both counts as
+/// [`IngestResponse`], e.g. `{"accepted": 2, "duplicates": 1, "rejected": 0}`.
+///
+/// # Relayed Events
+///
+/// Each event's `source` must match `X-Source-ID`, unless the event was
+/// relayed by another hub (see [`crate::relay`]): then the sender must be
+/// listed in `relay_sources` and be the event's last hop. Events failing
+/// these checks are rejected like invalid events. Events that have already
+/// passed through this hub (`relay_id`) are dropped and counted as
+/// duplicates.
+///
 /// # Responses
 ///
 /// - `202 Accepted` - Events accepted and queued for broadcast
-/// - `400 Bad Request` - Invalid event format
+/// - `207 Multi-Status` - Some events in a batch were rejected
+/// - `400 Bad Request` - Invalid body, or an invalid single event or one the
+///   sender may not submit
 /// - `401 Unauthorized` - Authentication failed
 /// - `429 Too Many Requests` - Rate limit exceeded
-async fn post_events(State(state): State<AppState>, headers: HeaderMap, body: Bytes) -> Response {
+async fn post_events(
+    State(state): State<AppState>,
+    tls: Option<Extension<ConnectInfo<TlsConnectInfo>>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let started = Instant::now();
+    state.metrics.observe_body_size(body.len());
+
     // Extract required headers
     let source_id = match headers.get(HEADER_SOURCE_ID).and_then(|v| v.to_str().ok()) {
         Some(id) if !id.is_empty() => id,
         _ => {
             debug!("Missing or empty X-Source-ID header");
+            state.metrics.record_auth_failure("missing_source");
             return (
                 StatusCode::UNAUTHORIZED,
                 Json(ErrorResponse::new("missing X-Source-ID header").with_code("missing_source")),
@@ -274,23 +721,31 @@ async fn post_events(State(state): State<AppState>, headers: HeaderMap, body: By
 
     // Authenticate if required
     if !state.config.unsafe_no_auth {
-        let signature = match headers.get(HEADER_SIGNATURE).and_then(|v| v.to_str().ok()) {
-            Some(sig) if !sig.is_empty() => sig,
-            _ => {
-                debug!(source = %source_id, "Missing or empty X-Signature header");
-                return (
-                    StatusCode::UNAUTHORIZED,
-                    Json(
-                        ErrorResponse::new("missing X-Signature header")
-                            .with_code("missing_signature"),
-                    ),
-                )
-                    .into_response();
-            }
+        let client_certificate =
+            tls.is_some_and(|Extension(ConnectInfo(info))| info.client_certificate);
+        if state.config.tls_client_ca.is_some() && !client_certificate {
+            debug!(source = %source_id, "Missing client certificate");
+            return unauthorized(
+                &state,
+                "client certificate required",
+                "client_certificate_required",
+            );
+        }
+
+        let Some(signature) = non_empty_header(&headers, HEADER_SIGNATURE) else {
+            debug!(source = %source_id, "Missing or empty X-Signature header");
+            return unauthorized(&state, "missing X-Signature header", "missing_signature");
+        };
+        let Some(timestamp) = non_empty_header(&headers, HEADER_TIMESTAMP) else {
+            debug!(source = %source_id, "Missing or empty X-Timestamp header");
+            return unauthorized(&state, "missing X-Timestamp header", "missing_timestamp");
+        };
+        let Some(nonce) = non_empty_header(&headers, HEADER_NONCE) else {
+            debug!(source = %source_id, "Missing or empty X-Nonce header");
+            return unauthorized(&state, "missing X-Nonce header", "missing_nonce");
         };
 
-        // Verify signature
-        if let Err(err) = verify_signature(source_id, signature, &body, &state.config.public_keys) {
+        if let Err(err) = verify_request(&state, source_id, signature, timestamp, nonce, &body) {
             warn!(source = %source_id, error = %err, "Signature verification failed");
             let (error_msg, error_code) = match err {
                 AuthError::UnknownSource(_) => ("unknown source", "unknown_source"),
@@ -298,12 +753,16 @@ async fn post_events(State(state): State<AppState>, headers: HeaderMap, body: By
                 AuthError::InvalidBase64(_) => ("invalid signature encoding", "invalid_encoding"),
                 AuthError::InvalidPublicKey => ("server configuration error", "server_error"),
                 AuthError::InvalidToken => ("invalid token", "invalid_token"),
+                AuthError::InvalidTimestamp => ("invalid timestamp", "invalid_timestamp"),
+                AuthError::StaleTimestamp => {
+                    ("timestamp outside allowed clock skew", "stale_timestamp")
+                }
+                AuthError::InvalidNonce => ("invalid nonce", "invalid_nonce"),
+                AuthError::ReplayedNonce => ("request already processed", "replayed_request"),
+                AuthError::RevokedKey => ("key revoked", "revoked_key"),
+                AuthError::ExpiredKey => ("key expired", "expired_key"),
             };
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse::new(error_msg).with_code(error_code)),
-            )
-                .into_response();
+            return unauthorized(&state, error_msg, error_code);
         }
     }
 
@@ -316,12 +775,7 @@ async fn post_events(State(state): State<AppState>, headers: HeaderMap, body: By
                 retry_after = retry_after_secs,
                 "Rate limit exceeded"
             );
-            return (
-                StatusCode::TOO_MANY_REQUESTS,
-                [(HEADER_RETRY_AFTER, retry_after_secs.to_string())],
-                Json(ErrorResponse::new("rate limit exceeded").with_code("rate_limited")),
-            )
-                .into_response();
+            return rate_limited(&state, source_id, retry_after_secs);
         }
     }
 
@@ -341,284 +795,945 @@ async fn post_events(State(state): State<AppState>, headers: HeaderMap, body: By
         }
     };
 
-    let events = events_payload.into_events();
-    let event_count = events.len();
+    let is_batch = events_payload.is_batch();
+    let values = events_payload.into_values();
+    let event_count = values.len();
+
+    // The global limit is counted in events, so it is checked once the body
+    // has been parsed
+    if let RateLimitResult::Limited { retry_after_secs } = state
+        .rate_limiter
+        .check_global_limit(source_id, event_count)
+        .await
+    {
+        info!(
+            source = %source_id,
+            events = event_count,
+            retry_after = retry_after_secs,
+            "Global rate limit exceeded"
+        );
+        return rate_limited(&state, source_id, retry_after_secs);
+    }
+
+    // Deserialize each event on its own so one bad event does not sink the batch
+    let now = Utc::now();
+    let validator = state.config.validator();
+    let mut rejected = Vec::new();
+    let mut parsed = Vec::with_capacity(event_count);
+    for (index, value) in values.into_iter().enumerate() {
+        let id = value
+            .get("id")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string);
+        let result = validator.check_schema(&value).and_then(|()| {
+            serde_json::from_value::<Event>(value)
+                .map_err(|err| ValidationError::InvalidFormat(err.to_string()))
+        });
+        match result {
+            Ok(event) => parsed.push((index, event)),
+            Err(err) => rejected.push((index, id, err)),
+        }
+    }
 
-    // Validate that all events have matching source
-    for event in &events {
-        if event.source != source_id {
+    let mut events = Vec::with_capacity(parsed.len());
+    for (index, event) in parsed {
+        if let Err(err) = check_event_origin(&state.config, source_id, &event) {
             warn!(
                 authenticated_source = %source_id,
                 event_source = %event.source,
                 event_id = %event.id,
-                "Event source mismatch"
+                hops = ?event.hops,
+                reason = err.code(),
+                "Event origin rejected"
             );
+            rejected.push((index, Some(event.id), err));
+            continue;
+        }
+        match validator.validate(&event, now) {
+            Ok(()) => events.push(event),
+            Err(err) => rejected.push((index, Some(event.id), err)),
+        }
+    }
+
+    rejected.sort_by_key(|(index, _, _)| *index);
+    let mut errors = Vec::with_capacity(rejected.len());
+    for (index, id, err) in rejected {
+        debug!(
+            source = %source_id,
+            index = index,
+            event_id = id.as_deref().unwrap_or_default(),
+            error = %err,
+            "Rejected invalid event"
+        );
+        state.metrics.record_invalid(source_id, err.code());
+        if !is_batch {
             return (
                 StatusCode::BAD_REQUEST,
-                Json(
-                    ErrorResponse::new("event source does not match authenticated source")
-                        .with_code("source_mismatch"),
-                ),
+                Json(ErrorResponse::new(err.to_string()).with_code(err.code())),
             )
                 .into_response();
         }
+        errors.push(EventError {
+            index,
+            id,
+            error: err.to_string(),
+            code: err.code().to_string(),
+        });
     }
 
-    // Broadcast events
-    for event in events {
-        trace!(
+    // Drop events that already passed through this hub, such as in a cycle
+    // of relays
+    let before = events.len();
+    if let Some(hub_id) = &state.config.relay_id {
+        events.retain(|event| !event.relayed_by(hub_id));
+    }
+    let looped = before - events.len();
+
+    // Drop events already accepted, such as from a retried batch
+    let (events, duplicates) = state.dedup.retain_new(events, now);
+    let duplicates = duplicates + looped;
+    let accepted = events.len();
+    if duplicates > 0 {
+        debug!(
             source = %source_id,
-            event_id = %event.id,
-            event_type = ?event.event_type,
-            "Broadcasting event"
+            duplicates = duplicates,
+            "Dropped duplicate events"
         );
-        state.broadcaster.broadcast(event);
+        state.metrics.record_duplicates(source_id, duplicates);
     }
 
+    state.publish(events).await;
+    state.metrics.observe_ingest_duration(started.elapsed());
+
     info!(
         source = %source_id,
         event_count = event_count,
+        accepted = accepted,
+        duplicates = duplicates,
+        rejected = errors.len(),
         "Events accepted and broadcast"
     );
 
-    StatusCode::ACCEPTED.into_response()
+    let status = if errors.is_empty() {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+    (
+        status,
+        Json(IngestResponse {
+            accepted,
+            duplicates,
+            rejected: errors.len(),
+            errors,
+        }),
+    )
+        .into_response()
+}
+
+/// Checks that the authenticated `source_id` may submit `event`.
+///
+/// Monitors may only submit their own events. Hubs listed in
+/// `relay_sources` may also submit events from other sources, provided they
+/// recorded themselves as the event's last hop.
+fn check_event_origin(
+    config: &Config,
+    source_id: &str,
+    event: &Event,
+) -> Result<(), ValidationError> {
+    let Some(last_hop) = event.hops.last() else {
+        if event.source == source_id {
+            return Ok(());
+        }
+        return Err(ValidationError::SourceMismatch);
+    };
+    if !config.relay_sources.iter().any(|hub| hub == source_id) {
+        return Err(ValidationError::RelayNotAllowed);
+    }
+    if last_hop != source_id {
+        return Err(ValidationError::HopMismatch);
+    }
+    Ok(())
+}
+
+/// Response body for `POST /events`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestResponse {
+    /// Events accepted and broadcast.
+    pub accepted: usize,
+
+    /// Events dropped because an event with the same ID was already
+    /// accepted from the same source.
+    pub duplicates: usize,
+
+    /// Events rejected by validation, or because the sender may not submit
+    /// them.
+    #[serde(default)]
+    pub rejected: usize,
+
+    /// Why each rejected event was refused, in request order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<EventError>,
+}
+
+/// An event rejected from a batch, as reported in [`IngestResponse`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventError {
+    /// Position of the event in the request array, starting at 0.
+    pub index: usize,
+
+    /// The event's ID, if it had one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Human-readable description of the problem.
+    pub error: String,
+
+    /// Machine-readable error code, such as `type_mismatch`.
+    pub code: String,
 }
 
 // ============================================================================
-// GET /ws - WebSocket Subscription
+// GET /events - Event Query
 // ============================================================================
 
-/// Query parameters for WebSocket subscription.
-#[derive(Debug, Deserialize)]
-pub struct WsQueryParams {
-    /// Authentication token (required unless unsafe_no_auth is enabled).
+/// Output format for `GET /events`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseFormat {
+    /// A JSON object with `events` and `next_cursor`.
+    #[default]
+    Json,
+
+    /// One JSON event per line, with the next cursor in `X-Next-Cursor`.
+    Ndjson,
+}
+
+/// Query parameters for `GET /events`.
+#[derive(Debug, Default, Deserialize)]
+pub struct EventsQueryParams {
+    /// Authentication token (may also be sent as a bearer token).
     pub token: Option<String>,
 
-    /// Filter events by source ID.
+    /// Filter events by source ID (comma-separated, `!` excludes, `*` globs).
     pub source: Option<String>,
 
-    /// Filter events by type.
+    /// Filter events by type (comma-separated, `!` excludes).
     #[serde(rename = "type")]
-    pub event_type: Option<EventType>,
+    pub event_type: Option<String>,
 
-    /// Filter events by project name.
+    /// Filter events by project name (comma-separated, `!` excludes, `*` globs).
     pub project: Option<String>,
-}
 
-impl WsQueryParams {
-    /// Builds a `SubscriberFilter` from the query parameters.
-    fn to_filter(&self) -> SubscriberFilter {
-        let mut filter = SubscriberFilter::new();
+    /// Filter tool events by tool name (comma-separated, `!` excludes, `*` globs).
+    pub tool: Option<String>,
 
-        if let Some(ref source) = self.source {
-            filter = filter.with_source(source.clone());
-        }
+    /// JSON filter object, instead of the individual filter parameters.
+    pub filter: Option<String>,
 
-        if let Some(event_type) = self.event_type {
-            filter = filter.with_event_type(event_type);
-        }
+    /// Only include events at or after this timestamp.
+    pub from: Option<DateTime<Utc>>,
+
+    /// Only include events before this timestamp.
+    pub to: Option<DateTime<Utc>>,
+
+    /// Continue after this event ID (the previous page's `next_cursor`).
+    pub cursor: Option<String>,
 
-        if let Some(ref project) = self.project {
-            filter = filter.with_project(project.clone());
+    /// Maximum number of events to return.
+    pub limit: Option<usize>,
+
+    /// Output format. Defaults to JSON unless the `Accept` header asks for NDJSON.
+    pub format: Option<ResponseFormat>,
+}
+
+impl EventsQueryParams {
+    /// Builds an `EventQuery` from the query parameters, limited to the
+    /// subscriber's scope.
+    fn to_query(&self, subscriber: Subscriber) -> Result<EventQuery, FilterError> {
+        let filter = subscriber_filter(
+            self.source.as_deref(),
+            self.event_type.as_deref(),
+            self.project.as_deref(),
+            self.tool.as_deref(),
+            self.filter.as_deref(),
+        )?
+        .with_scope(subscriber.scope);
+
+        let mut query = EventQuery::new().with_filter(filter);
+        if let Some(from) = self.from {
+            query = query.with_from(from);
+        }
+        if let Some(to) = self.to {
+            query = query.with_to(to);
         }
+        if let Some(ref cursor) = self.cursor {
+            query = query.with_cursor(cursor.clone());
+        }
+        if let Some(limit) = self.limit {
+            query = query.with_limit(limit);
+        }
+        Ok(query)
+    }
 
-        filter
+    /// Resolves the output format from the `format` parameter or `Accept` header.
+    fn response_format(&self, headers: &HeaderMap) -> ResponseFormat {
+        self.format.unwrap_or_else(|| {
+            let accepts_ndjson = headers
+                .get(ACCEPT)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|accept| accept.contains(CONTENT_TYPE_NDJSON));
+            if accepts_ndjson {
+                ResponseFormat::Ndjson
+            } else {
+                ResponseFormat::Json
+            }
+        })
     }
 }
 
-/// GET /ws - WebSocket subscription endpoint.
+/// Response body for `GET /events` in JSON format.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventsResponse {
+    /// Matching events, oldest first.
+    pub events: Vec<Event>,
+
+    /// Cursor for the next page, or `null` if this is the last page.
+    pub next_cursor: Option<String>,
+}
+
+/// GET /events - Query retained events.
+///
+/// Serves events from the persistent event log when one is configured, and
+/// otherwise from the in-memory history window.
 ///
 /// # Authentication
 ///
-/// Unless `unsafe_no_auth` is enabled, the `token` query parameter is required
-/// and must match the configured subscriber token.
+/// Unless `unsafe_no_auth` is enabled, the subscriber token is required,
+/// either as the `token` query parameter or as an `Authorization: Bearer`
+/// header.
 ///
 /// # Query Parameters
 ///
-/// - `token` - Authentication token (required unless unsafe_no_auth)
-/// - `source` - Filter events by source ID
-/// - `type` - Filter events by type (session, activity, tool, agent, summary, error)
-/// - `project` - Filter events by project name
-///
-/// # WebSocket Protocol
-///
-/// Once connected, the server sends JSON-encoded events as text messages.
-/// Events are filtered according to the provided query parameters.
+/// - `source`, `type`, `project`, `tool`, `filter` - Filters, as for `GET /ws`
+/// - `from` - Only events at or after this RFC 3339 timestamp
+/// - `to` - Only events before this RFC 3339 timestamp
+/// - `limit` - Page size (default 100, maximum 1000)
+/// - `cursor` - Continue after this event ID
+/// - `format` - `json` (default) or `ndjson`
 ///
 /// # Responses
 ///
-/// - `101 Switching Protocols` - WebSocket upgrade successful
+/// - `200 OK` - A page of events
+/// - `400 Bad Request` - Unknown cursor or malformed filter
 /// - `401 Unauthorized` - Invalid or missing token
-async fn get_ws(
+/// - `500 Internal Server Error` - The event log could not be read
+async fn get_events(
     State(state): State<AppState>,
-    Query(params): Query<WsQueryParams>,
-    ws: WebSocketUpgrade,
+    Query(params): Query<EventsQueryParams>,
+    headers: HeaderMap,
 ) -> Response {
-    // Authenticate if required
-    if !state.config.unsafe_no_auth {
-        let expected_token = match &state.config.subscriber_token {
-            Some(token) => token,
-            None => {
-                error!("Subscriber token not configured but auth is enabled");
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse::new("server configuration error")),
-                )
-                    .into_response();
-            }
-        };
-
-        let provided_token = match &params.token {
-            Some(token) if !token.is_empty() => token,
-            _ => {
-                debug!("Missing or empty token in WebSocket request");
-                return (
-                    StatusCode::UNAUTHORIZED,
-                    Json(ErrorResponse::new("missing token").with_code("missing_token")),
-                )
-                    .into_response();
-            }
-        };
+    let token = params.token.as_deref().or_else(|| bearer_token(&headers));
+    let subscriber = match authenticate_subscriber(&state, token) {
+        Ok(subscriber) => subscriber,
+        Err(err) => return err.into_response(),
+    };
 
-        if let Err(_err) = validate_token(provided_token, expected_token) {
-            debug!("Invalid token in WebSocket request");
+    let query = match params.to_query(subscriber) {
+        Ok(query) => query,
+        Err(err) => return invalid_filter(&err),
+    };
+    let format = params.response_format(&headers);
+    debug!(query = ?query, format = ?format, "Querying events");
+
+    // Reading the event log is blocking I/O
+    let result =
+        tokio::task::spawn_blocking(move || query.run(&state.broadcaster, state.store.as_ref()))
+            .await;
+
+    let page = match result {
+        Ok(Ok(page)) => page,
+        Ok(Err(QueryError::UnknownCursor(cursor))) => {
+            debug!(cursor = %cursor, "Unknown query cursor");
             return (
-                StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse::new("invalid token").with_code("invalid_token")),
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ErrorResponse::new("cursor is not a retained event matching the query")
+                        .with_code("invalid_cursor"),
+                ),
             )
                 .into_response();
         }
-    }
-
-    let filter = params.to_filter();
-    info!(
-        filter = ?filter,
-        "WebSocket client connecting"
-    );
-
-    // Upgrade to WebSocket
-    ws.on_upgrade(move |socket| handle_websocket(socket, state.broadcaster, filter))
-}
-
-/// Handles an established WebSocket connection.
-///
-/// Subscribes to the event broadcaster and forwards matching events to the client.
-async fn handle_websocket(
-    socket: axum::extract::ws::WebSocket,
-    broadcaster: EventBroadcaster,
-    filter: SubscriberFilter,
-) {
-    use axum::extract::ws::Message;
-    use futures_util::{SinkExt, StreamExt};
-
-    let (mut sender, mut receiver) = socket.split();
-    let mut event_rx = broadcaster.subscribe();
-
-    info!("WebSocket client connected");
-
-    // Spawn a task to forward events to the client
-    let forward_task = tokio::spawn(async move {
-        loop {
-            match event_rx.recv().await {
-                Ok(event) => {
-                    // Check if event matches filter
-                    if !filter.matches(&event) {
-                        trace!(event_id = %event.id, "Event filtered out");
-                        continue;
-                    }
+        Ok(Err(err)) => {
+            error!(error = %err, "Failed to query events");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new("failed to read events").with_code("server_error")),
+            )
+                .into_response();
+        }
+        Err(err) => {
+            error!(error = %err, "Event query task failed");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new("failed to read events").with_code("server_error")),
+            )
+                .into_response();
+        }
+    };
 
-                    // Serialize and send event
-                    match serde_json::to_string(&event) {
-                        Ok(json) => {
-                            trace!(event_id = %event.id, "Sending event to WebSocket client");
-                            if let Err(err) = sender.send(Message::Text(json.into())).await {
-                                debug!(error = %err, "Failed to send event to WebSocket client");
-                                break;
-                            }
-                        }
-                        Err(err) => {
-                            error!(error = %err, "Failed to serialize event");
-                        }
+    match format {
+        ResponseFormat::Json => Json(EventsResponse {
+            events: page.events,
+            next_cursor: page.next_cursor,
+        })
+        .into_response(),
+        ResponseFormat::Ndjson => {
+            let mut body = String::new();
+            for event in &page.events {
+                match serde_json::to_string(event) {
+                    Ok(line) => {
+                        body.push_str(&line);
+                        body.push('\n');
                     }
+                    Err(err) => error!(error = %err, "Failed to serialize event"),
                 }
-                Err(RecvError::Lagged(count)) => {
-                    warn!(skipped = count, "WebSocket client lagged, skipped events");
-                }
-                Err(RecvError::Closed) => {
-                    debug!("Event broadcaster closed");
-                    break;
-                }
-            }
-        }
-    });
-
-    // Wait for client to disconnect
-    while let Some(msg) = receiver.next().await {
-        match msg {
-            Ok(Message::Close(_)) => {
-                debug!("WebSocket client sent close frame");
-                break;
-            }
-            Ok(Message::Ping(data)) => {
-                // axum handles pong automatically
-                trace!(data_len = data.len(), "Received ping");
-            }
-            Ok(_) => {
-                // Ignore other messages from client
             }
-            Err(err) => {
-                debug!(error = %err, "WebSocket error");
-                break;
+
+            let mut response = ([(CONTENT_TYPE, CONTENT_TYPE_NDJSON)], body).into_response();
+            if let Some(cursor) = page
+                .next_cursor
+                .and_then(|cursor| HeaderValue::from_str(&cursor).ok())
+            {
+                response.headers_mut().insert(HEADER_NEXT_CURSOR, cursor);
             }
+            response
         }
     }
-
-    // Abort the forwarding task
-    forward_task.abort();
-    info!("WebSocket client disconnected");
 }
 
 // ============================================================================
-// GET /health - Health Check
+// GET /sessions - Session Registry
 // ============================================================================
 
-/// Response body for health check endpoint.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct HealthResponse {
-    /// Server status (always "ok" if responding).
-    pub status: String,
-
-    /// Number of active WebSocket connections.
-    pub connections: usize,
+/// Query parameters for `GET /sessions`.
+#[derive(Debug, Default, Deserialize)]
+pub struct SessionsQueryParams {
+    /// Authentication token (may also be sent as a bearer token).
+    pub token: Option<String>,
+}
 
-    /// Server uptime in seconds.
-    pub uptime_seconds: u64,
+/// Response body for `GET /sessions`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionsResponse {
+    /// Known sessions, most recently active first.
+    pub sessions: Vec<Session>,
 }
 
-/// GET /health - Health check endpoint.
+/// GET /sessions - Snapshot of known sessions.
 ///
-/// Returns server health status and statistics.
-/// No authentication required.
+/// Sessions are derived from ingested `session`, `activity`, `tool` and
+/// `summary` events. See [`crate::sessions`] for the lifecycle rules.
+/// Scoped tokens only see sessions from sources and projects in their scope.
 ///
-/// # Response
+/// # Authentication
 ///
-/// ```json
-/// {
-///   "status": "ok",
-///   "connections": 42,
-///   "uptime_seconds": 3600
-/// }
+/// Unless `unsafe_no_auth` is enabled, the subscriber token is required,
+/// either as the `token` query parameter or as an `Authorization: Bearer`
+/// header.
+///
+/// # Responses
+///
+/// - `200 OK` - Session snapshot
+/// - `401 Unauthorized` - Invalid or missing token
+async fn get_sessions(
+    State(state): State<AppState>,
+    Query(params): Query<SessionsQueryParams>,
+    headers: HeaderMap,
+) -> Response {
+    let token = params.token.as_deref().or_else(|| bearer_token(&headers));
+    let subscriber = match authenticate_subscriber(&state, token) {
+        Ok(subscriber) => subscriber,
+        Err(err) => return err.into_response(),
+    };
+
+    let mut sessions = state.sessions.snapshot(Utc::now());
+    sessions.retain(|session| subscriber.scope.allows_session(session));
+    Json(SessionsResponse { sessions }).into_response()
+}
+
+// ============================================================================
+// GET /stats - Rolling Aggregates
+// ============================================================================
+
+/// Query parameters for `GET /stats`.
+#[derive(Debug, Default, Deserialize)]
+pub struct StatsQueryParams {
+    /// Authentication token (may also be sent as a bearer token).
+    pub token: Option<String>,
+
+    /// Time span to aggregate over: `1h` (default), `24h` or `7d`.
+    #[serde(default)]
+    pub window: StatsWindow,
+}
+
+/// GET /stats - Rolling aggregates of ingested events.
+///
+/// Returns events by type, completed tool invocations by tool, tokens used
+/// per model per source and the number of active sessions over the
+/// requested window. See [`crate::stats`] for how they are computed.
+///
+/// # Authentication
+///
+/// Unless `unsafe_no_auth` is enabled, the subscriber token is required,
+/// either as the `token` query parameter or as an `Authorization: Bearer`
+/// header.
+///
+/// # Responses
+///
+/// - `200 OK` - Aggregates for the window
+/// - `400 Bad Request` - Unknown window
+/// - `401 Unauthorized` - Invalid, expired or missing token
+/// - `403 Forbidden` - The token is scoped
+async fn get_stats(
+    State(state): State<AppState>,
+    Query(params): Query<StatsQueryParams>,
+    headers: HeaderMap,
+) -> Response {
+    let token = params.token.as_deref().or_else(|| bearer_token(&headers));
+    match authenticate_subscriber(&state, token) {
+        // Aggregates cover every source, so scoped tokens may not read them
+        Ok(subscriber) if !subscriber.scope.is_unrestricted() => {
+            return SubscriberAuthError::InsufficientScope.into_response();
+        }
+        Ok(_) => {}
+        Err(err) => return err.into_response(),
+    }
+
+    Json(state.stats.snapshot(params.window, Utc::now())).into_response()
+}
+
+// ============================================================================
+// GET /ws - WebSocket Subscription
+// ============================================================================
+
+/// Query parameters for WebSocket and SSE subscriptions.
+#[derive(Debug, Deserialize)]
+pub struct WsQueryParams {
+    /// Authentication token (required unless unsafe_no_auth is enabled).
+    pub token: Option<String>,
+
+    /// Filter events by source ID (comma-separated, `!` excludes, `*` globs).
+    pub source: Option<String>,
+
+    /// Filter events by type (comma-separated, `!` excludes).
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+
+    /// Filter events by project name (comma-separated, `!` excludes, `*` globs).
+    pub project: Option<String>,
+
+    /// Filter tool events by tool name (comma-separated, `!` excludes, `*` globs).
+    pub tool: Option<String>,
+
+    /// JSON filter object, instead of the individual filter parameters.
+    pub filter: Option<String>,
+
+    /// Resume after the event with this ID.
+    pub since: Option<String>,
+
+    /// Resume after this timestamp (ignored if `since` is set).
+    pub since_ts: Option<DateTime<Utc>>,
+}
+
+impl WsQueryParams {
+    /// Returns the point to resume from, if the client asked to resume.
+    fn resume_point(&self) -> Option<ResumePoint> {
+        ws::resume_point(self.since.as_deref(), self.since_ts)
+    }
+
+    /// Builds a `SubscriberFilter` from the query parameters.
+    fn to_filter(&self) -> Result<SubscriberFilter, FilterError> {
+        subscriber_filter(
+            self.source.as_deref(),
+            self.event_type.as_deref(),
+            self.project.as_deref(),
+            self.tool.as_deref(),
+            self.filter.as_deref(),
+        )
+    }
+}
+
+/// GET /ws - WebSocket subscription endpoint.
+///
+/// # Authentication
+///
+/// Unless `unsafe_no_auth` is enabled, the `token` query parameter is required
+/// and must match a configured subscriber token. The token's scope is ANDed
+/// into the requested filter.
+///
+/// # Query Parameters
+///
+/// - `token` - Authentication token (required unless unsafe_no_auth)
+/// - `source` - Filter events by source ID
+/// - `type` - Filter events by type (session, activity, tool, agent, summary, error, ...)
+/// - `project` - Filter events by project name
+/// - `tool` - Filter tool events by tool name
+/// - `filter` - JSON filter object, instead of the four parameters above
+///
+/// Each filter parameter takes comma-separated values, and a value prefixed
+/// with `!` is excluded. Source, project and tool values may use `*` globs.
+/// See [`crate::filter`] for details. For example,
+/// `type=tool,session&project=web,api&tool=!Read`.
+/// - `since` - Resume after this event ID
+/// - `since_ts` - Resume after this RFC 3339 timestamp
+///
+/// # WebSocket Protocol
+///
+/// Once connected, the server sends JSON-encoded events as text messages.
+/// Events are filtered according to the provided query parameters. When
+/// resuming, missed events are replayed first, followed by a `resumed`
+/// control message. See [`crate::ws`] for the control protocol.
+///
+/// # Responses
+///
+/// - `101 Switching Protocols` - WebSocket upgrade successful
+/// - `400 Bad Request` - Malformed filter
+/// - `401 Unauthorized` - Invalid or missing token
+/// - `403 Forbidden` - The page's `Origin` is not allowed (see [`crate::cors`])
+/// - `429 Too Many Requests` - Client IP already has the maximum number of connections
+async fn get_ws(
+    State(state): State<AppState>,
+    Query(params): Query<WsQueryParams>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if let Err(err) = check_origin(&state, &headers) {
+        return err.into_response();
+    }
+
+    let subscriber = match authenticate_subscriber(&state, params.token.as_deref()) {
+        Ok(subscriber) => subscriber,
+        Err(err) => return err.into_response(),
+    };
+
+    let filter = match params.to_filter() {
+        Ok(filter) => filter.with_scope(subscriber.scope),
+        Err(err) => return invalid_filter(&err),
+    };
+
+    let permit = match acquire_connection(&state, connect_info) {
+        Ok(permit) => permit,
+        Err(err) => return err.into_response(),
+    };
+
+    info!(
+        subscriber = %subscriber.name,
+        filter = ?filter,
+        "WebSocket client connecting"
+    );
+
+    let resume = params.resume_point();
+
+    // Upgrade to WebSocket
+    ws.on_upgrade(move |socket| async move {
+        // Hold the connection slot until the socket closes
+        let _permit = permit;
+        let _connection = state.clients.connect();
+        ws::handle_websocket(socket, state, filter, resume).await;
+    })
+}
+
+/// The request came from a browser page whose origin is not allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OriginNotAllowed;
+
+impl IntoResponse for OriginNotAllowed {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse::new("origin not allowed").with_code("origin_not_allowed")),
+        )
+            .into_response()
+    }
+}
+
+/// Rejects subscriptions opened by browser pages on origins that are not
+/// allowed.
+///
+/// Browsers do not apply CORS to WebSocket upgrades, so the `Origin` header
+/// is the only way to stop another site's page from opening a socket.
+fn check_origin(state: &AppState, headers: &HeaderMap) -> Result<(), OriginNotAllowed> {
+    let origin = headers.get(ORIGIN);
+    if state.origins.allows(origin) {
+        Ok(())
+    } else {
+        warn!(origin = ?origin, "Rejected subscription from disallowed origin");
+        Err(OriginNotAllowed)
+    }
+}
+
+/// The client's IP address already holds the maximum number of connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TooManyConnections;
+
+impl IntoResponse for TooManyConnections {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ErrorResponse::new("too many connections").with_code("too_many_connections")),
+        )
+            .into_response()
+    }
+}
+
+/// Takes a connection slot for the client's IP address.
+///
+/// The peer address is only known when served with connect info; without it
+/// there is nothing to key the cap on, and `Ok(None)` is returned.
+fn acquire_connection(
+    state: &AppState,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+) -> Result<Option<ConnectionPermit>, TooManyConnections> {
+    let Some(Extension(ConnectInfo(addr))) = connect_info else {
+        return Ok(None);
+    };
+
+    match state.connection_limiter.try_acquire(addr.ip()) {
+        Some(permit) => Ok(Some(permit)),
+        None => {
+            warn!(ip = %addr.ip(), "Connection limit reached");
+            state.metrics.record_connection_rejected();
+            Err(TooManyConnections)
+        }
+    }
+}
+
+// ============================================================================
+// GET /stream - Server-Sent Events Subscription
+// ============================================================================
+
+/// GET /stream - Server-Sent Events subscription endpoint.
+///
+/// Serves the same filtered feed as `/ws` as `text/event-stream`, for clients
+/// that cannot hold a WebSocket. See [`crate::sse`] for the wire format.
+///
+/// # Authentication
+///
+/// Unless `unsafe_no_auth` is enabled, a subscriber token is required, either
+/// as the `token` query parameter (for `EventSource`, which cannot set
+/// headers) or as an `Authorization: Bearer` header. The token's scope is
+/// ANDed into the requested filter.
+///
+/// # Query Parameters
+///
+/// The same as `/ws`: `token`, the `source`, `type`, `project`, `tool` and
+/// `filter` filter parameters, and `since` or `since_ts` to resume.
+///
+/// # Resuming
+///
+/// A `Last-Event-ID` header, as sent by a reconnecting `EventSource`, resumes
+/// after that event and takes precedence over `since` and `since_ts`.
+///
+/// # Responses
+///
+/// - `200 OK` - Event stream
+/// - `400 Bad Request` - Malformed filter
+/// - `401 Unauthorized` - Invalid or missing token
+/// - `403 Forbidden` - The page's `Origin` is not allowed (see [`crate::cors`])
+/// - `429 Too Many Requests` - Client IP already has the maximum number of connections
+async fn get_stream(
+    State(state): State<AppState>,
+    Query(params): Query<WsQueryParams>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(err) = check_origin(&state, &headers) {
+        return err.into_response();
+    }
+
+    let token = params.token.as_deref().or_else(|| bearer_token(&headers));
+    let subscriber = match authenticate_subscriber(&state, token) {
+        Ok(subscriber) => subscriber,
+        Err(err) => return err.into_response(),
+    };
+
+    let filter = match params.to_filter() {
+        Ok(filter) => filter.with_scope(subscriber.scope),
+        Err(err) => return invalid_filter(&err),
+    };
+
+    let permit = match acquire_connection(&state, connect_info) {
+        Ok(permit) => permit,
+        Err(err) => return err.into_response(),
+    };
+
+    info!(
+        subscriber = %subscriber.name,
+        filter = ?filter,
+        "SSE client connecting"
+    );
+
+    let last_event_id = headers
+        .get(HEADER_LAST_EVENT_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty());
+    let resume = match last_event_id {
+        Some(id) => Some(ResumePoint::AfterEvent(id.to_string())),
+        None => params.resume_point(),
+    };
+
+    sse::event_stream(state, filter, resume, permit).into_response()
+}
+
+// ============================================================================
+// GET /health - Health Check
+// ============================================================================
+
+/// Response body for health check endpoint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthResponse {
+    /// Server status (always "ok" if responding).
+    pub status: String,
+
+    /// Number of active WebSocket and SSE connections.
+    pub connections: usize,
+
+    /// Server uptime in seconds.
+    pub uptime_seconds: u64,
+
+    /// Event wire format versions accepted by `POST /events`, oldest first.
+    /// Monitors send the newest version they share with the server.
+    #[serde(default)]
+    pub schema_versions: Vec<u32>,
+
+    /// Event types accepted by `POST /events`. Monitors drop events of
+    /// other types instead of sending them.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+
+    /// Whether `POST /events` signatures cover the `X-Timestamp` and
+    /// `X-Nonce` headers as well as the body. Monitors sign the body alone
+    /// for servers that do not say.
+    #[serde(default)]
+    pub replay_protection: bool,
+}
+
+/// GET /health - Health check endpoint.
+///
+/// Returns server health status and statistics, and the event formats the
+/// server accepts so monitors can negotiate them.
+/// No authentication required.
+///
+/// # Response
+///
+/// ```json
+/// {
+///   "status": "ok",
+///   "connections": 42,
+///   "uptime_seconds": 3600,
+///   "schema_versions": [1, 2],
+///   "event_types": ["session", "activity", "tool", ...],
+///   "replay_protection": true
+/// }
 /// ```
 async fn get_health(State(state): State<AppState>) -> Json<HealthResponse> {
     let uptime = state.start_time.elapsed();
 
     Json(HealthResponse {
         status: "ok".to_string(),
-        connections: state.broadcaster.subscriber_count(),
+        connections: state.clients.count(),
         uptime_seconds: uptime.as_secs(),
+        schema_versions: state.config.validator().schema_versions(),
+        event_types: EventType::ALL
+            .iter()
+            .map(|event_type| event_type.as_str().to_string())
+            .collect(),
+        replay_protection: true,
     })
 }
 
+// ============================================================================
+// GET /metrics - Prometheus Metrics
+// ============================================================================
+
+/// Query parameters for `GET /metrics`.
+#[derive(Debug, Default, Deserialize)]
+pub struct MetricsQueryParams {
+    /// Authentication token (may also be sent as a bearer token).
+    pub token: Option<String>,
+}
+
+/// GET /metrics - Prometheus metrics endpoint.
+///
+/// Returns the counters and histograms described in [`crate::metrics`], plus
+/// gauges for connections, uptime, tracked sessions, in-memory history and
+/// event log size.
+///
+/// # Authentication
+///
+/// Unless `unsafe_no_auth` is enabled, the subscriber token is required,
+/// either as the `token` query parameter or as an `Authorization: Bearer`
+/// header.
+///
+/// # Responses
+///
+/// - `200 OK` - Metrics in the Prometheus text format
+/// - `401 Unauthorized` - Invalid, expired or missing token
+/// - `403 Forbidden` - The token is scoped
+async fn get_metrics(
+    State(state): State<AppState>,
+    Query(params): Query<MetricsQueryParams>,
+    headers: HeaderMap,
+) -> Response {
+    let token = params.token.as_deref().or_else(|| bearer_token(&headers));
+    match authenticate_subscriber(&state, token) {
+        // Metrics cover every source, so scoped tokens may not read them
+        Ok(subscriber) if !subscriber.scope.is_unrestricted() => {
+            return SubscriberAuthError::InsufficientScope.into_response();
+        }
+        Ok(_) => {}
+        Err(err) => return err.into_response(),
+    }
+
+    let mut gauges = vec![
+        Gauge::new(
+            "vibetea_connections",
+            "Open subscriber connections.",
+            state.clients.count() as f64,
+        ),
+        Gauge::new(
+            "vibetea_uptime_seconds",
+            "Seconds since the server started.",
+            state.start_time.elapsed().as_secs_f64(),
+        ),
+        Gauge::new(
+            "vibetea_sessions",
+            "Sessions tracked by the session registry.",
+            state.sessions.len() as f64,
+        ),
+        Gauge::new(
+            "vibetea_history_events",
+            "Events retained in memory for resuming subscribers.",
+            state.broadcaster.history_len() as f64,
+        ),
+    ];
+    if let Some(store) = &state.store {
+        gauges.push(Gauge::new(
+            "vibetea_store_bytes",
+            "Size of the persistent event log.",
+            store.total_bytes() as f64,
+        ));
+    }
+
+    (
+        [(CONTENT_TYPE, metrics::CONTENT_TYPE)],
+        state.metrics.render(&gauges),
+    )
+        .into_response()
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -635,7 +1750,9 @@ mod tests {
     use tower::ServiceExt;
     use uuid::Uuid;
 
-    use crate::types::{EventPayload, SessionAction};
+    use crate::keys::KeyRecord;
+    use crate::tokens::{SubscriberToken, TokenScope};
+    use crate::types::{EventPayload, EventType, SessionAction, ToolStatus};
 
     /// Creates a test configuration with authentication disabled.
     fn test_config_no_auth() -> Config {
@@ -644,6 +1761,7 @@ mod tests {
             subscriber_token: None,
             port: 8080,
             unsafe_no_auth: true,
+            ..Config::default()
         }
     }
 
@@ -657,6 +1775,20 @@ mod tests {
             subscriber_token: Some("test-token".to_string()),
             port: 8080,
             unsafe_no_auth: false,
+            ..Config::default()
+        }
+    }
+
+    /// Creates a test configuration with authentication enabled and keys
+    /// supplied only through the key registry.
+    fn test_config_with_key_file() -> Config {
+        Config {
+            public_keys: HashMap::new(),
+            key_file: Some("keys.json".into()),
+            subscriber_token: Some("test-token".to_string()),
+            port: 8080,
+            unsafe_no_auth: false,
+            ..Config::default()
         }
     }
 
@@ -672,19 +1804,50 @@ mod tests {
         (signing_key, public_key_base64)
     }
 
-    /// Creates a test event.
+    /// Builds a `POST /events` request signed over the timestamp, nonce and body.
+    fn signed_request(
+        signing_key: &SigningKey,
+        source_id: &str,
+        body: &str,
+        timestamp: i64,
+        nonce: &str,
+    ) -> Request<Body> {
+        let timestamp = timestamp.to_string();
+        let message = signed_message(&timestamp, nonce, body.as_bytes());
+        let signature_base64 = BASE64_STANDARD.encode(signing_key.sign(&message).to_bytes());
+
+        Request::builder()
+            .method("POST")
+            .uri("/events")
+            .header("Content-Type", "application/json")
+            .header(HEADER_SOURCE_ID, source_id)
+            .header(HEADER_TIMESTAMP, timestamp)
+            .header(HEADER_NONCE, nonce)
+            .header(HEADER_SIGNATURE, signature_base64)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    /// Returns the `code` field of a JSON error response.
+    async fn error_code(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        error["code"].as_str().unwrap_or_default().to_string()
+    }
+
+    /// Creates a test event with a unique ID.
     fn create_test_event() -> Event {
-        Event {
-            id: "evt_test12345678901234".to_string(),
-            source: "test-source".to_string(),
-            timestamp: Utc::now(),
-            event_type: EventType::Session,
-            payload: EventPayload::Session {
+        Event::new(
+            "test-source".to_string(),
+            EventType::Session,
+            EventPayload::Session {
                 session_id: Uuid::new_v4(),
                 action: SessionAction::Started,
                 project: "test-project".to_string(),
             },
-        }
+        )
     }
 
     // ========================================================================
@@ -715,12 +1878,19 @@ mod tests {
 
         assert_eq!(health.status, "ok");
         assert_eq!(health.connections, 0);
+        assert_eq!(health.schema_versions, [1, 2]);
+        assert_eq!(health.event_types.len(), EventType::ALL.len());
+        assert!(health.event_types.iter().any(|t| t == "token_usage"));
+        assert!(health.replay_protection);
     }
 
     #[tokio::test]
-    async fn health_reports_subscriber_count() {
+    async fn health_reports_client_connections() {
         let state = AppState::new(test_config_no_auth());
-        let _subscriber = state.broadcaster.subscribe();
+        let _connection = state.clients.connect();
+        // Receivers held by the server itself, such as the relay's, are not
+        // client connections
+        let _relay = state.broadcaster.subscribe();
         let app = create_router(state);
 
         let response = app
@@ -804,34 +1974,73 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn post_events_rejects_missing_source_id() {
+    async fn post_events_drops_duplicate_event_ids() {
         let state = AppState::new(test_config_no_auth());
+        let mut receiver = state.broadcaster.subscribe();
         let app = create_router(state);
 
         let event = create_test_event();
-        let body = serde_json::to_string(&event).unwrap();
+        let post = |events: Vec<Event>| {
+            Request::builder()
+                .method("POST")
+                .uri("/events")
+                .header("Content-Type", "application/json")
+                .header(HEADER_SOURCE_ID, "test-source")
+                .body(Body::from(serde_json::to_string(&events).unwrap()))
+                .unwrap()
+        };
 
         let response = app
-            .oneshot(
-                Request::builder()
-                    .method("POST")
-                    .uri("/events")
-                    .header("Content-Type", "application/json")
-                    // Missing X-Source-ID header
-                    .body(Body::from(body))
-                    .unwrap(),
-            )
+            .clone()
+            .oneshot(post(vec![event.clone()]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
             .unwrap();
+        let ingest: IngestResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!((ingest.accepted, ingest.duplicates), (1, 0));
 
-        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        // A retried batch carrying the same event plus a new one
+        let fresh = create_test_event();
+        let response = app
+            .oneshot(post(vec![event.clone(), fresh.clone()]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let ingest: IngestResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!((ingest.accepted, ingest.duplicates), (1, 1));
+
+        assert_eq!(receiver.try_recv().unwrap().id, event.id);
+        assert_eq!(receiver.try_recv().unwrap().id, fresh.id);
+        assert!(receiver.try_recv().is_err());
     }
 
     #[tokio::test]
-    async fn post_events_rejects_invalid_json() {
+    async fn post_events_reports_invalid_events_in_batch() {
         let state = AppState::new(test_config_no_auth());
+        let mut receiver = state.broadcaster.subscribe();
+        let metrics = state.metrics.clone();
         let app = create_router(state);
 
+        let valid = create_test_event();
+        let mismatched = Event {
+            event_type: EventType::Tool,
+            ..create_test_event()
+        };
+        let future = Event {
+            timestamp: Utc::now() + chrono::Duration::hours(1),
+            ..create_test_event()
+        };
+        let mut body = serde_json::to_value(vec![&valid, &mismatched, &future]).unwrap();
+        body.as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({"id": "evt_bad", "source": "test-source"}));
+
         let response = app
             .oneshot(
                 Request::builder()
@@ -839,30 +2048,115 @@ mod tests {
                     .uri("/events")
                     .header("Content-Type", "application/json")
                     .header(HEADER_SOURCE_ID, "test-source")
-                    .body(Body::from("not valid json"))
+                    .body(Body::from(body.to_string()))
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let ingest: IngestResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!((ingest.accepted, ingest.rejected), (1, 3));
+        let errors: Vec<(usize, &str)> = ingest
+            .errors
+            .iter()
+            .map(|e| (e.index, e.code.as_str()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (1, "type_mismatch"),
+                (2, "future_timestamp"),
+                (3, "invalid_format")
+            ]
+        );
+        assert_eq!(ingest.errors[0].id.as_deref(), Some(mismatched.id.as_str()));
+
+        assert_eq!(receiver.try_recv().unwrap().id, valid.id);
+        assert!(receiver.try_recv().is_err());
+        assert!(metrics.render(&[]).contains(
+            r#"vibetea_invalid_events_total{source="test-source",reason="type_mismatch"} 1"#
+        ));
     }
 
-    // ========================================================================
-    // POST /events tests (with auth)
-    // ========================================================================
+    #[tokio::test]
+    async fn post_events_rejects_invalid_single_event() {
+        let state = AppState::new(test_config_no_auth());
+        let mut receiver = state.broadcaster.subscribe();
+        let app = create_router(state);
+
+        let event = Event {
+            id: "evt_123".to_string(),
+            ..create_test_event()
+        };
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/events")
+                    .header("Content-Type", "application/json")
+                    .header(HEADER_SOURCE_ID, "test-source")
+                    .body(Body::from(serde_json::to_string(&event).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "invalid_id");
+        assert!(receiver.try_recv().is_err());
+    }
 
     #[tokio::test]
-    async fn post_events_with_auth_accepts_valid_signature() {
-        let (signing_key, public_key_base64) = create_test_keypair();
-        let state = AppState::new(test_config_with_auth(&public_key_base64));
+    async fn post_events_rejects_legacy_events_when_compatibility_is_off() {
+        let state = AppState::new(Config {
+            accept_legacy_events: false,
+            ..test_config_no_auth()
+        });
         let mut receiver = state.broadcaster.subscribe();
         let app = create_router(state);
 
+        let current = serde_json::to_value(create_test_event()).unwrap();
+        let mut legacy = serde_json::to_value(create_test_event()).unwrap();
+        legacy.as_object_mut().unwrap().remove("schemaVersion");
+        let mut future = serde_json::to_value(create_test_event()).unwrap();
+        future["schemaVersion"] = 3.into();
+        let body = serde_json::Value::Array(vec![current, legacy, future]);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/events")
+                    .header("Content-Type", "application/json")
+                    .header(HEADER_SOURCE_ID, "test-source")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let ingest: IngestResponse = serde_json::from_slice(&body).unwrap();
+        let codes: Vec<&str> = ingest.errors.iter().map(|e| e.code.as_str()).collect();
+        assert_eq!(codes, ["legacy_schema", "unsupported_schema_version"]);
+        assert!(receiver.try_recv().is_ok());
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn post_events_rejects_missing_source_id() {
+        let state = AppState::new(test_config_no_auth());
+        let app = create_router(state);
+
         let event = create_test_event();
         let body = serde_json::to_string(&event).unwrap();
-        let signature = signing_key.sign(body.as_bytes());
-        let signature_base64 = BASE64_STANDARD.encode(signature.to_bytes());
 
         let response = app
             .oneshot(
@@ -870,18 +2164,97 @@ mod tests {
                     .method("POST")
                     .uri("/events")
                     .header("Content-Type", "application/json")
-                    .header(HEADER_SOURCE_ID, "test-source")
-                    .header(HEADER_SIGNATURE, signature_base64)
+                    // Missing X-Source-ID header
                     .body(Body::from(body))
                     .unwrap(),
             )
             .await
             .unwrap();
 
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn post_events_rejects_invalid_json() {
+        let state = AppState::new(test_config_no_auth());
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/events")
+                    .header("Content-Type", "application/json")
+                    .header(HEADER_SOURCE_ID, "test-source")
+                    .body(Body::from("not valid json"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    // ========================================================================
+    // POST /events tests (with auth)
+    // ========================================================================
+
+    #[tokio::test]
+    async fn post_events_with_auth_accepts_valid_signature() {
+        let (signing_key, public_key_base64) = create_test_keypair();
+        let state = AppState::new(test_config_with_auth(&public_key_base64));
+        let mut receiver = state.broadcaster.subscribe();
+        let app = create_router(state);
+
+        let event = create_test_event();
+        let body = serde_json::to_string(&event).unwrap();
+
+        let response = app
+            .oneshot(signed_request(
+                &signing_key,
+                "test-source",
+                &body,
+                Utc::now().timestamp(),
+                "nonce-1",
+            ))
+            .await
+            .unwrap();
+
         assert_eq!(response.status(), StatusCode::ACCEPTED);
         assert!(receiver.try_recv().is_ok());
     }
 
+    #[tokio::test]
+    async fn post_events_requires_client_certificate_when_client_ca_configured() {
+        let (signing_key, public_key_base64) = create_test_keypair();
+        let state = AppState::new(Config {
+            tls_cert: Some("cert.pem".into()),
+            tls_key: Some("key.pem".into()),
+            tls_client_ca: Some("ca.pem".into()),
+            ..test_config_with_auth(&public_key_base64)
+        });
+        let app = create_router(state);
+
+        let body = serde_json::to_string(&create_test_event()).unwrap();
+        let response = app
+            .oneshot(signed_request(
+                &signing_key,
+                "test-source",
+                &body,
+                Utc::now().timestamp(),
+                "nonce-1",
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["code"], "client_certificate_required");
+    }
+
     #[tokio::test]
     async fn post_events_with_auth_rejects_missing_signature() {
         let (_, public_key_base64) = create_test_keypair();
@@ -929,24 +2302,19 @@ mod tests {
             (signing_key, public_key_base64)
         };
 
-        let signature = wrong_key.sign(body.as_bytes());
-        let signature_base64 = BASE64_STANDARD.encode(signature.to_bytes());
-
         let response = app
-            .oneshot(
-                Request::builder()
-                    .method("POST")
-                    .uri("/events")
-                    .header("Content-Type", "application/json")
-                    .header(HEADER_SOURCE_ID, "test-source")
-                    .header(HEADER_SIGNATURE, signature_base64)
-                    .body(Body::from(body))
-                    .unwrap(),
-            )
+            .oneshot(signed_request(
+                &wrong_key,
+                "test-source",
+                &body,
+                Utc::now().timestamp(),
+                "nonce-1",
+            ))
             .await
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "invalid_signature");
     }
 
     #[tokio::test]
@@ -957,16 +2325,172 @@ mod tests {
 
         let event = create_test_event();
         let body = serde_json::to_string(&event).unwrap();
-        let signature = signing_key.sign(body.as_bytes());
-        let signature_base64 = BASE64_STANDARD.encode(signature.to_bytes());
+
+        let response = app
+            .oneshot(signed_request(
+                &signing_key,
+                "unknown-source", // Not in config
+                &body,
+                Utc::now().timestamp(),
+                "nonce-1",
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "unknown_source");
+    }
+
+    #[tokio::test]
+    async fn post_events_with_auth_rejects_missing_timestamp_and_nonce() {
+        let (signing_key, public_key_base64) = create_test_keypair();
+        let app = create_router(AppState::new(test_config_with_auth(&public_key_base64)));
+        let body = serde_json::to_string(&create_test_event()).unwrap();
+
+        let mut request = signed_request(
+            &signing_key,
+            "test-source",
+            &body,
+            Utc::now().timestamp(),
+            "n",
+        );
+        request.headers_mut().remove(HEADER_TIMESTAMP);
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "missing_timestamp");
+
+        let mut request = signed_request(
+            &signing_key,
+            "test-source",
+            &body,
+            Utc::now().timestamp(),
+            "n",
+        );
+        request.headers_mut().remove(HEADER_NONCE);
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "missing_nonce");
+    }
+
+    #[tokio::test]
+    async fn post_events_with_auth_rejects_stale_timestamp() {
+        let (signing_key, public_key_base64) = create_test_keypair();
+        let state = AppState::new(test_config_with_auth(&public_key_base64));
+        let skew = state.config.max_clock_skew.as_secs() as i64;
+        let app = create_router(state);
+        let body = serde_json::to_string(&create_test_event()).unwrap();
+
+        let stale = Utc::now().timestamp() - skew - 10;
+        let response = app
+            .oneshot(signed_request(
+                &signing_key,
+                "test-source",
+                &body,
+                stale,
+                "nonce-1",
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "stale_timestamp");
+    }
+
+    #[tokio::test]
+    async fn post_events_with_auth_rejects_replayed_request() {
+        let (signing_key, public_key_base64) = create_test_keypair();
+        let state = AppState::new(test_config_with_auth(&public_key_base64));
+        let mut receiver = state.broadcaster.subscribe();
+        let app = create_router(state);
+        let body = serde_json::to_string(&create_test_event()).unwrap();
+        let timestamp = Utc::now().timestamp();
+
+        let response = app
+            .clone()
+            .oneshot(signed_request(
+                &signing_key,
+                "test-source",
+                &body,
+                timestamp,
+                "nonce-1",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        // Byte-for-byte replay of the captured request
+        let response = app
+            .clone()
+            .oneshot(signed_request(
+                &signing_key,
+                "test-source",
+                &body,
+                timestamp,
+                "nonce-1",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "replayed_request");
+
+        // A fresh nonce is accepted, but the event itself is a duplicate
+        let response = app
+            .oneshot(signed_request(
+                &signing_key,
+                "test-source",
+                &body,
+                timestamp,
+                "nonce-2",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        assert!(receiver.try_recv().is_ok());
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn post_events_rejects_revoked_key() {
+        let (signing_key, public_key_base64) = create_test_keypair();
+        let state = AppState::new(test_config_with_key_file());
+        state.keys.replace(vec![KeyRecord {
+            revoked: true,
+            ..KeyRecord::new("test-source", public_key_base64)
+        }]);
+        let app = create_router(state);
+        let body = serde_json::to_string(&create_test_event()).unwrap();
+
+        let response = app
+            .oneshot(signed_request(
+                &signing_key,
+                "test-source",
+                &body,
+                Utc::now().timestamp(),
+                "nonce-1",
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "revoked_key");
+    }
+
+    #[tokio::test]
+    async fn post_events_rejects_body_only_signature() {
+        let (signing_key, public_key_base64) = create_test_keypair();
+        let app = create_router(AppState::new(test_config_with_auth(&public_key_base64)));
+        let body = serde_json::to_string(&create_test_event()).unwrap();
+        let signature_base64 = BASE64_STANDARD.encode(signing_key.sign(body.as_bytes()).to_bytes());
 
         let response = app
             .oneshot(
                 Request::builder()
                     .method("POST")
                     .uri("/events")
-                    .header("Content-Type", "application/json")
-                    .header(HEADER_SOURCE_ID, "unknown-source") // Not in config
+                    .header(HEADER_SOURCE_ID, "test-source")
+                    .header(HEADER_TIMESTAMP, Utc::now().timestamp().to_string())
+                    .header(HEADER_NONCE, "nonce-1")
                     .header(HEADER_SIGNATURE, signature_base64)
                     .body(Body::from(body))
                     .unwrap(),
@@ -975,6 +2499,7 @@ mod tests {
             .unwrap();
 
         assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "invalid_signature");
     }
 
     // ========================================================================
@@ -1026,6 +2551,68 @@ mod tests {
         assert!(response.headers().contains_key(HEADER_RETRY_AFTER));
     }
 
+    #[tokio::test]
+    async fn post_events_global_limit_counts_events() {
+        let state = AppState::with_components(
+            test_config_no_auth(),
+            EventBroadcaster::new(),
+            RateLimiter::new(100.0, 100).with_global_limit(0.001, 3),
+        );
+        let app = create_router(state);
+
+        let post_batch = |app: Router| async move {
+            let batch = vec![create_test_event(), create_test_event()];
+            app.oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/events")
+                    .header("Content-Type", "application/json")
+                    .header(HEADER_SOURCE_ID, "test-source")
+                    .body(Body::from(serde_json::to_string(&batch).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+        };
+
+        // Two events fit the burst of three, four do not
+        assert_eq!(post_batch(app.clone()).await.status(), StatusCode::ACCEPTED);
+        let response = post_batch(app).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(HEADER_RETRY_AFTER));
+    }
+
+    // ========================================================================
+    // Event store tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn post_events_persists_to_store() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = EventStore::open(crate::store::StoreConfig::new(dir.path())).unwrap();
+        let state = AppState::new(test_config_no_auth()).with_store(store.clone());
+        let app = create_router(state);
+
+        let events = vec![create_test_event(), create_test_event()];
+        let body = serde_json::to_string(&events).unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/events")
+                    .header("Content-Type", "application/json")
+                    .header(HEADER_SOURCE_ID, "test-source")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(store.replay(None).unwrap().len(), 2);
+    }
+
     // ========================================================================
     // Source validation tests
     // ========================================================================
@@ -1063,8 +2650,9 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn post_events_rejects_mixed_source_batch() {
+    async fn post_events_reports_mismatched_sources_in_batch() {
         let state = AppState::new(test_config_no_auth());
+        let mut receiver = state.broadcaster.subscribe();
         let app = create_router(state);
 
         // Create batch where first event matches but second doesn't
@@ -1073,7 +2661,7 @@ mod tests {
         let mut event2 = create_test_event();
         event2.source = "other-source".to_string();
 
-        let events = vec![event1, event2];
+        let events = vec![event1.clone(), event2.clone()];
         let body = serde_json::to_string(&events).unwrap();
 
         let response = app
@@ -1089,24 +2677,145 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
 
         let body = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
             .unwrap();
-        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
-        assert_eq!(error["code"], "source_mismatch");
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["accepted"], 1);
+        assert_eq!(body["rejected"], 1);
+        assert_eq!(body["errors"][0]["index"], 1);
+        assert_eq!(body["errors"][0]["id"], event2.id);
+        assert_eq!(body["errors"][0]["code"], "source_mismatch");
+
+        // Only the matching event is broadcast
+        assert_eq!(receiver.try_recv().unwrap().id, event1.id);
+        assert!(receiver.try_recv().is_err());
     }
 
-    #[tokio::test]
-    async fn post_events_accepts_matching_source() {
-        let state = AppState::new(test_config_no_auth());
-        let mut receiver = state.broadcaster.subscribe();
-        let app = create_router(state);
-
-        let mut event = create_test_event();
-        event.source = "matching-source".to_string();
-        let body = serde_json::to_string(&event).unwrap();
+    // ========================================================================
+    // Relayed event tests
+    // ========================================================================
+
+    /// Config for a hub `hub-global` that accepts events relayed by `hub-eu`.
+    fn test_config_relay() -> Config {
+        Config {
+            relay_id: Some("hub-global".to_string()),
+            relay_sources: vec!["hub-eu".to_string()],
+            ..test_config_no_auth()
+        }
+    }
+
+    /// An event from `laptop` relayed by `hops`.
+    fn relayed_event(hops: &[&str]) -> Event {
+        Event {
+            source: "laptop".to_string(),
+            hops: hops.iter().map(ToString::to_string).collect(),
+            ..create_test_event()
+        }
+    }
+
+    /// Posts `events` as `sender`, returning the status and JSON body.
+    async fn post_as(
+        state: AppState,
+        sender: &str,
+        events: &[Event],
+    ) -> (StatusCode, serde_json::Value) {
+        let response = create_router(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/events")
+                    .header("Content-Type", "application/json")
+                    .header(HEADER_SOURCE_ID, sender)
+                    .body(Body::from(serde_json::to_string(events).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn post_events_accepts_relayed_events_from_relay_sources() {
+        let state = AppState::new(test_config_relay());
+        let mut receiver = state.broadcaster.subscribe();
+
+        let event = relayed_event(&["hub-berlin", "hub-eu"]);
+        let (status, body) = post_as(state, "hub-eu", std::slice::from_ref(&event)).await;
+
+        assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+        let received = receiver.try_recv().unwrap();
+        assert_eq!(received.source, "laptop");
+        assert_eq!(received.hops, event.hops);
+    }
+
+    #[tokio::test]
+    async fn post_events_rejects_relayed_events_from_other_sources() {
+        let state = AppState::new(test_config_relay());
+
+        let (status, body) = post_as(state, "hub-rogue", &[relayed_event(&["hub-rogue"])]).await;
+
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        assert_eq!(body["accepted"], 0);
+        assert_eq!(body["errors"][0]["code"], "relay_not_allowed");
+    }
+
+    #[tokio::test]
+    async fn post_events_rejects_relayed_events_with_wrong_last_hop() {
+        let state = AppState::new(test_config_relay());
+
+        let (status, body) =
+            post_as(state, "hub-eu", &[relayed_event(&["hub-eu", "hub-us"])]).await;
+
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        assert_eq!(body["accepted"], 0);
+        assert_eq!(body["errors"][0]["code"], "hop_mismatch");
+    }
+
+    #[tokio::test]
+    async fn post_events_drops_events_that_looped_back() {
+        let state = AppState::new(test_config_relay());
+        let mut receiver = state.broadcaster.subscribe();
+
+        let looped = relayed_event(&["hub-global", "hub-eu"]);
+        let fresh = relayed_event(&["hub-eu"]);
+        let (status, body) = post_as(state, "hub-eu", &[looped, fresh.clone()]).await;
+
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(body["accepted"], 1);
+        assert_eq!(body["duplicates"], 1);
+        assert_eq!(receiver.try_recv().unwrap().id, fresh.id);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn post_events_rejects_events_relayed_too_often() {
+        let state = AppState::new(test_config_relay());
+
+        let mut hops = vec!["hub-x"; crate::validation::MAX_HOPS];
+        hops.push("hub-eu");
+        let (status, body) = post_as(state, "hub-eu", &[relayed_event(&hops)]).await;
+
+        assert_eq!(status, StatusCode::MULTI_STATUS);
+        assert_eq!(body["accepted"], 0);
+        assert_eq!(body["errors"][0]["code"], "too_many_hops");
+    }
+
+    #[tokio::test]
+    async fn post_events_accepts_matching_source() {
+        let state = AppState::new(test_config_no_auth());
+        let mut receiver = state.broadcaster.subscribe();
+        let app = create_router(state);
+
+        let mut event = create_test_event();
+        event.source = "matching-source".to_string();
+        let body = serde_json::to_string(&event).unwrap();
 
         let response = app
             .oneshot(
@@ -1153,49 +2862,669 @@ mod tests {
         assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
 
+    // ========================================================================
+    // GET /events tests
+    // ========================================================================
+
+    /// Creates state whose history holds `count` events from "test-source".
+    fn state_with_history(config: Config, count: usize) -> (AppState, Vec<Event>) {
+        let state = AppState::new(config);
+        let events: Vec<Event> = (0..count)
+            .map(|n| Event {
+                id: format!("evt_{n:0>20}"),
+                ..create_test_event()
+            })
+            .collect();
+        for event in &events {
+            state.broadcaster.broadcast(event.clone());
+        }
+        (state, events)
+    }
+
+    async fn get(app: Router, uri: &str, headers: &[(&str, &str)]) -> Response {
+        let mut request = Request::builder().uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body_string(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn get_events_paginates_json() {
+        let (state, events) = state_with_history(test_config_no_auth(), 3);
+        let app = create_router(state);
+
+        let response = get(app.clone(), "/events?limit=2", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(page.events.len(), 2);
+        assert_eq!(page.next_cursor.as_deref(), Some(events[1].id.as_str()));
+
+        let uri = format!("/events?limit=2&cursor={}", events[1].id);
+        let response = get(app, &uri, &[]).await;
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(page.events.len(), 1);
+        assert_eq!(page.events[0].id, events[2].id);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn get_events_applies_filters() {
+        let (state, _) = state_with_history(test_config_no_auth(), 2);
+        let app = create_router(state);
+
+        let response = get(app.clone(), "/events?source=other-source", &[]).await;
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert!(page.events.is_empty());
+
+        let response = get(app, "/events?type=session&project=test-project", &[]).await;
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(page.events.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_events_applies_rich_filters() {
+        let state = AppState::new(test_config_no_auth());
+        let tool_event = |source: &str, tool: &str| Event {
+            source: source.to_string(),
+            event_type: EventType::Tool,
+            payload: EventPayload::Tool {
+                session_id: Uuid::new_v4(),
+                tool: tool.to_string(),
+                status: ToolStatus::Completed,
+                context: None,
+                project: Some("web".to_string()),
+            },
+            ..create_test_event()
+        };
+        state
+            .publish(vec![
+                create_test_event(),
+                tool_event("ci-linux", "Bash"),
+                tool_event("dev-laptop", "Read"),
+                tool_event("dev-laptop", "Edit"),
+            ])
+            .await;
+        let app = create_router(state);
+
+        let sources = |page: EventsResponse| -> Vec<String> {
+            page.events.into_iter().map(|event| event.source).collect()
+        };
+
+        let response = get(app.clone(), "/events?type=tool,session&source=!ci-*", &[]).await;
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(sources(page), ["test-source", "dev-laptop", "dev-laptop"]);
+
+        // The tool filter leaves non-tool events alone
+        let response = get(app.clone(), "/events?tool=Bash,Ed*", &[]).await;
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(sources(page), ["test-source", "ci-linux", "dev-laptop"]);
+
+        let filter = r#"{"type":"tool","tool":["!Read"]}"#;
+        let uri = format!("/events?filter={}", filter.replace('"', "%22"));
+        let response = get(app, &uri, &[]).await;
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(sources(page), ["ci-linux", "dev-laptop"]);
+    }
+
+    #[tokio::test]
+    async fn get_events_rejects_invalid_filter() {
+        let app = create_router(AppState::new(test_config_no_auth()));
+
+        let response = get(app.clone(), "/events?type=bogus", &[]).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "invalid_filter");
+
+        let response = get(app, "/events?filter=%7B%7D&source=a", &[]).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "invalid_filter");
+    }
+
+    #[tokio::test]
+    async fn get_events_returns_ndjson() {
+        let (state, events) = state_with_history(test_config_no_auth(), 3);
+        let app = create_router(state);
+
+        let response = get(app.clone(), "/events?format=ndjson&limit=2", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            CONTENT_TYPE_NDJSON
+        );
+        assert_eq!(
+            response.headers().get(HEADER_NEXT_CURSOR).unwrap(),
+            events[1].id.as_str()
+        );
+        let body = body_string(response).await;
+        let lines: Vec<Event> = body
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].id, events[0].id);
+
+        let response = get(app, "/events", &[("Accept", CONTENT_TYPE_NDJSON)]).await;
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            CONTENT_TYPE_NDJSON
+        );
+        assert!(response.headers().get(HEADER_NEXT_CURSOR).is_none());
+        assert_eq!(body_string(response).await.lines().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn get_events_rejects_unknown_cursor() {
+        let (state, _) = state_with_history(test_config_no_auth(), 1);
+        let app = create_router(state);
+
+        let response = get(app, "/events?cursor=evt_missing", &[]).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(body_string(response).await.contains("invalid_cursor"));
+    }
+
+    #[tokio::test]
+    async fn get_events_requires_subscriber_token() {
+        let (_, public_key) = create_test_keypair();
+        let (state, _) = state_with_history(test_config_with_auth(&public_key), 1);
+        let app = create_router(state);
+
+        let response = get(app.clone(), "/events", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = get(app.clone(), "/events?token=wrong", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = get(app.clone(), "/events?token=test-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get(app, "/events", &[("Authorization", "Bearer test-token")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // ========================================================================
+    // GET /sessions tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn post_events_updates_session_registry() {
+        let state = AppState::new(test_config_no_auth());
+        let sessions = state.sessions.clone();
+        let app = create_router(state);
+
+        let event = create_test_event();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/events")
+                    .header("Content-Type", "application/json")
+                    .header(HEADER_SOURCE_ID, "test-source")
+                    .body(Body::from(serde_json::to_string(&event).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(sessions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_sessions_returns_snapshot() {
+        let (state, _) = state_with_history(test_config_no_auth(), 0);
+        state.publish(vec![create_test_event()]).await;
+        let app = create_router(state);
+
+        let response = get(app, "/sessions", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: SessionsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(body.sessions.len(), 1);
+        assert_eq!(body.sessions[0].project.as_deref(), Some("test-project"));
+    }
+
+    #[tokio::test]
+    async fn get_sessions_requires_subscriber_token() {
+        let (_, public_key) = create_test_keypair();
+        let app = create_router(AppState::new(test_config_with_auth(&public_key)));
+
+        let response = get(app.clone(), "/sessions", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = get(app, "/sessions", &[("Authorization", "Bearer test-token")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn get_stats_aggregates_window() {
+        let (state, _) = state_with_history(test_config_no_auth(), 0);
+        state
+            .publish(vec![create_test_event(), create_test_event()])
+            .await;
+        let app = create_router(state);
+
+        for uri in ["/stats", "/stats?window=24h", "/stats?window=7d"] {
+            let response = get(app.clone(), uri, &[]).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let body: serde_json::Value =
+                serde_json::from_str(&body_string(response).await).unwrap();
+            assert_eq!(body["totalEvents"], 2, "{uri}");
+            assert_eq!(body["eventsByType"]["session"], 2, "{uri}");
+            assert_eq!(body["activeSessions"], 2, "{uri}");
+        }
+
+        let response = get(app, "/stats?window=1y", &[]).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    // ========================================================================
+    // Scoped subscriber token tests
+    // ========================================================================
+
+    /// Creates authenticated state with a `ci-*` scoped token ("ci-token")
+    /// and events from both a CI and a developer source.
+    async fn state_with_scoped_token() -> AppState {
+        let (_, public_key) = create_test_keypair();
+        let state = AppState::new(test_config_with_auth(&public_key));
+        state
+            .tokens
+            .replace(vec![SubscriberToken::new("ci", "ci-token")
+                .with_scope(TokenScope::new().with_sources(["ci-*"]))]);
+        state
+            .publish(vec![
+                Event {
+                    source: "ci-linux".to_string(),
+                    ..create_test_event()
+                },
+                Event {
+                    source: "dev-laptop".to_string(),
+                    ..create_test_event()
+                },
+            ])
+            .await;
+        state
+    }
+
+    #[tokio::test]
+    async fn scoped_token_only_sees_events_in_scope() {
+        let app = create_router(state_with_scoped_token().await);
+
+        let response = get(app.clone(), "/events?token=ci-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(page.events.len(), 1);
+        assert_eq!(page.events[0].source, "ci-linux");
+
+        // Asking for an out-of-scope source does not widen the scope
+        let response = get(app.clone(), "/events?token=ci-token&source=dev-laptop", &[]).await;
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert!(page.events.is_empty());
+
+        // The shared token is unrestricted
+        let response = get(app, "/events?token=test-token", &[]).await;
+        let page: EventsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(page.events.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn scoped_token_only_sees_sessions_in_scope() {
+        let app = create_router(state_with_scoped_token().await);
+
+        let response = get(app, "/sessions", &[("Authorization", "Bearer ci-token")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: SessionsResponse = serde_json::from_str(&body_string(response).await).unwrap();
+        assert_eq!(body.sessions.len(), 1);
+        assert_eq!(body.sessions[0].source, "ci-linux");
+    }
+
+    #[tokio::test]
+    async fn scoped_token_cannot_read_metrics() {
+        let app = create_router(state_with_scoped_token().await);
+
+        let response = get(app.clone(), "/metrics?token=ci-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(error_code(response).await, "insufficient_scope");
+
+        let response = get(app, "/metrics?token=test-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn scoped_token_cannot_read_stats() {
+        let app = create_router(state_with_scoped_token().await);
+
+        let response = get(app.clone(), "/stats?token=ci-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = get(app, "/stats", &[("Authorization", "Bearer test-token")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn expired_and_removed_tokens_are_rejected() {
+        let state = state_with_scoped_token().await;
+        let app = create_router(state.clone());
+
+        state
+            .tokens
+            .replace(vec![SubscriberToken::new("old", "old-token")
+                .with_expires(Utc::now() - chrono::Duration::seconds(1))]);
+
+        let response = get(app.clone(), "/events?token=old-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "token_expired");
+
+        let response = get(app.clone(), "/events?token=ci-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(response).await, "invalid_token");
+
+        let response = get(app, "/events?token=test-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn tokens_file_alone_enables_subscriber_auth() {
+        let (_, public_key) = create_test_keypair();
+        let config = Config {
+            subscriber_token: None,
+            subscriber_tokens_file: Some("tokens.json".into()),
+            ..test_config_with_auth(&public_key)
+        };
+        let state = AppState::new(config);
+        let app = create_router(state.clone());
+
+        // Until the file is loaded there is nothing to authenticate against
+        let response = get(app.clone(), "/sessions?token=test-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        state
+            .tokens
+            .replace(vec![SubscriberToken::new("dash", "dash-token")]);
+        let response = get(app, "/sessions?token=dash-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // ========================================================================
+    // GET /metrics tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn metrics_reports_ingest_and_rejections() {
+        let state = AppState::with_components(
+            test_config_no_auth(),
+            EventBroadcaster::new(),
+            RateLimiter::new(1.0, 1),
+        );
+        let app = create_router(state);
+
+        let body = serde_json::to_string(&create_test_event()).unwrap();
+        for _ in 0..2 {
+            app.clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/events")
+                        .header("Content-Type", "application/json")
+                        .header(HEADER_SOURCE_ID, "test-source")
+                        .body(Body::from(body.clone()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let response = get(app, "/metrics", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            metrics::CONTENT_TYPE
+        );
+        let text = body_string(response).await;
+        assert!(text
+            .contains(r#"vibetea_events_accepted_total{source="test-source",type="session"} 1"#));
+        assert!(text.contains(r#"vibetea_rate_limited_total{source="test-source"} 1"#));
+        assert!(text.contains("vibetea_request_body_bytes_count 2"));
+        assert!(text.contains("vibetea_ingest_duration_seconds_count 1"));
+        assert!(text.contains("vibetea_connections 0"));
+    }
+
+    #[tokio::test]
+    async fn metrics_counts_signature_failures() {
+        let (_, public_key) = create_test_keypair();
+        let app = create_router(AppState::new(test_config_with_auth(&public_key)));
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/events")
+                    .header(HEADER_SOURCE_ID, "test-source")
+                    .header(HEADER_TIMESTAMP, Utc::now().timestamp().to_string())
+                    .header(HEADER_NONCE, "nonce-1")
+                    .header(HEADER_SIGNATURE, BASE64_STANDARD.encode([0u8; 64]))
+                    .body(Body::from("{}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let response = get(app.clone(), "/metrics", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = get(app, "/metrics?token=test-token", &[]).await;
+        let text = body_string(response).await;
+        assert!(text.contains(r#"vibetea_auth_failures_total{reason="invalid_signature"} 1"#));
+    }
+
+    // ========================================================================
+    // GET /stream tests
+    // ========================================================================
+
+    #[tokio::test]
+    async fn stream_requires_subscriber_token() {
+        let (_, public_key) = create_test_keypair();
+        let app = create_router(AppState::new(test_config_with_auth(&public_key)));
+
+        let response = get(app.clone(), "/stream", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = get(app.clone(), "/stream?token=wrong", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = get(app.clone(), "/stream?token=test-token", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+
+        let response = get(app, "/stream", &[("Authorization", "Bearer test-token")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn stream_rejects_invalid_filter() {
+        let app = create_router(AppState::new(test_config_no_auth()));
+
+        let response = get(app, "/stream?type=bogus", &[]).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "invalid_filter");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stream_sends_heartbeat_comments() {
+        use futures_util::StreamExt;
+
+        let app = create_router(AppState::new(test_config_no_auth()));
+        let response = get(app, "/stream", &[]).await;
+        let mut body = response.into_body().into_data_stream();
+
+        let first = body.next().await.unwrap().unwrap();
+        assert!(first.starts_with(b"event: sessions\n"));
+
+        // Time auto-advances while the stream is idle
+        let heartbeat = body.next().await.unwrap().unwrap();
+        assert_eq!(&heartbeat[..], b": heartbeat\n\n");
+    }
+
+    #[tokio::test]
+    async fn stream_resumes_from_last_event_id() {
+        use futures_util::StreamExt;
+
+        let (state, events) = state_with_history(test_config_no_auth(), 3);
+        let app = create_router(state);
+        let response = get(
+            app,
+            &format!("/stream?since={}", events[0].id),
+            &[(HEADER_LAST_EVENT_ID, events[1].id.as_str())],
+        )
+        .await;
+        let mut body = response.into_body().into_data_stream();
+
+        body.next().await.unwrap().unwrap(); // sessions
+        let replayed = body.next().await.unwrap().unwrap();
+        let replayed = String::from_utf8(replayed.to_vec()).unwrap();
+        assert!(replayed.starts_with(&format!("id: {}\n", events[2].id)));
+        let resumed = body.next().await.unwrap().unwrap();
+        assert!(resumed.starts_with(b"event: resumed\n"));
+    }
+
     // ========================================================================
     // WebSocket query params tests
     // ========================================================================
 
+    /// Creates WebSocket query parameters with no filters.
+    fn ws_params() -> WsQueryParams {
+        WsQueryParams {
+            token: None,
+            source: None,
+            event_type: None,
+            project: None,
+            tool: None,
+            filter: None,
+            since: None,
+            since_ts: None,
+        }
+    }
+
     #[test]
     fn ws_query_params_builds_empty_filter() {
         let params = WsQueryParams {
             token: Some("test".to_string()),
-            source: None,
-            event_type: None,
-            project: None,
+            ..ws_params()
         };
 
-        let filter = params.to_filter();
+        let filter = params.to_filter().unwrap();
         assert!(filter.is_empty());
     }
 
     #[test]
     fn ws_query_params_builds_filter_with_source() {
         let params = WsQueryParams {
-            token: None,
             source: Some("monitor-1".to_string()),
-            event_type: None,
-            project: None,
+            ..ws_params()
         };
 
-        let filter = params.to_filter();
-        assert_eq!(filter.source, Some("monitor-1".to_string()));
+        let filter = params.to_filter().unwrap();
+        assert_eq!(filter, SubscriberFilter::new().with_source("monitor-1"));
     }
 
     #[test]
     fn ws_query_params_builds_filter_with_all_fields() {
         let params = WsQueryParams {
-            token: None,
             source: Some("monitor-1".to_string()),
-            event_type: Some(EventType::Tool),
+            event_type: Some("tool".to_string()),
             project: Some("my-project".to_string()),
+            tool: Some("Bash".to_string()),
+            ..ws_params()
+        };
+
+        let filter = params.to_filter().unwrap();
+        assert_eq!(
+            filter,
+            SubscriberFilter::new()
+                .with_source("monitor-1")
+                .with_event_type(EventType::Tool)
+                .with_project("my-project")
+                .with_tool("Bash")
+        );
+    }
+
+    #[test]
+    fn ws_query_params_parses_sets_and_negation() {
+        let params = WsQueryParams {
+            source: Some("!ci-*".to_string()),
+            event_type: Some("tool,agent_spawn".to_string()),
+            project: Some("web,api".to_string()),
+            tool: Some("!Read".to_string()),
+            ..ws_params()
+        };
+
+        let filter = params.to_filter().unwrap();
+        assert_eq!(
+            filter,
+            SubscriberFilter::new()
+                .without_source("ci-*")
+                .with_event_type(EventType::Tool)
+                .with_event_type(EventType::AgentSpawn)
+                .with_project("web")
+                .with_project("api")
+                .without_tool("Read")
+        );
+    }
+
+    #[test]
+    fn ws_query_params_parses_json_filter() {
+        let params = WsQueryParams {
+            filter: Some(r#"{"type": ["tool", "session"], "project": "web,api"}"#.to_string()),
+            ..ws_params()
         };
 
-        let filter = params.to_filter();
-        assert_eq!(filter.source, Some("monitor-1".to_string()));
-        assert_eq!(filter.event_type, Some(EventType::Tool));
-        assert_eq!(filter.project, Some("my-project".to_string()));
+        let filter = params.to_filter().unwrap();
+        assert_eq!(
+            filter,
+            SubscriberFilter::new()
+                .with_event_type(EventType::Tool)
+                .with_event_type(EventType::Session)
+                .with_project("web")
+                .with_project("api")
+        );
+    }
+
+    #[test]
+    fn ws_query_params_rejects_invalid_filters() {
+        let unknown_type = WsQueryParams {
+            event_type: Some("tool,bogus".to_string()),
+            ..ws_params()
+        };
+        assert!(matches!(
+            unknown_type.to_filter(),
+            Err(FilterError::UnknownEventType(_))
+        ));
+
+        let conflict = WsQueryParams {
+            source: Some("a".to_string()),
+            filter: Some("{}".to_string()),
+            ..ws_params()
+        };
+        assert!(matches!(conflict.to_filter(), Err(FilterError::Conflict)));
+
+        let unknown_field = WsQueryParams {
+            filter: Some(r#"{"scope": {"sources": ["*"]}}"#.to_string()),
+            ..ws_params()
+        };
+        assert!(matches!(
+            unknown_field.to_filter(),
+            Err(FilterError::InvalidJson(_))
+        ));
     }
 
     // ========================================================================
@@ -1221,19 +3550,26 @@ mod tests {
     // ========================================================================
 
     #[test]
-    fn events_payload_single_into_events() {
+    fn events_payload_single_into_values() {
         let event = create_test_event();
-        let payload = EventsPayload::Single(event.clone());
-        let events = payload.into_events();
-        assert_eq!(events.len(), 1);
-        assert_eq!(events[0].id, event.id);
+        let payload = EventsPayload::Single(serde_json::to_value(&event).unwrap());
+        assert!(!payload.is_batch());
+        let values = payload.into_values();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0]["id"], event.id);
     }
 
     #[test]
-    fn events_payload_batch_into_events() {
-        let events = vec![create_test_event(), create_test_event()];
-        let payload = EventsPayload::Batch(events.clone());
-        let result = payload.into_events();
+    fn events_payload_batch_into_values() {
+        let events = [create_test_event(), create_test_event()];
+        let payload = EventsPayload::Batch(
+            events
+                .iter()
+                .map(|event| serde_json::to_value(event).unwrap())
+                .collect(),
+        );
+        assert!(payload.is_batch());
+        let result = payload.into_values();
         assert_eq!(result.len(), 2);
     }
 
@@ -0,0 +1,463 @@
+//! Server-side session tracking.
+//!
+//! The [`SessionRegistry`] folds `session`, `activity`, `tool` and `summary`
+//! events into a per-session view, so clients can ask the server which
+//! sessions are live instead of each re-deriving it from the event stream.
+//!
+//! # Lifecycle
+//!
+//! - A session becomes `active` on its first event
+//! - It becomes `inactive` after [`INACTIVE_THRESHOLD`] without events, and
+//!   `active` again on the next event
+//! - A `summary` event or `session` end event marks it `ended`
+//! - It is removed after [`REMOVAL_THRESHOLD`] without events
+//!
+//! Times are taken from event timestamps, so the registry can be rebuilt by
+//! replaying stored events.
+//!
+//! # Example
+//!
+//! ```rust
+//! use chrono::Utc;
+//! use uuid::Uuid;
+//! use vibetea_server::sessions::{SessionRegistry, SessionStatus};
+//! use vibetea_server::types::{Event, EventPayload, EventType, SessionAction};
+//!
+//! let registry = SessionRegistry::new();
+//! registry.record(&Event::new(
+//!     "macbook-pro".to_string(),
+//!     EventType::Session,
+//!     EventPayload::Session {
+//!         session_id: Uuid::new_v4(),
+//!         action: SessionAction::Started,
+//!         project: "vibetea".to_string(),
+//!     },
+//! ));
+//!
+//! let sessions = registry.snapshot(Utc::now());
+//! assert_eq!(sessions.len(), 1);
+//! assert_eq!(sessions[0].status, SessionStatus::Active);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::types::{Event, EventPayload, SessionAction, ToolStatus};
+
+/// Time without events after which a session is considered inactive.
+pub const INACTIVE_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+/// Time without events after which a session is removed.
+pub const REMOVAL_THRESHOLD: Duration = Duration::from_secs(30 * 60);
+
+/// Lifecycle status of a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStatus {
+    /// Received an event within [`INACTIVE_THRESHOLD`].
+    Active,
+
+    /// No events within [`INACTIVE_THRESHOLD`].
+    Inactive,
+
+    /// Ended by a summary or session end event.
+    Ended,
+}
+
+/// Current state of a single session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    /// Session identifier from the event payloads.
+    pub session_id: Uuid,
+
+    /// Source (monitor) that reported the session.
+    pub source: String,
+
+    /// Project name, once known.
+    pub project: Option<String>,
+
+    /// Timestamp of the first event seen for the session.
+    pub started_at: DateTime<Utc>,
+
+    /// Timestamp of the most recent event for the session.
+    pub last_event_at: DateTime<Utc>,
+
+    /// Lifecycle status as of the snapshot time.
+    pub status: SessionStatus,
+
+    /// Number of events recorded for the session.
+    pub event_count: u64,
+
+    /// Number of completed tool invocations.
+    pub tool_count: u64,
+
+    /// Name of the most recently used tool.
+    pub last_tool: Option<String>,
+}
+
+impl Session {
+    fn new(session_id: Uuid, event: &Event) -> Self {
+        Self {
+            session_id,
+            source: event.source.clone(),
+            project: None,
+            started_at: event.timestamp,
+            last_event_at: event.timestamp,
+            status: SessionStatus::Active,
+            event_count: 0,
+            tool_count: 0,
+            last_tool: None,
+        }
+    }
+
+    /// Returns the status as of `now`, applying the inactivity rule.
+    fn status_at(&self, now: DateTime<Utc>) -> SessionStatus {
+        if self.status == SessionStatus::Active && idle_for(self, now) >= INACTIVE_THRESHOLD {
+            SessionStatus::Inactive
+        } else {
+            self.status
+        }
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        idle_for(self, now) >= REMOVAL_THRESHOLD
+    }
+}
+
+fn idle_for(session: &Session, now: DateTime<Utc>) -> Duration {
+    (now - session.last_event_at).to_std().unwrap_or_default()
+}
+
+/// Thread-safe registry of known sessions.
+///
+/// Cloning is cheap and shares the underlying state.
+#[derive(Debug, Clone, Default)]
+pub struct SessionRegistry {
+    sessions: Arc<RwLock<HashMap<Uuid, Session>>>,
+}
+
+impl SessionRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the registry from an event.
+    ///
+    /// Events without a session-scoped `session`, `activity`, `tool` or
+    /// `summary` payload are ignored.
+    pub fn record(&self, event: &Event) {
+        let (session_id, project) = match &event.payload {
+            EventPayload::Session {
+                session_id,
+                project,
+                ..
+            } => (*session_id, Some(project)),
+            EventPayload::Activity {
+                session_id,
+                project,
+            }
+            | EventPayload::Tool {
+                session_id,
+                project,
+                ..
+            } => (*session_id, project.as_ref()),
+            EventPayload::Summary { session_id, .. } => (*session_id, None),
+            _ => return,
+        };
+
+        let mut sessions = self.write();
+        let session = sessions
+            .entry(session_id)
+            .or_insert_with(|| Session::new(session_id, event));
+
+        session.event_count += 1;
+        session.last_event_at = session.last_event_at.max(event.timestamp);
+        if let Some(project) = project {
+            session.project = Some(project.clone());
+        }
+
+        match &event.payload {
+            EventPayload::Session {
+                action: SessionAction::Started,
+                ..
+            } => {
+                session.started_at = event.timestamp;
+                session.status = SessionStatus::Active;
+            }
+            EventPayload::Session {
+                action: SessionAction::Ended,
+                ..
+            }
+            | EventPayload::Summary { .. } => {
+                session.status = SessionStatus::Ended;
+            }
+            EventPayload::Tool { tool, status, .. } => {
+                if *status == ToolStatus::Completed {
+                    session.tool_count += 1;
+                }
+                session.last_tool = Some(tool.clone());
+                reactivate(session);
+            }
+            _ => reactivate(session),
+        }
+    }
+
+    /// Returns all sessions that have not expired as of `now`, most recently
+    /// active first.
+    #[must_use]
+    pub fn snapshot(&self, now: DateTime<Utc>) -> Vec<Session> {
+        let mut sessions: Vec<Session> = self
+            .read()
+            .values()
+            .filter(|session| !session.is_expired(now))
+            .map(|session| Session {
+                status: session.status_at(now),
+                ..session.clone()
+            })
+            .collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.last_event_at));
+        sessions
+    }
+
+    /// Returns a single session as of `now`, if it exists and has not expired.
+    #[must_use]
+    pub fn get(&self, session_id: &Uuid, now: DateTime<Utc>) -> Option<Session> {
+        self.read()
+            .get(session_id)
+            .filter(|session| !session.is_expired(now))
+            .map(|session| Session {
+                status: session.status_at(now),
+                ..session.clone()
+            })
+    }
+
+    /// Removes sessions that have expired as of `now`.
+    ///
+    /// Returns the number of sessions removed.
+    pub fn prune(&self, now: DateTime<Utc>) -> usize {
+        let mut sessions = self.write();
+        let before = sessions.len();
+        sessions.retain(|_, session| !session.is_expired(now));
+        before - sessions.len()
+    }
+
+    /// Returns the number of tracked sessions, including expired ones not
+    /// yet pruned.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Returns `true` if no sessions are tracked.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Spawns a background task that periodically prunes expired sessions.
+    pub fn spawn_cleanup_task(&self, cleanup_interval: Duration) -> tokio::task::JoinHandle<()> {
+        let registry = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(cleanup_interval);
+
+            loop {
+                interval.tick().await;
+                let removed = registry.prune(Utc::now());
+                if removed > 0 {
+                    tracing::debug!(removed_count = removed, "Pruned expired sessions");
+                }
+            }
+        })
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<Uuid, Session>> {
+        self.sessions
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<Uuid, Session>> {
+        self.sessions
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Marks an inactive session active again; ended sessions stay ended.
+fn reactivate(session: &mut Session) {
+    if session.status != SessionStatus::Ended {
+        session.status = SessionStatus::Active;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::EventType;
+    use chrono::Duration as ChronoDuration;
+
+    fn event_at(payload: EventPayload, event_type: EventType, timestamp: DateTime<Utc>) -> Event {
+        Event::new("macbook-pro".to_string(), event_type, payload)
+            .with_id("evt_k7m2n9p4q1r6s3t8u5v0")
+            .with_timestamp(timestamp)
+    }
+
+    fn start(session_id: Uuid, at: DateTime<Utc>) -> Event {
+        event_at(
+            EventPayload::Session {
+                session_id,
+                action: SessionAction::Started,
+                project: "vibetea".to_string(),
+            },
+            EventType::Session,
+            at,
+        )
+    }
+
+    fn tool(session_id: Uuid, name: &str, at: DateTime<Utc>) -> Event {
+        event_at(
+            EventPayload::Tool {
+                session_id,
+                tool: name.to_string(),
+                status: ToolStatus::Completed,
+                context: None,
+                project: None,
+            },
+            EventType::Tool,
+            at,
+        )
+    }
+
+    fn summary(session_id: Uuid, at: DateTime<Utc>) -> Event {
+        event_at(
+            EventPayload::Summary {
+                session_id,
+                summary: "done".to_string(),
+            },
+            EventType::Summary,
+            at,
+        )
+    }
+
+    #[test]
+    fn tracks_project_and_tool_counts() {
+        let registry = SessionRegistry::new();
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+
+        registry.record(&start(id, now));
+        registry.record(&tool(id, "Read", now));
+        registry.record(&tool(id, "Edit", now));
+
+        let session = registry.get(&id, now).unwrap();
+        assert_eq!(session.project.as_deref(), Some("vibetea"));
+        assert_eq!(session.event_count, 3);
+        assert_eq!(session.tool_count, 2);
+        assert_eq!(session.last_tool.as_deref(), Some("Edit"));
+        assert_eq!(session.status, SessionStatus::Active);
+    }
+
+    #[test]
+    fn session_becomes_inactive_after_threshold() {
+        let registry = SessionRegistry::new();
+        let id = Uuid::new_v4();
+        let started = Utc::now() - ChronoDuration::minutes(6);
+
+        registry.record(&start(id, started));
+        assert_eq!(
+            registry.get(&id, Utc::now()).unwrap().status,
+            SessionStatus::Inactive
+        );
+
+        registry.record(&tool(id, "Read", Utc::now()));
+        assert_eq!(
+            registry.get(&id, Utc::now()).unwrap().status,
+            SessionStatus::Active
+        );
+    }
+
+    #[test]
+    fn summary_ends_session_and_it_stays_ended() {
+        let registry = SessionRegistry::new();
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+
+        registry.record(&start(id, now));
+        registry.record(&summary(id, now));
+        registry.record(&tool(id, "Read", now));
+
+        assert_eq!(registry.get(&id, now).unwrap().status, SessionStatus::Ended);
+    }
+
+    #[test]
+    fn expired_sessions_are_hidden_and_pruned() {
+        let registry = SessionRegistry::new();
+        let stale = Uuid::new_v4();
+        let fresh = Uuid::new_v4();
+        let now = Utc::now();
+
+        registry.record(&start(stale, now - ChronoDuration::minutes(31)));
+        registry.record(&start(fresh, now));
+
+        let snapshot = registry.snapshot(now);
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].session_id, fresh);
+
+        assert_eq!(registry.prune(now), 1);
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn snapshot_orders_most_recent_first() {
+        let registry = SessionRegistry::new();
+        let older = Uuid::new_v4();
+        let newer = Uuid::new_v4();
+        let now = Utc::now();
+
+        registry.record(&start(older, now - ChronoDuration::minutes(2)));
+        registry.record(&start(newer, now));
+
+        let ids: Vec<Uuid> = registry
+            .snapshot(now)
+            .iter()
+            .map(|s| s.session_id)
+            .collect();
+        assert_eq!(ids, vec![newer, older]);
+    }
+
+    #[test]
+    fn ignores_events_without_session_payload() {
+        let registry = SessionRegistry::new();
+        registry.record(&event_at(
+            EventPayload::Agent {
+                session_id: Uuid::new_v4(),
+                state: "thinking".to_string(),
+            },
+            EventType::Agent,
+            Utc::now(),
+        ));
+
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn session_serializes_camel_case() {
+        let registry = SessionRegistry::new();
+        let id = Uuid::new_v4();
+        registry.record(&start(id, Utc::now()));
+
+        let json = serde_json::to_value(registry.get(&id, Utc::now()).unwrap()).unwrap();
+        assert_eq!(json["sessionId"], id.to_string());
+        assert_eq!(json["status"], "active");
+        assert!(json["lastEventAt"].is_string());
+    }
+}
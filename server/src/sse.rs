@@ -0,0 +1,445 @@
+//! Server-Sent Events subscriptions for the VibeTea server.
+//!
+//! `GET /stream` serves the same filtered feed as `/ws` as a
+//! `text/event-stream` response, for clients that cannot hold a WebSocket
+//! (corporate proxies, `curl`-based scripts, serverless functions). The feed
+//! is read-only: the filter is fixed by the query string for the lifetime of
+//! the stream.
+//!
+//! # Wire Format
+//!
+//! Events are sent as unnamed SSE messages whose `id:` is the event ID and
+//! whose `data:` is the JSON-encoded [`Event`], exactly as it was ingested.
+//! The session snapshot and resume acknowledgement are sent as named events
+//! carrying the same JSON as the corresponding
+//! [`ServerMessage`](crate::ws::ServerMessage), without an `id:` so they do
+//! not move the client's resume position:
+//!
+//! ```text
+//! event: sessions
+//! data: {"type":"sessions","sessions":[...]}
+//!
+//! id: evt_k7m2n9p4q1r6s3t8u5v0
+//! data: {"id":"evt_k7m2n9p4q1r6s3t8u5v0","source":"monitor-1",...}
+//!
+//! event: resumed
+//! data: {"type":"resumed","replayed":12,"gap":false}
+//!
+//! : heartbeat
+//! ```
+//!
+//! Unscoped subscribers also receive the server's periodic `1h` rollup as a
+//! named `stats` event (see [`crate::stats`]).
+//!
+//! A `: heartbeat` comment is sent every [`HEARTBEAT_INTERVAL`] so that idle
+//! streams are not closed by intermediaries.
+//!
+//! # Resuming
+//!
+//! Browsers' `EventSource` reconnects automatically and sends the last `id:`
+//! it saw in the `Last-Event-ID` header. The server replays the missed events
+//! that match the filter, followed by a `resumed` event, and then continues
+//! with live events. Clients can also pass `since` or `since_ts` on the query
+//! string, as with `/ws`; `Last-Event-ID` takes precedence.
+//!
+//! # Example
+//!
+//! ```text
+//! curl -N -H 'Authorization: Bearer <token>' \
+//!     'http://localhost:8080/stream?type=tool,session&project=vibetea'
+//! ```
+
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
+use chrono::Utc;
+use futures_util::stream::{self, Stream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::watch;
+use tracing::{debug, error, info, trace, warn};
+
+use crate::broadcast::{ClientConnection, SubscriberFilter};
+use crate::rate_limit::ConnectionPermit;
+use crate::replay::{subscribe_from, Replay, ResumePoint};
+use crate::routes::AppState;
+use crate::sessions::Session;
+use crate::stats::{next_rollup, Stats};
+use crate::types::Event;
+use crate::ws::ServerMessage;
+
+/// Interval between heartbeat comments on an otherwise idle stream.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Text of the heartbeat comment.
+const HEARTBEAT_TEXT: &str = "heartbeat";
+
+/// Builds the `text/event-stream` response for an authenticated subscriber.
+///
+/// Sends the session snapshot, replays missed events if `resume` is set,
+/// then streams matching live events until the client disconnects or the
+/// broadcaster closes. `permit` is held for the lifetime of the stream.
+pub(crate) fn event_stream(
+    state: AppState,
+    filter: SubscriberFilter,
+    resume: Option<ResumePoint>,
+    permit: Option<ConnectionPermit>,
+) -> Sse<impl Stream<Item = Result<SseEvent, Infallible>>> {
+    info!("SSE client connected");
+
+    let feed = Feed::new(state, filter, resume.as_ref(), permit);
+    let stream = stream::unfold(feed, |mut feed| async move {
+        loop {
+            let message = feed.next_message().await?;
+            if let Some(event) = message.to_sse() {
+                return Some((Ok(event), feed));
+            }
+        }
+    });
+
+    Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(HEARTBEAT_INTERVAL)
+            .text(HEARTBEAT_TEXT),
+    )
+}
+
+/// A message queued for an SSE client.
+#[derive(Debug, Clone, PartialEq)]
+enum FeedMessage {
+    /// A matching event.
+    Event(Event),
+
+    /// A control message, sent as a named event.
+    Control(ServerMessage),
+}
+
+impl FeedMessage {
+    /// Encodes the message as an SSE event.
+    ///
+    /// Returns `None` if the message cannot be serialized.
+    fn to_sse(&self) -> Option<SseEvent> {
+        let result = match self {
+            Self::Event(event) => {
+                let sse = SseEvent::default();
+                // `id:` must be a single line; the event is still delivered
+                // without one, it just cannot be resumed from
+                let sse = if is_valid_sse_id(&event.id) {
+                    sse.id(&event.id)
+                } else {
+                    sse
+                };
+                sse.json_data(event)
+            }
+            Self::Control(message) => SseEvent::default()
+                .event(control_name(message))
+                .json_data(message),
+        };
+
+        match result {
+            Ok(sse) => Some(sse),
+            Err(err) => {
+                error!(error = %err, "Failed to serialize SSE message");
+                None
+            }
+        }
+    }
+}
+
+/// Returns the SSE event name for a control message, matching its `type`.
+fn control_name(message: &ServerMessage) -> &'static str {
+    match message {
+        ServerMessage::Sessions { .. } => "sessions",
+        ServerMessage::Subscribed { .. } => "subscribed",
+        ServerMessage::Unsubscribed { .. } => "unsubscribed",
+        ServerMessage::Resumed { .. } => "resumed",
+        ServerMessage::Pong { .. } => "pong",
+        ServerMessage::Lagged { .. } => "lagged",
+        ServerMessage::Stats { .. } => "stats",
+        ServerMessage::Error { .. } => "control_error",
+    }
+}
+
+/// Returns `true` if `id` can be sent as an SSE `id:` field.
+fn is_valid_sse_id(id: &str) -> bool {
+    !id.contains(['\n', '\r', '\0'])
+}
+
+/// Per-stream state.
+struct Feed {
+    state: AppState,
+
+    /// Filter applied to every event, including the token scope.
+    filter: SubscriberFilter,
+
+    /// Live events from the broadcaster.
+    rx: Receiver<Event>,
+
+    /// Published rollups, if the subscriber may receive them.
+    rollups: Option<watch::Receiver<Option<Arc<Stats>>>>,
+
+    /// Messages ready to send, ahead of anything still in `rx`.
+    queue: VecDeque<FeedMessage>,
+
+    /// Replay in progress, sent after `queue` and ahead of `rx`.
+    resuming: Option<Resuming>,
+
+    /// ID of the last event received from the broadcaster (matching or not),
+    /// used to recover from lag.
+    last_seen: Option<String>,
+
+    /// Connection slot, released when the stream is dropped.
+    _permit: Option<ConnectionPermit>,
+
+    /// Counts the stream as a connected client until it is dropped.
+    _connection: ClientConnection,
+}
+
+impl Feed {
+    /// Subscribes to the broadcaster and queues the session snapshot, followed
+    /// by any replay.
+    fn new(
+        state: AppState,
+        filter: SubscriberFilter,
+        resume: Option<&ResumePoint>,
+        permit: Option<ConnectionPermit>,
+    ) -> Self {
+        // Subscribe before taking the snapshot so events published in the
+        // meantime are kept; a resume replaces this receiver
+        let rx = state.broadcaster.subscribe();
+        // Rollups cover every source, so only unscoped subscribers receive them
+        let rollups = filter
+            .scope
+            .is_unrestricted()
+            .then(|| state.stats.subscribe());
+        let connection = state.clients.connect();
+        let mut feed = Self {
+            state,
+            filter,
+            rx,
+            rollups,
+            queue: VecDeque::new(),
+            resuming: None,
+            last_seen: None,
+            _permit: permit,
+            _connection: connection,
+        };
+
+        let sessions = feed.session_snapshot();
+        feed.queue
+            .push_back(FeedMessage::Control(ServerMessage::Sessions { sessions }));
+        if let Some(point) = resume {
+            feed.resume(point);
+        }
+        feed
+    }
+
+    /// Returns the sessions matching the filter.
+    fn session_snapshot(&self) -> Vec<Session> {
+        let mut sessions = self.state.sessions.snapshot(Utc::now());
+        sessions.retain(|session| self.filter.matches_session(session));
+        sessions
+    }
+
+    /// Waits for the next message to send.
+    ///
+    /// Returns `None` once the broadcaster has closed.
+    async fn next_message(&mut self) -> Option<FeedMessage> {
+        loop {
+            if let Some(message) = self.queue.pop_front() {
+                return Some(message);
+            }
+
+            if let Some(mut resuming) = self.resuming.take() {
+                match resuming.replay.next().await {
+                    Some(event) => {
+                        if self.push_event(event) {
+                            resuming.replayed += 1;
+                        }
+                        self.resuming = Some(resuming);
+                    }
+                    None => {
+                        let Resuming { replay, replayed } = resuming;
+                        let gap = replay.gap();
+                        info!(replayed, gap, "Replayed missed events to SSE client");
+                        self.queue
+                            .push_back(FeedMessage::Control(ServerMessage::Resumed {
+                                replayed,
+                                gap,
+                            }));
+                    }
+                }
+                continue;
+            }
+
+            let result = tokio::select! {
+                result = self.rx.recv() => result,
+                stats = next_rollup(&mut self.rollups) => {
+                    return Some(FeedMessage::Control(ServerMessage::Stats {
+                        stats: Box::new(Stats::clone(&stats)),
+                    }));
+                }
+            };
+
+            match result {
+                Ok(event) => {
+                    self.push_event(event);
+                }
+                Err(RecvError::Lagged(count)) => {
+                    warn!(skipped = count, "SSE client lagged, skipped events");
+                    self.state.metrics.record_lag("sse", count);
+                    // Recover the skipped events from history when possible
+                    if let Some(last_seen) = self.last_seen.clone() {
+                        self.resume(&ResumePoint::AfterEvent(last_seen));
+                    }
+                }
+                Err(RecvError::Closed) => {
+                    debug!("Event broadcaster closed");
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Queues `event` if it matches the filter.
+    ///
+    /// Returns `true` if the event was queued.
+    fn push_event(&mut self, event: Event) -> bool {
+        self.last_seen = Some(event.id.clone());
+
+        if !self.filter.matches(&event) {
+            trace!(event_id = %event.id, "Event filtered out");
+            return false;
+        }
+        self.queue.push_back(FeedMessage::Event(event));
+        true
+    }
+
+    /// Re-subscribes from `point`. Replayed events and a `resumed` message
+    /// are sent before any live events.
+    fn resume(&mut self, point: &ResumePoint) {
+        let (replay, rx) =
+            subscribe_from(&self.state.broadcaster, self.state.store.as_ref(), point);
+        self.rx = rx;
+        self.resuming = Some(Resuming {
+            replay,
+            replayed: 0,
+        });
+    }
+}
+
+/// A replay being sent to an SSE client.
+struct Resuming {
+    replay: Replay,
+
+    /// Replayed events that matched the filter so far.
+    replayed: usize,
+}
+
+impl Drop for Feed {
+    fn drop(&mut self) {
+        info!("SSE client disconnected");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::types::{EventPayload, EventType};
+    use uuid::Uuid;
+
+    fn state() -> AppState {
+        AppState::new(Config {
+            unsafe_no_auth: true,
+            ..Config::default()
+        })
+    }
+
+    fn event(n: usize, project: &str) -> Event {
+        Event::new(
+            "monitor-1".to_string(),
+            EventType::Activity,
+            EventPayload::Activity {
+                session_id: Uuid::new_v4(),
+                project: Some(project.to_string()),
+            },
+        )
+        .with_id(format!("evt_{n:0>20}"))
+    }
+
+    fn event_id(message: Option<FeedMessage>) -> String {
+        match message {
+            Some(FeedMessage::Event(event)) => event.id,
+            other => panic!("expected an event, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_sessions_then_matching_live_events() {
+        let state = state();
+        let filter = SubscriberFilter::new().with_project("alpha");
+        let mut feed = Feed::new(state.clone(), filter, None, None);
+
+        assert!(matches!(
+            feed.next_message().await,
+            Some(FeedMessage::Control(ServerMessage::Sessions { .. }))
+        ));
+
+        state
+            .publish(vec![event(0, "beta"), event(1, "alpha")])
+            .await;
+        assert_eq!(event_id(feed.next_message().await), event(1, "").id);
+        assert_eq!(feed.last_seen.as_deref(), Some(event(1, "").id.as_str()));
+    }
+
+    #[tokio::test]
+    async fn replays_filtered_events_after_resume_point() {
+        let state = state();
+        state
+            .publish(vec![event(0, "alpha"), event(1, "beta"), event(2, "alpha")])
+            .await;
+
+        let point = ResumePoint::AfterEvent(event(0, "").id);
+        let filter = SubscriberFilter::new().with_project("alpha");
+        let mut feed = Feed::new(state, filter, Some(&point), None);
+
+        assert!(matches!(
+            feed.next_message().await,
+            Some(FeedMessage::Control(ServerMessage::Sessions { .. }))
+        ));
+        assert_eq!(event_id(feed.next_message().await), event(2, "").id);
+        assert_eq!(
+            feed.next_message().await,
+            Some(FeedMessage::Control(ServerMessage::Resumed {
+                replayed: 1,
+                gap: false
+            }))
+        );
+    }
+
+    #[test]
+    fn control_messages_are_named_events() {
+        let message = ServerMessage::Resumed {
+            replayed: 0,
+            gap: false,
+        };
+        assert_eq!(control_name(&message), "resumed");
+        assert!(FeedMessage::Control(message).to_sse().is_some());
+    }
+
+    #[test]
+    fn multi_line_ids_are_not_sent() {
+        assert!(is_valid_sse_id("evt_k7m2n9p4q1r6s3t8u5v0"));
+        assert!(!is_valid_sse_id("evt_1\ndata: injected"));
+        assert!(!is_valid_sse_id("evt_1\r"));
+
+        let mut bad = event(0, "alpha");
+        bad.id = "evt_1\nid: evt_2".to_string();
+        // Would panic if the ID were passed through
+        assert!(FeedMessage::Event(bad).to_sse().is_some());
+    }
+}
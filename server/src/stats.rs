@@ -0,0 +1,602 @@
+//! Rolling aggregates of the event stream.
+//!
+//! The [`StatsAggregator`] folds every accepted event into per-minute and
+//! per-hour buckets, so dashboards can ask the server for team-wide totals
+//! instead of each recomputing them from the raw stream. Each bucket counts:
+//!
+//! - events by type
+//! - completed tool invocations by tool name
+//! - tokens used per model per source
+//! - distinct sessions that sent an event
+//!
+//! Minute buckets are kept for an hour and hour buckets for [`RETENTION`].
+//! A [`StatsWindow`] of `1h` is summed from minute buckets, `24h` and `7d`
+//! from hour buckets. Buckets are keyed by event timestamp, so the
+//! aggregates can be rebuilt by replaying stored events.
+//!
+//! # Token Usage
+//!
+//! Monitors report cumulative per-model counters in `token_usage` events. The
+//! aggregator remembers the last report for each source and model and counts
+//! the increase; a counter that goes down is treated as reset. The first
+//! report for a source and model only sets the baseline.
+//! `model_distribution` events carry the same counters and are not counted
+//! again.
+//!
+//! # Pushing
+//!
+//! [`StatsAggregator::spawn_push_task`] periodically publishes the `1h`
+//! rollup, which `/ws` and `/stream` forward to unscoped subscribers as a
+//! `stats` message (see [`crate::ws`]).
+//!
+//! # Example
+//!
+//! ```rust
+//! use chrono::Utc;
+//! use uuid::Uuid;
+//! use vibetea_server::stats::{StatsAggregator, StatsWindow};
+//! use vibetea_server::types::{Event, EventPayload, EventType, ToolStatus};
+//!
+//! let stats = StatsAggregator::new();
+//! stats.record(&Event::new(
+//!     "macbook-pro".to_string(),
+//!     EventType::Tool,
+//!     EventPayload::Tool {
+//!         session_id: Uuid::new_v4(),
+//!         tool: "Read".to_string(),
+//!         status: ToolStatus::Completed,
+//!         context: None,
+//!         project: Some("vibetea".to_string()),
+//!     },
+//! ));
+//!
+//! let rollup = stats.snapshot(StatsWindow::Hour, Utc::now());
+//! assert_eq!(rollup.total_events, 1);
+//! assert_eq!(rollup.tool_usage["Read"], 1);
+//! assert_eq!(rollup.active_sessions, 1);
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::types::{Event, EventPayload, EventType, TokenUsageSummary, ToolStatus};
+
+/// How long hour buckets are kept, and the longest [`StatsWindow`].
+pub const RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Default interval between pushed rollups.
+pub const DEFAULT_PUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Seconds per minute bucket.
+const MINUTE_SECS: i64 = 60;
+
+/// Seconds per hour bucket.
+const HOUR_SECS: i64 = 3600;
+
+/// Number of minute buckets kept.
+const MINUTE_BUCKETS: i64 = 60;
+
+/// Number of hour buckets kept.
+const HOUR_BUCKETS: i64 = 7 * 24;
+
+/// Time span covered by a rollup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatsWindow {
+    /// The last hour, in minute buckets.
+    #[default]
+    #[serde(rename = "1h")]
+    Hour,
+
+    /// The last 24 hours, in hour buckets.
+    #[serde(rename = "24h")]
+    Day,
+
+    /// The last 7 days, in hour buckets.
+    #[serde(rename = "7d")]
+    Week,
+}
+
+impl StatsWindow {
+    /// Returns the bucket size and number of buckets summed for the window.
+    const fn buckets(self) -> (i64, i64) {
+        match self {
+            Self::Hour => (MINUTE_SECS, MINUTE_BUCKETS),
+            Self::Day => (HOUR_SECS, 24),
+            Self::Week => (HOUR_SECS, HOUR_BUCKETS),
+        }
+    }
+}
+
+/// Aggregates over a [`StatsWindow`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    /// The window covered.
+    pub window: StatsWindow,
+
+    /// Start of the oldest bucket in the window.
+    pub from: DateTime<Utc>,
+
+    /// Time the rollup was taken.
+    pub to: DateTime<Utc>,
+
+    /// Number of events.
+    pub total_events: u64,
+
+    /// Number of events of each type.
+    pub events_by_type: BTreeMap<EventType, u64>,
+
+    /// Number of completed invocations of each tool.
+    pub tool_usage: BTreeMap<String, u64>,
+
+    /// Tokens used, by source and then by model.
+    pub tokens: BTreeMap<String, BTreeMap<String, TokenUsageSummary>>,
+
+    /// Number of distinct sessions that sent an event.
+    pub active_sessions: usize,
+}
+
+/// Counts for one minute or hour.
+#[derive(Debug, Default)]
+struct Bucket {
+    events_by_type: BTreeMap<EventType, u64>,
+    tool_usage: BTreeMap<String, u64>,
+    tokens: BTreeMap<String, BTreeMap<String, TokenUsageSummary>>,
+    sessions: HashSet<String>,
+}
+
+impl Bucket {
+    fn record(&mut self, event: &Event, tokens: Option<&TokenUsageSummary>) {
+        *self.events_by_type.entry(event.event_type).or_default() += 1;
+
+        if let EventPayload::Tool {
+            tool,
+            status: ToolStatus::Completed,
+            ..
+        } = &event.payload
+        {
+            *self.tool_usage.entry(tool.clone()).or_default() += 1;
+        }
+
+        if let (EventPayload::TokenUsage(usage), Some(delta)) = (&event.payload, tokens) {
+            let total = self
+                .tokens
+                .entry(event.source.clone())
+                .or_default()
+                .entry(usage.model.clone())
+                .or_default();
+            add_tokens(total, delta);
+        }
+
+        if let Some(session_id) = session_id(&event.payload) {
+            self.sessions.insert(session_id);
+        }
+    }
+}
+
+/// Bucketed counts and token baselines.
+#[derive(Debug, Default)]
+struct Rollups {
+    /// Minute buckets by start time (Unix seconds).
+    minutes: BTreeMap<i64, Bucket>,
+
+    /// Hour buckets by start time (Unix seconds).
+    hours: BTreeMap<i64, Bucket>,
+
+    /// Last cumulative token counters reported per source and model.
+    token_counters: HashMap<(String, String), TokenUsageSummary>,
+}
+
+/// Thread-safe rolling aggregates of the event stream.
+///
+/// Cloning is cheap and shares the underlying state.
+#[derive(Debug, Clone)]
+pub struct StatsAggregator {
+    rollups: Arc<RwLock<Rollups>>,
+    updates: Arc<watch::Sender<Option<Arc<Stats>>>>,
+}
+
+impl Default for StatsAggregator {
+    fn default() -> Self {
+        Self {
+            rollups: Arc::default(),
+            updates: Arc::new(watch::Sender::new(None)),
+        }
+    }
+}
+
+impl StatsAggregator {
+    /// Creates an empty aggregator.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an event to the minute and hour buckets containing its timestamp.
+    pub fn record(&self, event: &Event) {
+        let mut guard = self.write();
+        let rollups = &mut *guard;
+
+        let tokens = match &event.payload {
+            EventPayload::TokenUsage(usage) => {
+                let current = TokenUsageSummary {
+                    input_tokens: usage.input_tokens,
+                    output_tokens: usage.output_tokens,
+                    cache_read_tokens: usage.cache_read_tokens,
+                    cache_creation_tokens: usage.cache_creation_tokens,
+                };
+                let key = (event.source.clone(), usage.model.clone());
+                rollups
+                    .token_counters
+                    .insert(key, current.clone())
+                    .map(|previous| token_delta(&previous, &current))
+            }
+            _ => None,
+        };
+
+        let timestamp = event.timestamp.timestamp();
+        for (buckets, size, keep) in [
+            (&mut rollups.minutes, MINUTE_SECS, MINUTE_BUCKETS),
+            (&mut rollups.hours, HOUR_SECS, HOUR_BUCKETS),
+        ] {
+            let start = timestamp.div_euclid(size) * size;
+            let newest = buckets.last_key_value().map_or(start, |(&key, _)| key);
+            if start > newest {
+                // Drop buckets that have aged out relative to the new one
+                let oldest = start - (keep - 1) * size;
+                buckets.retain(|&key, _| key >= oldest);
+            } else if start < newest - (keep - 1) * size {
+                continue;
+            }
+            buckets
+                .entry(start)
+                .or_default()
+                .record(event, tokens.as_ref());
+        }
+    }
+
+    /// Returns the aggregates for `window` as of `now`.
+    #[must_use]
+    pub fn snapshot(&self, window: StatsWindow, now: DateTime<Utc>) -> Stats {
+        let (size, count) = window.buckets();
+        let from = now.timestamp().div_euclid(size) * size - (count - 1) * size;
+
+        let rollups = self.read();
+        let buckets = if size == MINUTE_SECS {
+            &rollups.minutes
+        } else {
+            &rollups.hours
+        };
+
+        let mut stats = Stats {
+            window,
+            from: DateTime::from_timestamp(from, 0).unwrap_or(now),
+            to: now,
+            total_events: 0,
+            events_by_type: BTreeMap::new(),
+            tool_usage: BTreeMap::new(),
+            tokens: BTreeMap::new(),
+            active_sessions: 0,
+        };
+        let mut sessions = HashSet::new();
+
+        for bucket in buckets
+            .range(from..=now.timestamp())
+            .map(|(_, bucket)| bucket)
+        {
+            for (&event_type, &count) in &bucket.events_by_type {
+                *stats.events_by_type.entry(event_type).or_default() += count;
+                stats.total_events += count;
+            }
+            for (tool, &count) in &bucket.tool_usage {
+                *stats.tool_usage.entry(tool.clone()).or_default() += count;
+            }
+            for (source, models) in &bucket.tokens {
+                let totals = stats.tokens.entry(source.clone()).or_default();
+                for (model, usage) in models {
+                    add_tokens(totals.entry(model.clone()).or_default(), usage);
+                }
+            }
+            sessions.extend(bucket.sessions.iter().map(String::as_str));
+        }
+        stats.active_sessions = sessions.len();
+
+        stats
+    }
+
+    /// Returns a receiver for rollups published by
+    /// [`spawn_push_task`](Self::spawn_push_task).
+    ///
+    /// The receiver holds `None` until the first rollup is published.
+    #[must_use]
+    pub fn subscribe(&self) -> watch::Receiver<Option<Arc<Stats>>> {
+        self.updates.subscribe()
+    }
+
+    /// Publishes the current `1h` rollup to subscribers.
+    pub fn publish(&self, now: DateTime<Utc>) {
+        let stats = self.snapshot(StatsWindow::Hour, now);
+        self.updates.send_replace(Some(Arc::new(stats)));
+    }
+
+    /// Spawns a background task that publishes the `1h` rollup every
+    /// `push_interval`.
+    pub fn spawn_push_task(&self, push_interval: Duration) -> tokio::task::JoinHandle<()> {
+        let aggregator = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(push_interval);
+            // The first tick completes immediately; skip it so the first
+            // rollup covers a full interval
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                aggregator.publish(Utc::now());
+                tracing::trace!("Published stats rollup");
+            }
+        })
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Rollups> {
+        self.rollups
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Rollups> {
+        self.rollups
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Waits for the next rollup published on `updates`.
+///
+/// Never completes if `updates` is `None` or the aggregator is dropped, so it
+/// can be used as a `select!` branch for subscribers that do not get stats.
+pub(crate) async fn next_rollup(
+    updates: &mut Option<watch::Receiver<Option<Arc<Stats>>>>,
+) -> Arc<Stats> {
+    loop {
+        let Some(rx) = updates.as_mut() else {
+            return std::future::pending().await;
+        };
+        if rx.changed().await.is_err() {
+            *updates = None;
+            continue;
+        }
+        if let Some(stats) = rx.borrow_and_update().clone() {
+            return stats;
+        }
+    }
+}
+
+/// Returns the session an event belongs to, if any.
+fn session_id(payload: &EventPayload) -> Option<String> {
+    match payload {
+        EventPayload::Tool { session_id, .. }
+        | EventPayload::Session { session_id, .. }
+        | EventPayload::Summary { session_id, .. }
+        | EventPayload::Agent { session_id, .. }
+        | EventPayload::Error { session_id, .. }
+        | EventPayload::Activity { session_id, .. } => Some(session_id.to_string()),
+        EventPayload::FileChange(change) => Some(change.session_id.clone()),
+        EventPayload::AgentSpawn(spawn) => Some(spawn.session_id.clone()),
+        EventPayload::SkillInvocation(skill) => Some(skill.session_id.clone()),
+        EventPayload::TodoProgress(todos) => Some(todos.session_id.clone()),
+        EventPayload::ProjectActivity(activity) => Some(activity.session_id.clone()),
+        EventPayload::TokenUsage(_)
+        | EventPayload::SessionMetrics(_)
+        | EventPayload::ModelDistribution(_)
+        | EventPayload::ActivityPattern(_) => None,
+    }
+}
+
+/// Returns the tokens used between two cumulative reports.
+fn token_delta(previous: &TokenUsageSummary, current: &TokenUsageSummary) -> TokenUsageSummary {
+    let delta = |previous: u64, current: u64| current.checked_sub(previous).unwrap_or(current);
+    TokenUsageSummary {
+        input_tokens: delta(previous.input_tokens, current.input_tokens),
+        output_tokens: delta(previous.output_tokens, current.output_tokens),
+        cache_read_tokens: delta(previous.cache_read_tokens, current.cache_read_tokens),
+        cache_creation_tokens: delta(
+            previous.cache_creation_tokens,
+            current.cache_creation_tokens,
+        ),
+    }
+}
+
+fn add_tokens(total: &mut TokenUsageSummary, usage: &TokenUsageSummary) {
+    total.input_tokens += usage.input_tokens;
+    total.output_tokens += usage.output_tokens;
+    total.cache_read_tokens += usage.cache_read_tokens;
+    total.cache_creation_tokens += usage.cache_creation_tokens;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TokenUsageEvent;
+    use chrono::TimeDelta;
+    use uuid::Uuid;
+
+    fn event_at(source: &str, payload: EventPayload, timestamp: DateTime<Utc>) -> Event {
+        let event_type = match &payload {
+            EventPayload::Tool { .. } => EventType::Tool,
+            EventPayload::TokenUsage(_) => EventType::TokenUsage,
+            _ => EventType::Activity,
+        };
+        Event::new(source.to_string(), event_type, payload)
+            .with_id("evt_k7m2n9p4q1r6s3t8u5v0")
+            .with_timestamp(timestamp)
+    }
+
+    fn tool(session_id: Uuid, name: &str, status: ToolStatus) -> EventPayload {
+        EventPayload::Tool {
+            session_id,
+            tool: name.to_string(),
+            status,
+            context: None,
+            project: None,
+        }
+    }
+
+    fn tokens(model: &str, input_tokens: u64, output_tokens: u64) -> EventPayload {
+        EventPayload::TokenUsage(TokenUsageEvent {
+            model: model.to_string(),
+            input_tokens,
+            output_tokens,
+            cache_read_tokens: 0,
+            cache_creation_tokens: 0,
+        })
+    }
+
+    /// A fixed time on a minute boundary, so tests do not straddle buckets.
+    fn base_time() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_767_225_600, 0).unwrap()
+    }
+
+    #[test]
+    fn counts_events_tools_and_sessions() {
+        let stats = StatsAggregator::new();
+        let now = base_time();
+        let session = Uuid::new_v4();
+
+        stats.record(&event_at(
+            "a",
+            tool(session, "Read", ToolStatus::Started),
+            now,
+        ));
+        stats.record(&event_at(
+            "a",
+            tool(session, "Read", ToolStatus::Completed),
+            now,
+        ));
+        stats.record(&event_at(
+            "b",
+            tool(Uuid::new_v4(), "Bash", ToolStatus::Completed),
+            now,
+        ));
+
+        let rollup = stats.snapshot(StatsWindow::Hour, now);
+        assert_eq!(rollup.total_events, 3);
+        assert_eq!(rollup.events_by_type[&EventType::Tool], 3);
+        assert_eq!(rollup.tool_usage["Read"], 1);
+        assert_eq!(rollup.tool_usage["Bash"], 1);
+        assert_eq!(rollup.active_sessions, 2);
+    }
+
+    #[test]
+    fn windows_include_only_their_buckets() {
+        let stats = StatsAggregator::new();
+        let now = base_time();
+        let session = Uuid::new_v4();
+
+        for age in [
+            TimeDelta::minutes(5),
+            TimeDelta::hours(2),
+            TimeDelta::days(3),
+            TimeDelta::days(8),
+        ] {
+            let payload = EventPayload::Activity {
+                session_id: session,
+                project: None,
+            };
+            stats.record(&event_at("a", payload, now - age));
+        }
+
+        assert_eq!(stats.snapshot(StatsWindow::Hour, now).total_events, 1);
+        assert_eq!(stats.snapshot(StatsWindow::Day, now).total_events, 2);
+        let week = stats.snapshot(StatsWindow::Week, now);
+        assert_eq!(week.total_events, 3);
+        assert_eq!(week.active_sessions, 1);
+        assert_eq!(week.from, now - TimeDelta::hours(HOUR_BUCKETS - 1));
+    }
+
+    #[test]
+    fn old_buckets_are_dropped() {
+        let stats = StatsAggregator::new();
+        let now = base_time();
+        let payload = || EventPayload::Activity {
+            session_id: Uuid::new_v4(),
+            project: None,
+        };
+
+        stats.record(&event_at("a", payload(), now - TimeDelta::hours(2)));
+        stats.record(&event_at("a", payload(), now));
+        // Too old relative to the newest bucket
+        stats.record(&event_at("a", payload(), now - TimeDelta::days(10)));
+
+        let rollups = stats.read();
+        assert_eq!(rollups.minutes.len(), 1);
+        assert_eq!(rollups.hours.len(), 2);
+    }
+
+    #[test]
+    fn token_usage_counts_increase_per_source_and_model() {
+        let stats = StatsAggregator::new();
+        let now = base_time();
+
+        // The first report only sets the baseline
+        stats.record(&event_at("a", tokens("opus", 1000, 100), now));
+        stats.record(&event_at("a", tokens("opus", 1500, 180), now));
+        stats.record(&event_at("b", tokens("opus", 50, 5), now));
+        stats.record(&event_at("b", tokens("opus", 70, 5), now));
+        // A reset counter counts from zero
+        stats.record(&event_at("b", tokens("opus", 30, 2), now));
+
+        let rollup = stats.snapshot(StatsWindow::Hour, now);
+        let a = &rollup.tokens["a"]["opus"];
+        assert_eq!((a.input_tokens, a.output_tokens), (500, 80));
+        let b = &rollup.tokens["b"]["opus"];
+        assert_eq!((b.input_tokens, b.output_tokens), (50, 2));
+        assert_eq!(rollup.active_sessions, 0);
+    }
+
+    #[test]
+    fn serializes_windows_and_event_types() {
+        let stats = StatsAggregator::new();
+        let now = base_time();
+        stats.record(&event_at(
+            "a",
+            tool(Uuid::new_v4(), "Read", ToolStatus::Completed),
+            now,
+        ));
+
+        let json = serde_json::to_value(stats.snapshot(StatsWindow::Day, now)).unwrap();
+        assert_eq!(json["window"], "24h");
+        assert_eq!(json["eventsByType"]["tool"], 1);
+        assert_eq!(json["toolUsage"]["Read"], 1);
+
+        let window: StatsWindow = serde_json::from_str(r#""7d""#).unwrap();
+        assert_eq!(window, StatsWindow::Week);
+        assert!(serde_json::from_str::<StatsWindow>(r#""1y""#).is_err());
+    }
+
+    #[tokio::test]
+    async fn published_rollups_reach_subscribers() {
+        let stats = StatsAggregator::new();
+        let mut updates = Some(stats.subscribe());
+        stats.record(&event_at(
+            "a",
+            tool(Uuid::new_v4(), "Read", ToolStatus::Completed),
+            Utc::now(),
+        ));
+
+        stats.publish(Utc::now());
+        let rollup = next_rollup(&mut updates).await;
+        assert_eq!(rollup.window, StatsWindow::Hour);
+        assert_eq!(rollup.total_events, 1);
+
+        let mut disabled = None;
+        let pending =
+            tokio::time::timeout(Duration::from_millis(10), next_rollup(&mut disabled)).await;
+        assert!(pending.is_err());
+    }
+}
@@ -0,0 +1,813 @@
+//! File-backed event log for the VibeTea server.
+//!
+//! This module provides an embedded, append-only event store so that events
+//! survive beyond the lifetime of the in-memory broadcast channel. Every event
+//! accepted by `POST /events` is appended to the store before it is broadcast,
+//! allowing dashboards to recover recent history after reconnecting.
+//!
+//! # Storage Layout
+//!
+//! Events are written as newline-delimited JSON (one [`Event`] per line) into
+//! numbered segment files inside the configured data directory:
+//!
+//! ```text
+//! $VIBETEA_DATA_DIR/
+//! ├── 00000000000000000001.ndjson
+//! ├── 00000000000000000002.ndjson
+//! └── 00000000000000000003.ndjson   <- active segment (appended to)
+//! ```
+//!
+//! When the active segment grows beyond [`StoreConfig::segment_bytes`], it is
+//! sealed and a new segment is started.
+//!
+//! # Retention
+//!
+//! Sealed segments are deleted when either:
+//! - Their last write is older than [`StoreConfig::max_age`], or
+//! - The total size of all segments exceeds [`StoreConfig::max_bytes`]
+//!   (oldest segments are removed first)
+//!
+//! The active segment is never deleted by retention.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use vibetea_server::store::{EventStore, StoreConfig};
+//!
+//! let store = EventStore::open(StoreConfig::new("/var/lib/vibetea")).unwrap();
+//!
+//! // Read back everything from the last hour
+//! let since = chrono::Utc::now() - chrono::Duration::hours(1);
+//! let events = store.replay(Some(since)).unwrap();
+//! println!("{} events in the last hour", events.len());
+//!
+//! // Enforce retention periodically
+//! let _handle = store.spawn_retention_task(Duration::from_secs(60));
+//! ```
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use crate::types::Event;
+
+/// Default maximum age of retained events (24 hours).
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default maximum total size of all segments (1 GiB).
+pub const DEFAULT_MAX_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Default size at which the active segment is sealed (16 MiB).
+pub const DEFAULT_SEGMENT_BYTES: u64 = 16 * 1024 * 1024;
+
+/// File extension used for segment files.
+const SEGMENT_EXTENSION: &str = "ndjson";
+
+/// Errors that can occur while reading or writing the event store.
+#[derive(Debug, Error)]
+pub enum StoreError {
+    /// Filesystem operation failed.
+    #[error("event store I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Event could not be serialized.
+    #[error("failed to serialize event: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Configuration for the file-backed event store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreConfig {
+    /// Directory holding the segment files.
+    pub dir: PathBuf,
+
+    /// Sealed segments whose last write is older than this are deleted.
+    pub max_age: Duration,
+
+    /// Upper bound on the combined size of all segments in bytes.
+    pub max_bytes: u64,
+
+    /// Size in bytes at which the active segment is sealed.
+    pub segment_bytes: u64,
+}
+
+impl StoreConfig {
+    /// Creates a store configuration for `dir` using default retention limits.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_age: DEFAULT_MAX_AGE,
+            max_bytes: DEFAULT_MAX_BYTES,
+            segment_bytes: DEFAULT_SEGMENT_BYTES,
+        }
+    }
+
+    /// Sets the maximum age of retained segments (builder pattern).
+    #[must_use]
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Sets the maximum combined size of all segments (builder pattern).
+    #[must_use]
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Sets the size at which the active segment is sealed (builder pattern).
+    #[must_use]
+    pub fn with_segment_bytes(mut self, segment_bytes: u64) -> Self {
+        self.segment_bytes = segment_bytes.max(1);
+        self
+    }
+}
+
+/// Metadata about a single segment file.
+#[derive(Debug)]
+struct Segment {
+    /// Monotonically increasing segment number (also the file name).
+    id: u64,
+
+    /// Path to the segment file.
+    path: PathBuf,
+
+    /// Current size of the segment in bytes.
+    size: u64,
+
+    /// Time of the most recent write to the segment.
+    last_write: SystemTime,
+}
+
+#[derive(Debug)]
+struct StoreInner {
+    /// All segments ordered from oldest to newest. The last one is active.
+    segments: Vec<Segment>,
+
+    /// Buffered writer for the active segment.
+    writer: BufWriter<File>,
+}
+
+/// Append-only, segmented event log backed by the local filesystem.
+///
+/// `EventStore` is cheap to clone; all clones share the same underlying files.
+/// Appends are serialized through an internal mutex and flushed before
+/// [`append`](Self::append) returns, so events are visible to readers
+/// immediately.
+#[derive(Debug, Clone)]
+pub struct EventStore {
+    config: Arc<StoreConfig>,
+    inner: Arc<Mutex<StoreInner>>,
+}
+
+impl EventStore {
+    /// Opens (or creates) an event store in the configured directory.
+    ///
+    /// Existing segments are discovered and the newest one becomes the active
+    /// segment for subsequent appends. A partial line left at the end of the
+    /// active segment by a crash mid-write is truncated, so the next append
+    /// starts on a fresh line.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError::Io`] if the directory cannot be created or read,
+    /// or the active segment cannot be opened for appending.
+    pub fn open(config: StoreConfig) -> Result<Self, StoreError> {
+        fs::create_dir_all(&config.dir)?;
+
+        let mut segments = discover_segments(&config.dir)?;
+        if segments.is_empty() {
+            segments.push(Segment::create(&config.dir, 1)?);
+        }
+
+        let active = segments.last_mut().expect("at least one segment exists");
+        let torn = truncate_partial_line(&active.path)?;
+        if torn > 0 {
+            warn!(
+                segment = %active.path.display(),
+                bytes = torn,
+                "Truncated partial event at end of event store"
+            );
+            active.size -= torn;
+        }
+        let writer = BufWriter::new(open_for_append(&active.path)?);
+
+        info!(
+            dir = %config.dir.display(),
+            segments = segments.len(),
+            "Opened event store"
+        );
+
+        Ok(Self {
+            config: Arc::new(config),
+            inner: Arc::new(Mutex::new(StoreInner { segments, writer })),
+        })
+    }
+
+    /// Returns the store configuration.
+    #[must_use]
+    pub fn config(&self) -> &StoreConfig {
+        &self.config
+    }
+
+    /// Appends events to the active segment.
+    ///
+    /// The active segment is sealed and a new one started once it exceeds
+    /// the configured segment size; size-based retention is applied on
+    /// rotation.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StoreError`] if an event cannot be serialized or written.
+    pub fn append(&self, events: &[Event]) -> Result<(), StoreError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        self.append_locked(&mut self.lock(), events)
+    }
+
+    /// Appends events like [`append`](Self::append), then passes them to
+    /// `then` before the store is unlocked.
+    ///
+    /// A snapshot taken with [`snapshot_then`](Self::snapshot_then) therefore
+    /// either contains the events and follows everything `then` did, or
+    /// neither. `then` runs even if the append fails.
+    pub fn append_then<R>(
+        &self,
+        events: Vec<Event>,
+        then: impl FnOnce(Vec<Event>) -> R,
+    ) -> (Result<(), StoreError>, R) {
+        let mut inner = self.lock();
+        let result = if events.is_empty() {
+            Ok(())
+        } else {
+            self.append_locked(&mut inner, &events)
+        };
+        (result, then(events))
+    }
+
+    fn append_locked(&self, inner: &mut StoreInner, events: &[Event]) -> Result<(), StoreError> {
+        let mut written = 0u64;
+        for event in events {
+            let mut line = serde_json::to_vec(event)?;
+            line.push(b'\n');
+            inner.writer.write_all(&line)?;
+            written += line.len() as u64;
+        }
+        inner.writer.flush()?;
+
+        let active = inner.segments.last_mut().expect("active segment exists");
+        active.size += written;
+        active.last_write = SystemTime::now();
+
+        if active.size >= self.config.segment_bytes {
+            self.rotate(inner)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads back stored events in the order they were appended.
+    ///
+    /// When `since` is set, only events whose timestamp is at or after it are
+    /// returned. Lines that cannot be parsed (for example a partial write
+    /// after a crash) are skipped with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError::Io`] if a segment cannot be read.
+    pub fn replay(&self, since: Option<DateTime<Utc>>) -> Result<Vec<Event>, StoreError> {
+        let mut events = Vec::new();
+        for event in self.snapshot().events() {
+            let event = event?;
+            if since.is_none_or(|since| event.timestamp >= since) {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Captures the events appended so far, for reading without holding up
+    /// appends.
+    ///
+    /// Events appended after the snapshot is taken are not part of it.
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot {
+        self.snapshot_then(|| ()).0
+    }
+
+    /// Captures a snapshot like [`snapshot`](Self::snapshot), running `then`
+    /// before the store is unlocked, so no
+    /// [`append_then`](Self::append_then) completes in between.
+    pub fn snapshot_then<R>(&self, then: impl FnOnce() -> R) -> (Snapshot, R) {
+        let inner = self.lock();
+        let snapshot = Snapshot {
+            segments: inner
+                .segments
+                .iter()
+                .map(|s| (s.path.clone(), s.size))
+                .collect(),
+        };
+        (snapshot, then())
+    }
+
+    /// Deletes sealed segments that fall outside the retention limits.
+    ///
+    /// Returns the number of segments removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StoreError::Io`] if a segment cannot be deleted.
+    pub fn enforce_retention(&self) -> Result<usize, StoreError> {
+        let mut inner = self.lock();
+        self.enforce_retention_locked(&mut inner)
+    }
+
+    /// Returns the number of segment files, including the active one.
+    #[must_use]
+    pub fn segment_count(&self) -> usize {
+        self.lock().segments.len()
+    }
+
+    /// Returns the combined size of all segments in bytes.
+    #[must_use]
+    pub fn total_bytes(&self) -> u64 {
+        self.lock().segments.iter().map(|s| s.size).sum()
+    }
+
+    /// Spawns a background task that periodically enforces retention.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - How often to check the retention limits
+    ///
+    /// # Returns
+    ///
+    /// A `JoinHandle` for the spawned task. The task runs until aborted.
+    pub fn spawn_retention_task(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let store = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+
+            loop {
+                interval.tick().await;
+                match store.enforce_retention() {
+                    Ok(0) => {}
+                    Ok(removed) => {
+                        debug!(removed_count = removed, "Removed expired event segments");
+                    }
+                    Err(err) => {
+                        warn!(error = %err, "Failed to enforce event store retention");
+                    }
+                }
+            }
+        })
+    }
+
+    /// Seals the active segment and starts a new one.
+    fn rotate(&self, inner: &mut StoreInner) -> Result<(), StoreError> {
+        let next_id = inner.segments.last().map_or(1, |s| s.id + 1);
+        let segment = Segment::create(&self.config.dir, next_id)?;
+
+        inner.writer = BufWriter::new(open_for_append(&segment.path)?);
+        inner.segments.push(segment);
+        debug!(segment_id = next_id, "Rotated event store segment");
+
+        self.enforce_retention_locked(inner)?;
+        Ok(())
+    }
+
+    fn enforce_retention_locked(&self, inner: &mut StoreInner) -> Result<usize, StoreError> {
+        let now = SystemTime::now();
+        let mut total: u64 = inner.segments.iter().map(|s| s.size).sum();
+        let mut removed = 0;
+
+        // Never remove the active (last) segment.
+        while inner.segments.len() > 1 {
+            let oldest = &inner.segments[0];
+            let age = now
+                .duration_since(oldest.last_write)
+                .unwrap_or(Duration::ZERO);
+
+            if age <= self.config.max_age && total <= self.config.max_bytes {
+                break;
+            }
+
+            match fs::remove_file(&oldest.path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+
+            total -= oldest.size;
+            removed += 1;
+            debug!(segment = %oldest.path.display(), "Deleted expired event segment");
+            inner.segments.remove(0);
+        }
+
+        Ok(removed)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, StoreInner> {
+        // A panic while holding the lock leaves the file state intact, so
+        // recovering the guard is safe.
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The events in an [`EventStore`] at a point in time.
+///
+/// Returned by [`EventStore::snapshot`].
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// Segment paths and their sizes when the snapshot was taken, oldest
+    /// first.
+    segments: Vec<(PathBuf, u64)>,
+}
+
+impl Snapshot {
+    /// Returns an iterator reading the snapshot's events in the order they
+    /// were appended.
+    ///
+    /// Segments are read lazily, one line at a time, so this blocks on file
+    /// I/O. Lines that cannot be parsed are skipped with a warning, and
+    /// segments removed by retention since the snapshot was taken are
+    /// skipped.
+    #[must_use]
+    pub fn events(&self) -> SnapshotEvents {
+        SnapshotEvents {
+            segments: self.segments.clone().into_iter(),
+            current: None,
+        }
+    }
+}
+
+/// Iterator over the events in a [`Snapshot`].
+#[derive(Debug)]
+pub struct SnapshotEvents {
+    segments: std::vec::IntoIter<(PathBuf, u64)>,
+    current: Option<(PathBuf, std::io::Lines<BufReader<std::io::Take<File>>>)>,
+}
+
+impl Iterator for SnapshotEvents {
+    type Item = Result<Event, StoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((path, lines)) = &mut self.current else {
+                let (path, size) = self.segments.next()?;
+                let file = match File::open(&path) {
+                    Ok(file) => file,
+                    // Segment removed by retention after the snapshot
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(err) => return Some(Err(err.into())),
+                };
+                // Bytes past `size` were appended after the snapshot
+                self.current = Some((path, BufReader::new(file.take(size)).lines()));
+                continue;
+            };
+
+            let line = match lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => return Some(Err(err.into())),
+                None => {
+                    self.current = None;
+                    continue;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Event>(&line) {
+                Ok(event) => return Some(Ok(event)),
+                Err(err) => {
+                    warn!(
+                        segment = %path.display(),
+                        error = %err,
+                        "Skipping unreadable event store line"
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Segment {
+    /// Creates a new empty segment file.
+    fn create(dir: &Path, id: u64) -> Result<Self, StoreError> {
+        let path = segment_path(dir, id);
+        File::create(&path)?;
+        Ok(Self {
+            id,
+            path,
+            size: 0,
+            last_write: SystemTime::now(),
+        })
+    }
+}
+
+/// Returns the path of the segment with the given id.
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{id:020}.{SEGMENT_EXTENSION}"))
+}
+
+/// Opens a segment file for appending.
+fn open_for_append(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Truncates `path` after its last newline, dropping a partial line left by
+/// an interrupted write.
+///
+/// Returns the number of bytes removed.
+fn truncate_partial_line(path: &Path) -> std::io::Result<u64> {
+    const CHUNK: u64 = 4096;
+
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let len = file.metadata()?.len();
+
+    // Scan backwards for the last newline
+    let mut end = len;
+    let mut buf = vec![0u8; CHUNK as usize];
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK);
+        let chunk = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        if let Some(pos) = chunk.iter().rposition(|&b| b == b'\n') {
+            end = start + pos as u64 + 1;
+            break;
+        }
+        end = start;
+    }
+
+    if end < len {
+        file.set_len(end)?;
+        file.sync_all()?;
+    }
+    Ok(len - end)
+}
+
+/// Lists existing segments in `dir`, ordered from oldest to newest.
+fn discover_segments(dir: &Path) -> Result<Vec<Segment>, StoreError> {
+    let mut segments = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if path.extension().and_then(|e| e.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+        let Some(id) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<u64>().ok())
+        else {
+            continue;
+        };
+
+        let metadata = entry.metadata()?;
+        segments.push(Segment {
+            id,
+            path,
+            size: metadata.len(),
+            last_write: metadata.modified().unwrap_or_else(|_| SystemTime::now()),
+        });
+    }
+
+    segments.sort_by_key(|s| s.id);
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{EventPayload, EventType, SessionAction};
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn make_event(n: usize) -> Event {
+        Event::new(
+            "monitor-1".to_string(),
+            EventType::Session,
+            EventPayload::Session {
+                session_id: Uuid::new_v4(),
+                action: SessionAction::Started,
+                project: "vibetea".to_string(),
+            },
+        )
+        .with_id(format!("evt_{n:0>20}"))
+    }
+
+    fn ids(events: &[Event]) -> Vec<String> {
+        events.iter().map(|e| e.id.clone()).collect()
+    }
+
+    #[test]
+    fn open_creates_directory_and_active_segment() {
+        let dir = TempDir::new().unwrap();
+        let store_dir = dir.path().join("events");
+
+        let store = EventStore::open(StoreConfig::new(&store_dir)).unwrap();
+
+        assert!(store_dir.is_dir());
+        assert_eq!(store.segment_count(), 1);
+        assert_eq!(store.total_bytes(), 0);
+    }
+
+    #[test]
+    fn append_and_replay_preserves_order() {
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+
+        let events: Vec<Event> = (0..5).map(make_event).collect();
+        store.append(&events[..2]).unwrap();
+        store.append(&events[2..]).unwrap();
+
+        let replayed = store.replay(None).unwrap();
+        assert_eq!(ids(&replayed), ids(&events));
+    }
+
+    #[test]
+    fn replay_filters_by_timestamp() {
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+
+        let mut old = make_event(1);
+        old.timestamp = Utc::now() - chrono::Duration::hours(2);
+        let recent = make_event(2);
+        store.append(&[old, recent.clone()]).unwrap();
+
+        let since = Utc::now() - chrono::Duration::hours(1);
+        let replayed = store.replay(Some(since)).unwrap();
+        assert_eq!(ids(&replayed), vec![recent.id]);
+    }
+
+    #[test]
+    fn snapshot_excludes_later_appends() {
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        store.append(&[make_event(1), make_event(2)]).unwrap();
+
+        let snapshot = store.snapshot();
+        store.append(&[make_event(3)]).unwrap();
+
+        let events: Vec<Event> = snapshot.events().collect::<Result<_, _>>().unwrap();
+        assert_eq!(ids(&events), vec![make_event(1).id, make_event(2).id]);
+        assert_eq!(store.replay(None).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn reopen_continues_existing_log() {
+        let dir = TempDir::new().unwrap();
+
+        {
+            let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+            store.append(&[make_event(1)]).unwrap();
+        }
+
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        store.append(&[make_event(2)]).unwrap();
+
+        let replayed = store.replay(None).unwrap();
+        assert_eq!(replayed.len(), 2);
+        assert_eq!(store.segment_count(), 1);
+    }
+
+    #[test]
+    fn rotates_when_segment_is_full() {
+        let dir = TempDir::new().unwrap();
+        let config = StoreConfig::new(dir.path()).with_segment_bytes(1);
+        let store = EventStore::open(config).unwrap();
+
+        store.append(&[make_event(1)]).unwrap();
+        store.append(&[make_event(2)]).unwrap();
+
+        // Each append fills a segment and opens a fresh active one
+        assert_eq!(store.segment_count(), 3);
+        assert_eq!(store.replay(None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn size_retention_removes_oldest_segments() {
+        let dir = TempDir::new().unwrap();
+        let event_size = serde_json::to_vec(&make_event(0)).unwrap().len() as u64 + 1;
+        let config = StoreConfig::new(dir.path())
+            .with_segment_bytes(1)
+            .with_max_bytes(event_size * 2);
+        let store = EventStore::open(config).unwrap();
+
+        for n in 0..5 {
+            store.append(&[make_event(n)]).unwrap();
+        }
+
+        let replayed = store.replay(None).unwrap();
+        assert_eq!(ids(&replayed), vec![make_event(3).id, make_event(4).id]);
+        assert!(store.total_bytes() <= event_size * 2);
+    }
+
+    #[test]
+    fn age_retention_removes_sealed_segments() {
+        let dir = TempDir::new().unwrap();
+        let config = StoreConfig::new(dir.path())
+            .with_segment_bytes(1)
+            .with_max_age(Duration::ZERO);
+        let store = EventStore::open(config).unwrap();
+
+        // Sealing the segment may already apply retention; either way the
+        // expired segment must be gone once retention has run.
+        store.append(&[make_event(1)]).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        store.enforce_retention().unwrap();
+
+        assert_eq!(store.segment_count(), 1);
+        assert!(store.replay(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn retention_never_removes_active_segment() {
+        let dir = TempDir::new().unwrap();
+        let config = StoreConfig::new(dir.path())
+            .with_max_age(Duration::ZERO)
+            .with_max_bytes(0);
+        let store = EventStore::open(config).unwrap();
+
+        store.append(&[make_event(1)]).unwrap();
+        assert_eq!(store.enforce_retention().unwrap(), 0);
+        assert_eq!(store.replay(None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn replay_skips_corrupt_lines() {
+        let dir = TempDir::new().unwrap();
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        store.append(&[make_event(1)]).unwrap();
+
+        // Simulate a torn write at the end of the active segment
+        let mut file = open_for_append(&segment_path(dir.path(), 1)).unwrap();
+        file.write_all(b"{\"id\":\"evt_trunc").unwrap();
+
+        let replayed = store.replay(None).unwrap();
+        assert_eq!(replayed.len(), 1);
+    }
+
+    #[test]
+    fn reopen_truncates_partial_last_line() {
+        let dir = TempDir::new().unwrap();
+
+        {
+            let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+            store.append(&[make_event(1)]).unwrap();
+        }
+        let path = segment_path(dir.path(), 1);
+        let complete = fs::metadata(&path).unwrap().len();
+
+        // Simulate a crash in the middle of writing the next event
+        let mut file = open_for_append(&path).unwrap();
+        file.write_all(b"{\"id\":\"evt_trunc").unwrap();
+        drop(file);
+
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        assert_eq!(store.total_bytes(), complete);
+        store.append(&[make_event(2)]).unwrap();
+
+        let replayed = store.replay(None).unwrap();
+        assert_eq!(ids(&replayed), vec![make_event(1).id, make_event(2).id]);
+    }
+
+    #[test]
+    fn reopen_truncates_segment_without_newline() {
+        let dir = TempDir::new().unwrap();
+        let path = segment_path(dir.path(), 1);
+        fs::write(&path, "{\"id\":").unwrap();
+
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        assert_eq!(store.total_bytes(), 0);
+        store.append(&[make_event(1)]).unwrap();
+        assert_eq!(store.replay(None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn ignores_unrelated_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("README.txt"), "not a segment").unwrap();
+        fs::write(dir.path().join("abc.ndjson"), "not numbered").unwrap();
+
+        let store = EventStore::open(StoreConfig::new(dir.path())).unwrap();
+        assert_eq!(store.segment_count(), 1);
+    }
+}
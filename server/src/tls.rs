@@ -0,0 +1,377 @@
+//! Native TLS termination.
+//!
+//! When `VIBETEA_TLS_CERT` and `VIBETEA_TLS_KEY` are set, the server accepts
+//! HTTPS and `wss://` connections directly, without a reverse proxy in front
+//! of it. [`TlsListener`] performs the handshake for each incoming TCP
+//! connection and hands the encrypted stream to axum.
+//!
+//! # Certificate Reload
+//!
+//! [`ServerTls`] holds the current rustls configuration. [`ServerTls::watch`]
+//! reloads it whenever the certificate, key or client CA file changes, so
+//! renewed certificates (for example from certbot) are picked up without a
+//! restart. New connections use the new certificate; established ones are
+//! unaffected. If the new files are invalid, the previous certificate stays
+//! in use and an error is logged.
+//!
+//! # Mutual TLS
+//!
+//! When `VIBETEA_TLS_CLIENT_CA` is set, clients may present a certificate
+//! signed by that CA. Presenting one is optional at the TLS layer, because
+//! browser dashboards have none, but `POST /events` then rejects monitors
+//! that did not present a valid client certificate, in addition to checking
+//! their Ed25519 signature. Handlers see the result through
+//! [`TlsConnectInfo`].
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::net::SocketAddr;
+//! use vibetea_server::config::Config;
+//! use vibetea_server::routes::{create_router, AppState};
+//! use vibetea_server::tls::{ServerTls, TlsConnectInfo, TlsListener};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = Config::from_env()?;
+//! let tls = ServerTls::load(config.tls_config().expect("TLS is not configured"))?;
+//! let _watchers = tls.watch()?;
+//!
+//! let tcp = tokio::net::TcpListener::bind(config.bind_addr()).await?;
+//! let app = create_router(AppState::new(config));
+//! axum::serve(
+//!     TlsListener::new(tcp, tls),
+//!     app.into_make_service_with_connect_info::<TlsConnectInfo>(),
+//! )
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use axum::extract::connect_info::Connected;
+use axum::extract::{ConnectInfo, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::serve::{IncomingStream, Listener};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use thiserror::Error;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{self, RootCertStore, ServerConfig};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, error, info, warn};
+
+use crate::file_watch::{watch_file, FileWatcher};
+
+/// Maximum time a client may take to complete the TLS handshake.
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handshakes completed but not yet accepted by the server.
+const ACCEPT_QUEUE: usize = 64;
+
+/// Errors that can occur when loading TLS certificates.
+#[derive(Debug, Error)]
+pub enum TlsError {
+    /// A certificate or key file could not be read or parsed.
+    #[error("failed to read {}: {message}", path.display())]
+    File { path: PathBuf, message: String },
+
+    /// A certificate file contained no certificates.
+    #[error("no certificates found in {}", .0.display())]
+    NoCertificates(PathBuf),
+
+    /// rustls rejected the certificate, key or client CA.
+    #[error("invalid TLS configuration: {0}")]
+    Invalid(String),
+}
+
+/// Paths of the PEM files used for TLS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// Certificate chain, leaf first.
+    pub cert: PathBuf,
+
+    /// Private key for the leaf certificate (PKCS#8, PKCS#1 or SEC1).
+    pub key: PathBuf,
+
+    /// CA certificates that may sign client certificates. `None` disables
+    /// client certificates.
+    pub client_ca: Option<PathBuf>,
+}
+
+/// The server's current TLS configuration, replaced on reload.
+///
+/// Cloning is cheap and shares the configuration.
+#[derive(Debug, Clone)]
+pub struct ServerTls {
+    config: Arc<TlsConfig>,
+    current: Arc<RwLock<Arc<ServerConfig>>>,
+}
+
+impl ServerTls {
+    /// Loads the certificate, key and client CA named by `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TlsError`] if a file cannot be read or rustls rejects its
+    /// contents.
+    pub fn load(config: TlsConfig) -> Result<Self, TlsError> {
+        let server_config = build_server_config(&config)?;
+        Ok(Self {
+            config: Arc::new(config),
+            current: Arc::new(RwLock::new(Arc::new(server_config))),
+        })
+    }
+
+    /// Returns the file paths this configuration was loaded from.
+    #[must_use]
+    pub fn config(&self) -> &TlsConfig {
+        &self.config
+    }
+
+    /// Returns `true` if clients can authenticate with a certificate.
+    #[must_use]
+    pub fn client_auth(&self) -> bool {
+        self.config.client_ca.is_some()
+    }
+
+    /// Reloads the files. The current configuration is kept if they are
+    /// invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TlsError`] if a file cannot be read or rustls rejects its
+    /// contents.
+    pub fn reload(&self) -> Result<(), TlsError> {
+        let server_config = build_server_config(&self.config)?;
+        *self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(server_config);
+        Ok(())
+    }
+
+    /// Reloads whenever one of the files changes, until the returned watchers
+    /// are dropped.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a watcher cannot be started.
+    pub fn watch(&self) -> Result<Vec<FileWatcher>, notify::Error> {
+        let config = &self.config;
+        [Some(&config.cert), Some(&config.key), config.client_ca.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|path| {
+                let tls = self.clone();
+                watch_file(path, move || match tls.reload() {
+                    Ok(()) => info!("Reloaded TLS certificate"),
+                    Err(err) => {
+                        error!(error = %err, "Failed to reload TLS certificate, keeping the previous one");
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Returns an acceptor using the current configuration.
+    fn acceptor(&self) -> TlsAcceptor {
+        let current = self
+            .current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        TlsAcceptor::from(Arc::clone(&current))
+    }
+}
+
+/// Builds a rustls server configuration from the files named by `config`.
+fn build_server_config(config: &TlsConfig) -> Result<ServerConfig, TlsError> {
+    let certs = read_certificates(&config.cert)?;
+    let key = PrivateKeyDer::from_pem_file(&config.key).map_err(|err| TlsError::File {
+        path: config.key.clone(),
+        message: err.to_string(),
+    })?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| TlsError::Invalid(err.to_string()))?;
+
+    let builder = match &config.client_ca {
+        Some(path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in read_certificates(path)? {
+                roots
+                    .add(cert)
+                    .map_err(|err| TlsError::Invalid(err.to_string()))?;
+            }
+            // Dashboards have no certificates, so POST /events enforces them
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .allow_unauthenticated()
+                .build()
+                .map_err(|err| TlsError::Invalid(err.to_string()))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut server_config = builder
+        .with_single_cert(certs, key)
+        .map_err(|err| TlsError::Invalid(err.to_string()))?;
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(server_config)
+}
+
+/// Reads every certificate in a PEM file.
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let file_error = |err: rustls_pki_types::pem::Error| TlsError::File {
+        path: path.to_path_buf(),
+        message: err.to_string(),
+    };
+    let certs = CertificateDer::pem_file_iter(path)
+        .map_err(file_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(file_error)?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificates(path.to_path_buf()));
+    }
+    Ok(certs)
+}
+
+/// Connection details for a TLS connection, available to handlers as
+/// `ConnectInfo<TlsConnectInfo>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsConnectInfo {
+    /// Address of the client.
+    pub remote_addr: SocketAddr,
+
+    /// `true` if the client presented a certificate signed by the client CA.
+    pub client_certificate: bool,
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for TlsConnectInfo {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        // The verifier rejects invalid certificates during the handshake, so
+        // any certificate still present was signed by the client CA
+        let (_, session) = stream.io().get_ref();
+        Self {
+            remote_addr: *stream.remote_addr(),
+            client_certificate: session.peer_certificates().is_some(),
+        }
+    }
+}
+
+/// Middleware that exposes the client address of TLS connections as
+/// `ConnectInfo<SocketAddr>`, as for plain TCP connections, so handlers do
+/// not need to know whether TLS is in use.
+pub async fn forward_connect_info(mut request: Request, next: Next) -> Response {
+    if let Some(ConnectInfo(info)) = request
+        .extensions()
+        .get::<ConnectInfo<TlsConnectInfo>>()
+        .copied()
+    {
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(info.remote_addr));
+    }
+    next.run(request).await
+}
+
+/// A listener that accepts TCP connections and completes the TLS handshake
+/// before handing them to axum.
+///
+/// Handshakes run in their own tasks with a [`HANDSHAKE_TIMEOUT`], so a slow
+/// or stalled client cannot hold up other connections.
+#[derive(Debug)]
+pub struct TlsListener {
+    local_addr: io::Result<SocketAddr>,
+    accepted: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    task: JoinHandle<()>,
+}
+
+impl TlsListener {
+    /// Wraps a bound TCP listener.
+    ///
+    /// Must be called from within a Tokio runtime.
+    #[must_use]
+    pub fn new(tcp: TcpListener, tls: ServerTls) -> Self {
+        let local_addr = tcp.local_addr();
+        let (tx, accepted) = mpsc::channel(ACCEPT_QUEUE);
+        let task = tokio::spawn(accept_connections(tcp, tls, tx));
+        Self {
+            local_addr,
+            accepted,
+            task,
+        }
+    }
+}
+
+impl Drop for TlsListener {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.accepted.recv().await {
+            Some(accepted) => accepted,
+            // The accept task only ends when the listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        match &self.local_addr {
+            Ok(addr) => Ok(*addr),
+            Err(err) => Err(io::Error::new(err.kind(), err.to_string())),
+        }
+    }
+}
+
+/// Accepts TCP connections and spawns a handshake for each.
+async fn accept_connections(
+    tcp: TcpListener,
+    tls: ServerTls,
+    tx: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+) {
+    loop {
+        let (stream, remote_addr) = match tcp.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                // Usually running out of file descriptors; back off briefly
+                warn!(error = %err, "Failed to accept TCP connection");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+
+        let acceptor = tls.acceptor();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => {
+                    let _ = tx.send((stream, remote_addr)).await;
+                }
+                Ok(Err(err)) => {
+                    debug!(error = %err, client = %remote_addr, "TLS handshake failed");
+                }
+                Err(_) => debug!(client = %remote_addr, "TLS handshake timed out"),
+            }
+        });
+    }
+}
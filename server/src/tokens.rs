@@ -0,0 +1,562 @@
+//! Named subscriber credentials with scopes and expiry.
+//!
+//! Subscribers (dashboards, WebSocket clients and API consumers) present a
+//! token. Besides the shared `VIBETEA_SUBSCRIBER_TOKEN`, which grants full
+//! access, any number of named tokens can be listed in a JSON file
+//! (`VIBETEA_SUBSCRIBER_TOKENS_FILE`). Each may be limited to certain sources,
+//! projects and event types, and may expire:
+//!
+//! ```json
+//! {
+//!   "tokens": [
+//!     {
+//!       "name": "ci-dashboard",
+//!       "token": "3b6f0c...",
+//!       "sources": ["ci-*"],
+//!       "eventTypes": ["session", "tool"],
+//!       "expires": "2026-12-31T00:00:00Z"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Source and project patterns may contain `*`, which matches any run of
+//! characters. An empty list places no restriction on that field. A token's
+//! [`TokenScope`] is ANDed into every filter the subscriber requests (see
+//! [`SubscriberFilter::with_scope`](crate::broadcast::SubscriberFilter::with_scope)),
+//! so it can never see more than its scope allows.
+//!
+//! The file is reloaded when it changes, so removing an entry revokes that
+//! token for new requests and connections without affecting anyone else.
+//!
+//! # Example
+//!
+//! ```rust
+//! use chrono::Utc;
+//! use vibetea_server::tokens::{SubscriberToken, TokenRegistry, TokenScope};
+//!
+//! let registry = TokenRegistry::new(Some("shared-secret"));
+//! registry.replace(vec![SubscriberToken::new("ci-dashboard", "ci-secret")
+//!     .with_scope(TokenScope::new().with_sources(["ci-*"]))]);
+//!
+//! let subscriber = registry.authenticate("ci-secret", Utc::now()).unwrap();
+//! assert_eq!(subscriber.name, "ci-dashboard");
+//! assert!(subscriber.scope.allows_source("ci-linux"));
+//! assert!(!subscriber.scope.allows_source("alice-laptop"));
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::auth::validate_token;
+use crate::file_watch::{watch_file, FileWatcher};
+use crate::filter::glob_match;
+use crate::sessions::Session;
+use crate::types::EventType;
+
+/// Name given to the credential from `VIBETEA_SUBSCRIBER_TOKEN`.
+pub const SHARED_TOKEN_NAME: &str = "default";
+
+/// Errors that can occur when loading or watching a tokens file.
+#[derive(Debug, Error)]
+pub enum TokenError {
+    /// The tokens file could not be read.
+    #[error("failed to read tokens file {path}: {source}")]
+    Io {
+        /// Path to the tokens file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// The tokens file is not valid JSON in the expected format.
+    #[error("failed to parse tokens file {path}: {source}")]
+    Parse {
+        /// Path to the tokens file.
+        path: PathBuf,
+        /// Underlying parse error.
+        source: serde_json::Error,
+    },
+
+    /// A token entry is invalid.
+    #[error("invalid subscriber token {name}: {reason}")]
+    InvalidToken {
+        /// Name of the offending entry.
+        name: String,
+        /// Why the entry was rejected.
+        reason: String,
+    },
+
+    /// The file watcher could not be started.
+    #[error("failed to watch tokens file: {0}")]
+    Watch(#[from] notify::Error),
+}
+
+/// Why a presented token was not accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenRejection {
+    /// The token matches no credential.
+    Invalid,
+
+    /// The token matches a credential whose expiry has passed.
+    Expired,
+}
+
+/// Limits on what a subscriber may see.
+///
+/// Each list is a set of allowed values; an empty list allows everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenScope {
+    /// Allowed source patterns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+
+    /// Allowed project patterns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+
+    /// Allowed event types.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_types: Vec<EventType>,
+}
+
+impl TokenScope {
+    /// Creates a scope with no restrictions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the scope to sources matching any of `patterns`.
+    #[must_use]
+    pub fn with_sources<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.sources = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Restricts the scope to projects matching any of `patterns`.
+    #[must_use]
+    pub fn with_projects<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.projects = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Restricts the scope to the given event types.
+    #[must_use]
+    pub fn with_event_types(mut self, event_types: impl IntoIterator<Item = EventType>) -> Self {
+        self.event_types = event_types.into_iter().collect();
+        self
+    }
+
+    /// Returns `true` if the scope places no restrictions.
+    #[must_use]
+    pub fn is_unrestricted(&self) -> bool {
+        self.sources.is_empty() && self.projects.is_empty() && self.event_types.is_empty()
+    }
+
+    /// Returns `true` if events from `source` are in scope.
+    #[must_use]
+    pub fn allows_source(&self, source: &str) -> bool {
+        self.sources.is_empty() || self.sources.iter().any(|p| glob_match(p, source))
+    }
+
+    /// Returns `true` if events for `project` are in scope.
+    ///
+    /// When projects are restricted, events without a project are out of
+    /// scope.
+    #[must_use]
+    pub fn allows_project(&self, project: Option<&str>) -> bool {
+        self.projects.is_empty()
+            || project.is_some_and(|project| self.projects.iter().any(|p| glob_match(p, project)))
+    }
+
+    /// Returns `true` if events of `event_type` are in scope.
+    #[must_use]
+    pub fn allows_event_type(&self, event_type: EventType) -> bool {
+        self.event_types.is_empty() || self.event_types.contains(&event_type)
+    }
+
+    /// Returns `true` if `session` is in scope.
+    #[must_use]
+    pub fn allows_session(&self, session: &Session) -> bool {
+        self.allows_source(&session.source) && self.allows_project(session.project.as_deref())
+    }
+}
+
+/// A named subscriber credential.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriberToken {
+    /// Name shown in logs, e.g. `ci-dashboard`.
+    pub name: String,
+
+    /// Secret presented by the subscriber.
+    pub token: String,
+
+    /// What the subscriber may see.
+    #[serde(flatten)]
+    pub scope: TokenScope,
+
+    /// When the token stops being accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl SubscriberToken {
+    /// Creates an unrestricted, non-expiring credential.
+    #[must_use]
+    pub fn new(name: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            token: token.into(),
+            scope: TokenScope::new(),
+            expires: None,
+        }
+    }
+
+    /// Sets the scope (builder pattern).
+    #[must_use]
+    pub fn with_scope(mut self, scope: TokenScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Sets the expiry (builder pattern).
+    #[must_use]
+    pub fn with_expires(mut self, expires: DateTime<Utc>) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+}
+
+/// An authenticated subscriber.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subscriber {
+    /// Name of the credential used.
+    pub name: String,
+
+    /// What the subscriber may see.
+    pub scope: TokenScope,
+}
+
+impl Subscriber {
+    /// A subscriber with full access, used when authentication is disabled.
+    #[must_use]
+    pub fn unrestricted(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            scope: TokenScope::new(),
+        }
+    }
+}
+
+/// On-disk format of the tokens file.
+#[derive(Debug, Deserialize)]
+struct TokensFile {
+    tokens: Vec<SubscriberToken>,
+}
+
+/// Loads and validates the credentials in a tokens file.
+///
+/// # Errors
+///
+/// Returns a [`TokenError`] if the file cannot be read or parsed, or if any
+/// entry has an empty name or token, or repeats another entry's name or
+/// token.
+pub fn load_tokens_file(path: &Path) -> Result<Vec<SubscriberToken>, TokenError> {
+    let contents = std::fs::read(path).map_err(|source| TokenError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let file: TokensFile =
+        serde_json::from_slice(&contents).map_err(|source| TokenError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+    let mut names = HashSet::new();
+    let mut secrets = HashSet::new();
+    for entry in &file.tokens {
+        let invalid = |reason: &str| TokenError::InvalidToken {
+            name: entry.name.clone(),
+            reason: reason.to_string(),
+        };
+        if entry.name.trim().is_empty() {
+            return Err(invalid("name must not be empty"));
+        }
+        if entry.token.trim().is_empty() {
+            return Err(invalid("token must not be empty"));
+        }
+        if !names.insert(entry.name.as_str()) {
+            return Err(invalid("duplicate name"));
+        }
+        if !secrets.insert(entry.token.trim()) {
+            return Err(invalid("token is shared with another entry"));
+        }
+    }
+
+    Ok(file.tokens)
+}
+
+/// Thread-safe set of subscriber credentials.
+///
+/// Cloning is cheap and shares the underlying state.
+#[derive(Debug, Clone)]
+pub struct TokenRegistry {
+    /// Credential from the environment, present in every reload.
+    shared: Option<SubscriberToken>,
+
+    /// Current credentials. Replaced wholesale on reload.
+    tokens: Arc<RwLock<Arc<Vec<SubscriberToken>>>>,
+}
+
+impl TokenRegistry {
+    /// Creates a registry holding the shared `VIBETEA_SUBSCRIBER_TOKEN`, if set.
+    #[must_use]
+    pub fn new(shared_token: Option<&str>) -> Self {
+        let shared = shared_token.map(|token| SubscriberToken::new(SHARED_TOKEN_NAME, token));
+        let tokens = shared.iter().cloned().collect();
+        Self {
+            shared,
+            tokens: Arc::new(RwLock::new(Arc::new(tokens))),
+        }
+    }
+
+    /// Replaces the file-provided credentials with `tokens`.
+    ///
+    /// The shared token is always kept.
+    pub fn replace(&self, tokens: Vec<SubscriberToken>) {
+        let tokens = self.shared.iter().cloned().chain(tokens).collect();
+        *self
+            .tokens
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(tokens);
+    }
+
+    /// Loads `path` and replaces the file-provided credentials with its
+    /// contents.
+    ///
+    /// Returns the number of credentials loaded from the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TokenError`] if the file is invalid, in which case the
+    /// current credentials are left unchanged.
+    pub fn load_file(&self, path: &Path) -> Result<usize, TokenError> {
+        let tokens = load_tokens_file(path)?;
+        let count = tokens.len();
+        self.replace(tokens);
+        Ok(count)
+    }
+
+    /// Watches `path` and reloads the registry whenever it changes.
+    ///
+    /// Reload failures are logged and the previous credentials are kept.
+    /// Watching stops when the returned [`FileWatcher`] is dropped.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenError::Watch`] if the watcher cannot be started.
+    pub fn watch(&self, path: impl Into<PathBuf>) -> Result<FileWatcher, TokenError> {
+        let path = path.into();
+        let registry = self.clone();
+        let watcher = watch_file(path.clone(), move || match registry.load_file(&path) {
+            Ok(count) => info!(path = %path.display(), tokens = count, "Reloaded tokens file"),
+            Err(err) => warn!(error = %err, "Tokens file reload failed, keeping previous tokens"),
+        })?;
+        Ok(watcher)
+    }
+
+    /// Returns the number of credentials, including the shared token.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.snapshot().len()
+    }
+
+    /// Returns `true` if no credentials are configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Finds the credential matching `provided`.
+    ///
+    /// Every credential is compared in constant time, so the response time
+    /// does not reveal which one matched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenRejection::Expired`] if the token has expired, or
+    /// [`TokenRejection::Invalid`] if it matches no credential.
+    pub fn authenticate(
+        &self,
+        provided: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Subscriber, TokenRejection> {
+        let tokens = self.snapshot();
+        let mut matched = None;
+        for entry in tokens.iter() {
+            if validate_token(provided, &entry.token).is_ok() && matched.is_none() {
+                matched = Some(entry);
+            }
+        }
+
+        let entry = matched.ok_or(TokenRejection::Invalid)?;
+        if entry.expires.is_some_and(|expires| expires <= now) {
+            return Err(TokenRejection::Expired);
+        }
+
+        Ok(Subscriber {
+            name: entry.name.clone(),
+            scope: entry.scope.clone(),
+        })
+    }
+
+    fn snapshot(&self) -> Arc<Vec<SubscriberToken>> {
+        Arc::clone(
+            &self
+                .tokens
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn scope_restrictions() {
+        let scope = TokenScope::new()
+            .with_sources(["ci-*"])
+            .with_projects(["vibetea"])
+            .with_event_types([EventType::Tool]);
+
+        assert!(!scope.is_unrestricted());
+        assert!(scope.allows_source("ci-1"));
+        assert!(!scope.allows_source("dev-1"));
+        assert!(scope.allows_project(Some("vibetea")));
+        assert!(!scope.allows_project(Some("other")));
+        assert!(!scope.allows_project(None));
+        assert!(scope.allows_event_type(EventType::Tool));
+        assert!(!scope.allows_event_type(EventType::Session));
+
+        let open = TokenScope::new();
+        assert!(open.is_unrestricted());
+        assert!(open.allows_project(None));
+    }
+
+    #[test]
+    fn authenticates_shared_and_named_tokens() {
+        let registry = TokenRegistry::new(Some("shared"));
+        registry.replace(vec![SubscriberToken::new("ci", "ci-secret")
+            .with_scope(TokenScope::new().with_sources(["ci-*"]))]);
+        let now = Utc::now();
+
+        let shared = registry.authenticate("shared", now).unwrap();
+        assert_eq!(shared.name, SHARED_TOKEN_NAME);
+        assert!(shared.scope.is_unrestricted());
+
+        let ci = registry.authenticate("ci-secret", now).unwrap();
+        assert_eq!(ci.name, "ci");
+        assert_eq!(ci.scope.sources, vec!["ci-*".to_string()]);
+
+        assert_eq!(
+            registry.authenticate("nope", now),
+            Err(TokenRejection::Invalid)
+        );
+    }
+
+    #[test]
+    fn rejects_expired_tokens() {
+        let now = Utc::now();
+        let registry = TokenRegistry::new(None);
+        registry.replace(vec![
+            SubscriberToken::new("old", "old-secret").with_expires(now - Duration::seconds(1)),
+            SubscriberToken::new("new", "new-secret").with_expires(now + Duration::hours(1)),
+        ]);
+
+        assert_eq!(
+            registry.authenticate("old-secret", now),
+            Err(TokenRejection::Expired)
+        );
+        assert!(registry.authenticate("new-secret", now).is_ok());
+    }
+
+    #[test]
+    fn replacing_tokens_revokes_removed_entries_and_keeps_shared() {
+        let registry = TokenRegistry::new(Some("shared"));
+        registry.replace(vec![SubscriberToken::new("ci", "ci-secret")]);
+        assert_eq!(registry.len(), 2);
+
+        registry.replace(Vec::new());
+        assert_eq!(
+            registry.authenticate("ci-secret", Utc::now()),
+            Err(TokenRejection::Invalid)
+        );
+        assert!(registry.authenticate("shared", Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn loads_tokens_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("tokens.json");
+        std::fs::write(
+            &path,
+            r#"{"tokens": [{"name": "ci", "token": "s3cret", "sources": ["ci-*"],
+                "eventTypes": ["tool"], "expires": "2030-01-01T00:00:00Z"}]}"#,
+        )
+        .unwrap();
+
+        let tokens = load_tokens_file(&path).unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(
+            tokens[0].scope,
+            TokenScope::new()
+                .with_sources(["ci-*"])
+                .with_event_types([EventType::Tool])
+        );
+        assert!(tokens[0].expires.is_some());
+    }
+
+    #[test]
+    fn rejects_invalid_tokens_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("tokens.json");
+        let registry = TokenRegistry::new(None);
+        registry.replace(vec![SubscriberToken::new("keep", "keep-secret")]);
+
+        for contents in [
+            r#"{"tokens": [{"name": "", "token": "x"}]}"#,
+            r#"{"tokens": [{"name": "a", "token": " "}]}"#,
+            r#"{"tokens": [{"name": "a", "token": "x"}, {"name": "a", "token": "y"}]}"#,
+            r#"{"tokens": [{"name": "a", "token": "x"}, {"name": "b", "token": "x"}]}"#,
+            "not json",
+        ] {
+            std::fs::write(&path, contents).unwrap();
+            assert!(registry.load_file(&path).is_err(), "{contents} should fail");
+        }
+
+        assert!(registry.authenticate("keep-secret", Utc::now()).is_ok());
+    }
+}
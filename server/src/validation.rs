@@ -0,0 +1,537 @@
+//! Semantic validation of ingested events.
+//!
+//! Deserializing an [`Event`] only proves the JSON has the right shape.
+//! [`EventPayload`] is decoded by its own `type` tag, which version 2 events
+//! must carry and version 1 events get from the event `type` when the payload
+//! has none. Nothing ties the payload tag to the event `type`, so a
+//! `type: "tool"` event carrying a payload tagged `activity` still
+//! deserializes. [`Validator`] checks what serde cannot:
+//!
+//! - the payload variant matches the event `type`
+//! - the ID is `evt_` followed by 20 alphanumeric characters
+//! - the timestamp is neither in the future beyond the allowed clock skew
+//!   nor older than the maximum event age (`VIBETEA_MAX_EVENT_AGE_SECS`)
+//! - string fields stay within [`MAX_NAME_LEN`] or [`MAX_TEXT_LEN`] bytes
+//! - a relayed event has passed through at most [`MAX_HOPS`] hubs
+//!
+//! Before an event is deserialized, [`Validator::check_schema`] checks its
+//! `schemaVersion`, so events from newer monitors and, when compatibility
+//! mode is off, version 1 events get a specific error code.
+//!
+//! `POST /events` validates each event on its own, so one bad event in a
+//! batch is reported back without rejecting the rest.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use chrono::Utc;
+//! use uuid::Uuid;
+//! use vibetea_server::types::{Event, EventPayload, EventType};
+//! use vibetea_server::validation::{ValidationError, Validator};
+//!
+//! let validator = Validator::new(Duration::from_secs(300), Duration::from_secs(86400));
+//! let mut event = Event::new(
+//!     "macbook-pro".to_string(),
+//!     EventType::Activity,
+//!     EventPayload::Activity { session_id: Uuid::new_v4(), project: None },
+//! );
+//! assert!(validator.validate(&event, Utc::now()).is_ok());
+//!
+//! event.event_type = EventType::Tool;
+//! assert!(matches!(
+//!     validator.validate(&event, Utc::now()),
+//!     Err(ValidationError::TypeMismatch { .. })
+//! ));
+//! ```
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use vibetea_protocol::id::{is_valid_event_id, EVENT_ID_PREFIX, EVENT_ID_SUFFIX_LEN};
+
+use crate::types::{
+    Event, EventPayload, EventType, LEGACY_SCHEMA_VERSION, SCHEMA_VERSION,
+    SUPPORTED_SCHEMA_VERSIONS,
+};
+
+/// Maximum length in bytes of names such as the source, tool, model or
+/// session ID.
+pub const MAX_NAME_LEN: usize = 256;
+
+/// Maximum length in bytes of free text such as project paths, tool
+/// context and summaries.
+pub const MAX_TEXT_LEN: usize = 4096;
+
+/// Maximum number of hubs an event may have been relayed through.
+pub const MAX_HOPS: usize = 8;
+
+/// Default maximum age of an ingested event (7 days).
+pub const DEFAULT_MAX_EVENT_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Reasons an event is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationError {
+    /// The event could not be deserialized.
+    #[error("invalid event format: {0}")]
+    InvalidFormat(String),
+
+    /// The event uses a wire format version this server cannot read.
+    #[error("unsupported schemaVersion {0}")]
+    UnsupportedSchemaVersion(u64),
+
+    /// The event has no `schemaVersion` and compatibility mode is off.
+    #[error("events must set schemaVersion {SCHEMA_VERSION}")]
+    LegacySchema,
+
+    /// The ID does not follow the `evt_` + 20 alphanumeric format.
+    #[error("event ID must be '{EVENT_ID_PREFIX}' followed by {EVENT_ID_SUFFIX_LEN} alphanumeric characters")]
+    InvalidId,
+
+    /// The payload belongs to a different event type.
+    #[error("event type '{}' does not match a '{}' payload", .event_type.as_str(), .payload.as_str())]
+    TypeMismatch {
+        event_type: EventType,
+        payload: EventType,
+    },
+
+    /// The timestamp is further in the future than the allowed clock skew.
+    #[error("timestamp is more than {0}s in the future")]
+    FutureTimestamp(u64),
+
+    /// The timestamp is older than the maximum event age.
+    #[error("timestamp is more than {0}s in the past")]
+    TimestampTooOld(u64),
+
+    /// A string field exceeds its maximum length.
+    #[error("field '{field}' exceeds {max} bytes")]
+    FieldTooLong { field: &'static str, max: usize },
+
+    /// The event has been relayed through more than [`MAX_HOPS`] hubs.
+    #[error("event has been relayed more than {MAX_HOPS} times")]
+    TooManyHops,
+
+    /// The event's `source` is not the authenticated sender, and the event
+    /// was not relayed.
+    #[error("event source does not match authenticated source")]
+    SourceMismatch,
+
+    /// The event was relayed, but the sender is not an allowed relay.
+    #[error("source is not allowed to relay events")]
+    RelayNotAllowed,
+
+    /// The event's last hop is not the authenticated sender.
+    #[error("last hop does not match authenticated source")]
+    HopMismatch,
+}
+
+impl ValidationError {
+    /// Returns the machine-readable code reported to clients.
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidFormat(_) => "invalid_format",
+            Self::UnsupportedSchemaVersion(_) => "unsupported_schema_version",
+            Self::LegacySchema => "legacy_schema",
+            Self::InvalidId => "invalid_id",
+            Self::TypeMismatch { .. } => "type_mismatch",
+            Self::FutureTimestamp(_) => "future_timestamp",
+            Self::TimestampTooOld(_) => "timestamp_too_old",
+            Self::FieldTooLong { .. } => "field_too_long",
+            Self::TooManyHops => "too_many_hops",
+            Self::SourceMismatch => "source_mismatch",
+            Self::RelayNotAllowed => "relay_not_allowed",
+            Self::HopMismatch => "hop_mismatch",
+        }
+    }
+}
+
+/// Checks ingested events against the schema rules serde cannot express.
+#[derive(Debug, Clone, Copy)]
+pub struct Validator {
+    max_future_skew: Duration,
+    max_age: Duration,
+    accept_legacy: bool,
+}
+
+impl Validator {
+    /// Creates a validator that accepts timestamps up to `max_future_skew`
+    /// ahead of the server clock and up to `max_age` behind it. A zero
+    /// `max_age` accepts events of any age. Version 1 events are accepted.
+    #[must_use]
+    pub const fn new(max_future_skew: Duration, max_age: Duration) -> Self {
+        Self {
+            max_future_skew,
+            max_age,
+            accept_legacy: true,
+        }
+    }
+
+    /// Sets whether events without `schemaVersion` are accepted.
+    #[must_use]
+    pub const fn with_legacy_events(mut self, accept: bool) -> Self {
+        self.accept_legacy = accept;
+        self
+    }
+
+    /// Returns the wire format versions accepted, oldest first.
+    #[must_use]
+    pub fn schema_versions(&self) -> Vec<u32> {
+        SUPPORTED_SCHEMA_VERSIONS
+            .into_iter()
+            .filter(|version| self.accept_legacy || *version != LEGACY_SCHEMA_VERSION)
+            .collect()
+    }
+
+    /// Checks the `schemaVersion` of a raw event before it is deserialized.
+    ///
+    /// Non-numeric versions are left for deserialization to reject.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::UnsupportedSchemaVersion`] for versions
+    /// this server cannot read, and [`ValidationError::LegacySchema`] for
+    /// events without a version when compatibility mode is off.
+    pub fn check_schema(&self, event: &serde_json::Value) -> Result<(), ValidationError> {
+        match event.get("schemaVersion") {
+            None if !self.accept_legacy => Err(ValidationError::LegacySchema),
+            Some(version) => match version.as_u64() {
+                Some(version)
+                    if !SUPPORTED_SCHEMA_VERSIONS
+                        .iter()
+                        .any(|supported| u64::from(*supported) == version) =>
+                {
+                    Err(ValidationError::UnsupportedSchemaVersion(version))
+                }
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Validates `event`, received at `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first rule the event breaks.
+    pub fn validate(&self, event: &Event, now: DateTime<Utc>) -> Result<(), ValidationError> {
+        validate_id(&event.id)?;
+        check_len("source", &event.source, MAX_NAME_LEN)?;
+        if event.hops.len() > MAX_HOPS {
+            return Err(ValidationError::TooManyHops);
+        }
+        for hop in &event.hops {
+            check_len("hops", hop, MAX_NAME_LEN)?;
+        }
+
+        let payload = event.payload.event_type();
+        if payload != event.event_type {
+            return Err(ValidationError::TypeMismatch {
+                event_type: event.event_type,
+                payload,
+            });
+        }
+
+        let skew =
+            chrono::Duration::from_std(self.max_future_skew).unwrap_or(chrono::Duration::MAX);
+        if event.timestamp > now + skew {
+            return Err(ValidationError::FutureTimestamp(
+                self.max_future_skew.as_secs(),
+            ));
+        }
+        if !self.max_age.is_zero() {
+            let max_age = chrono::Duration::from_std(self.max_age).unwrap_or(chrono::Duration::MAX);
+            if event.timestamp < now - max_age {
+                return Err(ValidationError::TimestampTooOld(self.max_age.as_secs()));
+            }
+        }
+
+        check_payload(&event.payload)
+    }
+}
+
+/// Checks that `id` is `evt_` followed by 20 ASCII alphanumeric characters.
+fn validate_id(id: &str) -> Result<(), ValidationError> {
+    if is_valid_event_id(id) {
+        Ok(())
+    } else {
+        Err(ValidationError::InvalidId)
+    }
+}
+
+fn check_len(field: &'static str, value: &str, max: usize) -> Result<(), ValidationError> {
+    if value.len() > max {
+        return Err(ValidationError::FieldTooLong { field, max });
+    }
+    Ok(())
+}
+
+fn check_optional_len(
+    field: &'static str,
+    value: Option<&String>,
+    max: usize,
+) -> Result<(), ValidationError> {
+    value.map_or(Ok(()), |value| check_len(field, value, max))
+}
+
+/// Checks the length of every string in the payload, including map keys.
+fn check_payload(payload: &EventPayload) -> Result<(), ValidationError> {
+    match payload {
+        EventPayload::Tool {
+            tool,
+            context,
+            project,
+            ..
+        } => {
+            check_len("tool", tool, MAX_NAME_LEN)?;
+            check_optional_len("context", context.as_ref(), MAX_TEXT_LEN)?;
+            check_optional_len("project", project.as_ref(), MAX_TEXT_LEN)
+        }
+        EventPayload::Session { project, .. } => check_len("project", project, MAX_TEXT_LEN),
+        EventPayload::Summary { summary, .. } => check_len("summary", summary, MAX_TEXT_LEN),
+        EventPayload::Agent { state, .. } => check_len("state", state, MAX_NAME_LEN),
+        EventPayload::Error { category, .. } => check_len("category", category, MAX_NAME_LEN),
+        EventPayload::FileChange(change) => {
+            check_len("sessionId", &change.session_id, MAX_NAME_LEN)?;
+            check_len("fileHash", &change.file_hash, MAX_NAME_LEN)
+        }
+        EventPayload::AgentSpawn(spawn) => {
+            check_len("sessionId", &spawn.session_id, MAX_NAME_LEN)?;
+            check_len("agentType", &spawn.agent_type, MAX_NAME_LEN)?;
+            check_len("description", &spawn.description, MAX_TEXT_LEN)
+        }
+        EventPayload::SkillInvocation(skill) => {
+            check_len("sessionId", &skill.session_id, MAX_NAME_LEN)?;
+            check_len("skillName", &skill.skill_name, MAX_NAME_LEN)?;
+            check_len("project", &skill.project, MAX_TEXT_LEN)
+        }
+        EventPayload::TokenUsage(usage) => check_len("model", &usage.model, MAX_NAME_LEN),
+        EventPayload::SessionMetrics(metrics) => {
+            check_len("longestSession", &metrics.longest_session, MAX_NAME_LEN)
+        }
+        EventPayload::ModelDistribution(distribution) => distribution
+            .model_usage
+            .keys()
+            .try_for_each(|model| check_len("modelUsage", model, MAX_NAME_LEN)),
+        EventPayload::TodoProgress(progress) => {
+            check_len("sessionId", &progress.session_id, MAX_NAME_LEN)
+        }
+        EventPayload::ActivityPattern(pattern) => pattern
+            .hour_counts
+            .keys()
+            .try_for_each(|hour| check_len("hourCounts", hour, MAX_NAME_LEN)),
+        EventPayload::ProjectActivity(activity) => {
+            check_len("projectPath", &activity.project_path, MAX_TEXT_LEN)?;
+            check_len("sessionId", &activity.session_id, MAX_NAME_LEN)
+        }
+        EventPayload::Activity { project, .. } => {
+            check_optional_len("project", project.as_ref(), MAX_TEXT_LEN)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{TokenUsageEvent, ToolStatus};
+    use uuid::Uuid;
+
+    const SKEW: Duration = Duration::from_secs(300);
+    const MAX_AGE: Duration = Duration::from_secs(3600);
+
+    fn validator() -> Validator {
+        Validator::new(SKEW, MAX_AGE)
+    }
+
+    fn tool_event(now: DateTime<Utc>) -> Event {
+        Event::new(
+            "macbook-pro".to_string(),
+            EventType::Tool,
+            EventPayload::Tool {
+                session_id: Uuid::new_v4(),
+                tool: "Read".to_string(),
+                status: ToolStatus::Completed,
+                context: Some("main.rs".to_string()),
+                project: Some("vibetea".to_string()),
+            },
+        )
+        .with_id("evt_k7m2n9p4q1r6s3t8u5v0")
+        .with_timestamp(now)
+    }
+
+    #[test]
+    fn accepts_well_formed_event() {
+        let now = Utc::now();
+        assert_eq!(validator().validate(&tool_event(now), now), Ok(()));
+    }
+
+    #[test]
+    fn rejects_malformed_ids() {
+        let now = Utc::now();
+        for id in [
+            "",
+            "evt_short",
+            "evt_k7m2n9p4q1r6s3t8u5v0x",
+            "abc_k7m2n9p4q1r6s3t8u5v0",
+            "evt_k7m2n9p4q1r6s3t8u5-0",
+        ] {
+            let event = Event {
+                id: id.to_string(),
+                ..tool_event(now)
+            };
+            assert_eq!(
+                validator().validate(&event, now),
+                Err(ValidationError::InvalidId),
+                "{id}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_type_payload_mismatch() {
+        let now = Utc::now();
+        let event = Event {
+            event_type: EventType::Tool,
+            payload: EventPayload::Activity {
+                session_id: Uuid::new_v4(),
+                project: None,
+            },
+            ..tool_event(now)
+        };
+
+        let err = validator().validate(&event, now).unwrap_err();
+        assert_eq!(
+            err,
+            ValidationError::TypeMismatch {
+                event_type: EventType::Tool,
+                payload: EventType::Activity,
+            }
+        );
+        assert_eq!(err.code(), "type_mismatch");
+        assert_eq!(
+            err.to_string(),
+            "event type 'tool' does not match a 'activity' payload"
+        );
+    }
+
+    #[test]
+    fn checks_schema_versions() {
+        let v1 = serde_json::json!({"id": "evt_k7m2n9p4q1r6s3t8u5v0"});
+        let v2 = serde_json::json!({"schemaVersion": 2});
+        let v3 = serde_json::json!({"schemaVersion": 3});
+
+        assert_eq!(validator().check_schema(&v1), Ok(()));
+        assert_eq!(validator().check_schema(&v2), Ok(()));
+        assert_eq!(
+            validator().check_schema(&v3),
+            Err(ValidationError::UnsupportedSchemaVersion(3))
+        );
+
+        let strict = validator().with_legacy_events(false);
+        assert_eq!(strict.check_schema(&v1), Err(ValidationError::LegacySchema));
+        assert_eq!(strict.check_schema(&v2), Ok(()));
+        assert_eq!(strict.schema_versions(), [SCHEMA_VERSION]);
+    }
+
+    #[test]
+    fn bounds_timestamps() {
+        let now = Utc::now();
+        let at = |offset: i64| Event {
+            timestamp: now + chrono::Duration::seconds(offset),
+            ..tool_event(now)
+        };
+
+        assert!(validator().validate(&at(299), now).is_ok());
+        assert_eq!(
+            validator().validate(&at(301), now),
+            Err(ValidationError::FutureTimestamp(300))
+        );
+        assert!(validator().validate(&at(-3599), now).is_ok());
+        assert_eq!(
+            validator().validate(&at(-3601), now),
+            Err(ValidationError::TimestampTooOld(3600))
+        );
+
+        // Zero max age accepts old events
+        let unbounded = Validator::new(SKEW, Duration::ZERO);
+        assert!(unbounded.validate(&at(-365 * 86400), now).is_ok());
+    }
+
+    #[test]
+    fn rejects_oversized_strings() {
+        let now = Utc::now();
+        let event = Event {
+            payload: EventPayload::Tool {
+                session_id: Uuid::new_v4(),
+                tool: "Read".to_string(),
+                status: ToolStatus::Completed,
+                context: Some("x".repeat(MAX_TEXT_LEN + 1)),
+                project: None,
+            },
+            ..tool_event(now)
+        };
+        assert_eq!(
+            validator().validate(&event, now),
+            Err(ValidationError::FieldTooLong {
+                field: "context",
+                max: MAX_TEXT_LEN,
+            })
+        );
+
+        let event = Event {
+            event_type: EventType::TokenUsage,
+            payload: EventPayload::TokenUsage(TokenUsageEvent {
+                model: "m".repeat(MAX_NAME_LEN + 1),
+                input_tokens: 1,
+                output_tokens: 1,
+                cache_read_tokens: 0,
+                cache_creation_tokens: 0,
+            }),
+            ..tool_event(now)
+        };
+        assert_eq!(
+            validator().validate(&event, now).unwrap_err().code(),
+            "field_too_long"
+        );
+
+        let event = Event {
+            source: "s".repeat(MAX_NAME_LEN + 1),
+            ..tool_event(now)
+        };
+        assert!(validator().validate(&event, now).is_err());
+    }
+
+    #[test]
+    fn limits_relay_hops() {
+        let now = Utc::now();
+        let hops = |count: usize| (0..count).map(|i| format!("hub-{i}")).collect();
+
+        let event = Event {
+            hops: hops(MAX_HOPS),
+            ..tool_event(now)
+        };
+        assert!(validator().validate(&event, now).is_ok());
+
+        let event = Event {
+            hops: hops(MAX_HOPS + 1),
+            ..tool_event(now)
+        };
+        assert_eq!(
+            validator().validate(&event, now),
+            Err(ValidationError::TooManyHops)
+        );
+
+        let event = Event {
+            hops: vec!["h".repeat(MAX_NAME_LEN + 1)],
+            ..tool_event(now)
+        };
+        assert_eq!(
+            validator().validate(&event, now),
+            Err(ValidationError::FieldTooLong {
+                field: "hops",
+                max: MAX_NAME_LEN,
+            })
+        );
+    }
+}
@@ -0,0 +1,984 @@
+//! Outbound webhooks for the VibeTea server.
+//!
+//! The hub can push matching events to HTTP endpoints, so reacting to an
+//! `agent_spawn` or `error` event does not require a long-running WebSocket
+//! client. Webhooks are listed in a JSON file (`VIBETEA_WEBHOOKS_FILE`):
+//!
+//! ```json
+//! {
+//!   "deadLetterFile": "/var/lib/vibetea/webhooks-dead-letter.ndjson",
+//!   "webhooks": [
+//!     {
+//!       "name": "slack-errors",
+//!       "url": "https://hooks.slack.com/services/...",
+//!       "format": "slack",
+//!       "filter": {"type": ["error", "agent_spawn"]}
+//!     },
+//!     {
+//!       "name": "ci-audit",
+//!       "url": "https://audit.example.com/vibetea",
+//!       "filter": {"source": "ci-*", "type": "tool"},
+//!       "secret": "5c0f9e...",
+//!       "batchWindowMs": 5000,
+//!       "maxBatch": 500
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Each webhook has its own [`SubscriberFilter`] (see [`crate::filter`] for
+//! the syntax) and receives events in batches: the first matching event opens
+//! a window of `batchWindowMs` (default 1000), and the batch is sent when the
+//! window closes or `maxBatch` events (default 100) have been collected.
+//!
+//! # Payloads
+//!
+//! With `"format": "json"` (the default) the body is
+//! `{"webhook": "<name>", "events": [...]}`. With `"format": "slack"` it is a
+//! Slack-compatible `{"text": "..."}` message with one line per event.
+//!
+//! # Signatures
+//!
+//! When a webhook has a `secret`, each request carries an
+//! [`HEADER_TIMESTAMP`] header with the Unix time in seconds and an
+//! [`HEADER_SIGNATURE`] header of the form `sha256=<hex>`, the HMAC-SHA256 of
+//! `<timestamp>.<body>` keyed with the secret (see [`signature`]). Receivers
+//! should recompute it and reject stale timestamps.
+//!
+//! # Retries
+//!
+//! Connection errors, timeouts, `429` and `5xx` responses are retried up to
+//! `maxAttempts` times (default 5) with exponential backoff starting at
+//! `retryDelayMs` (default 1000) and capped at one minute. Other responses
+//! fail immediately. Batches that cannot be delivered are appended to the
+//! dead-letter file as NDJSON [`DeadLetter`] records, if one is configured,
+//! and counted in `vibetea_webhook_deliveries_total`.
+//!
+//! Batches are collected independently of delivery, so a slow endpoint does
+//! not hold up the broadcast channel. Up to [`MAX_QUEUED_BATCHES`] batches
+//! wait while one is being retried; further batches are dropped and counted
+//! with the `dropped` outcome.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use vibetea_server::broadcast::SubscriberFilter;
+//! use vibetea_server::types::EventType;
+//! use vibetea_server::webhooks::{Webhook, WebhookFormat};
+//!
+//! let webhook = Webhook::new("slack-errors", "https://hooks.slack.com/services/T0/B0/x")
+//!     .with_format(WebhookFormat::Slack)
+//!     .with_filter(SubscriberFilter::new().with_event_type(EventType::Error))
+//!     .with_batch_window(Duration::from_secs(5));
+//! assert_eq!(webhook.batch_window_ms, 5000);
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout_at, Instant};
+use tracing::{debug, error, info, warn};
+
+use crate::broadcast::SubscriberFilter;
+use crate::metrics::Metrics;
+use crate::routes::AppState;
+use crate::types::{Event, EventPayload, SessionAction, ToolStatus};
+
+/// Header carrying the request signature, `sha256=<hex>`.
+pub const HEADER_SIGNATURE: &str = "X-VibeTea-Signature";
+
+/// Header carrying the signed Unix timestamp in seconds.
+pub const HEADER_TIMESTAMP: &str = "X-VibeTea-Timestamp";
+
+/// Header carrying the webhook name.
+pub const HEADER_WEBHOOK: &str = "X-VibeTea-Webhook";
+
+/// Default time to collect events before sending a batch, in milliseconds.
+pub const DEFAULT_BATCH_WINDOW_MS: u64 = 1000;
+
+/// Default maximum number of events per batch.
+pub const DEFAULT_MAX_BATCH: usize = 100;
+
+/// Default number of delivery attempts per batch.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Default delay before the first retry, in milliseconds.
+pub const DEFAULT_RETRY_DELAY_MS: u64 = 1000;
+
+/// Maximum delay between retries.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Batches per webhook waiting for delivery before new batches are dropped.
+pub const MAX_QUEUED_BATCHES: usize = 64;
+
+/// Timeout for a single delivery attempt.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Errors that can occur when loading a webhooks file.
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    /// The webhooks file could not be read.
+    #[error("failed to read webhooks file {path}: {source}")]
+    Io {
+        /// Path to the webhooks file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// The webhooks file is not valid JSON in the expected format.
+    #[error("failed to parse webhooks file {path}: {source}")]
+    Parse {
+        /// Path to the webhooks file.
+        path: PathBuf,
+        /// Underlying parse error.
+        source: serde_json::Error,
+    },
+
+    /// A webhook entry is invalid.
+    #[error("invalid webhook {name}: {reason}")]
+    InvalidWebhook {
+        /// Name of the offending entry.
+        name: String,
+        /// Why the entry was rejected.
+        reason: String,
+    },
+}
+
+/// Body format for webhook requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// `{"webhook": "<name>", "events": [...]}`.
+    #[default]
+    Json,
+
+    /// A Slack-compatible incoming webhook message, `{"text": "..."}`.
+    Slack,
+}
+
+/// An HTTP endpoint that receives matching events.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Webhook {
+    /// Name shown in logs, metrics and dead-letter records.
+    pub name: String,
+
+    /// URL to POST batches to.
+    pub url: String,
+
+    /// Body format.
+    #[serde(default)]
+    pub format: WebhookFormat,
+
+    /// Which events to send.
+    #[serde(default)]
+    pub filter: SubscriberFilter,
+
+    /// Key for the HMAC signature header. Requests are unsigned if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+
+    /// Time to collect events before sending a batch, in milliseconds.
+    #[serde(default = "default_batch_window_ms")]
+    pub batch_window_ms: u64,
+
+    /// Maximum number of events per batch.
+    #[serde(default = "default_max_batch")]
+    pub max_batch: usize,
+
+    /// Number of delivery attempts before a batch is dead-lettered.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, in milliseconds. Doubles on each retry.
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+}
+
+fn default_batch_window_ms() -> u64 {
+    DEFAULT_BATCH_WINDOW_MS
+}
+
+fn default_max_batch() -> usize {
+    DEFAULT_MAX_BATCH
+}
+
+fn default_max_attempts() -> u32 {
+    DEFAULT_MAX_ATTEMPTS
+}
+
+fn default_retry_delay_ms() -> u64 {
+    DEFAULT_RETRY_DELAY_MS
+}
+
+impl Webhook {
+    /// Creates a JSON webhook that receives every event, with default
+    /// batching and retries.
+    #[must_use]
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            format: WebhookFormat::default(),
+            filter: SubscriberFilter::new(),
+            secret: None,
+            batch_window_ms: DEFAULT_BATCH_WINDOW_MS,
+            max_batch: DEFAULT_MAX_BATCH,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            retry_delay_ms: DEFAULT_RETRY_DELAY_MS,
+        }
+    }
+
+    /// Sets the body format (builder pattern).
+    #[must_use]
+    pub fn with_format(mut self, format: WebhookFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the event filter (builder pattern).
+    #[must_use]
+    pub fn with_filter(mut self, filter: SubscriberFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Sets the signing secret (builder pattern).
+    #[must_use]
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Sets the batching window (builder pattern).
+    #[must_use]
+    pub fn with_batch_window(mut self, window: Duration) -> Self {
+        self.batch_window_ms = u64::try_from(window.as_millis()).unwrap_or(u64::MAX);
+        self
+    }
+
+    /// Sets the maximum batch size (builder pattern).
+    #[must_use]
+    pub fn with_max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch;
+        self
+    }
+
+    /// Sets the number of attempts and the initial retry delay (builder
+    /// pattern).
+    #[must_use]
+    pub fn with_retries(mut self, max_attempts: u32, initial_delay: Duration) -> Self {
+        self.max_attempts = max_attempts;
+        self.retry_delay_ms = u64::try_from(initial_delay.as_millis()).unwrap_or(u64::MAX);
+        self
+    }
+
+    /// Checks that the entry can be used.
+    fn validate(&self) -> Result<(), WebhookError> {
+        let invalid = |reason: &str| WebhookError::InvalidWebhook {
+            name: self.name.clone(),
+            reason: reason.to_string(),
+        };
+        if self.name.trim().is_empty() {
+            return Err(invalid("name must not be empty"));
+        }
+        match Url::parse(&self.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            Ok(_) => return Err(invalid("url must use http or https")),
+            Err(err) => return Err(invalid(&format!("invalid url: {err}"))),
+        }
+        if self.secret.as_deref().is_some_and(|s| s.is_empty()) {
+            return Err(invalid("secret must not be empty"));
+        }
+        if self.max_batch == 0 {
+            return Err(invalid("maxBatch must be at least 1"));
+        }
+        if self.max_attempts == 0 {
+            return Err(invalid("maxAttempts must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// Contents of a webhooks file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// File that undeliverable batches are appended to. Failures are only
+    /// logged if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_letter_file: Option<PathBuf>,
+
+    /// Configured endpoints.
+    pub webhooks: Vec<Webhook>,
+}
+
+/// Loads and validates a webhooks file.
+///
+/// # Errors
+///
+/// Returns a [`WebhookError`] if the file cannot be read or parsed, or if any
+/// entry has an empty or repeated name, a URL that is not `http` or `https`,
+/// an empty secret, or a zero `maxBatch` or `maxAttempts`.
+pub fn load_webhooks_file(path: &Path) -> Result<WebhookConfig, WebhookError> {
+    let contents = std::fs::read(path).map_err(|source| WebhookError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let config: WebhookConfig =
+        serde_json::from_slice(&contents).map_err(|source| WebhookError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+    let mut names = HashSet::new();
+    for webhook in &config.webhooks {
+        webhook.validate()?;
+        if !names.insert(webhook.name.as_str()) {
+            return Err(WebhookError::InvalidWebhook {
+                name: webhook.name.clone(),
+                reason: "duplicate name".to_string(),
+            });
+        }
+    }
+
+    Ok(config)
+}
+
+/// A batch that could not be delivered, as written to the dead-letter file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    /// Name of the webhook.
+    pub webhook: String,
+
+    /// URL the batch was sent to.
+    pub url: String,
+
+    /// When the last attempt failed.
+    pub failed_at: DateTime<Utc>,
+
+    /// Number of attempts made.
+    pub attempts: u32,
+
+    /// Why the last attempt failed.
+    pub error: String,
+
+    /// The undelivered events.
+    pub events: Vec<Event>,
+}
+
+/// Computes the [`HEADER_SIGNATURE`] value for a request body.
+///
+/// # Example
+///
+/// ```rust
+/// use vibetea_server::webhooks::signature;
+///
+/// let value = signature("secret", "1767225600", b"{}");
+/// assert!(value.starts_with("sha256="));
+/// assert_eq!(value.len(), "sha256=".len() + 64);
+/// ```
+#[must_use]
+pub fn signature(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    let mut value = String::from("sha256=");
+    for byte in mac.finalize().into_bytes() {
+        let _ = write!(value, "{byte:02x}");
+    }
+    value
+}
+
+/// Running webhook delivery tasks.
+///
+/// Dropping the dispatcher stops delivery; batches being collected, queued
+/// or retried are abandoned.
+#[derive(Debug)]
+pub struct WebhookDispatcher {
+    /// Collector and delivery task for each webhook.
+    tasks: Vec<[JoinHandle<()>; 2]>,
+}
+
+impl Drop for WebhookDispatcher {
+    fn drop(&mut self) {
+        for task in self.tasks.iter().flatten() {
+            task.abort();
+        }
+    }
+}
+
+impl WebhookDispatcher {
+    /// Subscribes each webhook in `config` to the broadcaster in `state` and
+    /// starts delivering batches.
+    ///
+    /// Must be called from within a Tokio runtime.
+    #[must_use]
+    pub fn start(config: WebhookConfig, state: &AppState) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        let dead_letter = config
+            .dead_letter_file
+            .map(DeadLetterLog::new)
+            .map(Arc::new);
+
+        let tasks = config
+            .webhooks
+            .into_iter()
+            .map(|webhook| {
+                info!(webhook = %webhook.name, url = %webhook.url, "Starting webhook");
+                let webhook = Arc::new(webhook);
+                let (queue, batches) = mpsc::channel(MAX_QUEUED_BATCHES);
+                let collector = Collector {
+                    webhook: Arc::clone(&webhook),
+                    metrics: state.metrics.clone(),
+                    queue,
+                };
+                let delivery = Delivery {
+                    webhook,
+                    client: client.clone(),
+                    metrics: state.metrics.clone(),
+                    dead_letter: dead_letter.clone(),
+                };
+                [
+                    tokio::spawn(collector.run(state.broadcaster.subscribe())),
+                    tokio::spawn(delivery.run(batches)),
+                ]
+            })
+            .collect();
+
+        Self { tasks }
+    }
+
+    /// Returns the number of webhooks being delivered.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns `true` if no webhooks are configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
+
+/// Appends dead letters to a file, one JSON object per line.
+#[derive(Debug)]
+struct DeadLetterLog {
+    path: PathBuf,
+
+    /// Serializes appends from concurrent webhooks.
+    lock: Mutex<()>,
+}
+
+impl DeadLetterLog {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    async fn append(&self, record: &DeadLetter) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        let _guard = self.lock.lock().await;
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?
+                .write_all(&line)
+        })
+        .await
+        .map_err(std::io::Error::other)?
+    }
+}
+
+/// Why a delivery attempt failed.
+#[derive(Debug)]
+struct AttemptError {
+    message: String,
+
+    /// Whether a later attempt might succeed.
+    retryable: bool,
+}
+
+/// Batches matching events for one webhook.
+struct Collector {
+    webhook: Arc<Webhook>,
+    metrics: Metrics,
+
+    /// Batches waiting for the webhook's [`Delivery`].
+    queue: mpsc::Sender<Vec<Event>>,
+}
+
+impl Collector {
+    /// Collects batches and queues them for delivery until the broadcaster
+    /// closes.
+    async fn run(self, mut rx: Receiver<Event>) {
+        let window = Duration::from_millis(self.webhook.batch_window_ms);
+        let mut batch = Vec::new();
+        let mut closed = false;
+        let mut overflowing = false;
+
+        while !closed {
+            // Wait for the first matching event, then collect until the
+            // window closes or the batch is full
+            let deadline = match self.next_event(&mut rx).await {
+                Some(event) => {
+                    batch.push(event);
+                    Instant::now() + window
+                }
+                None => break,
+            };
+            while batch.len() < self.webhook.max_batch {
+                match timeout_at(deadline, self.next_event(&mut rx)).await {
+                    Ok(Some(event)) => batch.push(event),
+                    Ok(None) => {
+                        closed = true;
+                        break;
+                    }
+                    Err(_) => break,
+                }
+            }
+
+            self.enqueue(std::mem::take(&mut batch), &mut overflowing);
+        }
+
+        debug!(webhook = %self.webhook.name, "Event broadcaster closed, webhook stopped");
+    }
+
+    /// Queues a batch for delivery, dropping it if the queue is full.
+    ///
+    /// `overflowing` tracks whether the previous batch was dropped, so a
+    /// full queue is only logged once.
+    fn enqueue(&self, batch: Vec<Event>, overflowing: &mut bool) {
+        match self.queue.try_send(batch) {
+            Ok(()) => *overflowing = false,
+            Err(TrySendError::Full(batch)) => {
+                if !*overflowing {
+                    warn!(
+                        webhook = %self.webhook.name,
+                        events = batch.len(),
+                        "Webhook delivery queue full, dropping batches"
+                    );
+                }
+                *overflowing = true;
+                self.metrics
+                    .record_webhook_delivery(&self.webhook.name, "dropped");
+            }
+            // Delivery stopped; nothing left to do
+            Err(TrySendError::Closed(_)) => {}
+        }
+    }
+
+    /// Waits for the next event matching the filter.
+    ///
+    /// Returns `None` once the broadcaster has closed.
+    async fn next_event(&self, rx: &mut Receiver<Event>) -> Option<Event> {
+        loop {
+            match rx.recv().await {
+                Ok(event) if self.webhook.filter.matches(&event) => return Some(event),
+                Ok(_) => {}
+                Err(RecvError::Lagged(count)) => {
+                    warn!(
+                        webhook = %self.webhook.name,
+                        skipped = count,
+                        "Webhook fell behind, skipped events"
+                    );
+                    self.metrics
+                        .record_lag(&format!("webhook:{}", self.webhook.name), count);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Delivery state for one webhook.
+struct Delivery {
+    webhook: Arc<Webhook>,
+    client: reqwest::Client,
+    metrics: Metrics,
+    dead_letter: Option<Arc<DeadLetterLog>>,
+}
+
+impl Delivery {
+    /// Sends queued batches until the collector stops.
+    async fn run(self, mut batches: mpsc::Receiver<Vec<Event>>) {
+        while let Some(batch) = batches.recv().await {
+            self.deliver(batch).await;
+        }
+    }
+
+    /// Sends a batch, retrying transient failures and dead-lettering it if
+    /// every attempt fails.
+    async fn deliver(&self, events: Vec<Event>) {
+        let body = match self.body(&events) {
+            Ok(body) => body,
+            Err(err) => {
+                error!(webhook = %self.webhook.name, error = %err, "Failed to serialize webhook batch");
+                return;
+            }
+        };
+
+        let mut delay = Duration::from_millis(self.webhook.retry_delay_ms);
+        let mut attempts = 0;
+        let error = loop {
+            attempts += 1;
+            match self.attempt(&body).await {
+                Ok(()) => {
+                    debug!(
+                        webhook = %self.webhook.name,
+                        events = events.len(),
+                        attempts,
+                        "Delivered webhook batch"
+                    );
+                    self.metrics
+                        .record_webhook_delivery(&self.webhook.name, "delivered");
+                    return;
+                }
+                Err(err) if err.retryable && attempts < self.webhook.max_attempts => {
+                    warn!(
+                        webhook = %self.webhook.name,
+                        error = %err.message,
+                        attempt = attempts,
+                        delay_ms = delay.as_millis() as u64,
+                        "Webhook delivery failed, will retry"
+                    );
+                    sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RETRY_DELAY);
+                }
+                Err(err) => break err.message,
+            }
+        };
+
+        error!(
+            webhook = %self.webhook.name,
+            error = %error,
+            attempts,
+            events = events.len(),
+            "Webhook delivery failed"
+        );
+        self.metrics
+            .record_webhook_delivery(&self.webhook.name, "failed");
+
+        if let Some(log) = &self.dead_letter {
+            let record = DeadLetter {
+                webhook: self.webhook.name.clone(),
+                url: self.webhook.url.clone(),
+                failed_at: Utc::now(),
+                attempts,
+                error,
+                events,
+            };
+            if let Err(err) = log.append(&record).await {
+                error!(
+                    path = %log.path.display(),
+                    error = %err,
+                    "Failed to write webhook dead letter"
+                );
+            }
+        }
+    }
+
+    /// Makes one delivery attempt.
+    async fn attempt(&self, body: &[u8]) -> Result<(), AttemptError> {
+        let mut request = self
+            .client
+            .post(&self.webhook.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(HEADER_WEBHOOK, &self.webhook.name)
+            .body(body.to_vec());
+        if let Some(secret) = &self.webhook.secret {
+            let timestamp = Utc::now().timestamp().to_string();
+            request = request
+                .header(HEADER_SIGNATURE, signature(secret, &timestamp, body))
+                .header(HEADER_TIMESTAMP, timestamp);
+        }
+
+        let response = request.send().await.map_err(|err| AttemptError {
+            message: err.to_string(),
+            retryable: err.is_timeout() || err.is_connect() || err.is_request(),
+        })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        Err(AttemptError {
+            message: format!("endpoint returned {status}"),
+            retryable: status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+        })
+    }
+
+    /// Encodes a batch in the webhook's format.
+    fn body(&self, events: &[Event]) -> serde_json::Result<Vec<u8>> {
+        match self.webhook.format {
+            WebhookFormat::Json => serde_json::to_vec(&serde_json::json!({
+                "webhook": self.webhook.name,
+                "events": events,
+            })),
+            WebhookFormat::Slack => {
+                let text = events.iter().map(slack_line).collect::<Vec<_>>().join("\n");
+                serde_json::to_vec(&serde_json::json!({ "text": text }))
+            }
+        }
+    }
+}
+
+/// Formats an event as one line of a Slack message.
+fn slack_line(event: &Event) -> String {
+    format!(
+        "*{}* from `{}`: {}",
+        event.event_type.as_str(),
+        event.source,
+        describe(&event.payload)
+    )
+}
+
+/// Summarizes an event payload in a few words.
+fn describe(payload: &EventPayload) -> String {
+    let in_project = |project: Option<&str>| {
+        project
+            .map(|project| format!(" in {project}"))
+            .unwrap_or_default()
+    };
+
+    match payload {
+        EventPayload::Session {
+            action, project, ..
+        } => {
+            let action = match action {
+                SessionAction::Started => "started",
+                SessionAction::Ended => "ended",
+            };
+            format!("session {action}{}", in_project(Some(project)))
+        }
+        EventPayload::Tool {
+            tool,
+            status,
+            project,
+            ..
+        } => {
+            let status = match status {
+                ToolStatus::Started => "started",
+                ToolStatus::Completed => "completed",
+            };
+            format!("{tool} {status}{}", in_project(project.as_deref()))
+        }
+        EventPayload::Activity { project, .. } => {
+            format!("activity{}", in_project(project.as_deref()))
+        }
+        EventPayload::Summary { summary, .. } => summary.clone(),
+        EventPayload::Agent { state, .. } => format!("agent {state}"),
+        EventPayload::Error { category, .. } => format!("{category} error"),
+        EventPayload::AgentSpawn(spawn) => {
+            format!("spawned {} agent: {}", spawn.agent_type, spawn.description)
+        }
+        EventPayload::SkillInvocation(skill) => format!(
+            "invoked {}{}",
+            skill.skill_name,
+            in_project(Some(&skill.project))
+        ),
+        EventPayload::TokenUsage(usage) => format!(
+            "{}: {} input, {} output tokens",
+            usage.model, usage.input_tokens, usage.output_tokens
+        ),
+        EventPayload::TodoProgress(todos) => format!(
+            "todos: {} done, {} in progress, {} pending",
+            todos.completed, todos.in_progress, todos.pending
+        ),
+        EventPayload::FileChange(change) => format!(
+            "file changed: +{} -{} ~{} lines",
+            change.lines_added, change.lines_removed, change.lines_modified
+        ),
+        EventPayload::ProjectActivity(activity) => format!(
+            "{} {}",
+            activity.project_path,
+            if activity.is_active {
+                "active"
+            } else {
+                "inactive"
+            }
+        ),
+        EventPayload::SessionMetrics(_)
+        | EventPayload::ModelDistribution(_)
+        | EventPayload::ActivityPattern(_) => "statistics updated".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgentSpawnEvent, EventType};
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    #[test]
+    fn signature_matches_known_hmac() {
+        // HMAC-SHA256 of "1700000000.{}" keyed with "secret"
+        assert_eq!(
+            signature("secret", "1700000000", b"{}"),
+            "sha256=b8569b78799ff9e3cbff0fc2d63a33a2b57f3282abd07c37ae5e8e7d79a5f163"
+        );
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let value = signature("secret", "1700000000", b"{}");
+        assert_ne!(value, signature("secret", "1700000001", b"{}"));
+        assert_ne!(value, signature("secret", "1700000000", b"[]"));
+        assert_ne!(value, signature("other", "1700000000", b"{}"));
+    }
+
+    #[test]
+    fn parses_webhook_with_defaults() {
+        let webhook: Webhook = serde_json::from_str(
+            r#"{"name": "errors", "url": "http://localhost/hook", "format": "slack",
+                "filter": {"type": ["error", "agent_spawn"]}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(webhook.format, WebhookFormat::Slack);
+        assert_eq!(webhook.batch_window_ms, DEFAULT_BATCH_WINDOW_MS);
+        assert_eq!(webhook.max_attempts, DEFAULT_MAX_ATTEMPTS);
+        assert_eq!(
+            webhook.filter,
+            SubscriberFilter::new()
+                .with_event_type(EventType::Error)
+                .with_event_type(EventType::AgentSpawn)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_webhooks_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("webhooks.json");
+
+        for contents in [
+            r#"{"webhooks": [{"name": "", "url": "http://localhost/"}]}"#,
+            r#"{"webhooks": [{"name": "a", "url": "ftp://localhost/"}]}"#,
+            r#"{"webhooks": [{"name": "a", "url": "not a url"}]}"#,
+            r#"{"webhooks": [{"name": "a", "url": "http://localhost/", "maxBatch": 0}]}"#,
+            r#"{"webhooks": [{"name": "a", "url": "http://localhost/", "secret": ""}]}"#,
+            r#"{"webhooks": [{"name": "a", "url": "http://localhost/", "filter": {"type": "bogus"}}]}"#,
+            r#"{"webhooks": [{"name": "a", "url": "http://localhost/", "bogus": 1}]}"#,
+            r#"{"webhooks": [{"name": "a", "url": "http://a/"}, {"name": "a", "url": "http://b/"}]}"#,
+        ] {
+            std::fs::write(&path, contents).unwrap();
+            assert!(load_webhooks_file(&path).is_err(), "{contents} should fail");
+        }
+
+        std::fs::write(
+            &path,
+            r#"{"deadLetterFile": "/tmp/dead.ndjson", "webhooks": [{"name": "a", "url": "https://a/"}]}"#,
+        )
+        .unwrap();
+        let config = load_webhooks_file(&path).unwrap();
+        assert_eq!(config.webhooks.len(), 1);
+        assert_eq!(
+            config.dead_letter_file.as_deref(),
+            Some(Path::new("/tmp/dead.ndjson"))
+        );
+    }
+
+    #[tokio::test]
+    async fn collector_drops_batches_when_queue_is_full() {
+        let broadcaster = crate::broadcast::EventBroadcaster::new();
+        let metrics = Metrics::new();
+        let (queue, mut batches) = mpsc::channel(1);
+        let collector = Collector {
+            webhook: Arc::new(
+                Webhook::new("slow", "http://localhost/hook")
+                    .with_batch_window(Duration::ZERO)
+                    .with_max_batch(1),
+            ),
+            metrics: metrics.clone(),
+            queue,
+        };
+        let rx = broadcaster.subscribe();
+        for n in 0..3 {
+            broadcaster.broadcast(
+                Event::new(
+                    "monitor-1".to_string(),
+                    EventType::Activity,
+                    EventPayload::Activity {
+                        session_id: Uuid::new_v4(),
+                        project: None,
+                    },
+                )
+                .with_id(format!("evt_{n:0>20}")),
+            );
+        }
+        drop(broadcaster);
+
+        // Nothing is delivering, so only the first batch fits in the queue
+        collector.run(rx).await;
+
+        assert_eq!(
+            batches.recv().await.unwrap()[0].id,
+            format!("evt_{:0>20}", 0)
+        );
+        assert!(batches.recv().await.is_none());
+        assert!(metrics
+            .render(&[])
+            .contains(r#"vibetea_webhook_deliveries_total{webhook="slow",outcome="dropped"} 2"#));
+    }
+
+    #[test]
+    fn slack_lines_summarize_events() {
+        let event = Event::new(
+            "ci-linux".to_string(),
+            EventType::AgentSpawn,
+            EventPayload::AgentSpawn(AgentSpawnEvent {
+                session_id: Uuid::new_v4().to_string(),
+                agent_type: "task".to_string(),
+                description: "Run the test suite".to_string(),
+                timestamp: Utc::now(),
+            }),
+        )
+        .with_id("evt_k7m2n9p4q1r6s3t8u5v0");
+        assert_eq!(
+            slack_line(&event),
+            "*agent_spawn* from `ci-linux`: spawned task agent: Run the test suite"
+        );
+
+        let tool = EventPayload::Tool {
+            session_id: Uuid::new_v4(),
+            tool: "Bash".to_string(),
+            status: ToolStatus::Completed,
+            context: None,
+            project: Some("vibetea".to_string()),
+        };
+        assert_eq!(describe(&tool), "Bash completed in vibetea");
+    }
+}
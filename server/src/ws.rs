@@ -0,0 +1,924 @@
+//! WebSocket subscription handling for the VibeTea server.
+//!
+//! This module drives an established `/ws` connection: it forwards filtered
+//! events from the [`EventBroadcaster`](crate::broadcast::EventBroadcaster) to
+//! the client and processes the JSON control messages a client may send to
+//! change its subscriptions, replay missed events or check on the server.
+//!
+//! # Wire Format
+//!
+//! Events are sent as JSON-encoded [`Event`] text messages, exactly as they
+//! were ingested. Control messages use a `type` field that never collides with
+//! an event type, so clients that only understand events can ignore them.
+//!
+//! ## Server → Client
+//!
+//! ```json
+//! {"type": "sessions", "sessions": [{"sessionId": "...", "status": "active", ...}]}
+//! {"type": "subscribed", "id": "tools", "filter": {"type": ["tool"]}}
+//! {"type": "unsubscribed", "id": "tools"}
+//! {"type": "resumed", "replayed": 12, "gap": false}
+//! {"type": "pong", "id": "42", "stats": {"connections": 3, "uptimeSeconds": 3600, ...}}
+//! {"type": "stats", "stats": {"window": "1h", "totalEvents": 1200, "eventsByType": {...}, ...}}
+//! {"type": "lagged", "skipped": 42}
+//! {"type": "control_error", "code": "unknown_subscription", "message": "...", "id": "tools"}
+//! ```
+//!
+//! ## Client → Server
+//!
+//! ```json
+//! {"type": "subscribe", "id": "tools", "filter": {"type": "tool", "project": ["web", "api"]}}
+//! {"type": "unsubscribe", "id": "tools"}
+//! {"type": "resume", "since": "evt_k7m2n9p4q1r6s3t8u5v0"}
+//! {"type": "replay", "sinceTs": "2026-02-02T14:30:00Z"}
+//! {"type": "ping", "id": "42"}
+//! ```
+//!
+//! # Subscriptions
+//!
+//! A connection holds up to [`MAX_SUBSCRIPTIONS`] named subscriptions, each
+//! with its own [`SubscriberFilter`] (see [`crate::filter`] for the filter
+//! object syntax). An event is sent once if it matches any of them. The
+//! filter from the `/ws` query string becomes the `default` subscription.
+//!
+//! `subscribe` adds a subscription, or replaces the filter of an existing one
+//! in place, so a client can change what it receives without reconnecting or
+//! missing events. `subscribe` and `unsubscribe` default to the `default`
+//! subscription when no `id` is given. Every filter is restricted to the
+//! subscriber's token scope. With no subscriptions left, only control
+//! messages are sent.
+//!
+//! # Session Snapshot
+//!
+//! Immediately after connecting, and after each `subscribe`, the server sends
+//! a `sessions` message with the current
+//! [`SessionRegistry`](crate::sessions::SessionRegistry) snapshot, restricted
+//! to the sessions matching a subscription's `source` and `project` filters
+//! and the subscriber's token scope.
+//!
+//! # Resuming
+//!
+//! A client that reconnects can pass `since=<event id>` or `since_ts=<RFC 3339>`
+//! on the `/ws` query string, or send a `resume` control message at any time.
+//! (`replay` is accepted as an alias). The server replays the missed events
+//! that match the connection's subscriptions, followed by a `resumed`
+//! message, and then continues with live events. `gap: true` indicates the
+//! resume point was older than the retained window.
+//!
+//! # Rollups
+//!
+//! Connections whose token is not scoped also receive a `stats` message each
+//! time the server publishes its `1h` rollup (every
+//! `VIBETEA_STATS_INTERVAL_SECS`, see [`crate::stats`]), regardless of their
+//! subscriptions. The rollup is the same as `GET /stats?window=1h`.
+//!
+//! # Slow Clients
+//!
+//! Messages for each connection go through a bounded queue drained by a
+//! separate writer task, with a timeout on every write. When a client falls
+//! behind, events are dropped, coalesced or the connection is closed according
+//! to the configured policy, and dropped events are reported with a `lagged`
+//! message. See [`crate::outbox`].
+//!
+//! # Heartbeat
+//!
+//! The server sends a ping frame every `VIBETEA_WS_PING_INTERVAL_SECS` and
+//! drops the connection once `VIBETEA_WS_MAX_MISSED_PONGS` pings in a row go
+//! unanswered, so half-open connections from sleeping laptops stop counting
+//! as subscribers. WebSocket clients answer pings automatically. If
+//! `VIBETEA_WS_MAX_LIFETIME_SECS` is set, connections older than that are
+//! closed with [`CLOSE_GOING_AWAY`] and are expected to reconnect and resume.
+
+use std::collections::BTreeMap;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::ws::{CloseFrame, Message, WebSocket};
+use chrono::{DateTime, Utc};
+use futures_util::stream::SplitSink;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+use tokio::time::{interval_at, sleep, timeout, Instant, Interval, MissedTickBehavior};
+use tracing::{debug, error, info, trace, warn};
+
+use crate::broadcast::SubscriberFilter;
+use crate::metrics::Metrics;
+use crate::outbox::{Closed, Outbox, Outgoing, CLOSE_SLOW_CONSUMER};
+use crate::replay::{subscribe_from, ResumePoint};
+use crate::routes::AppState;
+use crate::sessions::Session;
+use crate::stats::{next_rollup, Stats};
+use crate::tokens::TokenScope;
+use crate::types::Event;
+
+/// ID of the subscription created from the `/ws` query string, and the
+/// subscription `subscribe` and `unsubscribe` act on when no `id` is given.
+pub const DEFAULT_SUBSCRIPTION: &str = "default";
+
+/// Maximum number of subscriptions on one connection.
+pub const MAX_SUBSCRIPTIONS: usize = 16;
+
+/// Default interval between server-initiated ping frames.
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default number of consecutive unanswered pings before a connection is
+/// dropped.
+pub const DEFAULT_MAX_MISSED_PONGS: u32 = 2;
+
+/// Close code sent when the server ends a connection because it reached its
+/// maximum lifetime or stopped answering pings ("Going Away").
+pub const CLOSE_GOING_AWAY: u16 = 1001;
+
+/// Control messages sent from the server to a WebSocket client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Snapshot of known sessions, sent on connect and after `subscribe`.
+    Sessions {
+        /// Sessions matching the connection's subscriptions, most recent first.
+        sessions: Vec<Session>,
+    },
+
+    /// Acknowledges a `subscribe` message.
+    Subscribed {
+        /// ID of the subscription.
+        id: String,
+
+        /// The filter now in effect for the subscription.
+        filter: Box<SubscriberFilter>,
+    },
+
+    /// Acknowledges an `unsubscribe` message.
+    Unsubscribed {
+        /// ID of the removed subscription.
+        id: String,
+    },
+
+    /// Sent after replayed events, before live events resume.
+    Resumed {
+        /// Number of events replayed (after filtering).
+        replayed: usize,
+
+        /// `true` if events between the resume point and the replay may be
+        /// missing because they fell outside the retained window.
+        gap: bool,
+    },
+
+    /// Reply to a `ping` message.
+    Pong {
+        /// The `id` of the `ping`, if it had one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+
+        /// Current server and connection statistics.
+        stats: ServerStats,
+    },
+
+    /// Periodic rollup of server-wide aggregates.
+    Stats {
+        /// Aggregates over the last hour.
+        stats: Box<Stats>,
+    },
+
+    /// Events were dropped because the client was reading too slowly.
+    Lagged {
+        /// Number of events dropped since the last `lagged` message.
+        skipped: u64,
+    },
+
+    /// A client message could not be processed.
+    ///
+    /// Tagged `control_error` so that it cannot be mistaken for an `error`
+    /// event.
+    #[serde(rename = "control_error")]
+    Error {
+        /// Machine-readable error code.
+        code: String,
+
+        /// Human-readable description.
+        message: String,
+
+        /// ID of the subscription or request the error refers to, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+}
+
+/// Statistics reported in a `pong` message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStats {
+    /// Open subscriber connections across the server.
+    pub connections: usize,
+
+    /// Seconds since the server started.
+    pub uptime_seconds: u64,
+
+    /// Sessions tracked by the session registry.
+    pub sessions: usize,
+
+    /// Events retained in memory for resuming subscribers.
+    pub history_events: usize,
+
+    /// Subscriptions on this connection.
+    pub subscriptions: usize,
+
+    /// Events sent on this connection.
+    pub events_sent: u64,
+}
+
+/// Control messages sent from a WebSocket client to the server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Adds a subscription, or replaces the filter of an existing one.
+    Subscribe {
+        /// ID of the subscription.
+        #[serde(default = "default_subscription_id")]
+        id: String,
+
+        /// Events to receive. An empty filter receives everything in scope.
+        #[serde(default)]
+        filter: Box<SubscriberFilter>,
+    },
+
+    /// Removes a subscription.
+    Unsubscribe {
+        /// ID of the subscription.
+        #[serde(default = "default_subscription_id")]
+        id: String,
+    },
+
+    /// Replay events missed since an event ID or timestamp.
+    #[serde(rename_all = "camelCase", alias = "replay")]
+    Resume {
+        /// ID of the last event the client received.
+        since: Option<String>,
+
+        /// Timestamp of the last event the client received.
+        since_ts: Option<DateTime<Utc>>,
+    },
+
+    /// Asks for a `pong` with server statistics.
+    Ping {
+        /// Echoed back in the `pong`.
+        #[serde(default)]
+        id: Option<String>,
+    },
+}
+
+fn default_subscription_id() -> String {
+    DEFAULT_SUBSCRIPTION.to_string()
+}
+
+/// Builds a resume point from an event ID and/or timestamp.
+///
+/// The event ID takes precedence because it identifies an exact position in
+/// the stream; timestamps are only as precise as the monitors' clocks.
+#[must_use]
+pub fn resume_point(since: Option<&str>, since_ts: Option<DateTime<Utc>>) -> Option<ResumePoint> {
+    match (since.filter(|id| !id.is_empty()), since_ts) {
+        (Some(id), _) => Some(ResumePoint::AfterEvent(id.to_string())),
+        (None, Some(ts)) => Some(ResumePoint::AfterTimestamp(ts)),
+        (None, None) => None,
+    }
+}
+
+type WsSender = SplitSink<WebSocket, Message>;
+
+/// Why a connection stopped forwarding events.
+#[derive(Debug)]
+struct Disconnected;
+
+impl From<Closed> for Disconnected {
+    fn from(_: Closed) -> Self {
+        Self
+    }
+}
+
+/// Handles an established WebSocket connection.
+///
+/// `filter` becomes the `default` subscription, and its scope applies to
+/// every subscription the client adds. Replays missed events if `resume` is
+/// set, then forwards matching live events until the client disconnects, the
+/// broadcaster closes, or the client falls too far behind (see
+/// [`crate::outbox`]).
+pub(crate) async fn handle_websocket(
+    socket: WebSocket,
+    state: AppState,
+    filter: SubscriberFilter,
+    resume: Option<ResumePoint>,
+) {
+    let (sender, mut receiver) = socket.split();
+
+    info!("WebSocket client connected");
+
+    let config = Arc::clone(&state.config);
+    let outbox = Outbox::new(config.ws_queue_capacity, config.slow_consumer_policy);
+    let writer = tokio::spawn(write_messages(
+        sender,
+        outbox.clone(),
+        config.ws_write_timeout,
+        state.metrics.clone(),
+    ));
+
+    let scope = filter.scope.clone();
+    // Rollups cover every source, so only unscoped subscribers receive them
+    let mut rollups = scope.is_unrestricted().then(|| state.stats.subscribe());
+    let mut connection = Connection {
+        state,
+        outbox,
+        subscriptions: BTreeMap::from([(default_subscription_id(), filter)]),
+        scope,
+        last_seen: None,
+        events_sent: 0,
+    };
+
+    // The session snapshot always comes first, followed by any replay
+    let subscribed = match resume {
+        Some(point) => match connection.send_sessions() {
+            Ok(()) => connection.resume(&point).await,
+            Err(Disconnected) => Err(Disconnected),
+        },
+        None => {
+            // Subscribe first so events published during the snapshot are kept
+            let rx = connection.state.broadcaster.subscribe();
+            connection.send_sessions().map(|()| rx)
+        }
+    };
+
+    if let Ok(mut event_rx) = subscribed {
+        let mut heartbeat = heartbeat_interval(config.ws_ping_interval);
+        let mut missed_pongs = 0;
+        let lifetime = sleep_or_pending(config.ws_max_lifetime);
+        tokio::pin!(lifetime);
+
+        loop {
+            tokio::select! {
+                result = event_rx.recv() => match result {
+                    Ok(event) => {
+                        if connection.forward(event).is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(count)) => {
+                        warn!(skipped = count, "WebSocket client lagged, skipped events");
+                        connection.state.metrics.record_lag("websocket", count);
+                        // Recover the skipped events from history when possible
+                        if let Some(last_seen) = connection.last_seen.clone() {
+                            let point = ResumePoint::AfterEvent(last_seen);
+                            match connection.resume(&point).await {
+                                Ok(rx) => event_rx = rx,
+                                Err(Disconnected) => break,
+                            }
+                        } else {
+                            connection.outbox.record_skipped(count);
+                        }
+                    }
+                    Err(RecvError::Closed) => {
+                        debug!("Event broadcaster closed");
+                        break;
+                    }
+                },
+                stats = next_rollup(&mut rollups) => {
+                    let message = ServerMessage::Stats {
+                        stats: Box::new(Stats::clone(&stats)),
+                    };
+                    if connection.send(message).is_err() {
+                        break;
+                    }
+                }
+                () = tick(&mut heartbeat) => {
+                    if missed_pongs >= config.ws_max_missed_pongs {
+                        info!(missed_pongs, "WebSocket client stopped answering pings, dropping connection");
+                        connection.outbox.close_with(CLOSE_GOING_AWAY, "heartbeat timeout");
+                        break;
+                    }
+                    missed_pongs += 1;
+                    if connection.outbox.push_ping().is_err() {
+                        break;
+                    }
+                }
+                () = &mut lifetime => {
+                    debug!("WebSocket connection reached its maximum lifetime");
+                    connection.outbox.close_with(CLOSE_GOING_AWAY, "connection lifetime exceeded");
+                    break;
+                }
+                msg = receiver.next() => match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        match connection.handle_text(text.as_str()).await {
+                            Ok(Some(rx)) => event_rx = rx,
+                            Ok(None) => {}
+                            Err(Disconnected) => break,
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) => {
+                        debug!("WebSocket client sent close frame");
+                        break;
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        // axum handles pong automatically
+                        trace!(data_len = data.len(), "Received ping");
+                    }
+                    Some(Ok(Message::Pong(_))) => {
+                        missed_pongs = 0;
+                    }
+                    Some(Ok(_)) => {
+                        // Ignore binary messages
+                    }
+                    Some(Err(err)) => {
+                        debug!(error = %err, "WebSocket error");
+                        break;
+                    }
+                    None => break,
+                },
+            }
+        }
+    }
+
+    // Let the writer flush what is queued; each write is bounded by the
+    // write timeout
+    connection.outbox.close();
+    let _ = writer.await;
+
+    info!("WebSocket client disconnected");
+}
+
+/// Sends queued messages to the client until the outbox is closed and
+/// drained, a write fails, or a write takes longer than `write_timeout`.
+async fn write_messages(
+    mut sender: WsSender,
+    outbox: Outbox,
+    write_timeout: Duration,
+    metrics: Metrics,
+) {
+    while let Some(outgoing) = outbox.pop().await {
+        let message = match outgoing {
+            Outgoing::Event(event) => match serde_json::to_string(&event) {
+                Ok(json) => {
+                    trace!(event_id = %event.id, "Sending event to WebSocket client");
+                    Message::Text(json.into())
+                }
+                Err(err) => {
+                    error!(error = %err, "Failed to serialize event");
+                    continue;
+                }
+            },
+            Outgoing::Control(message) => {
+                if let ServerMessage::Lagged { skipped } = message {
+                    warn!(skipped, "WebSocket client is slow, dropped events");
+                    metrics.record_lag("websocket", skipped);
+                }
+                match serde_json::to_string(&message) {
+                    Ok(json) => Message::Text(json.into()),
+                    Err(err) => {
+                        error!(error = %err, "Failed to serialize control message");
+                        continue;
+                    }
+                }
+            }
+            Outgoing::Ping => Message::Ping(Bytes::new()),
+            Outgoing::Close { code, reason } => {
+                if code == CLOSE_SLOW_CONSUMER {
+                    warn!("WebSocket client is too slow, closing connection");
+                }
+                Message::Close(Some(CloseFrame {
+                    code,
+                    reason: reason.into(),
+                }))
+            }
+        };
+
+        match timeout(write_timeout, sender.send(message)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => {
+                debug!(error = %err, "Failed to send message to WebSocket client");
+                break;
+            }
+            Err(_) => {
+                warn!(
+                    timeout_secs = write_timeout.as_secs_f64(),
+                    "WebSocket write timed out, closing connection"
+                );
+                break;
+            }
+        }
+    }
+
+    // Stop the connection task from queueing more
+    outbox.close();
+}
+
+/// Returns the heartbeat interval, or `None` if pings are disabled.
+///
+/// The first tick is one period after the connection opens.
+fn heartbeat_interval(period: Duration) -> Option<Interval> {
+    if period.is_zero() {
+        return None;
+    }
+    let mut interval = interval_at(Instant::now() + period, period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    Some(interval)
+}
+
+/// Waits for the next heartbeat tick. Never completes if pings are disabled.
+async fn tick(heartbeat: &mut Option<Interval>) {
+    match heartbeat {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Sleeps for `duration`. Never completes if `duration` is zero.
+async fn sleep_or_pending(duration: Duration) {
+    if duration.is_zero() {
+        std::future::pending::<()>().await;
+    }
+    sleep(duration).await;
+}
+
+/// Per-connection state.
+struct Connection {
+    state: AppState,
+
+    /// Messages waiting for the writer task.
+    outbox: Outbox,
+
+    /// Active subscriptions by ID. An event is sent if any of them match.
+    subscriptions: BTreeMap<String, SubscriberFilter>,
+
+    /// Scope of the subscriber's token, applied to every subscription.
+    scope: TokenScope,
+
+    /// ID of the last event received from the broadcaster (matching or not),
+    /// used to recover from lag.
+    last_seen: Option<String>,
+
+    /// Number of events forwarded to the client.
+    events_sent: u64,
+}
+
+impl Connection {
+    /// Queues a control message.
+    fn send(&self, message: ServerMessage) -> Result<(), Disconnected> {
+        self.outbox
+            .push_control(message)
+            .map_err(Disconnected::from)
+    }
+
+    /// Queues an `error` control message.
+    fn send_error(
+        &self,
+        code: &str,
+        message: String,
+        id: Option<String>,
+    ) -> Result<(), Disconnected> {
+        self.send(ServerMessage::Error {
+            code: code.to_string(),
+            message,
+            id,
+        })
+    }
+
+    /// Queues the session snapshot.
+    fn send_sessions(&self) -> Result<(), Disconnected> {
+        let sessions = self.session_snapshot();
+        self.send(ServerMessage::Sessions { sessions })
+    }
+
+    /// Returns the sessions matching any subscription's source and project
+    /// filters and the token scope.
+    fn session_snapshot(&self) -> Vec<Session> {
+        let mut sessions = self.state.sessions.snapshot(Utc::now());
+        sessions.retain(|session| {
+            self.subscriptions
+                .values()
+                .any(|filter| filter.matches_session(session))
+        });
+        sessions
+    }
+
+    /// Returns `true` if `event` matches any subscription.
+    fn matches(&self, event: &Event) -> bool {
+        self.subscriptions
+            .values()
+            .any(|filter| filter.matches(event))
+    }
+
+    /// Returns the current server and connection statistics.
+    fn stats(&self) -> ServerStats {
+        ServerStats {
+            connections: self.state.clients.count(),
+            uptime_seconds: self.state.start_time.elapsed().as_secs(),
+            sessions: self.state.sessions.len(),
+            history_events: self.state.broadcaster.history_len(),
+            subscriptions: self.subscriptions.len(),
+            events_sent: self.events_sent,
+        }
+    }
+
+    /// Queues a live event if it matches any subscription, applying the
+    /// slow-consumer policy if the client is behind.
+    fn forward(&mut self, event: Event) -> Result<(), Disconnected> {
+        self.last_seen = Some(event.id.clone());
+
+        if !self.matches(&event) {
+            trace!(event_id = %event.id, "Event filtered out");
+            return Ok(());
+        }
+
+        self.outbox.push_event(event)?;
+        self.events_sent += 1;
+        Ok(())
+    }
+
+    /// Re-subscribes from `point`, queueing replayed events and a `resumed`
+    /// message.
+    ///
+    /// Replayed events wait for space in the outbox rather than being dropped.
+    async fn resume(&mut self, point: &ResumePoint) -> Result<Receiver<Event>, Disconnected> {
+        let (mut replay, rx) =
+            subscribe_from(&self.state.broadcaster, self.state.store.as_ref(), point);
+
+        let mut replayed = 0;
+        while let Some(event) = replay.next().await {
+            self.last_seen = Some(event.id.clone());
+            if self.matches(&event) {
+                self.outbox.push_event_wait(event).await?;
+                self.events_sent += 1;
+                replayed += 1;
+            }
+        }
+
+        let gap = replay.gap();
+        info!(replayed, gap, "Replayed missed events to WebSocket client");
+        self.send(ServerMessage::Resumed { replayed, gap })?;
+        Ok(rx)
+    }
+
+    /// Processes a text message from the client.
+    ///
+    /// Returns a new receiver if the message re-subscribed the connection.
+    async fn handle_text(&mut self, text: &str) -> Result<Option<Receiver<Event>>, Disconnected> {
+        let message = match serde_json::from_str::<ClientMessage>(text) {
+            Ok(message) => message,
+            Err(err) => {
+                debug!(error = %err, "Invalid WebSocket control message");
+                let message = format!("invalid control message: {err}");
+                self.send_error("invalid_message", message, None)?;
+                return Ok(None);
+            }
+        };
+
+        match message {
+            ClientMessage::Subscribe { id, filter } => {
+                self.subscribe(id, *filter)?;
+                Ok(None)
+            }
+            ClientMessage::Unsubscribe { id } => {
+                if self.subscriptions.remove(&id).is_some() {
+                    debug!(subscription = %id, "WebSocket client unsubscribed");
+                    self.send(ServerMessage::Unsubscribed { id })?;
+                } else {
+                    let message = format!("no subscription with id '{id}'");
+                    self.send_error("unknown_subscription", message, Some(id))?;
+                }
+                Ok(None)
+            }
+            ClientMessage::Resume { since, since_ts } => {
+                let Some(point) = resume_point(since.as_deref(), since_ts) else {
+                    let message = "resume requires 'since' or 'sinceTs'".to_string();
+                    self.send_error("invalid_resume", message, None)?;
+                    return Ok(None);
+                };
+                self.resume(&point).await.map(Some)
+            }
+            ClientMessage::Ping { id } => {
+                let stats = self.stats();
+                self.send(ServerMessage::Pong { id, stats })?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Adds or replaces a subscription, then acknowledges it and sends a
+    /// fresh session snapshot.
+    fn subscribe(&mut self, id: String, filter: SubscriberFilter) -> Result<(), Disconnected> {
+        if id.is_empty() {
+            let message = "subscription id must not be empty".to_string();
+            return self.send_error("invalid_subscription", message, None);
+        }
+        if !self.subscriptions.contains_key(&id) && self.subscriptions.len() >= MAX_SUBSCRIPTIONS {
+            let message = format!("at most {MAX_SUBSCRIPTIONS} subscriptions per connection");
+            return self.send_error("too_many_subscriptions", message, Some(id));
+        }
+
+        let filter = filter.with_scope(self.scope.clone());
+        debug!(subscription = %id, filter = ?filter, "WebSocket client subscribed");
+        self.subscriptions.insert(id.clone(), filter.clone());
+        self.send(ServerMessage::Subscribed {
+            id,
+            filter: Box::new(filter),
+        })?;
+        self.send_sessions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_message_resumed_serialization() {
+        let json = serde_json::to_value(ServerMessage::Resumed {
+            replayed: 3,
+            gap: false,
+        })
+        .unwrap();
+        assert_eq!(json["type"], "resumed");
+        assert_eq!(json["replayed"], 3);
+        assert_eq!(json["gap"], false);
+    }
+
+    #[test]
+    fn client_message_resume_by_id() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type":"resume","since":"evt_abc"}"#).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Resume {
+                since: Some("evt_abc".to_string()),
+                since_ts: None,
+            }
+        );
+    }
+
+    #[test]
+    fn client_message_resume_by_timestamp() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type":"resume","sinceTs":"2026-02-02T14:30:00Z"}"#).unwrap();
+        let ClientMessage::Resume { since, since_ts } = message else {
+            panic!("expected resume, got {message:?}");
+        };
+        assert!(since.is_none());
+        assert!(since_ts.is_some());
+    }
+
+    #[test]
+    fn client_message_replay_is_resume() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type":"replay","since":"evt_abc"}"#).unwrap();
+        assert!(matches!(message, ClientMessage::Resume { .. }));
+    }
+
+    #[test]
+    fn client_message_subscribe_with_filter() {
+        let message: ClientMessage = serde_json::from_str(
+            r#"{"type":"subscribe","id":"tools","filter":{"type":"tool","project":["a","b"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Subscribe {
+                id: "tools".to_string(),
+                filter: Box::new(
+                    SubscriberFilter::new()
+                        .with_event_type(crate::types::EventType::Tool)
+                        .with_project("a")
+                        .with_project("b")
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn client_message_ids_default_to_default_subscription() {
+        let message: ClientMessage = serde_json::from_str(r#"{"type":"subscribe"}"#).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Subscribe {
+                id: DEFAULT_SUBSCRIPTION.to_string(),
+                filter: Box::default(),
+            }
+        );
+
+        let message: ClientMessage = serde_json::from_str(r#"{"type":"unsubscribe"}"#).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Unsubscribe {
+                id: DEFAULT_SUBSCRIPTION.to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn client_message_rejects_invalid_filter() {
+        assert!(serde_json::from_str::<ClientMessage>(
+            r#"{"type":"subscribe","filter":{"type":"bogus"}}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn server_message_types_never_collide_with_event_types() {
+        use crate::stats::{StatsAggregator, StatsWindow};
+        use crate::types::EventType;
+
+        let stats = StatsAggregator::new().snapshot(StatsWindow::Hour, Utc::now());
+        let messages = [
+            ServerMessage::Sessions {
+                sessions: Vec::new(),
+            },
+            ServerMessage::Subscribed {
+                id: "default".to_string(),
+                filter: Box::default(),
+            },
+            ServerMessage::Unsubscribed {
+                id: "default".to_string(),
+            },
+            ServerMessage::Resumed {
+                replayed: 0,
+                gap: false,
+            },
+            ServerMessage::Pong {
+                id: None,
+                stats: ServerStats {
+                    connections: 0,
+                    uptime_seconds: 0,
+                    sessions: 0,
+                    history_events: 0,
+                    subscriptions: 0,
+                    events_sent: 0,
+                },
+            },
+            ServerMessage::Stats {
+                stats: Box::new(stats),
+            },
+            ServerMessage::Lagged { skipped: 0 },
+            ServerMessage::Error {
+                code: "invalid_message".to_string(),
+                message: String::new(),
+                id: None,
+            },
+        ];
+
+        for message in messages {
+            // Fails to compile when a variant is added, so it gets listed above
+            match message {
+                ServerMessage::Sessions { .. }
+                | ServerMessage::Subscribed { .. }
+                | ServerMessage::Unsubscribed { .. }
+                | ServerMessage::Resumed { .. }
+                | ServerMessage::Pong { .. }
+                | ServerMessage::Stats { .. }
+                | ServerMessage::Lagged { .. }
+                | ServerMessage::Error { .. } => {}
+            }
+
+            let json = serde_json::to_value(&message).unwrap();
+            let tag = json["type"].as_str().unwrap();
+            assert!(
+                EventType::ALL.iter().all(|t| t.as_str() != tag),
+                "control message type `{tag}` is also an event type"
+            );
+        }
+    }
+
+    #[test]
+    fn server_message_error_omits_missing_id() {
+        let json = serde_json::to_value(ServerMessage::Error {
+            code: "invalid_message".to_string(),
+            message: "bad".to_string(),
+            id: None,
+        })
+        .unwrap();
+        assert_eq!(json["type"], "control_error");
+        assert!(json.get("id").is_none());
+    }
+
+    #[test]
+    fn client_message_rejects_unknown_type() {
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"type":"dance"}"#).is_err());
+    }
+
+    #[test]
+    fn resume_point_prefers_event_id() {
+        let ts = Utc::now();
+        assert_eq!(
+            resume_point(Some("evt_abc"), Some(ts)),
+            Some(ResumePoint::AfterEvent("evt_abc".to_string()))
+        );
+        assert_eq!(
+            resume_point(None, Some(ts)),
+            Some(ResumePoint::AfterTimestamp(ts))
+        );
+        assert_eq!(resume_point(Some(""), None), None);
+        assert_eq!(resume_point(None, None), None);
+    }
+}
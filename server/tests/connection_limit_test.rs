@@ -0,0 +1,109 @@
+//! Integration tests for the per-IP WebSocket connection cap.
+//!
+//! The server must be served with connect info for the peer address to be
+//! known, mirroring how `main` runs it.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use tokio::net::TcpListener;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Error as WsError;
+
+use vibetea_server::config::Config;
+use vibetea_server::routes::{create_router, AppState};
+
+fn test_config(max_per_ip: usize) -> Config {
+    Config {
+        public_keys: HashMap::new(),
+        subscriber_token: None,
+        port: 0,
+        unsafe_no_auth: true,
+        max_ws_connections_per_ip: max_per_ip,
+        ..Config::default()
+    }
+}
+
+async fn spawn_test_server(config: Config) -> (SocketAddr, AppState, tokio::task::JoinHandle<()>) {
+    let state = AppState::new(config);
+    let app = create_router(state.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    (addr, state, handle)
+}
+
+fn rejection_status(result: Result<impl Sized, WsError>) -> u16 {
+    match result {
+        Err(WsError::Http(response)) => response.status().as_u16(),
+        Err(other) => panic!("unexpected error: {other}"),
+        Ok(_) => panic!("connection should have been rejected"),
+    }
+}
+
+#[tokio::test]
+async fn rejects_connections_over_the_per_ip_cap() {
+    let (addr, state, server) = spawn_test_server(test_config(2)).await;
+    let url = format!("ws://{addr}/ws");
+
+    let (_first, _) = connect_async(&url).await.expect("first connection");
+    let (_second, _) = connect_async(&url).await.expect("second connection");
+
+    assert_eq!(rejection_status(connect_async(&url).await), 429);
+    assert!(state
+        .metrics
+        .render(&[])
+        .contains("vibetea_ws_connections_rejected_total 1"));
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn closing_a_connection_frees_its_slot() {
+    let (addr, state, server) = spawn_test_server(test_config(1)).await;
+    let url = format!("ws://{addr}/ws");
+    let ip = "127.0.0.1".parse().unwrap();
+
+    let (mut first, _) = connect_async(&url).await.expect("first connection");
+    assert_eq!(rejection_status(connect_async(&url).await), 429);
+
+    first.close(None).await.unwrap();
+    while first.next().await.is_some() {}
+
+    // The handler releases the slot once it notices the close
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while state.connection_limiter.connection_count(ip) > 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("slot should be released");
+
+    connect_async(&url).await.expect("slot should be reusable");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn zero_disables_the_cap() {
+    let (addr, _state, server) = spawn_test_server(test_config(0)).await;
+    let url = format!("ws://{addr}/ws");
+
+    let mut clients = Vec::new();
+    for _ in 0..5 {
+        clients.push(connect_async(&url).await.expect("connection").0);
+    }
+
+    server.abort();
+}
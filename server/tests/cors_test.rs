@@ -0,0 +1,189 @@
+//! Integration tests for the browser origin policy.
+//!
+//! These tests verify CORS headers on the REST routes, including preflight
+//! requests, and `Origin` checks on WebSocket upgrades.
+
+use std::net::SocketAddr;
+
+use reqwest::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN,
+};
+use reqwest::Method;
+use tokio::net::TcpListener;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Error as WsError;
+
+use vibetea_server::config::Config;
+use vibetea_server::routes::{create_router, AppState};
+
+const DASHBOARD: &str = "https://dashboard.example.com";
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn test_config(allowed_origins: &[&str]) -> Config {
+    Config {
+        unsafe_no_auth: true,
+        allowed_origins: allowed_origins.iter().map(ToString::to_string).collect(),
+        ..Config::default()
+    }
+}
+
+async fn spawn_server(config: Config) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let app = create_router(AppState::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    (addr, handle)
+}
+
+/// Opens `/ws`, optionally as a page on `origin`.
+async fn connect_ws(addr: SocketAddr, origin: Option<&str>) -> Result<(), WsError> {
+    let mut request = format!("ws://{addr}/ws").into_client_request().unwrap();
+    if let Some(origin) = origin {
+        request
+            .headers_mut()
+            .insert("Origin", HeaderValue::from_str(origin).unwrap());
+    }
+    connect_async(request).await.map(|_| ())
+}
+
+fn rejection_status(result: Result<(), WsError>) -> u16 {
+    match result {
+        Err(WsError::Http(response)) => response.status().as_u16(),
+        Err(other) => panic!("unexpected error: {other}"),
+        Ok(()) => panic!("connection should have been rejected"),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn allowed_origin_gets_cors_headers() {
+    let (addr, server) = spawn_server(test_config(&[DASHBOARD])).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("http://{addr}/sessions"))
+        .header(ORIGIN, DASHBOARD)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], DASHBOARD);
+
+    // Preflight for a bearer-token request
+    let preflight = client
+        .request(Method::OPTIONS, format!("http://{addr}/events"))
+        .header(ORIGIN, DASHBOARD)
+        .header(ACCESS_CONTROL_REQUEST_METHOD, "GET")
+        .header(ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+        .send()
+        .await
+        .unwrap();
+    assert!(preflight.status().is_success());
+    assert_eq!(preflight.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], DASHBOARD);
+    let allowed_headers = preflight.headers()[ACCESS_CONTROL_ALLOW_HEADERS]
+        .to_str()
+        .unwrap();
+    assert!(allowed_headers.contains("authorization"));
+    assert!(preflight
+        .headers()
+        .get(ACCESS_CONTROL_ALLOW_CREDENTIALS)
+        .is_none());
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn other_origins_get_no_cors_headers() {
+    let (addr, server) = spawn_server(test_config(&[DASHBOARD])).await;
+
+    let response = reqwest::Client::new()
+        .get(format!("http://{addr}/sessions"))
+        .header(ORIGIN, "https://evil.example")
+        .send()
+        .await
+        .unwrap();
+    assert!(response
+        .headers()
+        .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn credentials_are_allowed_when_configured() {
+    let (addr, server) = spawn_server(Config {
+        cors_allow_credentials: true,
+        ..test_config(&[DASHBOARD])
+    })
+    .await;
+
+    let response = reqwest::Client::new()
+        .get(format!("http://{addr}/health"))
+        .header(ORIGIN, DASHBOARD)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], DASHBOARD);
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn websocket_upgrade_checks_origin() {
+    let (addr, server) = spawn_server(test_config(&[DASHBOARD])).await;
+
+    connect_ws(addr, Some(DASHBOARD))
+        .await
+        .expect("allowed origin should connect");
+    connect_ws(addr, None)
+        .await
+        .expect("non-browser clients should connect");
+    assert_eq!(
+        rejection_status(connect_ws(addr, Some("https://evil.example")).await),
+        403
+    );
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn unconfigured_policy_accepts_any_origin() {
+    let (addr, server) = spawn_server(test_config(&[])).await;
+
+    connect_ws(addr, Some("https://anywhere.example"))
+        .await
+        .expect("any origin should connect");
+
+    let response = reqwest::Client::new()
+        .get(format!("http://{addr}/health"))
+        .header(ORIGIN, "https://anywhere.example")
+        .send()
+        .await
+        .unwrap();
+    assert!(response
+        .headers()
+        .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+
+    server.abort();
+}
@@ -0,0 +1,174 @@
+//! Integration tests for relay mode.
+//!
+//! These tests run several hubs in-process and verify that relayed events
+//! keep their source and gain hops, are buffered while the upstream hub is
+//! down, and are delivered once even when relays form a cycle.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::Receiver;
+use tokio::time::{sleep, timeout};
+use uuid::Uuid;
+
+use vibetea_sender::{Crypto, RetryPolicy};
+use vibetea_server::config::Config;
+use vibetea_server::relay::{Relay, RelayConfig};
+use vibetea_server::routes::{create_router, AppState};
+use vibetea_server::types::{Event, EventPayload, EventType};
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+/// A hub that accepts unsigned requests, identifying as `hub_id` when it
+/// relays and accepting events relayed by `relay_sources`.
+fn hub_config(hub_id: &str, relay_sources: &[&str]) -> Config {
+    Config {
+        unsafe_no_auth: true,
+        relay_id: Some(hub_id.to_string()),
+        relay_sources: relay_sources.iter().map(ToString::to_string).collect(),
+        ..Config::default()
+    }
+}
+
+fn create_event(n: usize) -> Event {
+    Event::new(
+        "laptop".to_string(),
+        EventType::Activity,
+        EventPayload::Activity {
+            session_id: Uuid::new_v4(),
+            project: Some("vibetea".to_string()),
+        },
+    )
+    .with_id(format!("evt_{n:0>20}"))
+}
+
+fn start_relay(state: &AppState, upstream: SocketAddr, hub_id: &str, crypto: Crypto) -> Relay {
+    let config = RelayConfig::new(format!("http://{upstream}"), hub_id, "/unused")
+        .with_retry_policy(RetryPolicy::fast_for_tests());
+    Relay::start(config, crypto, state)
+}
+
+async fn serve(listener: TcpListener, state: AppState) {
+    let app = create_router(state);
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+}
+
+async fn post_events(addr: SocketAddr, events: &[Event]) {
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr}/events"))
+        .header("X-Source-ID", "laptop")
+        .json(events)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+}
+
+async fn next_event(rx: &mut Receiver<Event>) -> Event {
+    timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("timed out waiting for relayed event")
+        .unwrap()
+}
+
+// ============================================================================
+// Relay Tests
+// ============================================================================
+
+#[tokio::test]
+async fn relays_events_upstream_with_source_and_hops() {
+    let crypto = Crypto::generate();
+
+    // The upstream hub authenticates the relay by its key
+    let upstream = AppState::new(Config {
+        public_keys: HashMap::from([("hub-eu".to_string(), crypto.public_key_base64())]),
+        subscriber_token: Some("token".to_string()),
+        relay_sources: vec!["hub-eu".to_string()],
+        ..Config::default()
+    });
+    let mut upstream_rx = upstream.broadcaster.subscribe();
+    let upstream_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream_addr = upstream_listener.local_addr().unwrap();
+    serve(upstream_listener, upstream).await;
+
+    let hub = AppState::new(hub_config("hub-eu", &[]));
+    let _relay = start_relay(&hub, upstream_addr, "hub-eu", crypto);
+    let hub_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hub_addr = hub_listener.local_addr().unwrap();
+    serve(hub_listener, hub).await;
+
+    let event = create_event(1);
+    post_events(hub_addr, std::slice::from_ref(&event)).await;
+
+    let relayed = next_event(&mut upstream_rx).await;
+    assert_eq!(relayed.id, event.id);
+    assert_eq!(relayed.source, "laptop");
+    assert_eq!(relayed.hops, vec!["hub-eu"]);
+}
+
+#[tokio::test]
+async fn buffers_events_while_upstream_is_down() {
+    // Reserve an address for the upstream hub, which starts later
+    let upstream_addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let hub = AppState::new(hub_config("hub-eu", &[]));
+    let relay = start_relay(&hub, upstream_addr, "hub-eu", Crypto::generate());
+    let hub_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hub_addr = hub_listener.local_addr().unwrap();
+    serve(hub_listener, hub).await;
+
+    let events: Vec<Event> = (1..=3).map(create_event).collect();
+    post_events(hub_addr, &events).await;
+    sleep(Duration::from_millis(100)).await;
+    assert!(relay.buffered() <= events.len());
+
+    let upstream = AppState::new(hub_config("hub-global", &["hub-eu"]));
+    let mut upstream_rx = upstream.broadcaster.subscribe();
+    serve(TcpListener::bind(upstream_addr).await.unwrap(), upstream).await;
+
+    for event in &events {
+        assert_eq!(next_event(&mut upstream_rx).await.id, event.id);
+    }
+    assert_eq!(relay.buffered(), 0);
+}
+
+#[tokio::test]
+async fn relay_cycle_delivers_each_event_once() {
+    let listener_a = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let listener_b = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr_a = listener_a.local_addr().unwrap();
+    let addr_b = listener_b.local_addr().unwrap();
+
+    // Each hub relays to the other
+    let hub_a = AppState::new(hub_config("hub-a", &["hub-b"]));
+    let hub_b = AppState::new(hub_config("hub-b", &["hub-a"]));
+    let mut rx_a = hub_a.broadcaster.subscribe();
+    let mut rx_b = hub_b.broadcaster.subscribe();
+    let _relay_a = start_relay(&hub_a, addr_b, "hub-a", Crypto::generate());
+    let _relay_b = start_relay(&hub_b, addr_a, "hub-b", Crypto::generate());
+    serve(listener_a, hub_a).await;
+    serve(listener_b, hub_b).await;
+
+    let event = create_event(1);
+    post_events(addr_a, std::slice::from_ref(&event)).await;
+
+    let at_a = next_event(&mut rx_a).await;
+    assert!(at_a.hops.is_empty());
+    let at_b = next_event(&mut rx_b).await;
+    assert_eq!(at_b.hops, vec!["hub-a"]);
+
+    // hub-b relays the event back to hub-a, which drops it
+    sleep(Duration::from_millis(300)).await;
+    assert!(rx_a.try_recv().is_err());
+    assert!(rx_b.try_recv().is_err());
+}
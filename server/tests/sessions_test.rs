@@ -0,0 +1,160 @@
+//! Integration tests for the server-side session registry.
+//!
+//! These tests verify that ingested events are folded into sessions exposed
+//! via `GET /sessions` and the `sessions` snapshot sent on WebSocket connect.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio::time::timeout;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+use vibetea_server::config::Config;
+use vibetea_server::routes::{create_router, AppState, SessionsResponse};
+use vibetea_server::sessions::SessionStatus;
+use vibetea_server::types::{Event, EventPayload, EventType, SessionAction, ToolStatus};
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn test_config() -> Config {
+    Config {
+        public_keys: HashMap::new(),
+        subscriber_token: None,
+        port: 0,
+        unsafe_no_auth: true,
+        ..Config::default()
+    }
+}
+
+fn create_event(event_type: EventType, payload: EventPayload) -> Event {
+    Event::new("monitor-1".to_string(), event_type, payload).with_id(format!(
+        "evt_{}",
+        &Uuid::new_v4().simple().to_string()[..20]
+    ))
+}
+
+fn session_started(session_id: Uuid, project: &str) -> Event {
+    create_event(
+        EventType::Session,
+        EventPayload::Session {
+            session_id,
+            action: SessionAction::Started,
+            project: project.to_string(),
+        },
+    )
+}
+
+fn tool_completed(session_id: Uuid, tool: &str) -> Event {
+    create_event(
+        EventType::Tool,
+        EventPayload::Tool {
+            session_id,
+            tool: tool.to_string(),
+            status: ToolStatus::Completed,
+            context: None,
+            project: None,
+        },
+    )
+}
+
+async fn spawn_test_server() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let app = create_router(AppState::new(test_config()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (addr, handle)
+}
+
+async fn post_events(addr: SocketAddr, events: &[Event]) {
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr}/events"))
+        .header("X-Source-ID", "monitor-1")
+        .json(events)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn get_sessions_reflects_ingested_events() {
+    let (addr, server) = spawn_test_server().await;
+    let session_id = Uuid::new_v4();
+    post_events(
+        addr,
+        &[
+            session_started(session_id, "vibetea"),
+            tool_completed(session_id, "Read"),
+            tool_completed(session_id, "Bash"),
+        ],
+    )
+    .await;
+
+    let response: SessionsResponse = reqwest::get(format!("http://{addr}/sessions"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(response.sessions.len(), 1);
+    let session = &response.sessions[0];
+    assert_eq!(session.session_id, session_id);
+    assert_eq!(session.source, "monitor-1");
+    assert_eq!(session.project.as_deref(), Some("vibetea"));
+    assert_eq!(session.status, SessionStatus::Active);
+    assert_eq!(session.tool_count, 2);
+    assert_eq!(session.last_tool.as_deref(), Some("Bash"));
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn websocket_sends_filtered_session_snapshot_on_connect() {
+    let (addr, server) = spawn_test_server().await;
+    let alpha = Uuid::new_v4();
+    post_events(
+        addr,
+        &[
+            session_started(alpha, "alpha"),
+            session_started(Uuid::new_v4(), "beta"),
+        ],
+    )
+    .await;
+
+    let (mut client, _) = connect_async(format!("ws://{addr}/ws?project=alpha"))
+        .await
+        .unwrap();
+    let message = timeout(Duration::from_secs(2), client.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let Message::Text(text) = message else {
+        panic!("expected text message, got {message:?}");
+    };
+    let snapshot: Value = serde_json::from_str(text.as_str()).unwrap();
+
+    assert_eq!(snapshot["type"], "sessions");
+    let sessions = snapshot["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["sessionId"], alpha.to_string());
+
+    server.abort();
+}
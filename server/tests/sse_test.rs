@@ -0,0 +1,202 @@
+//! Integration tests for the Server-Sent Events endpoint.
+//!
+//! These tests verify that `/stream` delivers the same filtered feed as `/ws`
+//! over a real HTTP connection, with `id:` fields that let a reconnecting
+//! client resume via `Last-Event-ID`.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+use vibetea_server::config::Config;
+use vibetea_server::routes::{create_router, AppState};
+use vibetea_server::types::{Event, EventPayload, EventType};
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn test_config() -> Config {
+    Config {
+        public_keys: HashMap::new(),
+        subscriber_token: None,
+        port: 0,
+        unsafe_no_auth: true,
+        ..Config::default()
+    }
+}
+
+fn create_event(n: usize, project: &str) -> Event {
+    Event::new(
+        "monitor-1".to_string(),
+        EventType::Activity,
+        EventPayload::Activity {
+            session_id: Uuid::new_v4(),
+            project: Some(project.to_string()),
+        },
+    )
+    .with_id(format!("evt_{n:0>20}"))
+}
+
+async fn spawn_test_server() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let app = create_router(AppState::new(test_config()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (addr, handle)
+}
+
+async fn post_events(addr: SocketAddr, events: &[Event]) {
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr}/events"))
+        .header("X-Source-ID", "monitor-1")
+        .json(events)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+}
+
+/// A parsed SSE message.
+#[derive(Debug, Default)]
+struct Message {
+    id: Option<String>,
+    event: Option<String>,
+    data: Value,
+}
+
+/// Reads SSE messages from a streaming response, skipping comments.
+struct EventStream {
+    response: reqwest::Response,
+    buffer: String,
+}
+
+impl EventStream {
+    async fn connect(addr: SocketAddr, query: &str, last_event_id: Option<&str>) -> Self {
+        let mut request = reqwest::Client::new().get(format!("http://{addr}/stream?{query}"));
+        if let Some(id) = last_event_id {
+            request = request.header("Last-Event-ID", id);
+        }
+        let response = request.send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers()["content-type"].to_str().unwrap(),
+            "text/event-stream"
+        );
+        Self {
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    /// Receives the next message, waiting at most two seconds.
+    async fn next(&mut self) -> Message {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let block: String = self.buffer.drain(..end + 2).collect();
+                if let Some(message) = parse_message(&block) {
+                    return message;
+                }
+                continue;
+            }
+            let chunk = timeout(Duration::from_secs(2), self.response.chunk())
+                .await
+                .expect("timed out waiting for message")
+                .unwrap()
+                .expect("stream ended");
+            self.buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    }
+}
+
+/// Parses one SSE block, returning `None` for comment-only blocks.
+fn parse_message(block: &str) -> Option<Message> {
+    let mut message = Message::default();
+    let mut data = None;
+    for line in block.lines() {
+        let (field, value) = line.split_once(": ").unwrap_or((line, ""));
+        match field {
+            "id" => message.id = Some(value.to_string()),
+            "event" => message.event = Some(value.to_string()),
+            "data" => data = Some(serde_json::from_str(value).unwrap()),
+            _ => {}
+        }
+    }
+    message.data = data?;
+    Some(message)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn streams_filtered_events_with_ids() {
+    let (addr, server) = spawn_test_server().await;
+    let mut stream = EventStream::connect(addr, "project=alpha", None).await;
+
+    let sessions = stream.next().await;
+    assert_eq!(sessions.event.as_deref(), Some("sessions"));
+    assert!(sessions.id.is_none());
+
+    let alpha = create_event(0, "alpha");
+    let beta = create_event(1, "beta");
+    post_events(addr, &[beta, alpha.clone()]).await;
+
+    let message = stream.next().await;
+    assert_eq!(message.id.as_deref(), Some(alpha.id.as_str()));
+    assert!(message.event.is_none());
+    assert_eq!(message.data["id"], alpha.id.as_str());
+    assert_eq!(message.data["payload"]["project"], "alpha");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn last_event_id_resumes_stream() {
+    let (addr, server) = spawn_test_server().await;
+    let events: Vec<Event> = (0..3).map(|n| create_event(n, "alpha")).collect();
+    post_events(addr, &events).await;
+
+    let mut stream = EventStream::connect(addr, "", Some(&events[0].id)).await;
+    assert_eq!(stream.next().await.event.as_deref(), Some("sessions"));
+    for event in &events[1..] {
+        assert_eq!(stream.next().await.id.as_deref(), Some(event.id.as_str()));
+    }
+    let resumed = stream.next().await;
+    assert_eq!(resumed.event.as_deref(), Some("resumed"));
+    assert_eq!(resumed.data["replayed"], 2);
+    assert_eq!(resumed.data["gap"], false);
+
+    // Live events follow the replay
+    let live = create_event(3, "alpha");
+    post_events(addr, std::slice::from_ref(&live)).await;
+    assert_eq!(stream.next().await.id.as_deref(), Some(live.id.as_str()));
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn stream_counts_as_connection() {
+    let (addr, server) = spawn_test_server().await;
+    let _stream = EventStream::connect(addr, "", None).await;
+
+    let health: Value = reqwest::get(format!("http://{addr}/health"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(health["connections"], 1);
+
+    server.abort();
+}
@@ -0,0 +1,300 @@
+//! Integration tests for native TLS termination.
+//!
+//! These tests serve the router over [`TlsListener`] with certificates
+//! generated on the fly, and verify HTTPS requests, client certificate
+//! enforcement on `POST /events` and reloading the client CA when its file
+//! changes.
+
+use std::collections::HashMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use base64::prelude::*;
+use chrono::Utc;
+use ed25519_dalek::{Signer, SigningKey};
+use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, IsCa, KeyPair};
+use serde_json::Value;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tokio::time::{sleep, Instant};
+use uuid::Uuid;
+
+use vibetea_server::auth::signed_message;
+use vibetea_server::config::Config;
+use vibetea_server::routes::{create_router, AppState};
+use vibetea_server::tls::{ServerTls, TlsConfig, TlsConnectInfo, TlsListener};
+use vibetea_server::types::{Event, EventPayload, EventType};
+
+const SOURCE_ID: &str = "monitor-1";
+
+static NONCE: AtomicU64 = AtomicU64::new(0);
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+/// A certificate authority that issues server and client certificates.
+struct TestCa {
+    issuer: CertifiedIssuer<'static, KeyPair>,
+}
+
+impl TestCa {
+    fn new(name: &str) -> Self {
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, name);
+        let issuer = CertifiedIssuer::self_signed(params, KeyPair::generate().unwrap()).unwrap();
+        Self { issuer }
+    }
+
+    fn pem(&self) -> String {
+        self.issuer.pem()
+    }
+
+    /// Issues a certificate for `name`, returning the certificate and key PEM.
+    fn issue(&self, name: &str) -> (String, String) {
+        let key = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec![name.to_string()])
+            .unwrap()
+            .signed_by(&key, &self.issuer)
+            .unwrap();
+        (cert.pem(), key.serialize_pem())
+    }
+}
+
+/// Writes the server certificate and key, and optionally the client CA,
+/// returning their paths.
+fn write_tls_files(dir: &Path, server_ca: &TestCa, client_ca: Option<&TestCa>) -> TlsConfig {
+    let (cert, key) = server_ca.issue("localhost");
+    let config = TlsConfig {
+        cert: dir.join("cert.pem"),
+        key: dir.join("key.pem"),
+        client_ca: client_ca.map(|_| dir.join("client-ca.pem")),
+    };
+    fs::write(&config.cert, cert).unwrap();
+    fs::write(&config.key, key).unwrap();
+    if let (Some(path), Some(ca)) = (&config.client_ca, client_ca) {
+        fs::write(path, ca.pem()).unwrap();
+    }
+    config
+}
+
+/// A monitor signing key and its registered public key.
+fn monitor_key() -> (SigningKey, HashMap<String, String>) {
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let public_key = BASE64_STANDARD.encode(signing_key.verifying_key().to_bytes());
+    (
+        signing_key,
+        HashMap::from([(SOURCE_ID.to_string(), public_key)]),
+    )
+}
+
+async fn spawn_tls_server(
+    config: Config,
+    tls: ServerTls,
+) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let app = create_router(AppState::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move {
+        axum::serve(
+            TlsListener::new(listener, tls),
+            app.into_make_service_with_connect_info::<TlsConnectInfo>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    (addr, handle)
+}
+
+/// An HTTPS client that trusts `server_ca` and connects `localhost` to
+/// `addr`, optionally presenting a client certificate.
+fn https_client(
+    addr: SocketAddr,
+    server_ca: &TestCa,
+    identity: Option<(String, String)>,
+) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(server_ca.pem().as_bytes()).unwrap())
+        .resolve("localhost", addr);
+    if let Some((cert, key)) = identity {
+        builder = builder
+            .identity(reqwest::Identity::from_pkcs8_pem(cert.as_bytes(), key.as_bytes()).unwrap());
+    }
+    builder.build().unwrap()
+}
+
+/// Posts a signed event, returning the status code.
+async fn post_event(
+    client: &reqwest::Client,
+    addr: SocketAddr,
+    signing_key: &SigningKey,
+) -> reqwest::Result<reqwest::StatusCode> {
+    let n = NONCE.fetch_add(1, Ordering::SeqCst);
+    let body = serde_json::to_vec(
+        &Event::new(
+            SOURCE_ID.to_string(),
+            EventType::Activity,
+            EventPayload::Activity {
+                session_id: Uuid::new_v4(),
+                project: None,
+            },
+        )
+        .with_id(format!("evt_{n:0>20}")),
+    )
+    .unwrap();
+    let timestamp = Utc::now().timestamp().to_string();
+    let nonce = format!("nonce-{n}");
+    let signature = signing_key.sign(&signed_message(&timestamp, &nonce, &body));
+
+    let response = client
+        .post(format!("https://localhost:{}/events", addr.port()))
+        .header("Content-Type", "application/json")
+        .header("X-Source-ID", SOURCE_ID)
+        .header("X-Timestamp", timestamp)
+        .header("X-Nonce", nonce)
+        .header("X-Signature", BASE64_STANDARD.encode(signature.to_bytes()))
+        .body(body)
+        .send()
+        .await?;
+    Ok(response.status())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn serves_https() {
+    let dir = TempDir::new().unwrap();
+    let ca = TestCa::new("server ca");
+    let tls = ServerTls::load(write_tls_files(dir.path(), &ca, None)).unwrap();
+    let (addr, server) = spawn_tls_server(
+        Config {
+            unsafe_no_auth: true,
+            ..Config::default()
+        },
+        tls,
+    )
+    .await;
+
+    let health: Value = https_client(addr, &ca, None)
+        .get(format!("https://localhost:{}/health", addr.port()))
+        .send()
+        .await
+        .expect("HTTPS request should succeed")
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(health["status"], "ok");
+
+    // Plain HTTP is not accepted on the TLS port
+    let plain = reqwest::get(format!("http://{addr}/health")).await;
+    assert!(plain.is_err());
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn post_events_requires_client_certificate() {
+    let dir = TempDir::new().unwrap();
+    let server_ca = TestCa::new("server ca");
+    let client_ca = TestCa::new("client ca");
+    let tls_config = write_tls_files(dir.path(), &server_ca, Some(&client_ca));
+    let (signing_key, public_keys) = monitor_key();
+    let (addr, server) = spawn_tls_server(
+        Config {
+            public_keys,
+            subscriber_token: Some("test-token".to_string()),
+            tls_cert: Some(tls_config.cert.clone()),
+            tls_key: Some(tls_config.key.clone()),
+            tls_client_ca: tls_config.client_ca.clone(),
+            ..Config::default()
+        },
+        ServerTls::load(tls_config).unwrap(),
+    )
+    .await;
+
+    // Without a client certificate, the signature alone is not enough
+    let anonymous = https_client(addr, &server_ca, None);
+    let status = post_event(&anonymous, addr, &signing_key).await.unwrap();
+    assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+
+    // Other endpoints do not need a client certificate
+    let health = anonymous
+        .get(format!("https://localhost:{}/health", addr.port()))
+        .send()
+        .await
+        .unwrap();
+    assert!(health.status().is_success());
+
+    let monitor = https_client(addr, &server_ca, Some(client_ca.issue(SOURCE_ID)));
+    let status = post_event(&monitor, addr, &signing_key).await.unwrap();
+    assert_eq!(status, reqwest::StatusCode::ACCEPTED);
+
+    // A certificate from another CA fails the handshake
+    let untrusted = TestCa::new("untrusted ca");
+    let impostor = https_client(addr, &server_ca, Some(untrusted.issue(SOURCE_ID)));
+    assert!(post_event(&impostor, addr, &signing_key).await.is_err());
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn reloads_client_ca_when_file_changes() {
+    let dir = TempDir::new().unwrap();
+    let server_ca = TestCa::new("server ca");
+    let old_ca = TestCa::new("old client ca");
+    let tls_config = write_tls_files(dir.path(), &server_ca, Some(&old_ca));
+    let client_ca_path = tls_config.client_ca.clone().unwrap();
+    let (signing_key, public_keys) = monitor_key();
+
+    let tls = ServerTls::load(tls_config.clone()).unwrap();
+    let _watchers = tls.watch().unwrap();
+    let (addr, server) = spawn_tls_server(
+        Config {
+            public_keys,
+            subscriber_token: Some("test-token".to_string()),
+            tls_cert: Some(tls_config.cert),
+            tls_key: Some(tls_config.key),
+            tls_client_ca: tls_config.client_ca,
+            ..Config::default()
+        },
+        tls,
+    )
+    .await;
+
+    let new_ca = TestCa::new("new client ca");
+    let monitor = https_client(addr, &server_ca, Some(new_ca.issue(SOURCE_ID)));
+    assert!(post_event(&monitor, addr, &signing_key).await.is_err());
+
+    // An invalid file keeps the previous CA in use
+    fs::write(&client_ca_path, "not a certificate").unwrap();
+    sleep(Duration::from_millis(500)).await;
+    let old_monitor = https_client(addr, &server_ca, Some(old_ca.issue(SOURCE_ID)));
+    let status = post_event(&old_monitor, addr, &signing_key).await.unwrap();
+    assert_eq!(status, reqwest::StatusCode::ACCEPTED);
+
+    fs::write(&client_ca_path, new_ca.pem()).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        // Each attempt needs a fresh connection, since established ones keep
+        // the configuration they were accepted with
+        let monitor = https_client(addr, &server_ca, Some(new_ca.issue(SOURCE_ID)));
+        if let Ok(status) = post_event(&monitor, addr, &signing_key).await {
+            assert_eq!(status, reqwest::StatusCode::ACCEPTED);
+            break;
+        }
+        assert!(Instant::now() < deadline, "client CA was not reloaded");
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    server.abort();
+}
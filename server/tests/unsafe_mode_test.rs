@@ -16,7 +16,6 @@ use std::time::Duration;
 
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
-use chrono::Utc;
 use serde_json::json;
 use tokio::net::TcpListener;
 use tokio::time::timeout;
@@ -38,22 +37,21 @@ fn unsafe_config() -> Config {
         subscriber_token: None,
         port: 0, // Will be overridden when binding
         unsafe_no_auth: true,
+        ..Config::default()
     }
 }
 
 /// Creates a test event for use in POST /events requests.
 fn create_test_event(source: &str) -> Event {
-    Event {
-        id: format!("evt_test{}", Uuid::new_v4().simple()),
-        source: source.to_string(),
-        timestamp: Utc::now(),
-        event_type: EventType::Session,
-        payload: EventPayload::Session {
+    Event::new(
+        source.to_string(),
+        EventType::Session,
+        EventPayload::Session {
             session_id: Uuid::new_v4(),
             action: SessionAction::Started,
             project: "test-project".to_string(),
         },
-    }
+    )
 }
 
 /// Spawns a test server on a random available port.
@@ -600,7 +598,7 @@ async fn multiple_events_broadcast_in_order_in_unsafe_mode() {
     let events: Vec<Event> = (0..3)
         .map(|i| {
             let mut event = create_test_event("test-monitor");
-            event.id = format!("evt_order_test_{:03}", i);
+            event.id = format!("evt_ordertest{:011}", i);
             event
         })
         .collect();
@@ -0,0 +1,297 @@
+//! Integration tests for outbound webhooks.
+//!
+//! These tests run the webhook dispatcher against a local stub server and
+//! verify filtering, batching, signatures, retries and dead-lettering.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::Value;
+use tempfile::TempDir;
+use uuid::Uuid;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+use vibetea_server::broadcast::SubscriberFilter;
+use vibetea_server::config::Config;
+use vibetea_server::routes::AppState;
+use vibetea_server::types::{Event, EventPayload, EventType};
+use vibetea_server::webhooks::{
+    signature, DeadLetter, Webhook, WebhookConfig, WebhookDispatcher, WebhookFormat,
+    HEADER_SIGNATURE, HEADER_TIMESTAMP,
+};
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn test_state() -> AppState {
+    AppState::new(Config {
+        public_keys: HashMap::new(),
+        unsafe_no_auth: true,
+        ..Config::default()
+    })
+}
+
+fn create_event(n: usize, event_type: EventType) -> Event {
+    let session_id = Uuid::new_v4();
+    let payload = match event_type {
+        EventType::Error => EventPayload::Error {
+            session_id,
+            category: "tool_failure".to_string(),
+        },
+        _ => EventPayload::Activity {
+            session_id,
+            project: Some("vibetea".to_string()),
+        },
+    };
+    Event::new("monitor-1".to_string(), event_type, payload).with_id(format!("evt_{n:0>20}"))
+}
+
+/// A webhook pointing at the stub server with a short batch window and fast
+/// retries.
+fn stub_webhook(server: &MockServer) -> Webhook {
+    Webhook::new("stub", format!("{}/hook", server.uri()))
+        .with_batch_window(Duration::from_millis(50))
+        .with_retries(3, Duration::from_millis(10))
+}
+
+fn start(
+    state: &AppState,
+    webhooks: Vec<Webhook>,
+    dead_letter: Option<&Path>,
+) -> WebhookDispatcher {
+    WebhookDispatcher::start(
+        WebhookConfig {
+            dead_letter_file: dead_letter.map(Path::to_path_buf),
+            webhooks,
+        },
+        state,
+    )
+}
+
+/// Waits until the stub server has received `count` requests.
+async fn wait_for_requests(server: &MockServer, count: usize) -> Vec<Request> {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let requests = server.received_requests().await.unwrap();
+            if requests.len() >= count {
+                return requests;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("timed out waiting for webhook requests")
+}
+
+/// Waits until the dead-letter file has a record and returns it.
+async fn wait_for_dead_letter(path: &Path) -> DeadLetter {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            // Only complete lines, in case the record is still being written
+            if let Ok(contents) = std::fs::read_to_string(path) {
+                if let Some((line, _)) = contents.split_once('\n') {
+                    return serde_json::from_str(line).unwrap();
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("timed out waiting for dead letter")
+}
+
+/// Waits until the rendered metrics contain `expected`.
+async fn wait_for_metric(state: &AppState, expected: &str) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !state.metrics.render(&[]).contains(expected) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("timed out waiting for metric {expected}"));
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn delivers_matching_events_in_signed_batches() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .and(header("X-VibeTea-Webhook", "stub"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let state = test_state();
+    let webhook = stub_webhook(&server)
+        .with_filter(SubscriberFilter::new().with_event_type(EventType::Error))
+        .with_secret("s3cret");
+    let _dispatcher = start(&state, vec![webhook], None);
+
+    let errors = [
+        create_event(0, EventType::Error),
+        create_event(2, EventType::Error),
+    ];
+    state
+        .publish(vec![
+            errors[0].clone(),
+            create_event(1, EventType::Activity),
+            errors[1].clone(),
+        ])
+        .await;
+
+    let requests = wait_for_requests(&server, 1).await;
+    let request = &requests[0];
+    let body: Value = request.body_json().unwrap();
+    assert_eq!(body["webhook"], "stub");
+    let ids: Vec<&str> = body["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|event| event["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec![errors[0].id.as_str(), errors[1].id.as_str()]);
+
+    let timestamp = request.headers[HEADER_TIMESTAMP].to_str().unwrap();
+    assert_eq!(
+        request.headers[HEADER_SIGNATURE].to_str().unwrap(),
+        signature("s3cret", timestamp, &request.body)
+    );
+}
+
+#[tokio::test]
+async fn splits_batches_at_max_batch() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+
+    let state = test_state();
+    let webhook = stub_webhook(&server)
+        .with_batch_window(Duration::from_secs(60))
+        .with_max_batch(2);
+    let _dispatcher = start(&state, vec![webhook], None);
+
+    state
+        .publish(
+            (0..4)
+                .map(|n| create_event(n, EventType::Activity))
+                .collect(),
+        )
+        .await;
+
+    let requests = wait_for_requests(&server, 2).await;
+    for request in &requests {
+        let body: Value = request.body_json().unwrap();
+        assert_eq!(body["events"].as_array().unwrap().len(), 2);
+    }
+    // Webhooks without a secret are unsigned
+    assert!(!requests[0].headers.contains_key(HEADER_SIGNATURE));
+}
+
+#[tokio::test]
+async fn retries_server_errors_then_delivers() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let state = test_state();
+    let _dispatcher = start(&state, vec![stub_webhook(&server)], None);
+    state
+        .publish(vec![create_event(0, EventType::Activity)])
+        .await;
+
+    let requests = wait_for_requests(&server, 3).await;
+    assert_eq!(requests[0].body, requests[2].body);
+
+    wait_for_metric(
+        &state,
+        r#"vibetea_webhook_deliveries_total{webhook="stub",outcome="delivered"} 1"#,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn dead_letters_batches_after_exhausting_retries() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let dir = TempDir::new().unwrap();
+    let dead_letter_path = dir.path().join("dead-letter.ndjson");
+    let state = test_state();
+    let _dispatcher = start(&state, vec![stub_webhook(&server)], Some(&dead_letter_path));
+
+    let event = create_event(0, EventType::Activity);
+    state.publish(vec![event.clone()]).await;
+
+    let record = wait_for_dead_letter(&dead_letter_path).await;
+    assert_eq!(record.webhook, "stub");
+    assert_eq!(record.attempts, 3);
+    assert!(record.error.contains("500"));
+    assert_eq!(record.events, vec![event]);
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+
+    let metrics = state.metrics.render(&[]);
+    assert!(
+        metrics.contains(r#"vibetea_webhook_deliveries_total{webhook="stub",outcome="failed"} 1"#)
+    );
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let dir = TempDir::new().unwrap();
+    let dead_letter_path = dir.path().join("dead-letter.ndjson");
+    let state = test_state();
+    let _dispatcher = start(&state, vec![stub_webhook(&server)], Some(&dead_letter_path));
+    state
+        .publish(vec![create_event(0, EventType::Activity)])
+        .await;
+
+    let record = wait_for_dead_letter(&dead_letter_path).await;
+    assert_eq!(record.attempts, 1);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn slack_format_sends_text_message() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let state = test_state();
+    let webhook = stub_webhook(&server).with_format(WebhookFormat::Slack);
+    let _dispatcher = start(&state, vec![webhook], None);
+    state.publish(vec![create_event(0, EventType::Error)]).await;
+
+    let requests = wait_for_requests(&server, 1).await;
+    let body: Value = requests[0].body_json().unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({"text": "*error* from `monitor-1`: tool_failure error"})
+    );
+}
@@ -0,0 +1,367 @@
+//! Integration tests for the WebSocket control protocol.
+//!
+//! These tests verify that a connected client can add, replace and remove
+//! subscriptions without reconnecting, request a replay, and ping for server
+//! statistics, receiving typed acknowledgements and errors, and that
+//! published stats rollups are pushed to connected clients.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+
+use vibetea_server::config::Config;
+use vibetea_server::outbox::{SlowConsumerPolicy, CLOSE_SLOW_CONSUMER};
+use vibetea_server::routes::{create_router, AppState};
+use vibetea_server::types::{Event, EventPayload, EventType};
+use vibetea_server::ws::MAX_SUBSCRIPTIONS;
+
+type WsClient = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn test_config() -> Config {
+    Config {
+        public_keys: HashMap::new(),
+        subscriber_token: None,
+        port: 0,
+        unsafe_no_auth: true,
+        ..Config::default()
+    }
+}
+
+fn create_event(n: usize, project: &str) -> Event {
+    Event::new(
+        "monitor-1".to_string(),
+        EventType::Activity,
+        EventPayload::Activity {
+            session_id: Uuid::new_v4(),
+            project: Some(project.to_string()),
+        },
+    )
+    .with_id(format!("evt_{n:0>20}"))
+}
+
+async fn spawn_test_server() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    spawn_server(AppState::new(test_config())).await
+}
+
+async fn spawn_server(state: AppState) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let app = create_router(state);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (addr, handle)
+}
+
+async fn post_events(addr: SocketAddr, events: &[Event]) {
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr}/events"))
+        .header("X-Source-ID", "monitor-1")
+        .json(events)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+}
+
+/// Connects and consumes the initial `sessions` snapshot.
+async fn connect(addr: SocketAddr, query: &str) -> WsClient {
+    let (mut client, _) = connect_async(format!("ws://{addr}/ws?{query}"))
+        .await
+        .expect("WebSocket connect should succeed");
+    assert_eq!(next_json(&mut client).await["type"], "sessions");
+    client
+}
+
+/// Sends a control message.
+async fn send(client: &mut WsClient, message: Value) {
+    client
+        .send(Message::Text(message.to_string().into()))
+        .await
+        .unwrap();
+}
+
+/// Receives the next text message as JSON.
+async fn next_json(client: &mut WsClient) -> Value {
+    loop {
+        let message = timeout(Duration::from_secs(2), client.next())
+            .await
+            .expect("timed out waiting for message")
+            .expect("stream ended")
+            .expect("WebSocket error");
+        if let Message::Text(text) = message {
+            return serde_json::from_str(text.as_str()).unwrap();
+        }
+    }
+}
+
+/// Sends a subscribe message and consumes its acknowledgement and the
+/// session snapshot that follows, returning the acknowledgement.
+async fn subscribe(client: &mut WsClient, id: &str, filter: Value) -> Value {
+    send(
+        client,
+        json!({"type": "subscribe", "id": id, "filter": filter}),
+    )
+    .await;
+    let ack = next_json(client).await;
+    assert_eq!(next_json(client).await["type"], "sessions");
+    ack
+}
+
+/// Pings the server and returns the `pong`, asserting that no other message
+/// arrives first.
+async fn ping(client: &mut WsClient) -> Value {
+    send(client, json!({"type": "ping", "id": "p"})).await;
+    let reply = next_json(client).await;
+    assert_eq!(reply["type"], "pong", "unexpected message: {reply}");
+    assert_eq!(reply["id"], "p");
+    reply
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn subscribe_replaces_filter_without_reconnecting() {
+    let (addr, server) = spawn_test_server().await;
+    let mut client = connect(addr, "project=alpha").await;
+
+    let ack = subscribe(&mut client, "default", json!({"project": "beta"})).await;
+    assert_eq!(ack["type"], "subscribed");
+    assert_eq!(ack["id"], "default");
+    assert_eq!(ack["filter"], json!({"project": ["beta"]}));
+
+    let alpha = create_event(0, "alpha");
+    let beta = create_event(1, "beta");
+    post_events(addr, &[alpha, beta.clone()]).await;
+    assert_eq!(next_json(&mut client).await["id"], beta.id.as_str());
+    ping(&mut client).await;
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn events_matching_several_subscriptions_are_sent_once() {
+    let (addr, server) = spawn_test_server().await;
+    let mut client = connect(addr, "project=alpha").await;
+
+    subscribe(&mut client, "beta", json!({"project": ["beta"]})).await;
+    subscribe(&mut client, "everything", json!({})).await;
+
+    let events = vec![create_event(0, "alpha"), create_event(1, "beta")];
+    post_events(addr, &events).await;
+    for event in &events {
+        assert_eq!(next_json(&mut client).await["id"], event.id.as_str());
+    }
+
+    let pong = ping(&mut client).await;
+    assert_eq!(pong["stats"]["subscriptions"], 3);
+    assert_eq!(pong["stats"]["eventsSent"], 2);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn unsubscribe_stops_events() {
+    let (addr, server) = spawn_test_server().await;
+    let mut client = connect(addr, "").await;
+
+    send(&mut client, json!({"type": "unsubscribe"})).await;
+    let ack = next_json(&mut client).await;
+    assert_eq!(ack, json!({"type": "unsubscribed", "id": "default"}));
+
+    post_events(addr, &[create_event(0, "alpha")]).await;
+    let pong = ping(&mut client).await;
+    assert_eq!(pong["stats"]["subscriptions"], 0);
+    assert_eq!(pong["stats"]["eventsSent"], 0);
+
+    send(&mut client, json!({"type": "unsubscribe", "id": "default"})).await;
+    let reply = next_json(&mut client).await;
+    assert_eq!(reply["type"], "control_error");
+    assert_eq!(reply["code"], "unknown_subscription");
+    assert_eq!(reply["id"], "default");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn subscribe_rejects_invalid_requests() {
+    let (addr, server) = spawn_test_server().await;
+    let mut client = connect(addr, "").await;
+
+    send(
+        &mut client,
+        json!({"type": "subscribe", "filter": {"type": "bogus"}}),
+    )
+    .await;
+    let reply = next_json(&mut client).await;
+    assert_eq!(reply["code"], "invalid_message");
+
+    send(&mut client, json!({"type": "subscribe", "id": ""})).await;
+    let reply = next_json(&mut client).await;
+    assert_eq!(reply["code"], "invalid_subscription");
+
+    for n in 1..MAX_SUBSCRIPTIONS {
+        subscribe(&mut client, &format!("sub-{n}"), json!({})).await;
+    }
+    send(
+        &mut client,
+        json!({"type": "subscribe", "id": "one-too-many"}),
+    )
+    .await;
+    let reply = next_json(&mut client).await;
+    assert_eq!(reply["code"], "too_many_subscriptions");
+    assert_eq!(reply["id"], "one-too-many");
+
+    // Replacing an existing subscription is still allowed at the limit
+    let ack = subscribe(&mut client, "sub-1", json!({"project": "alpha"})).await;
+    assert_eq!(ack["type"], "subscribed");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn replay_uses_current_subscriptions() {
+    let (addr, server) = spawn_test_server().await;
+    let events = vec![
+        create_event(0, "alpha"),
+        create_event(1, "alpha"),
+        create_event(2, "beta"),
+    ];
+    post_events(addr, &events).await;
+
+    let mut client = connect(addr, "project=alpha").await;
+    subscribe(&mut client, "default", json!({"project": "beta"})).await;
+
+    send(
+        &mut client,
+        json!({"type": "replay", "since": events[0].id}),
+    )
+    .await;
+    assert_eq!(next_json(&mut client).await["id"], events[2].id.as_str());
+    let resumed = next_json(&mut client).await;
+    assert_eq!(resumed["type"], "resumed");
+    assert_eq!(resumed["replayed"], 1);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn ping_reports_server_stats() {
+    let (addr, server) = spawn_test_server().await;
+    post_events(addr, &[create_event(0, "alpha")]).await;
+    let mut client = connect(addr, "").await;
+
+    let pong = ping(&mut client).await;
+    let stats = &pong["stats"];
+    assert_eq!(stats["connections"], 1);
+    assert_eq!(stats["sessions"], 1);
+    assert_eq!(stats["historyEvents"], 1);
+    assert_eq!(stats["subscriptions"], 1);
+    assert!(stats["uptimeSeconds"].is_u64());
+
+    send(&mut client, json!({"type": "ping"})).await;
+    let pong = next_json(&mut client).await;
+    assert_eq!(pong["type"], "pong");
+    assert!(pong.get("id").is_none());
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn published_rollups_are_pushed_regardless_of_subscriptions() {
+    let state = AppState::new(test_config());
+    let (addr, server) = spawn_server(state.clone()).await;
+    let mut client = connect(addr, "project=beta").await;
+
+    post_events(addr, &[create_event(0, "alpha")]).await;
+    state.stats.publish(Utc::now());
+
+    let message = next_json(&mut client).await;
+    assert_eq!(message["type"], "stats");
+    assert_eq!(message["stats"]["window"], "1h");
+    assert_eq!(message["stats"]["totalEvents"], 1);
+    assert_eq!(message["stats"]["eventsByType"]["activity"], 1);
+    assert_eq!(message["stats"]["activeSessions"], 1);
+
+    server.abort();
+}
+
+/// Publishes `count` events too large to sit in socket buffers for long,
+/// yielding between them so the connection task keeps up with the broadcaster.
+async fn publish_large_events(state: &AppState, count: usize) {
+    let project = "x".repeat(64 * 1024);
+    for n in 0..count {
+        state.publish(vec![create_event(n, &project)]).await;
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn slow_client_is_told_how_many_events_were_dropped() {
+    let state = AppState::new(Config {
+        ws_queue_capacity: 4,
+        ..test_config()
+    });
+    let (addr, server) = spawn_server(state.clone()).await;
+    let mut client = connect(addr, "").await;
+
+    publish_large_events(&state, 400).await;
+
+    let mut received = 0;
+    let mut skipped = 0;
+    while received + skipped < 400 {
+        let message = next_json(&mut client).await;
+        match message["type"].as_str() {
+            Some("activity") => received += 1,
+            Some("lagged") => skipped += message["skipped"].as_u64().unwrap(),
+            _ => panic!("unexpected message: {message}"),
+        }
+    }
+    assert!(skipped > 0, "expected the client to fall behind");
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn slow_client_is_disconnected_under_disconnect_policy() {
+    let state = AppState::new(Config {
+        ws_queue_capacity: 4,
+        slow_consumer_policy: SlowConsumerPolicy::Disconnect,
+        ..test_config()
+    });
+    let (addr, server) = spawn_server(state.clone()).await;
+    let mut client = connect(addr, "").await;
+
+    publish_large_events(&state, 400).await;
+
+    let close = loop {
+        let message = timeout(Duration::from_secs(2), client.next())
+            .await
+            .expect("timed out waiting for close")
+            .expect("stream ended")
+            .expect("WebSocket error");
+        if let Message::Close(frame) = message {
+            break frame.expect("close frame should have a code");
+        }
+    };
+    assert_eq!(u16::from(close.code), CLOSE_SLOW_CONSUMER);
+
+    server.abort();
+}
@@ -0,0 +1,156 @@
+//! Integration tests for WebSocket heartbeats and connection lifetime.
+//!
+//! These tests verify that the server pings connected clients, drops clients
+//! that stop answering so they no longer count as connections in `/health`,
+//! keeps clients that answer, and closes connections that exceed the maximum
+//! lifetime.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde_json::Value;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{sleep, timeout, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use vibetea_server::config::Config;
+use vibetea_server::routes::{create_router, AppState};
+use vibetea_server::ws::CLOSE_GOING_AWAY;
+
+type WsClient = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const PING_INTERVAL: Duration = Duration::from_millis(100);
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn test_config() -> Config {
+    Config {
+        public_keys: HashMap::new(),
+        subscriber_token: None,
+        port: 0,
+        unsafe_no_auth: true,
+        ws_ping_interval: PING_INTERVAL,
+        ws_max_missed_pongs: 2,
+        ..Config::default()
+    }
+}
+
+async fn spawn_server(config: Config) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let app = create_router(AppState::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (addr, handle)
+}
+
+async fn connect(addr: SocketAddr) -> WsClient {
+    let (client, _) = connect_async(format!("ws://{addr}/ws"))
+        .await
+        .expect("WebSocket connect should succeed");
+    client
+}
+
+/// Receives the next frame of any kind.
+async fn next_message(client: &mut WsClient) -> Message {
+    timeout(Duration::from_secs(2), client.next())
+        .await
+        .expect("timed out waiting for message")
+        .expect("stream ended")
+        .expect("WebSocket error")
+}
+
+/// Returns the connection count reported by `/health`.
+async fn health_connections(addr: SocketAddr) -> u64 {
+    let health: Value = reqwest::get(format!("http://{addr}/health"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    health["connections"].as_u64().unwrap()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn unresponsive_client_is_dropped_from_health_count() {
+    let (addr, server) = spawn_server(test_config()).await;
+    let mut client = connect(addr).await;
+
+    // Wait for a ping, then stop reading so no pongs are sent
+    loop {
+        if let Message::Ping(_) = next_message(&mut client).await {
+            break;
+        }
+    }
+    assert_eq!(health_connections(addr).await, 1);
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while health_connections(addr).await > 0 {
+        assert!(
+            Instant::now() < deadline,
+            "unresponsive client was not dropped"
+        );
+        sleep(PING_INTERVAL).await;
+    }
+
+    drop(client);
+    server.abort();
+}
+
+#[tokio::test]
+async fn responsive_client_stays_connected() {
+    let (addr, server) = spawn_server(test_config()).await;
+    let mut client = connect(addr).await;
+
+    // Reading answers pings automatically
+    let mut pings = 0;
+    let deadline = Instant::now() + PING_INTERVAL * 6;
+    while Instant::now() < deadline {
+        if let Ok(message) = timeout(PING_INTERVAL, client.next()).await {
+            let message = message.expect("stream ended").expect("WebSocket error");
+            assert!(
+                !matches!(message, Message::Close(_)),
+                "responsive client was closed"
+            );
+            if let Message::Ping(_) = message {
+                pings += 1;
+            }
+        }
+    }
+
+    assert!(pings >= 3, "expected several pings, got {pings}");
+    assert_eq!(health_connections(addr).await, 1);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn connection_is_closed_after_max_lifetime() {
+    let (addr, server) = spawn_server(Config {
+        ws_max_lifetime: Duration::from_millis(200),
+        ..test_config()
+    })
+    .await;
+    let mut client = connect(addr).await;
+
+    let close = loop {
+        if let Message::Close(frame) = next_message(&mut client).await {
+            break frame.expect("close frame should have a code");
+        }
+    };
+    assert_eq!(u16::from(close.code), CLOSE_GOING_AWAY);
+
+    server.abort();
+}
@@ -0,0 +1,229 @@
+//! Integration tests for resuming WebSocket subscriptions.
+//!
+//! These tests verify that a client reconnecting with `since` or `since_ts`,
+//! or sending a `resume` control message, receives the events it missed
+//! followed by a `resumed` message and then live events.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use chrono::{SecondsFormat, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+
+use vibetea_server::config::Config;
+use vibetea_server::routes::{create_router, AppState};
+use vibetea_server::types::{Event, EventPayload, EventType};
+
+type WsClient = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn test_config() -> Config {
+    Config {
+        public_keys: HashMap::new(),
+        subscriber_token: None,
+        port: 0,
+        unsafe_no_auth: true,
+        ..Config::default()
+    }
+}
+
+fn create_event(n: usize, project: &str) -> Event {
+    Event::new(
+        "monitor-1".to_string(),
+        EventType::Activity,
+        EventPayload::Activity {
+            session_id: Uuid::new_v4(),
+            project: Some(project.to_string()),
+        },
+    )
+    .with_id(format!("evt_{n:0>20}"))
+}
+
+async fn spawn_test_server() -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let app = create_router(AppState::new(test_config()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (addr, handle)
+}
+
+async fn post_events(addr: SocketAddr, events: &[Event]) {
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr}/events"))
+        .header("X-Source-ID", "monitor-1")
+        .json(events)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+}
+
+/// Connects and consumes the initial `sessions` snapshot.
+async fn connect(addr: SocketAddr, query: &str) -> WsClient {
+    let (mut client, _) = connect_async(format!("ws://{addr}/ws?{query}"))
+        .await
+        .expect("WebSocket connect should succeed");
+    assert_eq!(next_json(&mut client).await["type"], "sessions");
+    client
+}
+
+/// Receives the next text message as JSON.
+async fn next_json(client: &mut WsClient) -> Value {
+    loop {
+        let message = timeout(Duration::from_secs(2), client.next())
+            .await
+            .expect("timed out waiting for message")
+            .expect("stream ended")
+            .expect("WebSocket error");
+        if let Message::Text(text) = message {
+            return serde_json::from_str(text.as_str()).unwrap();
+        }
+    }
+}
+
+/// Receives messages until `resumed`, returning the replayed event IDs and
+/// the `resumed` message.
+async fn collect_replay(client: &mut WsClient) -> (Vec<String>, Value) {
+    let mut ids = Vec::new();
+    loop {
+        let message = next_json(client).await;
+        if message["type"] == "resumed" {
+            return (ids, message);
+        }
+        ids.push(message["id"].as_str().unwrap().to_string());
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn resumes_from_event_id_then_streams_live() {
+    let (addr, server) = spawn_test_server().await;
+    let events: Vec<Event> = (0..4).map(|n| create_event(n, "alpha")).collect();
+    post_events(addr, &events).await;
+
+    let mut client = connect(addr, &format!("since={}", events[1].id)).await;
+    let (ids, resumed) = collect_replay(&mut client).await;
+
+    assert_eq!(ids, vec![events[2].id.clone(), events[3].id.clone()]);
+    assert_eq!(resumed["replayed"], 2);
+    assert_eq!(resumed["gap"], false);
+
+    let live = create_event(4, "alpha");
+    post_events(addr, std::slice::from_ref(&live)).await;
+    assert_eq!(next_json(&mut client).await["id"], live.id.as_str());
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn resume_applies_subscription_filter() {
+    let (addr, server) = spawn_test_server().await;
+    let events = vec![
+        create_event(0, "alpha"),
+        create_event(1, "beta"),
+        create_event(2, "alpha"),
+    ];
+    post_events(addr, &events).await;
+
+    let mut client = connect(addr, &format!("since={}&project=alpha", events[0].id)).await;
+    let (ids, resumed) = collect_replay(&mut client).await;
+
+    assert_eq!(ids, vec![events[2].id.clone()]);
+    assert_eq!(resumed["replayed"], 1);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn unknown_event_id_reports_gap() {
+    let (addr, server) = spawn_test_server().await;
+    post_events(addr, &[create_event(0, "alpha")]).await;
+
+    let mut client = connect(addr, "since=evt_unknown").await;
+    let (ids, resumed) = collect_replay(&mut client).await;
+
+    assert_eq!(ids.len(), 1);
+    assert_eq!(resumed["gap"], true);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn resumes_from_timestamp() {
+    let (addr, server) = spawn_test_server().await;
+    let mut old = create_event(0, "alpha");
+    old.timestamp = Utc::now() - chrono::Duration::minutes(10);
+    let recent = create_event(1, "alpha");
+    post_events(addr, &[old, recent.clone()]).await;
+
+    let since =
+        (Utc::now() - chrono::Duration::minutes(5)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut client = connect(addr, &format!("since_ts={since}")).await;
+    let (ids, _) = collect_replay(&mut client).await;
+
+    assert_eq!(ids, vec![recent.id]);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn resume_control_message_replays_events() {
+    let (addr, server) = spawn_test_server().await;
+    let mut client = connect(addr, "").await;
+
+    let events: Vec<Event> = (0..3).map(|n| create_event(n, "alpha")).collect();
+    post_events(addr, &events).await;
+    for event in &events {
+        assert_eq!(next_json(&mut client).await["id"], event.id.as_str());
+    }
+
+    let resume = serde_json::json!({"type": "resume", "since": events[0].id});
+    client
+        .send(Message::Text(resume.to_string().into()))
+        .await
+        .unwrap();
+    let (ids, resumed) = collect_replay(&mut client).await;
+
+    assert_eq!(ids, vec![events[1].id.clone(), events[2].id.clone()]);
+    assert_eq!(resumed["gap"], false);
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn invalid_control_message_returns_error() {
+    let (addr, server) = spawn_test_server().await;
+    let mut client = connect(addr, "").await;
+
+    client
+        .send(Message::Text(r#"{"type":"resume"}"#.into()))
+        .await
+        .unwrap();
+    let reply = next_json(&mut client).await;
+    assert_eq!(reply["type"], "control_error");
+    assert_eq!(reply["code"], "invalid_resume");
+
+    client.send(Message::Text("not json".into())).await.unwrap();
+    let reply = next_json(&mut client).await;
+    assert_eq!(reply["type"], "control_error");
+    assert_eq!(reply["code"], "invalid_message");
+
+    server.abort();
+}
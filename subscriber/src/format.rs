@@ -0,0 +1,181 @@
+//! Human-readable event formatting for terminals.
+//!
+//! Each event is rendered on one line as its time, source, type and the most
+//! useful payload fields, for example:
+//!
+//! ```text
+//! 14:30:00 laptop-1  tool               Read completed project=web
+//! ```
+
+use vibetea_protocol::types::{Event, EventPayload, SessionAction, ToolStatus};
+
+/// Width of the event type column, fitting the longest type name.
+const TYPE_WIDTH: usize = 18;
+
+/// Formats an event as a single line.
+#[must_use]
+pub fn pretty(event: &Event) -> String {
+    format!(
+        "{} {} {:<TYPE_WIDTH$} {}",
+        event.timestamp.format("%H:%M:%S"),
+        event.source,
+        event.event_type.as_str(),
+        details(&event.payload)
+    )
+    .trim_end()
+    .to_string()
+}
+
+/// Summarises the payload fields worth showing.
+fn details(payload: &EventPayload) -> String {
+    match payload {
+        EventPayload::Tool {
+            tool,
+            status,
+            project,
+            ..
+        } => {
+            let status = match status {
+                ToolStatus::Started => "started",
+                ToolStatus::Completed => "completed",
+            };
+            with_project(format!("{tool} {status}"), project.as_deref())
+        }
+        EventPayload::Session {
+            action, project, ..
+        } => {
+            let action = match action {
+                SessionAction::Started => "started",
+                SessionAction::Ended => "ended",
+            };
+            with_project(action.to_string(), Some(project))
+        }
+        EventPayload::Summary { summary, .. } => summary.clone(),
+        EventPayload::Agent { state, .. } => state.clone(),
+        EventPayload::Error { category, .. } => category.clone(),
+        EventPayload::FileChange(change) => format!(
+            "{} v{} +{} -{} ~{}",
+            change.file_hash,
+            change.version,
+            change.lines_added,
+            change.lines_removed,
+            change.lines_modified
+        ),
+        EventPayload::AgentSpawn(spawn) => {
+            format!("{}: {}", spawn.agent_type, spawn.description)
+        }
+        EventPayload::SkillInvocation(skill) => {
+            with_project(skill.skill_name.clone(), Some(&skill.project))
+        }
+        EventPayload::TokenUsage(usage) => format!(
+            "{} in={} out={}",
+            usage.model, usage.input_tokens, usage.output_tokens
+        ),
+        EventPayload::SessionMetrics(metrics) => format!(
+            "sessions={} messages={} tools={}",
+            metrics.total_sessions, metrics.total_messages, metrics.total_tool_usage
+        ),
+        EventPayload::ModelDistribution(distribution) => {
+            let mut models: Vec<&str> = distribution
+                .model_usage
+                .keys()
+                .map(String::as_str)
+                .collect();
+            models.sort_unstable();
+            models.join(", ")
+        }
+        EventPayload::TodoProgress(todos) => {
+            let mut text = format!(
+                "done={} active={} pending={}",
+                todos.completed, todos.in_progress, todos.pending
+            );
+            if todos.abandoned {
+                text.push_str(" (abandoned)");
+            }
+            text
+        }
+        EventPayload::ActivityPattern(pattern) => {
+            let total: u64 = pattern.hour_counts.values().sum();
+            format!("{total} events")
+        }
+        EventPayload::ProjectActivity(activity) => format!(
+            "{} {}",
+            activity.project_path,
+            if activity.is_active {
+                "active"
+            } else {
+                "inactive"
+            }
+        ),
+        EventPayload::Activity { project, .. } => with_project(String::new(), project.as_deref()),
+    }
+}
+
+fn with_project(text: String, project: Option<&str>) -> String {
+    match project {
+        Some(project) if text.is_empty() => format!("project={project}"),
+        Some(project) => format!("{text} project={project}"),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+    use vibetea_protocol::types::{EventType, TodoProgressEvent};
+
+    fn event(payload: EventPayload) -> Event {
+        let mut event = Event::new("laptop-1".to_string(), payload.event_type(), payload);
+        event.timestamp = Utc.with_ymd_and_hms(2026, 2, 2, 14, 30, 0).unwrap();
+        event
+    }
+
+    #[test]
+    fn formats_tool_events() {
+        let line = pretty(&event(EventPayload::Tool {
+            session_id: Uuid::new_v4(),
+            tool: "Read".to_string(),
+            status: ToolStatus::Completed,
+            context: Some("main.rs".to_string()),
+            project: Some("web".to_string()),
+        }));
+
+        assert_eq!(
+            line,
+            "14:30:00 laptop-1 tool               Read completed project=web"
+        );
+    }
+
+    #[test]
+    fn pads_type_column_to_longest_type() {
+        let longest = EventType::ALL
+            .iter()
+            .map(|event_type| event_type.as_str().len())
+            .max()
+            .unwrap();
+        assert_eq!(longest, TYPE_WIDTH);
+
+        let line = pretty(&event(EventPayload::TodoProgress(TodoProgressEvent {
+            session_id: Uuid::new_v4().to_string(),
+            completed: 3,
+            in_progress: 1,
+            pending: 2,
+            abandoned: true,
+        })));
+        assert_eq!(
+            line,
+            "14:30:00 laptop-1 todo_progress      done=3 active=1 pending=2 (abandoned)"
+        );
+    }
+
+    #[test]
+    fn omits_trailing_space_without_details() {
+        let line = pretty(&event(EventPayload::Activity {
+            session_id: Uuid::new_v4(),
+            project: None,
+        }));
+        assert_eq!(line, "14:30:00 laptop-1 activity");
+    }
+}
@@ -0,0 +1,18 @@
+//! VibeTea Subscriber - Client library for the live event feed.
+//!
+//! This crate subscribes to a VibeTea server's `/ws` endpoint and exposes the
+//! matching events as an async [`Stream`](futures_util::Stream) of
+//! [`Event`]s, reconnecting and resuming automatically when the connection
+//! drops. It also provides the `vibetea-tail` command-line tool.
+//!
+//! # Modules
+//!
+//! - [`subscriber`]: Reconnecting WebSocket subscription
+//! - [`format`]: Human-readable event formatting for terminals
+
+pub mod format;
+pub mod subscriber;
+
+pub use subscriber::{ReconnectPolicy, Subscriber, SubscriberConfig, SubscriberError};
+pub use vibetea_protocol::filter::SubscriberFilter;
+pub use vibetea_protocol::types::{Event, EventType};
@@ -0,0 +1,163 @@
+//! vibetea-tail - Follow the live event feed of a VibeTea server.
+//!
+//! Subscribes to the server's `/ws` endpoint with an optional filter and
+//! prints each event as it arrives, either as a human-readable line or as
+//! newline-delimited JSON for piping into other tools. Disconnects are
+//! retried with backoff, resuming after the last event printed.
+//!
+//! # Example
+//!
+//! ```bash
+//! # Tool events from the web project
+//! vibetea-tail --server https://vibetea.example.com --token secret --type tool --project web
+//!
+//! # Everything except activity heartbeats, as NDJSON
+//! vibetea-tail --type '!activity' --ndjson | jq .
+//! ```
+
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use futures_util::StreamExt;
+use tokio::signal;
+use tracing_subscriber::EnvFilter;
+
+use vibetea_protocol::filter::{EventTypeSet, PatternSet};
+use vibetea_subscriber::{format, Subscriber, SubscriberConfig, SubscriberFilter};
+
+/// Follow the live event feed of a VibeTea server.
+///
+/// Filters take comma-separated values; prefix a value with `!` to exclude
+/// it. Source, project and tool filters accept `*` and `?` wildcards.
+#[derive(Parser, Debug)]
+#[command(name = "vibetea-tail")]
+#[command(author, version, about, long_about = None)]
+#[command(after_help = "\
+ENVIRONMENT VARIABLES:
+    VIBETEA_SERVER_URL         Server URL (default: http://localhost:8080)
+    VIBETEA_SUBSCRIBER_TOKEN   Subscriber token, if the server requires one
+    RUST_LOG                   Log level for diagnostics on stderr (default: warn)
+
+EXAMPLES:
+    # Tool events from the web project
+    vibetea-tail --type tool --project web
+
+    # Everything except activity heartbeats, as NDJSON
+    vibetea-tail --type '!activity' --ndjson | jq .
+
+    # Resume after an event ID printed earlier
+    vibetea-tail --since evt_k7m2n9p4q1r6s3t8u5v0
+")]
+struct Cli {
+    /// Base URL of the server.
+    #[arg(
+        long,
+        env = "VIBETEA_SERVER_URL",
+        default_value = "http://localhost:8080"
+    )]
+    server: String,
+
+    /// Subscriber token.
+    #[arg(long, env = "VIBETEA_SUBSCRIBER_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// Event types to include or exclude.
+    #[arg(long = "type", value_name = "TYPES", value_parser = EventTypeSet::parse)]
+    event_type: Option<EventTypeSet>,
+
+    /// Source IDs to include or exclude.
+    #[arg(long, value_name = "PATTERNS", value_parser = PatternSet::parse)]
+    source: Option<PatternSet>,
+
+    /// Projects to include or exclude.
+    #[arg(long, value_name = "PATTERNS", value_parser = PatternSet::parse)]
+    project: Option<PatternSet>,
+
+    /// Tool names to include or exclude. Other events are unaffected.
+    #[arg(long, value_name = "PATTERNS", value_parser = PatternSet::parse)]
+    tool: Option<PatternSet>,
+
+    /// Start after this event ID, replaying what followed it.
+    #[arg(long, value_name = "EVENT_ID")]
+    since: Option<String>,
+
+    /// Print events as newline-delimited JSON.
+    #[arg(long)]
+    ndjson: bool,
+}
+
+impl Cli {
+    fn into_config(self) -> SubscriberConfig {
+        let filter = SubscriberFilter {
+            source: self.source.unwrap_or_default(),
+            event_type: self.event_type.unwrap_or_default(),
+            project: self.project.unwrap_or_default(),
+            tool: self.tool.unwrap_or_default(),
+        };
+
+        let mut config = SubscriberConfig::new(self.server).with_filter(filter);
+        if let Some(token) = self.token {
+            config = config.with_token(token);
+        }
+        if let Some(since) = self.since {
+            config = config.with_since(since);
+        }
+        config
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Logs go to stderr so they never mix with events on stdout
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        )
+        .with_writer(io::stderr)
+        .init();
+
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("vibetea-tail: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let ndjson = cli.ndjson;
+    let config = cli.into_config();
+    let server = config.server_url.clone();
+
+    let mut events = Subscriber::connect(config)
+        .await
+        .with_context(|| format!("failed to subscribe to {server}"))?;
+
+    let mut stdout = io::stdout().lock();
+    loop {
+        let event = tokio::select! {
+            event = events.next() => event,
+            _ = signal::ctrl_c() => return Ok(()),
+        };
+        let Some(event) = event else {
+            anyhow::bail!("subscription to {server} ended");
+        };
+
+        let written = if ndjson {
+            serde_json::to_writer(&mut stdout, &event)
+                .map_err(io::Error::from)
+                .and_then(|()| writeln!(stdout))
+        } else {
+            writeln!(stdout, "{}", format::pretty(&event))
+        };
+        match written.and_then(|()| stdout.flush()) {
+            Ok(()) => {}
+            // The reader went away, e.g. `vibetea-tail | head`
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            Err(e) => return Err(e).context("failed to write event"),
+        }
+    }
+}
@@ -0,0 +1,567 @@
+//! Reconnecting WebSocket subscription to a VibeTea server.
+//!
+//! A [`Subscriber`] connects to the server's `/ws` endpoint and yields the
+//! events matching its [`SubscriberFilter`] as a [`Stream`]. Control messages
+//! (session snapshots, rollups, acknowledgements) are handled internally.
+//!
+//! # Features
+//!
+//! - Typed filters, sent as the JSON `filter` query parameter
+//! - Automatic reconnect with exponential backoff (1s → 30s max, ±25% jitter)
+//! - Resume: after a reconnect, the server replays the events missed since
+//!   the last one received, so none are lost or repeated while the server
+//!   still retains them
+//!
+//! The first connection is made by [`Subscriber::connect`], so a bad URL,
+//! token or filter is reported straight away. Later disconnects are retried
+//! until the server rejects the subscription (for example because the token
+//! was revoked), at which point the stream ends.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures_util::StreamExt;
+//! use vibetea_protocol::filter::SubscriberFilter;
+//! use vibetea_protocol::types::EventType;
+//! use vibetea_subscriber::{Subscriber, SubscriberConfig};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let config = SubscriberConfig::new("https://vibetea.example.com")
+//!         .with_token("secret-token")
+//!         .with_filter(SubscriberFilter::new().with_event_type(EventType::Tool));
+//!
+//!     let mut events = Subscriber::connect(config).await?;
+//!     while let Some(event) = events.next().await {
+//!         println!("{} {}", event.source, event.id);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_util::{Stream, StreamExt};
+use rand::Rng;
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tracing::{debug, error, info, trace, warn};
+use url::Url;
+use vibetea_protocol::filter::SubscriberFilter;
+use vibetea_protocol::types::Event;
+
+/// Initial delay before reconnecting.
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum delay between reconnect attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Jitter factor for reconnect delays (±25%).
+const JITTER_FACTOR: f64 = 0.25;
+
+/// Events buffered between the connection task and the stream's reader.
+const EVENT_BUFFER: usize = 256;
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Errors that can occur when subscribing.
+#[derive(Error, Debug)]
+pub enum SubscriberError {
+    /// The server URL cannot be turned into a WebSocket URL.
+    #[error("invalid server URL: {0}")]
+    InvalidUrl(String),
+
+    /// The server answered the WebSocket upgrade with an HTTP error status.
+    #[error("server rejected the subscription with HTTP {0}")]
+    Rejected(u16),
+
+    /// The connection failed or was interrupted.
+    #[error("WebSocket error: {0}")]
+    WebSocket(#[from] tungstenite::Error),
+
+    /// The filter could not be encoded.
+    #[error("failed to encode filter: {0}")]
+    Filter(#[from] serde_json::Error),
+}
+
+impl SubscriberError {
+    /// Returns `true` if connecting again later may succeed.
+    ///
+    /// Rejections for the request itself, such as a bad token (401) or
+    /// filter (400), are permanent. Rate limiting and server errors are not.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::InvalidUrl(_) | Self::Filter(_) => false,
+            Self::Rejected(status) => !(400..500).contains(status) || *status == 429,
+            Self::WebSocket(_) => true,
+        }
+    }
+}
+
+/// Backoff between reconnect attempts.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnect attempt.
+    pub initial_delay: Duration,
+    /// Maximum delay between attempts.
+    pub max_delay: Duration,
+    /// Jitter factor (0.0 to 1.0) - e.g., 0.25 means ±25%.
+    pub jitter_factor: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: INITIAL_RECONNECT_DELAY,
+            max_delay: MAX_RECONNECT_DELAY,
+            jitter_factor: JITTER_FACTOR,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Creates a policy that reconnects within milliseconds, for tests.
+    #[must_use]
+    pub fn fast_for_tests() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+            jitter_factor: 0.0, // No jitter for deterministic tests
+        }
+    }
+
+    /// Returns the delay before reconnect attempt `attempt` (0-based).
+    #[must_use]
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self
+            .initial_delay
+            .saturating_mul(2u32.saturating_pow(attempt.min(16)))
+            .min(self.max_delay);
+
+        let jitter_factor = if self.jitter_factor.is_finite() {
+            self.jitter_factor.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        if jitter_factor == 0.0 {
+            return base;
+        }
+        let jitter = rand::rng().random_range(-jitter_factor..=jitter_factor);
+        base.mul_f64(1.0 + jitter)
+    }
+}
+
+/// Where and how to subscribe.
+#[derive(Debug, Clone)]
+pub struct SubscriberConfig {
+    /// Base URL of the server, e.g. `https://vibetea.example.com`.
+    ///
+    /// `http` and `https` URLs are mapped to `ws` and `wss`.
+    pub server_url: String,
+
+    /// Subscriber token, if the server requires one.
+    pub token: Option<String>,
+
+    /// Events to receive.
+    pub filter: SubscriberFilter,
+
+    /// ID of the last event already seen, to resume after it on connect.
+    pub since: Option<String>,
+
+    /// Backoff between reconnect attempts.
+    pub reconnect: ReconnectPolicy,
+}
+
+impl SubscriberConfig {
+    /// Creates a configuration that receives every event from `server_url`.
+    #[must_use]
+    pub fn new(server_url: impl Into<String>) -> Self {
+        Self {
+            server_url: server_url.into(),
+            token: None,
+            filter: SubscriberFilter::default(),
+            since: None,
+            reconnect: ReconnectPolicy::default(),
+        }
+    }
+
+    /// Sets the subscriber token (builder pattern).
+    #[must_use]
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Sets the events to receive (builder pattern).
+    #[must_use]
+    pub fn with_filter(mut self, filter: SubscriberFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Resumes after the event with this ID (builder pattern).
+    #[must_use]
+    pub fn with_since(mut self, event_id: impl Into<String>) -> Self {
+        self.since = Some(event_id.into());
+        self
+    }
+
+    /// Sets the reconnect backoff (builder pattern).
+    #[must_use]
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Builds the `/ws` URL, resuming after `since` if given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server URL is not an `http`, `https`, `ws` or
+    /// `wss` URL, or the filter cannot be encoded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vibetea_subscriber::SubscriberConfig;
+    ///
+    /// let config = SubscriberConfig::new("https://vibetea.example.com").with_token("secret");
+    /// let url = config.ws_url(Some("evt_k7m2n9p4q1r6s3t8u5v0")).unwrap();
+    /// assert_eq!(
+    ///     url.as_str(),
+    ///     "wss://vibetea.example.com/ws?token=secret&since=evt_k7m2n9p4q1r6s3t8u5v0"
+    /// );
+    /// ```
+    pub fn ws_url(&self, since: Option<&str>) -> Result<Url, SubscriberError> {
+        let mut url = Url::parse(&self.server_url)
+            .map_err(|e| SubscriberError::InvalidUrl(format!("{}: {e}", self.server_url)))?;
+
+        let scheme = match url.scheme() {
+            "http" | "ws" => "ws",
+            "https" | "wss" => "wss",
+            other => {
+                return Err(SubscriberError::InvalidUrl(format!(
+                    "unsupported scheme '{other}'"
+                )))
+            }
+        };
+        url.set_scheme(scheme)
+            .map_err(|()| SubscriberError::InvalidUrl(self.server_url.clone()))?;
+
+        let path = format!("{}/ws", url.path().trim_end_matches('/'));
+        url.set_path(&path);
+
+        {
+            let mut query = url.query_pairs_mut();
+            query.clear();
+            if let Some(token) = &self.token {
+                query.append_pair("token", token);
+            }
+            if !self.filter.is_empty() {
+                query.append_pair("filter", &serde_json::to_string(&self.filter)?);
+            }
+            if let Some(since) = since {
+                query.append_pair("since", since);
+            }
+        }
+        if url.query() == Some("") {
+            url.set_query(None);
+        }
+
+        Ok(url)
+    }
+}
+
+/// Live stream of events from a VibeTea server.
+///
+/// The connection is driven by a background task, which is stopped when the
+/// `Subscriber` is dropped. The stream ends only if the server rejects a
+/// reconnect.
+pub struct Subscriber {
+    events: mpsc::Receiver<Event>,
+    task: JoinHandle<()>,
+}
+
+impl Subscriber {
+    /// Connects to the server and starts receiving events.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the first connection fails, including when the
+    /// server rejects the token or filter.
+    pub async fn connect(config: SubscriberConfig) -> Result<Self, SubscriberError> {
+        let socket = open(&config, config.since.as_deref()).await?;
+        info!(server = %config.server_url, "Subscribed to event feed");
+
+        let (tx, rx) = mpsc::channel(EVENT_BUFFER);
+        let task = tokio::spawn(run(config, socket, tx));
+        Ok(Self { events: rx, task })
+    }
+}
+
+impl Stream for Subscriber {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Opens a `/ws` connection, resuming after `since` if given.
+async fn open(config: &SubscriberConfig, since: Option<&str>) -> Result<WsStream, SubscriberError> {
+    let url = config.ws_url(since)?;
+    match connect_async(url.as_str()).await {
+        Ok((socket, _response)) => Ok(socket),
+        Err(tungstenite::Error::Http(response)) => {
+            Err(SubscriberError::Rejected(response.status().as_u16()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Forwards events from `socket`, reconnecting and resuming whenever the
+/// connection drops, until the stream is dropped or the server rejects a
+/// reconnect.
+async fn run(config: SubscriberConfig, mut socket: WsStream, tx: mpsc::Sender<Event>) {
+    let mut last_seen = config.since.clone();
+
+    loop {
+        if forward(&mut socket, &tx, &mut last_seen).await == Ended::Closed {
+            return;
+        }
+
+        let mut attempt = 0;
+        socket = loop {
+            let delay = config.reconnect.delay(attempt);
+            debug!(attempt, delay_ms = delay.as_millis(), "Reconnecting");
+            sleep(delay).await;
+            if tx.is_closed() {
+                return;
+            }
+
+            match open(&config, last_seen.as_deref()).await {
+                Ok(socket) => break socket,
+                Err(e) if e.is_retryable() => {
+                    warn!(error = %e, attempt, "Reconnect failed");
+                    attempt = attempt.saturating_add(1);
+                }
+                Err(e) => {
+                    error!(error = %e, "Server rejected the subscription, stopping");
+                    return;
+                }
+            }
+        };
+        info!("Reconnected to event feed");
+    }
+}
+
+/// Why [`forward`] returned.
+#[derive(Debug, PartialEq, Eq)]
+enum Ended {
+    /// The connection dropped; reconnect.
+    Disconnected,
+    /// The stream was dropped; stop.
+    Closed,
+}
+
+/// Forwards events from one connection until it drops.
+async fn forward(
+    socket: &mut WsStream,
+    tx: &mpsc::Sender<Event>,
+    last_seen: &mut Option<String>,
+) -> Ended {
+    while let Some(message) = socket.next().await {
+        let text = match message {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(frame)) => {
+                debug!(?frame, "Server closed the connection");
+                break;
+            }
+            Ok(_) => continue,
+            Err(e) => {
+                warn!(error = %e, "Connection to server lost");
+                break;
+            }
+        };
+
+        match decode(text.as_str()) {
+            Incoming::Event(event) => {
+                *last_seen = Some(event.id.clone());
+                if tx.send(*event).await.is_err() {
+                    return Ended::Closed;
+                }
+            }
+            Incoming::Control(control) => control.log(),
+            Incoming::Invalid(e) => warn!(error = %e, "Ignoring message that could not be decoded"),
+        }
+    }
+    Ended::Disconnected
+}
+
+/// A text message received from the server.
+enum Incoming {
+    Event(Box<Event>),
+    Control(Control),
+    Invalid(serde_json::Error),
+}
+
+/// Server control messages. Their contents are only used for logging.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Control {
+    Sessions {},
+    Subscribed {},
+    Unsubscribed {},
+    Resumed {
+        replayed: usize,
+        gap: bool,
+    },
+    Pong {},
+    Stats {},
+    Lagged {
+        skipped: u64,
+    },
+    #[serde(rename = "control_error")]
+    Error {
+        code: String,
+        message: String,
+    },
+}
+
+impl Control {
+    fn log(&self) {
+        match self {
+            Self::Resumed { replayed, gap } => {
+                debug!(replayed, "Resumed event feed");
+                if *gap {
+                    warn!("Some events were missed while disconnected");
+                }
+            }
+            Self::Lagged { skipped } => {
+                warn!(skipped, "Reading too slowly, server dropped events");
+            }
+            Self::Error { code, message } => {
+                warn!(%code, %message, "Server reported an error");
+            }
+            other => trace!(message = ?other, "Ignoring control message"),
+        }
+    }
+}
+
+fn decode(text: &str) -> Incoming {
+    match serde_json::from_str::<Event>(text) {
+        Ok(event) => Incoming::Event(Box::new(event)),
+        Err(event_error) => match serde_json::from_str::<Control>(text) {
+            Ok(control) => Incoming::Control(control),
+            Err(_) => Incoming::Invalid(event_error),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vibetea_protocol::types::EventType;
+
+    #[test]
+    fn ws_url_maps_scheme_and_encodes_query() {
+        let config = SubscriberConfig::new("http://localhost:8080/vibetea/")
+            .with_token("a b&c")
+            .with_filter(SubscriberFilter::new().with_event_type(EventType::Tool));
+
+        let url = config.ws_url(None).unwrap();
+        assert_eq!(url.scheme(), "ws");
+        assert_eq!(url.path(), "/vibetea/ws");
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(
+            query,
+            [
+                ("token".to_string(), "a b&c".to_string()),
+                ("filter".to_string(), r#"{"type":["tool"]}"#.to_string()),
+            ]
+        );
+
+        let url = SubscriberConfig::new("http://localhost:8080")
+            .ws_url(None)
+            .unwrap();
+        assert_eq!(url.as_str(), "ws://localhost:8080/ws");
+
+        assert!(matches!(
+            SubscriberConfig::new("ftp://example.com").ws_url(None),
+            Err(SubscriberError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            SubscriberConfig::new("not a url").ws_url(None),
+            Err(SubscriberError::InvalidUrl(_))
+        ));
+    }
+
+    #[test]
+    fn reconnect_delay_backs_off_to_the_maximum() {
+        let policy = ReconnectPolicy {
+            jitter_factor: 0.0,
+            ..ReconnectPolicy::default()
+        };
+        assert_eq!(policy.delay(0), Duration::from_secs(1));
+        assert_eq!(policy.delay(1), Duration::from_secs(2));
+        assert_eq!(policy.delay(4), Duration::from_secs(16));
+        assert_eq!(policy.delay(5), Duration::from_secs(30));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(30));
+
+        let jittered = ReconnectPolicy::default().delay(2);
+        assert!(jittered >= Duration::from_secs(3) && jittered <= Duration::from_secs(5));
+    }
+
+    #[test]
+    fn rejections_for_the_request_are_not_retried() {
+        assert!(!SubscriberError::Rejected(401).is_retryable());
+        assert!(!SubscriberError::Rejected(400).is_retryable());
+        assert!(SubscriberError::Rejected(429).is_retryable());
+        assert!(SubscriberError::Rejected(503).is_retryable());
+    }
+
+    #[test]
+    fn decodes_events_and_control_messages() {
+        let event = r#"{"schemaVersion": 2, "id": "evt_k7m2n9p4q1r6s3t8u5v0", "source": "m",
+            "timestamp": "2026-02-02T14:30:00Z", "type": "agent",
+            "payload": {"type": "agent", "sessionId": "550e8400-e29b-41d4-a716-446655440000", "state": "idle"}}"#;
+        assert!(matches!(decode(event), Incoming::Event(_)));
+
+        assert!(matches!(
+            decode(r#"{"type": "resumed", "replayed": 3, "gap": true}"#),
+            Incoming::Control(Control::Resumed {
+                replayed: 3,
+                gap: true
+            })
+        ));
+        assert!(matches!(
+            decode(r#"{"type": "sessions", "sessions": []}"#),
+            Incoming::Control(Control::Sessions {})
+        ));
+        assert!(matches!(
+            decode(r#"{"type": "control_error", "code": "invalid_message", "message": "bad"}"#),
+            Incoming::Control(Control::Error { .. })
+        ));
+
+        // A malformed event is not mistaken for a control message
+        assert!(matches!(
+            decode(r#"{"type": "tool", "id": "evt_1"}"#),
+            Incoming::Invalid(_)
+        ));
+    }
+}
@@ -0,0 +1,183 @@
+//! Integration tests for the subscriber client.
+//!
+//! These tests run the server router from `create_router` in-process and
+//! subscribe to it over a real WebSocket connection.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use tokio::net::TcpListener;
+use tokio::time::{sleep, timeout};
+use uuid::Uuid;
+
+use vibetea_protocol::types::{Event, EventPayload, EventType, SessionAction, ToolStatus};
+use vibetea_server::config::Config;
+use vibetea_server::routes::{create_router, AppState};
+use vibetea_subscriber::{
+    ReconnectPolicy, Subscriber, SubscriberConfig, SubscriberError, SubscriberFilter,
+};
+
+/// Upper bound on waiting for any single event.
+const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn test_config() -> Config {
+    Config {
+        unsafe_no_auth: true,
+        ..Config::default()
+    }
+}
+
+/// Starts the server, returning its URL and state for broadcasting events.
+async fn spawn_server(config: Config) -> (String, AppState) {
+    let state = AppState::new(config);
+    let app = create_router(state.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    (format!("http://{addr}"), state)
+}
+
+fn tool_event(source: &str, tool: &str) -> Event {
+    Event::new(
+        source.to_string(),
+        EventType::Tool,
+        EventPayload::Tool {
+            session_id: Uuid::new_v4(),
+            tool: tool.to_string(),
+            status: ToolStatus::Completed,
+            context: None,
+            project: Some("web".to_string()),
+        },
+    )
+}
+
+fn session_event(source: &str) -> Event {
+    Event::new(
+        source.to_string(),
+        EventType::Session,
+        EventPayload::Session {
+            session_id: Uuid::new_v4(),
+            action: SessionAction::Started,
+            project: "web".to_string(),
+        },
+    )
+}
+
+/// Broadcasts a marker event and returns its ID.
+///
+/// Subscribing with `since` set to the marker replays everything broadcast
+/// after it, so tests do not race the server registering the connection.
+fn marker(state: &AppState) -> String {
+    let event = session_event("marker");
+    let id = event.id.clone();
+    state.broadcaster.broadcast(event);
+    id
+}
+
+async fn next(subscriber: &mut Subscriber) -> Event {
+    timeout(EVENT_TIMEOUT, subscriber.next())
+        .await
+        .expect("timed out waiting for an event")
+        .expect("subscription ended")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[tokio::test]
+async fn receives_only_events_matching_the_filter() {
+    let (url, state) = spawn_server(test_config()).await;
+    let since = marker(&state);
+
+    let filter = SubscriberFilter::new()
+        .with_event_type(EventType::Tool)
+        .without_source("ci-*");
+    let config = SubscriberConfig::new(url)
+        .with_filter(filter)
+        .with_since(since);
+    let mut subscriber = Subscriber::connect(config).await.unwrap();
+
+    let expected = tool_event("laptop-1", "Read");
+    state.broadcaster.broadcast(session_event("laptop-1"));
+    state.broadcaster.broadcast(tool_event("ci-runner", "Bash"));
+    state.broadcaster.broadcast(expected.clone());
+
+    assert_eq!(next(&mut subscriber).await, expected);
+    assert!(
+        timeout(Duration::from_millis(200), subscriber.next())
+            .await
+            .is_err(),
+        "no other events should be received"
+    );
+}
+
+#[tokio::test]
+async fn reconnects_and_resumes_without_losing_events() {
+    let config = Config {
+        ws_max_lifetime: Duration::from_millis(200),
+        ..test_config()
+    };
+    let (url, state) = spawn_server(config).await;
+    let since = marker(&state);
+
+    let config = SubscriberConfig::new(url)
+        .with_since(since)
+        .with_reconnect_policy(ReconnectPolicy::fast_for_tests());
+    let mut subscriber = Subscriber::connect(config).await.unwrap();
+
+    // Spread events over several connection lifetimes
+    let sent: Vec<Event> = (0..25)
+        .map(|i| tool_event("laptop-1", &format!("tool-{i}")))
+        .collect();
+    let broadcaster = state.broadcaster.clone();
+    let events = sent.clone();
+    tokio::spawn(async move {
+        for event in events {
+            broadcaster.broadcast(event);
+            sleep(Duration::from_millis(40)).await;
+        }
+    });
+
+    let mut received = Vec::new();
+    for _ in 0..sent.len() {
+        received.push(next(&mut subscriber).await);
+    }
+    assert_eq!(received, sent);
+    assert!(
+        timeout(Duration::from_millis(300), subscriber.next())
+            .await
+            .is_err(),
+        "events should not be repeated after reconnecting"
+    );
+}
+
+#[tokio::test]
+async fn missing_token_is_rejected() {
+    let config = Config {
+        unsafe_no_auth: false,
+        subscriber_token: Some("secret-token".to_string()),
+        ..Config::default()
+    };
+    let (url, _state) = spawn_server(config).await;
+
+    let result = Subscriber::connect(SubscriberConfig::new(url.clone())).await;
+    assert!(matches!(result, Err(SubscriberError::Rejected(401))));
+
+    let config = SubscriberConfig::new(url).with_token("secret-token");
+    assert!(Subscriber::connect(config).await.is_ok());
+}